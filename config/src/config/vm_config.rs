@@ -5,12 +5,26 @@ use libra_types::transaction::SCRIPT_HASH_LENGTH;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{collections::HashSet, hash::BuildHasher};
 
-/// Holds the VM configuration, currently this is only the publishing options for scripts and
-/// modules, but in the future this may need to be expanded to hold more information.
+/// The maximum number of elements a vector can grow to by default, the same as the
+/// `DEFAULT_MAX_VECTOR_LENGTH` of the vector natives.
+pub const DEFAULT_MAX_VECTOR_LENGTH: usize = 1 << 20;
+
+/// The protocol version the VM runs natives at by default, the same as the
+/// `DEFAULT_NATIVE_VERSION` of the natives.
+pub const DEFAULT_NATIVE_VERSION: u64 = 0;
+
+/// Holds the VM configuration: the publishing options for scripts and modules, and the limits the
+/// VM enforces on the values Move code creates.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct VMConfig {
     pub publishing_options: VMPublishingOption,
+    /// The maximum number of elements a vector can grow to through `Vector::push_back`,
+    /// `Vector::insert` and `Vector::fill`.
+    pub max_vector_length: usize,
+    /// The protocol version natives are resolved and run at. Natives rolled out from a later
+    /// version do not exist for the VM, so every validator of a network must agree on it.
+    pub native_version: u64,
 }
 
 impl Default for VMConfig {
@@ -32,6 +46,8 @@ impl Default for VMConfig {
 
         VMConfig {
             publishing_options: VMPublishingOption::Locked(whitelist),
+            max_vector_length: DEFAULT_MAX_VECTOR_LENGTH,
+            native_version: DEFAULT_NATIVE_VERSION,
         }
     }
 }
@@ -44,6 +60,8 @@ impl VMConfig {
     pub fn empty_whitelist_FOR_TESTING() -> Self {
         VMConfig {
             publishing_options: VMPublishingOption::Locked(HashSet::new()),
+            max_vector_length: DEFAULT_MAX_VECTOR_LENGTH,
+            native_version: DEFAULT_NATIVE_VERSION,
         }
     }
 }
@@ -31,12 +31,12 @@
 //! performance in consensus.
 
 use crate::{traits::*, HashValue};
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use core::convert::TryFrom;
 use libra_crypto_derive::{Deref, SilentDebug, SilentDisplay};
 use pairing::{
-    bls12_381::{Fr, FrRepr},
-    PrimeField,
+    bls12_381::{Fr, FrRepr, G1Compressed, G2Compressed, G1, G2},
+    CurveAffine, CurveProjective, EncodedPoint, PrimeField,
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -72,6 +72,31 @@ impl BLS12381PublicKey {
     pub fn to_bytes(&self) -> [u8; BLS12381_PUBLIC_KEY_LENGTH] {
         self.0.to_bytes()
     }
+
+    /// Aggregates public keys into a single key, against which the aggregate of the signatures
+    /// of the corresponding private keys on one message verifies.
+    ///
+    /// **Note**: aggregation alone is vulnerable to rogue key attacks, so callers must only
+    /// aggregate keys whose possession has been proven.
+    pub fn aggregate(public_keys: &[BLS12381PublicKey]) -> Result<BLS12381PublicKey> {
+        let mut sum = G1::zero();
+        for public_key in public_keys {
+            let mut compressed = G1Compressed::empty();
+            compressed
+                .as_mut()
+                .copy_from_slice(&public_key.to_bytes()[..]);
+            let point = compressed.into_affine()?;
+            sum.add_assign_mixed(&point);
+        }
+        if sum.is_zero() {
+            bail!("The aggregate of the provided public keys is the identity");
+        }
+        let mut bytes = [0u8; BLS12381_PUBLIC_KEY_LENGTH];
+        bytes.copy_from_slice(sum.into_affine().into_compressed().as_ref());
+        threshold_crypto::PublicKey::from_bytes(bytes)
+            .map(BLS12381PublicKey)
+            .map_err(|_| anyhow!("The aggregate public key is invalid"))
+    }
 }
 
 impl BLS12381Signature {
@@ -79,6 +104,25 @@ impl BLS12381Signature {
     pub fn to_bytes(&self) -> [u8; BLS12381_SIGNATURE_LENGTH] {
         self.0.to_bytes()
     }
+
+    /// Aggregates signatures on one message into a single signature, which verifies against the
+    /// aggregate of the corresponding public keys (see `BLS12381PublicKey::aggregate`).
+    pub fn aggregate(signatures: &[BLS12381Signature]) -> Result<BLS12381Signature> {
+        let mut sum = G2::zero();
+        for signature in signatures {
+            let mut compressed = G2Compressed::empty();
+            compressed
+                .as_mut()
+                .copy_from_slice(&signature.to_bytes()[..]);
+            let point = compressed.into_affine()?;
+            sum.add_assign_mixed(&point);
+        }
+        let mut bytes = [0u8; BLS12381_SIGNATURE_LENGTH];
+        bytes.copy_from_slice(sum.into_affine().into_compressed().as_ref());
+        threshold_crypto::Signature::from_bytes(bytes)
+            .map(BLS12381Signature)
+            .map_err(|_| anyhow!("The aggregate signature is invalid"))
+    }
 }
 
 ///////////////////////
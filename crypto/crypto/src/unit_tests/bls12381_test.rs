@@ -3,7 +3,7 @@
 
 use crate::{
     bls12381::{
-        BLS12381PrivateKey, BLS12381PublicKey, BLS12381_PRIVATE_KEY_LENGTH,
+        BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature, BLS12381_PRIVATE_KEY_LENGTH,
         BLS12381_PUBLIC_KEY_LENGTH, BLS12381_SIGNATURE_LENGTH,
     },
     hash::HashValue,
@@ -89,3 +89,42 @@ proptest! {
         prop_assert!(keypair.public_key.verify_signature(&hash, &deserialized).is_ok());
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_aggregate_sign_and_verify(
+        hash in any::<HashValue>(),
+        keypairs in proptest::collection::vec(
+            uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+            1..5,
+        )
+    ) {
+        let signatures: Vec<_> = keypairs
+            .iter()
+            .map(|keypair| keypair.private_key.sign_message(&hash))
+            .collect();
+        let public_keys: Vec<_> = keypairs
+            .iter()
+            .map(|keypair| keypair.public_key.clone())
+            .collect();
+        let aggregate_signature = BLS12381Signature::aggregate(&signatures).unwrap();
+        let aggregate_public_key = BLS12381PublicKey::aggregate(&public_keys).unwrap();
+        prop_assert!(aggregate_public_key
+            .verify_signature(&hash, &aggregate_signature)
+            .is_ok());
+        // Dropping a signer breaks verification.
+        if public_keys.len() > 1 {
+            let partial_public_key = BLS12381PublicKey::aggregate(&public_keys[1..]).unwrap();
+            prop_assert!(partial_public_key
+                .verify_signature(&hash, &aggregate_signature)
+                .is_err());
+        }
+    }
+}
+
+#[test]
+fn test_aggregate_empty_public_keys() {
+    assert!(BLS12381PublicKey::aggregate(&[]).is_err());
+}
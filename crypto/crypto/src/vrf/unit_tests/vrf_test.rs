@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{hash::HashValue, unit_tests::uniform_keypair_strategy, vrf::ecvrf::*};
+use crate::{
+    hash::HashValue, traits::CryptoMaterialError, unit_tests::uniform_keypair_strategy,
+    vrf::ecvrf::*,
+};
 use core::convert::TryFrom;
 use curve25519_dalek::{
     constants::ED25519_BASEPOINT_POINT, edwards::CompressedEdwardsY,
@@ -165,6 +168,23 @@ fn test_verify() {
     }
 }
 
+#[test]
+fn test_malformed_proof() {
+    let pi = ::hex::decode(TESTVECTORS[0].pi).unwrap();
+    assert_eq!(
+        Proof::try_from(&pi[..PROOF_LENGTH - 1]).err(),
+        Some(CryptoMaterialError::WrongLengthError)
+    );
+    // The y-coordinate 2 has no matching x on the curve, so gamma does not decompress.
+    let mut bad_gamma = pi;
+    bad_gamma[..32].copy_from_slice(&[0u8; 32]);
+    bad_gamma[0] = 2;
+    assert_eq!(
+        Proof::try_from(bad_gamma.as_slice()).err(),
+        Some(CryptoMaterialError::DeserializationError)
+    );
+}
+
 #[test]
 fn test_output_from_proof() {
     for tv in TESTVECTORS.iter() {
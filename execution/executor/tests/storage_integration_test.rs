@@ -143,6 +143,7 @@ fn test_reconfiguration() {
     let (mut config, genesis_key) = config_builder::test_config();
     config.vm_config = VMConfig {
         publishing_options: VMPublishingOption::CustomScripts,
+        ..VMConfig::default()
     };
     let (_storage_server_handle, executor, committed_trees) =
         create_storage_service_and_executor(&config);
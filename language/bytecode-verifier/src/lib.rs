@@ -33,5 +33,6 @@ pub use struct_defs::RecursiveStructDefChecker;
 pub use unused_entries::UnusedEntryChecker;
 pub use verifier::{
     batch_verify_modules, verify_main_signature, verify_module_dependencies,
-    verify_script_dependencies, VerifiedModule, VerifiedScript,
+    verify_module_dependencies_with_natives, verify_script_dependencies, VerifiedModule,
+    VerifiedScript,
 };
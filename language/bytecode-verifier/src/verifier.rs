@@ -13,7 +13,8 @@ use libra_types::{
     vm_error::{StatusCode, VMStatus},
 };
 use move_vm_types::{
-    native_functions::dispatch::NativeFunction, native_structs::dispatch::resolve_native_struct,
+    native_functions::{dispatch::LATEST_NATIVE_VERSION, table::NativeFunctionTable},
+    native_structs::dispatch::resolve_native_struct,
 };
 use std::{collections::BTreeMap, fmt};
 use vm::{
@@ -328,6 +329,23 @@ pub fn verify_main_signature(script: &CompiledScript) -> Vec<VMStatus> {
 pub fn verify_module_dependencies<'a>(
     module: &VerifiedModule,
     dependencies: impl IntoIterator<Item = &'a VerifiedModule>,
+) -> Vec<VMStatus> {
+    verify_module_dependencies_with_natives(
+        module,
+        dependencies,
+        NativeFunctionTable::builtin(),
+        LATEST_NATIVE_VERSION,
+    )
+}
+
+/// `verify_module_dependencies`, checking the native functions 'module' declares against the
+/// ones in 'natives' that exist at protocol version 'native_version', rather than against every
+/// built-in one.
+pub fn verify_module_dependencies_with_natives<'a>(
+    module: &VerifiedModule,
+    dependencies: impl IntoIterator<Item = &'a VerifiedModule>,
+    natives: &NativeFunctionTable,
+    native_version: u64,
 ) -> Vec<VMStatus> {
     let module_id = module.self_id();
     let mut dependency_map = BTreeMap::new();
@@ -348,7 +366,11 @@ pub fn verify_module_dependencies<'a>(
         &module_view,
         &dependency_map,
     ));
-    errors.append(&mut verify_native_functions(&module_view));
+    errors.append(&mut verify_native_functions(
+        &module_view,
+        natives,
+        native_version,
+    ));
     errors.append(&mut verify_native_structs(&module_view));
     errors
 }
@@ -366,7 +388,11 @@ pub fn verify_script_dependencies<'a>(
     verify_module_dependencies(&fake_module, dependencies)
 }
 
-fn verify_native_functions(module_view: &ModuleView<VerifiedModule>) -> Vec<VMStatus> {
+fn verify_native_functions(
+    module_view: &ModuleView<VerifiedModule>,
+    natives: &NativeFunctionTable,
+    native_version: u64,
+) -> Vec<VMStatus> {
     let mut errors = vec![];
 
     let module_id = module_view.id();
@@ -376,7 +402,7 @@ fn verify_native_functions(module_view: &ModuleView<VerifiedModule>) -> Vec<VMSt
         .filter(|fdv| fdv.1.is_native())
     {
         let function_name = native_function_definition_view.name();
-        match NativeFunction::resolve(&module_id, function_name) {
+        match natives.resolve_with_version(&module_id, function_name, native_version) {
             None => errors.push(verification_error(
                 IndexKind::FunctionHandle,
                 idx,
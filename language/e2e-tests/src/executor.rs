@@ -24,6 +24,7 @@ use libra_types::{
     vm_error::{StatusCode, VMStatus},
     write_set::WriteSet,
 };
+use move_vm_types::native_functions::dispatch::LATEST_NATIVE_VERSION;
 use stdlib::{stdlib_modules, StdLibOptions};
 use vm::CompiledModule;
 use vm_genesis::GENESIS_KEYPAIR;
@@ -166,6 +167,12 @@ impl FakeExecutor {
         self.data_store.add_account_data(account_data)
     }
 
+    /// Runs the natives of the latest native version, including the ones not rolled out by
+    /// default.
+    pub fn enable_latest_natives(&mut self) {
+        self.config.native_version = LATEST_NATIVE_VERSION;
+    }
+
     /// Adds a module to this executor's data store.
     ///
     /// Does not do any sort of verification on the module.
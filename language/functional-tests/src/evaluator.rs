@@ -570,6 +570,8 @@ pub fn eval<TComp: Compiler>(
             VMPublishingOption::Open,
         )
     };
+    // Move code is tested against every native, rolled out or not
+    exec.enable_latest_natives();
     for data in config.accounts.values() {
         exec.add_account_data(&data);
     }
@@ -0,0 +1,123 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Overhead of calling a native function that does nothing: with the arguments collected into a
+//! `VecDeque` and the type arguments cloned, as natives used to be called, against popping them
+//! into `NativeArguments` and lending the type arguments, as `dispatch` does now.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use libra_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_vm_types::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{NativeArguments, NativeResult},
+        table::NativeFunctionTable,
+    },
+    values::Value,
+};
+use std::collections::VecDeque;
+use vm::{
+    errors::VMResult,
+    file_format::{FunctionSignature, Kind, SignatureToken},
+    gas_schedule::{GasAlgebra, GasUnits},
+};
+
+/// The number of arguments of the native, which is as many as most natives take.
+const NUM_ARGS: usize = 2;
+
+fn noop_vec_deque(_ty_args: Vec<TypeTag>, _arguments: VecDeque<Value>) -> VMResult<NativeResult> {
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
+
+fn noop_native_arguments(
+    _ty_args: &[TypeTag],
+    _arguments: NativeArguments,
+) -> VMResult<NativeResult> {
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
+
+fn operand_stack() -> Vec<Value> {
+    (0..NUM_ARGS as u64).map(Value::u64).collect()
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let ty_args = vec![TypeTag::U64];
+    let mut group = c.benchmark_group("native_dispatch");
+
+    group.bench_function("vec_deque", |b| {
+        b.iter_batched_ref(
+            operand_stack,
+            |stack| {
+                let mut arguments = VecDeque::new();
+                for _ in 0..NUM_ARGS {
+                    arguments.push_front(stack.pop().unwrap());
+                }
+                noop_vec_deque(black_box(ty_args.clone()), arguments).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("native_arguments", |b| {
+        b.iter_batched_ref(
+            operand_stack,
+            |stack| {
+                let start = stack.len() - NUM_ARGS;
+                let arguments = stack.drain(start..).collect();
+                noop_native_arguments(black_box(&ty_args), arguments).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // The same native called through `dispatch`, with its arity and type argument checks
+    let module = ModuleId::new(
+        AccountAddress::new([0xab; 32]),
+        Identifier::new("Bench").unwrap(),
+    );
+    let name = Identifier::new("noop").unwrap();
+    let mut table = NativeFunctionTable::new();
+    table
+        .register(
+            module.clone(),
+            name.clone(),
+            NUM_ARGS,
+            FunctionSignature {
+                return_types: vec![],
+                arg_types: vec![SignatureToken::U64; NUM_ARGS],
+                type_formals: vec![Kind::Unrestricted],
+            },
+            Box::new(|_, _, _, _, _| Ok(NativeResult::ok(GasUnits::new(0), vec![]))),
+        )
+        .unwrap();
+    let entry = table.resolve(&module, &name).unwrap();
+    let mut context = MockNativeContext::for_testing();
+    group.bench_function("registered_dispatch", |b| {
+        b.iter_batched_ref(
+            operand_stack,
+            |stack| {
+                let start = stack.len() - NUM_ARGS;
+                let arguments = stack.drain(start..).collect();
+                entry
+                    .dispatch(
+                        &mut context,
+                        black_box(&ty_args),
+                        vec![Kind::Unrestricted],
+                        vec![],
+                        arguments,
+                    )
+                    .unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_dispatch);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wall-clock comparison of the BLAKE3 and SHA3-256 natives, used to justify the relative
+//! entries for `NativeCostIndex::BLAKE3` and `NativeCostIndex::SHA3_256` in the cost table.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use libra_types::language_storage::TypeTag;
+use move_vm_types::{
+    native_functions::{
+        arguments::Arguments,
+        context::{MockNativeContext, NativeContext},
+        dispatch::{NativeArguments, NativeResult},
+        hash,
+    },
+    values::Value,
+};
+use vm::errors::VMResult;
+
+type HashNative = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+const INPUT_SIZES: &[usize] = &[64, 1 << 10, 1 << 16, 1 << 20, 4 << 20];
+
+fn bench_hashes(c: &mut Criterion) {
+    let context = MockNativeContext::for_testing();
+    let natives: &[(&str, HashNative)] = &[
+        ("sha3_256", hash::native_sha3_256),
+        ("blake3", hash::native_blake3),
+    ];
+
+    let mut group = c.benchmark_group("hash_natives");
+    for size in INPUT_SIZES {
+        let input: Vec<u8> = (0..*size).map(|i| i as u8).collect();
+        group.throughput(Throughput::Bytes(*size as u64));
+        for (name, native) in natives {
+            group.bench_with_input(BenchmarkId::new(*name, size), &input, |b, input| {
+                b.iter(|| {
+                    let mut args = NativeArguments::new();
+                    args.push(Value::vector_u8(input.clone()));
+                    native(&context, &[], args.into()).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hashes);
+criterion_main!(benches);
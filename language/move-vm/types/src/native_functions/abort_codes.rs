@@ -0,0 +1,39 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The namespaces of the codes natives fail with, one per native module. A module exports its
+//! namespace as `DEFAULT_ERROR_CODE` and numbers its codes up from it, so the codes of two modules
+//! never collide and a code identifies the module that produced it.
+//!
+//! Natives report user errors with these codes through `NativeResult::abort`. A new native module
+//! takes a namespace at least `NAMESPACE_SIZE` away from every other one.
+
+/// The number of codes a namespace holds.
+pub const NAMESPACE_SIZE: u64 = 0x100;
+
+pub const ACCOUNT: u64 = 0x0ACC_0000;
+pub const BIGUINT: u64 = 0x0B16_0000;
+pub const BLOCK: u64 = 0x0B10_C000;
+pub const FIXED_POINT: u64 = 0x0F1D_3232;
+pub const HASH: u64 = 0x04B0_F500;
+pub const LCS: u64 = 0x01C5_0000;
+pub const PEDERSEN: u64 = 0x0ED5_0000;
+pub const PRIMITIVE_HELPERS: u64 = 0x0B17_E500;
+pub const RISTRETTO255: u64 = 0x0255_2551;
+pub const SIGNATURE: u64 = 0x0ED2_5519;
+pub const VECTOR: u64 = 0x0EC7_0000;
+
+/// Every namespace, with the module it belongs to.
+pub const ALL: &[(&str, u64)] = &[
+    ("account", ACCOUNT),
+    ("biguint", BIGUINT),
+    ("block", BLOCK),
+    ("fixed_point", FIXED_POINT),
+    ("hash", HASH),
+    ("lcs", LCS),
+    ("pedersen", PEDERSEN),
+    ("primitive_helpers", PRIMITIVE_HELPERS),
+    ("ristretto255", RISTRETTO255),
+    ("signature", SIGNATURE),
+    ("vector", VECTOR),
+];
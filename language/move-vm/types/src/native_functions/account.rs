@@ -0,0 +1,120 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Natives of the `LibraAccount` module, which hand their effects to the `NativeContext`.
+
+use crate::{
+    loaded_data::types::Type,
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Struct,
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_config,
+    contract_event::ContractEvent,
+    event::EventKey,
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::convert::TryFrom;
+use vm::{errors::VMResult, gas_schedule::NativeCostIndex};
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::ACCOUNT;
+/// The context has no event sink to emit events to
+pub const NO_EVENT_SINK: u64 = DEFAULT_ERROR_CODE + 1;
+/// The context has no resource writer to save accounts with
+pub const NO_RESOURCE_WRITER: u64 = DEFAULT_ERROR_CODE + 2;
+/// The context cannot resolve the layout of the account resource
+pub const NO_ACCOUNT_LAYOUT: u64 = DEFAULT_ERROR_CODE + 3;
+
+/// Emit the event `msg: T` to the event stream `guid`, as its `count`-th event. The event carries
+/// the LCS serialization of `msg`, using the layout of the type `T` the native was instantiated
+/// with.
+pub fn native_write_to_event_store(
+    context: &mut dyn NativeContext,
+    ty_args: &[TypeTag],
+    mut ty_layouts: Vec<Type>,
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let type_tag = ty_args[0].clone();
+    let layout = ty_layouts.pop().unwrap();
+
+    let msg = arguments
+        .pop()?
+        .simple_serialize(&layout)
+        .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))?;
+    let count = arguments.pop_as::<u64>()?;
+    let key = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let guid = EventKey::try_from(key.as_slice())
+        .map_err(|_| VMStatus::new(StatusCode::EVENT_KEY_MISMATCH))?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::WRITE_TO_EVENT_STORE,
+        msg.len(),
+    );
+    match context.event_sink() {
+        Some(sink) => {
+            sink.emit_event(ContractEvent::new(guid, count, type_tag, msg));
+            Ok(NativeResult::ok(cost, vec![]))
+        }
+        None => Ok(NativeResult::err(
+            cost,
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                .with_sub_status(NO_EVENT_SINK)
+                .with_message("write_to_event_store needs a context with an event sink".to_owned()),
+        )),
+    }
+}
+
+/// Save `account`, the `LibraAccount::T` resource of a new account, under `address`. The resource
+/// is handed to the context's resource writer as its LCS serialization, using the layout of
+/// `LibraAccount::T` the context resolves.
+pub fn native_save_account(
+    context: &mut dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let account = arguments.pop_as::<Struct>()?;
+    let address = arguments.pop_as::<AccountAddress>()?;
+    arguments.finish()?;
+    if address == account_config::CORE_CODE_ADDRESS {
+        return Err(VMStatus::new(StatusCode::CREATE_NULL_ACCOUNT));
+    }
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::SAVE_ACCOUNT, 1);
+    let struct_tag = account_config::account_struct_tag();
+    let layout = match context.type_to_layout(&TypeTag::Struct(struct_tag.clone())) {
+        Ok(Type::Struct(layout)) => layout,
+        resolved => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(NO_ACCOUNT_LAYOUT)
+                    .with_message(format!("no layout for {:?}: {:?}", struct_tag, resolved)),
+            ))
+        }
+    };
+    let value_blob = account
+        .simple_serialize(&layout)
+        .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))?;
+    match context.resource_writer() {
+        Some(writer) => {
+            writer.save_resource(address, struct_tag, value_blob);
+            Ok(NativeResult::ok(cost, vec![]))
+        }
+        None => Ok(NativeResult::err(
+            cost,
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                .with_sub_status(NO_RESOURCE_WRITER)
+                .with_message("save_account needs a context with a resource writer".to_owned()),
+        )),
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    native_functions::dispatch::{missing_argument_error, NativeArguments, NativeFunction},
+    values::{Reference, VMValueCast, Value},
+};
+use libra_types::vm_error::{StatusCode, VMStatus};
+use std::any::type_name;
+use vm::errors::VMResult;
+
+/// The arguments of a call to a native function, popped from the last one. Natives pop every
+/// argument they were called with and then call `finish`, which fails if they did not. Failing
+/// to pop an argument reports the native function, the position of the argument and the type it
+/// was expected to have.
+///
+/// `dispatch` passes natives their arguments as `Arguments` naming the native. Natives called
+/// directly, as benchmarks and tests do, can be passed `NativeArguments` converted with `into`.
+pub struct Arguments {
+    native: Option<NativeFunction>,
+    values: NativeArguments,
+}
+
+impl Arguments {
+    pub fn new(native: NativeFunction, values: NativeArguments) -> Self {
+        Self {
+            native: Some(native),
+            values,
+        }
+    }
+
+    /// The number of arguments not popped yet.
+    pub fn remaining(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Pops the last argument, whatever its type. Fails with the status of
+    /// `missing_argument_error` if every argument was popped.
+    pub fn pop(&mut self) -> VMResult<Value> {
+        match self.values.pop() {
+            Some(value) => Ok(value),
+            None => Err(self.error::<Value>(missing_argument_error(), None)),
+        }
+    }
+
+    /// Pops the last argument as a `T`. Fails with the status of `missing_argument_error` if
+    /// every argument was popped, and with the status of `value_as` if it is not a `T`.
+    pub fn pop_as<T>(&mut self) -> VMResult<T>
+    where
+        Value: VMValueCast<T>,
+    {
+        let value = match self.values.pop() {
+            Some(value) => value,
+            None => return Err(self.error::<T>(missing_argument_error(), None)),
+        };
+        let index = self.values.len();
+        value
+            .value_as::<T>()
+            .map_err(|err| self.error::<T>(err, Some(index)))
+    }
+
+    /// Pops the last argument as a reference.
+    pub fn pop_ref(&mut self) -> VMResult<Reference> {
+        self.pop_as::<Reference>()
+    }
+
+    /// Pops the last argument as a vector of `T`s.
+    pub fn pop_vec_of<T>(&mut self) -> VMResult<Vec<T>>
+    where
+        Value: VMValueCast<Vec<T>>,
+    {
+        self.pop_as::<Vec<T>>()
+    }
+
+    /// Checks that every argument was popped. Fails with `NATIVE_FUNCTION_INTERNAL_INCONSISTENCY`
+    /// otherwise: `dispatch` checks the number of arguments before natives run, so a native
+    /// leaving some is implemented incorrectly.
+    pub fn finish(self) -> VMResult<()> {
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        Err(
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(
+                format!(
+                    "{} left {} of its arguments unused",
+                    self.name(),
+                    self.values.len()
+                ),
+            ),
+        )
+    }
+
+    fn name(&self) -> String {
+        match self.native {
+            Some(native) => native.to_string(),
+            None => "Native function".to_owned(),
+        }
+    }
+
+    /// `err`, with a message naming the native function, the position of the argument that
+    /// could not be popped if there was one, and the type it was popped as.
+    fn error<T>(&self, err: VMStatus, index: Option<usize>) -> VMStatus {
+        let argument = match index {
+            Some(index) => format!("argument {}", index),
+            None => "a missing argument".to_owned(),
+        };
+        let mut msg = format!(
+            "{} popped {} as {}",
+            self.name(),
+            argument,
+            type_name::<T>()
+        );
+        if let Some(cause) = &err.message {
+            msg = format!("{}: {}", msg, cause);
+        }
+        err.with_message(msg)
+    }
+}
+
+impl From<NativeArguments> for Arguments {
+    fn from(values: NativeArguments) -> Self {
+        Self {
+            native: None,
+            values,
+        }
+    }
+}
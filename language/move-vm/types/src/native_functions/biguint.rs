@@ -0,0 +1,266 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Arithmetic on arbitrary-precision unsigned integers, exchanged as big-endian byte vectors.
+//! Inputs may carry leading zero bytes, but outputs are always canonical: they have no leading
+//! zero bytes, so zero is the empty vector.
+
+use crate::{
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::cmp::Ordering;
+use vm::{
+    errors::VMResult,
+    gas_schedule::{CostTable, NativeCostIndex},
+};
+
+/// Maximum length in bytes of an operand
+pub const MAX_OPERAND_LENGTH: usize = 512;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::BIGUINT;
+/// An operand is longer than `MAX_OPERAND_LENGTH`
+pub const OPERAND_TOO_LONG: u64 = DEFAULT_ERROR_CODE + 1;
+/// The modulus is zero
+pub const DIVISION_BY_ZERO: u64 = DEFAULT_ERROR_CODE + 2;
+
+pub fn native_add(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let (a, b) = pop_operands(arguments)?;
+    if let Some(result) = check_lengths(context.cost_table(), NativeCostIndex::BIGUINT_ADD, &a, &b)
+    {
+        return Ok(result);
+    }
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BIGUINT_ADD,
+        1 + a.len() + b.len(),
+    );
+    let sum = add(&from_bytes(&a), &from_bytes(&b));
+    Ok(NativeResult::ok(
+        cost,
+        vec![Value::vector_u8(to_bytes(sum))],
+    ))
+}
+
+pub fn native_mul(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let (a, b) = pop_operands(arguments)?;
+    if let Some(result) = check_lengths(context.cost_table(), NativeCostIndex::BIGUINT_MUL, &a, &b)
+    {
+        return Ok(result);
+    }
+
+    // Schoolbook multiplication takes one step per pair of digits
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BIGUINT_MUL,
+        1 + a.len() * b.len(),
+    );
+    let product = mul(&from_bytes(&a), &from_bytes(&b));
+    Ok(NativeResult::ok(
+        cost,
+        vec![Value::vector_u8(to_bytes(product))],
+    ))
+}
+
+/// Returns 0, 1 or 2 when the first operand is less than, equal to or greater than the second.
+pub fn native_cmp(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let (a, b) = pop_operands(arguments)?;
+    if let Some(result) = check_lengths(context.cost_table(), NativeCostIndex::BIGUINT_CMP, &a, &b)
+    {
+        return Ok(result);
+    }
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BIGUINT_CMP,
+        1 + a.len() + b.len(),
+    );
+    let ordering = match cmp(&from_bytes(&a), &from_bytes(&b)) {
+        Ordering::Less => 0,
+        Ordering::Equal => 1,
+        Ordering::Greater => 2,
+    };
+    Ok(NativeResult::ok(cost, vec![Value::u64(ordering)]))
+}
+
+pub fn native_mod(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let (a, modulus) = pop_operands(arguments)?;
+    if let Some(result) = check_lengths(
+        context.cost_table(),
+        NativeCostIndex::BIGUINT_MOD,
+        &a,
+        &modulus,
+    ) {
+        return Ok(result);
+    }
+
+    // Each bit of the dividend is shifted into the remainder, which is then compared against and
+    // possibly reduced by the modulus
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BIGUINT_MOD,
+        1 + a.len() * modulus.len(),
+    );
+    let modulus = from_bytes(&modulus);
+    if modulus.is_empty() {
+        return Ok(NativeResult::err(
+            cost,
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(DIVISION_BY_ZERO),
+        ));
+    }
+    let remainder = rem(&from_bytes(&a), &modulus);
+    Ok(NativeResult::ok(
+        cost,
+        vec![Value::vector_u8(to_bytes(remainder))],
+    ))
+}
+
+fn pop_operands(mut arguments: Arguments) -> VMResult<(Vec<u8>, Vec<u8>)> {
+    let b = arguments.pop_vec_of::<u8>()?;
+    let a = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    Ok((a, b))
+}
+
+/// Returns the charged error for operands over `MAX_OPERAND_LENGTH`, which only costs as much as
+/// reading them.
+fn check_lengths(
+    cost_table: &CostTable,
+    cost_index: NativeCostIndex,
+    a: &[u8],
+    b: &[u8],
+) -> Option<NativeResult> {
+    if a.len() <= MAX_OPERAND_LENGTH && b.len() <= MAX_OPERAND_LENGTH {
+        return None;
+    }
+    let cost = native_gas(cost_table, cost_index, 1 + a.len() + b.len());
+    Some(NativeResult::err(
+        cost,
+        VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(OPERAND_TOO_LONG),
+    ))
+}
+
+// Internally numbers are little-endian base 256 digits without trailing (most significant) zero
+// digits, so zero has no digits and comparing lengths compares magnitudes.
+
+fn from_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut digits: Vec<u8> = bytes.iter().rev().cloned().collect();
+    normalize(&mut digits);
+    digits
+}
+
+fn to_bytes(mut digits: Vec<u8>) -> Vec<u8> {
+    digits.reverse();
+    digits
+}
+
+fn normalize(digits: &mut Vec<u8>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u16;
+    for i in 0..a.len().max(b.len()) {
+        let digit = u16::from(*a.get(i).unwrap_or(&0)) + u16::from(*b.get(i).unwrap_or(&0));
+        let digit = digit + carry;
+        sum.push(digit as u8);
+        carry = digit >> 8;
+    }
+    if carry != 0 {
+        sum.push(carry as u8);
+    }
+    sum
+}
+
+fn mul(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut product = vec![0u8; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u32;
+        for (j, &y) in b.iter().enumerate() {
+            let digit = u32::from(product[i + j]) + u32::from(x) * u32::from(y) + carry;
+            product[i + j] = digit as u8;
+            carry = digit >> 8;
+        }
+        product[i + b.len()] = carry as u8;
+    }
+    normalize(&mut product);
+    product
+}
+
+/// Binary long division, keeping only the remainder. `modulus` must be non-zero.
+fn rem(a: &[u8], modulus: &[u8]) -> Vec<u8> {
+    let mut remainder = Vec::with_capacity(modulus.len() + 1);
+    for &digit in a.iter().rev() {
+        for i in (0..8).rev() {
+            shift_in(&mut remainder, (digit >> i) & 1);
+            if cmp(&remainder, modulus) != Ordering::Less {
+                sub_assign(&mut remainder, modulus);
+            }
+        }
+    }
+    remainder
+}
+
+/// Computes `2 * digits + bit` in place.
+fn shift_in(digits: &mut Vec<u8>, bit: u8) {
+    let mut carry = bit;
+    for digit in digits.iter_mut() {
+        let next = *digit >> 7;
+        *digit = (*digit << 1) | carry;
+        carry = next;
+    }
+    if carry != 0 {
+        digits.push(carry);
+    }
+}
+
+/// Computes `a - b` in place. `a` must not be less than `b`.
+fn sub_assign(a: &mut Vec<u8>, b: &[u8]) {
+    let mut borrow = 0i16;
+    for (i, digit) in a.iter_mut().enumerate() {
+        let mut difference = i16::from(*digit) - i16::from(*b.get(i).unwrap_or(&0)) - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 256;
+            borrow = 1;
+        }
+        *digit = difference as u8;
+    }
+    normalize(a);
+}
@@ -0,0 +1,75 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Metadata about the block the transaction executes in, read from the `NativeContext`.
+
+use crate::{
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use vm::{
+    errors::VMResult,
+    gas_schedule::{CostTable, NativeCostIndex},
+};
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::BLOCK;
+/// The context carries no block metadata, as at genesis
+pub const NO_BLOCK_METADATA: u64 = DEFAULT_ERROR_CODE + 1;
+
+/// Return the height of the block the transaction executes in.
+pub fn native_get_height(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    block_field(
+        NativeCostIndex::BLOCK_GET_HEIGHT,
+        context.block_height(),
+        arguments,
+        context.cost_table(),
+    )
+}
+
+/// Return the timestamp of the block the transaction executes in, in microseconds.
+pub fn native_get_timestamp_micros(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    block_field(
+        NativeCostIndex::BLOCK_GET_TIMESTAMP_MICROS,
+        context.block_timestamp_micros(),
+        arguments,
+        context.cost_table(),
+    )
+}
+
+/// Return `value`, failing with `NO_BLOCK_METADATA` rather than defaulting to zero if the context
+/// does not know it.
+fn block_field(
+    cost_index: NativeCostIndex,
+    value: Option<u64>,
+    arguments: Arguments,
+    cost_table: &CostTable,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, 1);
+    match value {
+        Some(value) => Ok(NativeResult::ok(cost, vec![Value::u64(value)])),
+        None => Ok(NativeResult::err(
+            cost,
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(NO_BLOCK_METADATA),
+        )),
+    }
+}
@@ -0,0 +1,1006 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The implementations of the native functions built into the VM, one per `NativeFunction`.
+
+#[cfg(feature = "debug_natives")]
+use super::debug;
+#[cfg(feature = "testing")]
+use super::unit_test;
+use super::{
+    account,
+    arguments::Arguments,
+    biguint, block,
+    context::NativeContext,
+    dispatch::{NativeFunction, NativeFunctionImpl, NativeResult, StructHandleLookup},
+    fixed_point, hash, lcs, pedersen, primitive_helpers, reflection, ristretto255, signature,
+    transaction,
+};
+use crate::{loaded_data::types::Type, values::vector};
+use libra_types::language_storage::TypeTag;
+use vm::{
+    errors::VMResult,
+    file_format::{FunctionSignature, Kind, SignatureToken::*},
+    gas_schedule::NativeCostIndex,
+};
+
+/// The Rust function executing a built-in native, called with the arguments of `dispatch`.
+type BuiltinFn = fn(
+    &mut dyn NativeContext,
+    &[TypeTag],
+    Vec<Kind>,
+    Vec<Type>,
+    Arguments,
+) -> VMResult<NativeResult>;
+
+/// A native function built into the VM: its index in the cost table, its arity, its signature and
+/// the Rust function executing it.
+struct BuiltinNative {
+    cost_index: Option<NativeCostIndex>,
+    arity: usize,
+    signature: fn(StructHandleLookup) -> Option<FunctionSignature>,
+    execute: BuiltinFn,
+}
+
+impl NativeFunctionImpl for BuiltinNative {
+    fn cost_index(&self) -> Option<NativeCostIndex> {
+        self.cost_index
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn signature(&self, struct_handle: StructHandleLookup) -> Option<FunctionSignature> {
+        (self.signature)(struct_handle)
+    }
+
+    fn execute(
+        &self,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: Arguments,
+    ) -> VMResult<NativeResult> {
+        (self.execute)(context, t, kinds, layouts, v)
+    }
+}
+
+/// A signature without references to structs, with the type formals `$kinds` if the function is
+/// generic.
+macro_rules! simple {
+    ($args:expr, $ret:expr) => {{
+        simple!(vec![], $args, $ret)
+    }};
+    ($kinds:expr, $args:expr, $ret:expr) => {{
+        Some(FunctionSignature {
+            return_types: $ret,
+            arg_types: $args,
+            type_formals: $kinds,
+        })
+    }};
+}
+
+/// A `&'static BuiltinNative` charging by the cost table entry `$cost`, or free if declared with
+/// `None`, with the given arity, signature and implementation.
+macro_rules! builtin {
+    (None, $arity:expr, $signature:expr, $execute:expr) => {{
+        builtin!(@ None, $arity, $signature, $execute)
+    }};
+    ($cost:ident, $arity:expr, $signature:expr, $execute:expr) => {{
+        builtin!(@ Some(NativeCostIndex::$cost), $arity, $signature, $execute)
+    }};
+    (@ $cost_index:expr, $arity:expr, $signature:expr, $execute:expr) => {{
+        static IMPLEMENTATION: BuiltinNative = BuiltinNative {
+            cost_index: $cost_index,
+            arity: $arity,
+            signature: $signature,
+            execute: $execute,
+        };
+        &IMPLEMENTATION
+    }};
+}
+
+impl NativeFunction {
+    /// The implementation of the native function. Every variant has one, so a native cannot be
+    /// declared without its cost table entry, arity, signature and implementation.
+    pub(crate) fn implementation(self) -> &'static dyn NativeFunctionImpl {
+        match self {
+            Self::HashSha2_256 => builtin!(
+                SHA2_256,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_sha2_256(context, t, v)
+            ),
+            Self::HashSha3_256 => builtin!(
+                SHA3_256,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_sha3_256(context, t, v)
+            ),
+            Self::HashKeccak256 => builtin!(
+                KECCAK_256,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_keccak_256(context, t, v)
+            ),
+            Self::HashSha2_512 => builtin!(
+                SHA2_512,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_sha2_512(context, t, v)
+            ),
+            Self::HashRipemd160 => builtin!(
+                RIPEMD_160,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_ripemd_160(context, t, v)
+            ),
+            Self::HashBlake2b256 => builtin!(
+                BLAKE2B_256,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_blake2b_256(context, t, v)
+            ),
+            Self::HashBlake3 => builtin!(
+                BLAKE3,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_blake3(context, t, v)
+            ),
+            Self::HashSha3_512 => builtin!(
+                SHA3_512,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| hash::native_sha3_512(context, t, v)
+            ),
+            Self::HashHmacSha256 => builtin!(
+                HMAC_SHA256,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| hash::native_hmac_sha256(context, t, v)
+            ),
+            Self::HashHkdfSha256 => builtin!(
+                HKDF_SHA256,
+                4,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        U64
+                    ],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| hash::native_hkdf_sha256(context, t, v)
+            ),
+            Self::HashSha3_256WithDomain => builtin!(
+                SHA3_256_WITH_DOMAIN,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| hash::native_sha3_256_with_domain(context, t, v)
+            ),
+            Self::SigED25519Verify => builtin!(
+                ED25519_VERIFY,
+                3,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![Bool]
+                ),
+                |context, t, _, _, v| signature::native_ed25519_signature_verification(
+                    context, t, v
+                )
+            ),
+            Self::SigED25519ThresholdVerify => builtin!(
+                ED25519_THRESHOLD_VERIFY,
+                4,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![U64]
+                ),
+                |context, t, _, _, v| signature::native_ed25519_threshold_signature_verification(
+                    context, t, v
+                )
+            ),
+            Self::SigSecp256k1Recover => builtin!(
+                SECP256K1_RECOVER,
+                3,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), U8, Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8)), Bool]
+                ),
+                |context, t, _, _, v| signature::native_secp256k1_recover(context, t, v)
+            ),
+            Self::SigBls12381AggregateVerify => builtin!(
+                BLS12381_AGGREGATE_VERIFY,
+                3,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(Vector(Box::new(U8)))),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![Bool]
+                ),
+                |context, t, _, _, v| signature::native_bls12381_aggregate_signature_verification(
+                    context, t, v
+                )
+            ),
+            Self::SigMultiEd25519Verify => builtin!(
+                MULTI_ED25519_VERIFY,
+                4,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![Bool]
+                ),
+                |context, t, _, _, v| signature::native_multi_ed25519_signature_verification(
+                    context, t, v
+                )
+            ),
+            Self::SigEcvrfVerify => builtin!(
+                ECVRF_VERIFY,
+                3,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![Bool, Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| signature::native_ecvrf_verify(context, t, v)
+            ),
+            Self::SigSchnorrVerify => builtin!(
+                SCHNORR_VERIFY,
+                3,
+                |_| simple!(
+                    vec![
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8)),
+                        Vector(Box::new(U8))
+                    ],
+                    vec![Bool]
+                ),
+                |context, t, _, _, v| signature::native_schnorr_signature_verification(
+                    context, t, v
+                )
+            ),
+            Self::RistrettoPointAdd => builtin!(
+                RISTRETTO_POINT_ADD,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| ristretto255::native_point_add(context, t, v)
+            ),
+            Self::RistrettoScalarMul => builtin!(
+                RISTRETTO_SCALAR_MUL,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| ristretto255::native_scalar_mul(context, t, v)
+            ),
+            Self::RistrettoPointIsValid => builtin!(
+                RISTRETTO_POINT_IS_VALID,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool]),
+                |context, t, _, _, v| ristretto255::native_point_is_valid(context, t, v)
+            ),
+            Self::CryptoPedersenVerify => builtin!(
+                PEDERSEN_VERIFY,
+                3,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), U64, Vector(Box::new(U8))],
+                    vec![Bool]
+                ),
+                |context, t, _, _, v| pedersen::native_pedersen_verify(context, t, v)
+            ),
+            Self::AddrUtilToBytes => builtin!(
+                ADDRESS_TO_BYTES,
+                1,
+                |_| simple!(vec![Address], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_address_to_bytes(context, t, v)
+            ),
+            Self::AddrUtilFromBytes => builtin!(
+                ADDRESS_FROM_BYTES,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Address]),
+                |context, t, _, _, v| primitive_helpers::native_address_from_bytes(context, t, v)
+            ),
+            Self::AccountAddressFromAuthKey => builtin!(
+                ACCOUNT_ADDRESS_FROM_AUTH_KEY,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Address]),
+                |context, t, _, _, v| primitive_helpers::native_address_from_auth_key(
+                    context, t, v
+                )
+            ),
+            Self::EventDeriveGuid => builtin!(
+                EVENT_GUID,
+                2,
+                |_| simple!(vec![U64, Address], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_event_guid(context, t, v)
+            ),
+            Self::U64UtilToBytes => builtin!(
+                U64_TO_BYTES,
+                1,
+                |_| simple!(vec![U64], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_u64_to_bytes(context, t, v)
+            ),
+            Self::U64UtilToBytesBE => builtin!(
+                U64_TO_BYTES_BE,
+                1,
+                |_| simple!(vec![U64], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_u64_to_bytes_be(context, t, v)
+            ),
+            Self::U64UtilFromBytes => builtin!(
+                BYTES_TO_U64,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![U64]),
+                |context, t, _, _, v| primitive_helpers::native_bytes_to_u64(context, t, v)
+            ),
+            Self::U64UtilFromDecimalString => builtin!(
+                U64_FROM_DECIMAL_STRING,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_u64_from_decimal_string(
+                    context, t, v
+                )
+            ),
+            Self::U64UtilToDecimalString => builtin!(
+                U64_TO_DECIMAL_STRING,
+                1,
+                |_| simple!(vec![U64], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_u64_to_decimal_string(
+                    context, t, v
+                )
+            ),
+            Self::U128UtilToBytes => builtin!(
+                U128_TO_BYTES,
+                1,
+                |_| simple!(vec![U128], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_u128_to_bytes(context, t, v)
+            ),
+            Self::U128UtilFromBytes => builtin!(
+                U128_FROM_BYTES,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![U128]),
+                |context, t, _, _, v| primitive_helpers::native_u128_from_bytes(context, t, v)
+            ),
+            Self::U64UtilCheckedAdd => builtin!(
+                U64_CHECKED_ADD,
+                2,
+                |_| simple!(vec![U64, U64], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_u64_checked_add(context, t, v)
+            ),
+            Self::U64UtilCheckedMul => builtin!(
+                U64_CHECKED_MUL,
+                2,
+                |_| simple!(vec![U64, U64], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_u64_checked_mul(context, t, v)
+            ),
+            Self::U128UtilCheckedAdd => builtin!(
+                U128_CHECKED_ADD,
+                2,
+                |_| simple!(vec![U128, U128], vec![Bool, U128]),
+                |context, t, _, _, v| primitive_helpers::native_u128_checked_add(context, t, v)
+            ),
+            Self::U128UtilCheckedMul => builtin!(
+                U128_CHECKED_MUL,
+                2,
+                |_| simple!(vec![U128, U128], vec![Bool, U128]),
+                |context, t, _, _, v| primitive_helpers::native_u128_checked_mul(context, t, v)
+            ),
+            Self::U128UtilToU64 => builtin!(
+                U128_TO_U64,
+                1,
+                |_| simple!(vec![U128], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_u128_to_u64(context, t, v)
+            ),
+            Self::MathPowU64 => builtin!(
+                POW_U64,
+                2,
+                |_| simple!(vec![U64, U64], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_pow_u64(context, t, v)
+            ),
+            Self::MathSqrtU128 => builtin!(
+                SQRT_U128,
+                1,
+                |_| simple!(vec![U128], vec![U128]),
+                |context, t, _, _, v| primitive_helpers::native_sqrt_u128(context, t, v)
+            ),
+            Self::MathLog2FloorU64 => builtin!(
+                LOG2_FLOOR_U64,
+                1,
+                |_| simple!(vec![U64], vec![U64]),
+                |context, t, _, _, v| primitive_helpers::native_log2_floor_u64(context, t, v)
+            ),
+            Self::MathLeadingZerosU64 => builtin!(
+                LEADING_ZEROS_U64,
+                1,
+                |_| simple!(vec![U64], vec![U64]),
+                |context, t, _, _, v| {
+                    primitive_helpers::native_leading_zeros_u64(context, t, v)
+                }
+            ),
+            Self::MathPopcountU64 => builtin!(
+                POPCOUNT_U64,
+                1,
+                |_| simple!(vec![U64], vec![U64]),
+                |context, t, _, _, v| primitive_helpers::native_popcount_u64(context, t, v)
+            ),
+            Self::MathAddModU128 => builtin!(
+                ADD_MOD_U128,
+                3,
+                |_| simple!(vec![U128, U128, U128], vec![U128]),
+                |context, t, _, _, v| primitive_helpers::native_add_mod_u128(context, t, v)
+            ),
+            Self::MathMulModU128 => builtin!(
+                MUL_MOD_U128,
+                3,
+                |_| simple!(vec![U128, U128, U128], vec![U128]),
+                |context, t, _, _, v| primitive_helpers::native_mul_mod_u128(context, t, v)
+            ),
+            Self::MathDivModU128 => builtin!(
+                DIV_MOD_U128,
+                2,
+                |_| simple!(vec![U128, U128], vec![U128, U128]),
+                |context, t, _, _, v| primitive_helpers::native_div_mod_u128(context, t, v)
+            ),
+            Self::MathMulDivU64 => builtin!(
+                MUL_DIV_U64,
+                3,
+                |_| simple!(vec![U64, U64, U64], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_mul_div_u64(context, t, v)
+            ),
+            Self::FixedPoint32CreateFromRational => builtin!(
+                FIXED_POINT32_CREATE_FROM_RATIONAL,
+                2,
+                |_| simple!(vec![U64, U64], vec![U64]),
+                |context, t, _, _, v| fixed_point::native_create_from_rational(context, t, v)
+            ),
+            Self::FixedPoint32Multiply => builtin!(
+                FIXED_POINT32_MULTIPLY,
+                2,
+                |_| simple!(vec![U64, U64], vec![U64]),
+                |context, t, _, _, v| fixed_point::native_multiply_u64(context, t, v)
+            ),
+            Self::FixedPoint32Divide => builtin!(
+                FIXED_POINT32_DIVIDE,
+                2,
+                |_| simple!(vec![U64, U64], vec![U64]),
+                |context, t, _, _, v| fixed_point::native_divide_u64(context, t, v)
+            ),
+            Self::BigUintAdd => builtin!(
+                BIGUINT_ADD,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| biguint::native_add(context, t, v)
+            ),
+            Self::BigUintMul => builtin!(
+                BIGUINT_MUL,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| biguint::native_mul(context, t, v)
+            ),
+            Self::BigUintCmp => builtin!(
+                BIGUINT_CMP,
+                2,
+                |_| simple!(vec![Vector(Box::new(U8)), Vector(Box::new(U8))], vec![U64]),
+                |context, t, _, _, v| biguint::native_cmp(context, t, v)
+            ),
+            Self::BigUintMod => builtin!(
+                BIGUINT_MOD,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| biguint::native_mod(context, t, v)
+            ),
+            Self::BytearrayConcat => builtin!(
+                BYTEARRAY_CONCAT,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![ByteArray]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_concat(context, t, v)
+            ),
+            Self::BytearraySlice => builtin!(
+                BYTEARRAY_SLICE,
+                3,
+                |_| simple!(vec![ByteArray, U64, U64], vec![ByteArray]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_slice(context, t, v)
+            ),
+            Self::BytearrayIndexOf => builtin!(
+                BYTEARRAY_INDEX_OF,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![Bool, U64]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_index_of(context, t, v)
+            ),
+            Self::BytearrayXor => builtin!(
+                BYTEARRAY_XOR,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![ByteArray]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_xor(context, t, v)
+            ),
+            Self::BytearrayAnd => builtin!(
+                BYTEARRAY_AND,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![ByteArray]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_and(context, t, v)
+            ),
+            Self::BytearrayOr => builtin!(
+                BYTEARRAY_OR,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![ByteArray]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_or(context, t, v)
+            ),
+            Self::BytearrayConstantTimeEq => builtin!(
+                BYTEARRAY_CONSTANT_TIME_EQ,
+                2,
+                |_| simple!(vec![ByteArray, ByteArray], vec![Bool]),
+                |context, t, _, _, v| primitive_helpers::native_bytearray_constant_time_eq(
+                    context, t, v
+                )
+            ),
+            Self::Base58Encode => builtin!(
+                BASE58_ENCODE,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_base58_encode(context, t, v)
+            ),
+            Self::Base58Decode => builtin!(
+                BASE58_DECODE,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_base58_decode(context, t, v)
+            ),
+            Self::Bech32Encode => builtin!(
+                BECH32_ENCODE,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Bool, Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| primitive_helpers::native_bech32_encode(context, t, v)
+            ),
+            Self::Bech32Decode => builtin!(
+                BECH32_DECODE,
+                2,
+                |_| simple!(
+                    vec![Vector(Box::new(U8)), Vector(Box::new(U8))],
+                    vec![Bool, Vector(Box::new(U8))]
+                ),
+                |context, t, _, _, v| primitive_helpers::native_bech32_decode(context, t, v)
+            ),
+            Self::Base64Encode => builtin!(
+                BASE64_ENCODE,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_base64_encode(context, t, v)
+            ),
+            Self::Base64Decode => builtin!(
+                BASE64_DECODE,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_base64_decode(context, t, v)
+            ),
+            Self::StringIsValidUtf8 => builtin!(
+                STRING_IS_VALID_UTF8,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool]),
+                |context, t, _, _, v| primitive_helpers::native_string_is_valid_utf8(context, t, v)
+            ),
+            Self::StringIsAscii => builtin!(
+                STRING_IS_ASCII,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool]),
+                |context, t, _, _, v| primitive_helpers::native_string_is_ascii(context, t, v)
+            ),
+            Self::StringToUppercaseAscii => builtin!(
+                STRING_TO_UPPERCASE_ASCII,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| primitive_helpers::native_string_to_uppercase_ascii(
+                    context, t, v
+                )
+            ),
+            Self::LcsToBytes => builtin!(
+                LCS_TO_BYTES,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Reference(Box::new(TypeParameter(0)))],
+                    vec![Vector(Box::new(U8))]
+                ),
+                |context, t, _, layouts, v| lcs::native_to_bytes(context, t, layouts, v)
+            ),
+            Self::LcsSerializedSize => builtin!(
+                LCS_SERIALIZED_SIZE,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Reference(Box::new(TypeParameter(0)))],
+                    vec![U64]
+                ),
+                |context, t, _, layouts, v| lcs::native_serialized_size(context, t, layouts, v)
+            ),
+            Self::LcsFromBytesU64 => builtin!(
+                LCS_FROM_BYTES_U64,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, U64]),
+                |context, t, _, _, v| lcs::native_from_bytes_u64(context, t, v)
+            ),
+            Self::LcsFromBytesBool => builtin!(
+                LCS_FROM_BYTES_BOOL,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, Bool]),
+                |context, t, _, _, v| lcs::native_from_bytes_bool(context, t, v)
+            ),
+            Self::LcsFromBytesAddress => builtin!(
+                LCS_FROM_BYTES_ADDRESS,
+                1,
+                |_| simple!(vec![Vector(Box::new(U8))], vec![Bool, Address]),
+                |context, t, _, _, v| lcs::native_from_bytes_address(context, t, v)
+            ),
+            Self::CompareGeneric => builtin!(
+                COMPARE_GENERIC,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        Reference(Box::new(TypeParameter(0))),
+                        Reference(Box::new(TypeParameter(0)))
+                    ],
+                    vec![U64]
+                ),
+                |context, t, _, layouts, v| lcs::native_compare(context, t, layouts, v)
+            ),
+            Self::ReflectTypeOf => builtin!(
+                REFLECT_TYPE_OF,
+                0,
+                |_| simple!(vec![Kind::All], vec![], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| reflection::native_type_of(context, t, v)
+            ),
+            Self::ReflectTypeName => builtin!(
+                REFLECT_TYPE_NAME,
+                0,
+                |_| simple!(vec![Kind::All], vec![], vec![Vector(Box::new(U8))]),
+                |context, t, _, _, v| reflection::native_type_name(context, t, v)
+            ),
+            Self::ReflectTypesAreEqual => builtin!(
+                REFLECT_TYPES_ARE_EQUAL,
+                0,
+                |_| simple!(vec![Kind::All, Kind::All], vec![], vec![Bool]),
+                |context, t, _, _, v| reflection::native_types_are_equal(context, t, v)
+            ),
+            Self::TxnSender => builtin!(
+                TXN_SENDER,
+                0,
+                |_| simple!(vec![], vec![Address]),
+                |context, t, _, _, v| transaction::native_sender(context, t, v)
+            ),
+            Self::TxnMaxGasAmount => builtin!(
+                TXN_MAX_GAS_AMOUNT,
+                0,
+                |_| simple!(vec![], vec![U64]),
+                |context, t, _, _, v| transaction::native_max_gas_amount(context, t, v)
+            ),
+            Self::TxnGasRemaining => builtin!(
+                TXN_GAS_REMAINING,
+                0,
+                |_| simple!(vec![], vec![U64]),
+                |context, t, _, _, v| transaction::native_gas_remaining(context, t, v)
+            ),
+            Self::BlockGetHeight => builtin!(
+                BLOCK_GET_HEIGHT,
+                0,
+                |_| simple!(vec![], vec![U64]),
+                |context, t, _, _, v| block::native_get_height(context, t, v)
+            ),
+            Self::BlockGetTimestampMicros => builtin!(
+                BLOCK_GET_TIMESTAMP_MICROS,
+                0,
+                |_| simple!(vec![], vec![U64]),
+                |context, t, _, _, v| block::native_get_timestamp_micros(context, t, v)
+            ),
+            #[cfg(feature = "debug_natives")]
+            Self::DebugPrint => builtin!(
+                None,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Reference(Box::new(TypeParameter(0)))],
+                    vec![]
+                ),
+                |context, t, _, layouts, v| debug::native_print(context, t, layouts, v)
+            ),
+            #[cfg(feature = "debug_natives")]
+            Self::DebugPrintStackTrace => builtin!(
+                None,
+                0,
+                |_| simple!(vec![], vec![]),
+                |context, t, _, _, v| debug::native_print_stack_trace(context, t, v)
+            ),
+            Self::VectorLength => builtin!(
+                LENGTH,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Reference(Box::new(Vector(Box::new(TypeParameter(0)))))],
+                    vec![U64]
+                ),
+                |context, t, _, _, v| vector::native_length(context, t, v)
+            ),
+            Self::VectorEmpty => builtin!(
+                EMPTY,
+                0,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![],
+                    vec![Vector(Box::new(TypeParameter(0)))]
+                ),
+                |context, t, _, _, v| vector::native_empty(context, t, v)
+            ),
+            Self::VectorSingleton => builtin!(
+                SINGLETON,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![TypeParameter(0)],
+                    vec![Vector(Box::new(TypeParameter(0)))]
+                ),
+                |context, t, _, _, v| vector::native_singleton(context, t, v)
+            ),
+            Self::VectorFill => builtin!(
+                FILL,
+                2,
+                |_| simple!(
+                    vec![Kind::Unrestricted],
+                    vec![TypeParameter(0), U64],
+                    vec![Vector(Box::new(TypeParameter(0)))]
+                ),
+                |context, t, _, _, v| vector::native_fill(context, t, v)
+            ),
+            Self::VectorBorrow => builtin!(
+                BORROW,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Reference(Box::new(Vector(Box::new(TypeParameter(0))))), U64],
+                    vec![Reference(Box::new(TypeParameter(0)))]
+                ),
+                |context, t, _, _, v| vector::native_borrow(context, t, v)
+            ),
+            Self::VectorBorrowMut => builtin!(
+                BORROW_MUT,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        U64
+                    ],
+                    vec![MutableReference(Box::new(TypeParameter(0)))]
+                ),
+                |context, t, _, _, v| vector::native_borrow_mut(context, t, v)
+            ),
+            Self::VectorPushBack => builtin!(
+                PUSH_BACK,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        TypeParameter(0),
+                    ],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_push_back(context, t, v)
+            ),
+            Self::VectorPopBack => builtin!(
+                POP_BACK,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![MutableReference(Box::new(Vector(Box::new(TypeParameter(
+                        0
+                    )))))],
+                    vec![TypeParameter(0)]
+                ),
+                |context, t, _, _, v| vector::native_pop(context, t, v)
+            ),
+            Self::VectorDestroyEmpty => builtin!(
+                DESTROY_EMPTY,
+                1,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![Vector(Box::new(TypeParameter(0)))],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_destroy_empty(context, t, v)
+            ),
+            Self::VectorSwap => builtin!(
+                SWAP,
+                3,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        U64,
+                        U64,
+                    ],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_swap(context, t, v)
+            ),
+            Self::VectorInsert => builtin!(
+                INSERT,
+                3,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        U64,
+                        TypeParameter(0),
+                    ],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_insert(context, t, v)
+            ),
+            Self::VectorTruncate => builtin!(
+                TRUNCATE,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        U64,
+                    ],
+                    vec![]
+                ),
+                |context, t, kinds, _, v| vector::native_truncate(context, t, kinds, v)
+            ),
+            Self::VectorRotate => builtin!(
+                ROTATE,
+                2,
+                |_| simple!(
+                    vec![Kind::All],
+                    vec![
+                        MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
+                        U64,
+                    ],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_rotate(context, t, v)
+            ),
+            Self::VectorSortU64 => builtin!(
+                SORT_U64,
+                1,
+                |_| simple!(
+                    vec![],
+                    vec![MutableReference(Box::new(Vector(Box::new(U64))))],
+                    vec![]
+                ),
+                |context, t, _, _, v| vector::native_sort_u64(context, t, v)
+            ),
+            Self::VectorBinarySearchU64 => builtin!(
+                BINARY_SEARCH_U64,
+                2,
+                |_| simple!(
+                    vec![],
+                    vec![Reference(Box::new(Vector(Box::new(U64)))), U64],
+                    vec![Bool, U64]
+                ),
+                |context, t, _, _, v| vector::native_binary_search_u64(context, t, v)
+            ),
+            Self::VectorMaxU64 => builtin!(
+                VECTOR_MAX_U64,
+                1,
+                |_| simple!(
+                    vec![],
+                    vec![Reference(Box::new(Vector(Box::new(U64))))],
+                    vec![Bool, U64]
+                ),
+                |context, t, _, _, v| vector::native_max_u64(context, t, v)
+            ),
+            Self::VectorMinU64 => builtin!(
+                VECTOR_MIN_U64,
+                1,
+                |_| simple!(
+                    vec![],
+                    vec![Reference(Box::new(Vector(Box::new(U64))))],
+                    vec![Bool, U64]
+                ),
+                |context, t, _, _, v| vector::native_min_u64(context, t, v)
+            ),
+            Self::VectorSumU64 => builtin!(
+                VECTOR_SUM_U64,
+                1,
+                |_| simple!(
+                    vec![],
+                    vec![Reference(Box::new(Vector(Box::new(U64))))],
+                    vec![U128]
+                ),
+                |context, t, _, _, v| vector::native_sum_u64(context, t, v)
+            ),
+            Self::AccountWriteEvent => builtin!(
+                WRITE_TO_EVENT_STORE,
+                3,
+                |_| simple!(
+                    vec![Kind::Unrestricted],
+                    vec![Vector(Box::new(U8)), U64, TypeParameter(0)],
+                    vec![]
+                ),
+                |context, t, _, layouts, v| account::native_write_to_event_store(
+                    context, t, layouts, v
+                )
+            ),
+            Self::AccountSaveAccount => builtin!(
+                SAVE_ACCOUNT,
+                2,
+                |struct_handle| {
+                    let type_formals = vec![];
+                    let t_idx = struct_handle("T")?;
+                    let arg_types = vec![Address, Struct(t_idx, vec![])];
+                    let return_types = vec![];
+                    Some(FunctionSignature {
+                        type_formals,
+                        arg_types,
+                        return_types,
+                    })
+                },
+                |context, t, _, _, v| account::native_save_account(context, t, v)
+            ),
+            #[cfg(feature = "testing")]
+            Self::UnitTestCreateSigners => builtin!(
+                None,
+                1,
+                |_| simple!(vec![U64], vec![Vector(Box::new(Address))]),
+                |context, t, _, _, v| unit_test::native_create_signers_for_testing(context, t, v)
+            ),
+            #[cfg(feature = "testing")]
+            Self::UnitTestPoison => builtin!(
+                None,
+                0,
+                |_| simple!(vec![], vec![]),
+                |context, t, _, _, v| unit_test::native_poison(context, t, v)
+            ),
+        }
+    }
+
+    /// The index of the native in the cost table, the one its implementation charges by. Natives
+    /// without one are free.
+    pub(crate) fn cost_index(self) -> Option<NativeCostIndex> {
+        self.implementation().cost_index()
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The execution context the VM hands to native functions, so they can read the cost table and
+//! metadata about the executing transaction without it being threaded through their arguments.
+//!
+//! `NativeContext` is a trait, implemented by the VM and by `MockNativeContext`. Tests calling
+//! natives without a VM get a context from `<dyn NativeContext>::for_testing()`.
+
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::layout::{resolve_layout, TypeLayoutResolver},
+    values::vector::DEFAULT_MAX_VECTOR_LENGTH,
+};
+use libra_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    vm_error::{StatusCode, VMStatus},
+};
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+};
+use vm::{
+    errors::VMResult,
+    file_format::NUMBER_OF_NATIVE_FUNCTIONS,
+    gas_schedule::{CostTable, GasAlgebra, GasCarrier, GasCost, GasUnits},
+};
+
+/// Metadata about the executing transaction that native functions can query. New metadata is
+/// added as a method here: only the natives reading it and the implementors of this trait change.
+pub trait NativeContext {
+    /// The cost table natives are charged from.
+    fn cost_table(&self) -> &CostTable;
+
+    /// The address of the account that sent the transaction.
+    fn sender(&self) -> AccountAddress;
+
+    /// The maximum number of gas units the transaction may spend.
+    fn max_gas_amount(&self) -> GasUnits<GasCarrier>;
+
+    /// The number of gas units left to the transaction when the native was called, before its
+    /// own cost is charged.
+    fn remaining_gas(&self) -> GasUnits<GasCarrier>;
+
+    /// The height of the block the transaction executes in, if it is known.
+    fn block_height(&self) -> Option<u64>;
+
+    /// The timestamp of the block the transaction executes in, in microseconds, if it is known.
+    fn block_timestamp_micros(&self) -> Option<u64>;
+
+    /// The Move call stack, outermost frame first, if it is known. Natives have no frame of
+    /// their own, so the last frame is the function calling the native.
+    fn call_stack(&self) -> Option<Vec<CallFrame>>;
+
+    /// The maximum number of elements the vector natives grow a vector to, as configured for the
+    /// VM.
+    fn max_vector_length(&self) -> usize;
+
+    /// Where the events natives emit go, if the context collects them.
+    fn event_sink(&mut self) -> Option<&mut dyn EventSink>;
+
+    /// Resolves the layouts of the types natives are called with.
+    fn layout_resolver(&self) -> &dyn TypeLayoutResolver;
+
+    /// The layout of the values of type `tag`, or an error if the context cannot resolve it.
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type> {
+        self.layout_resolver().type_to_layout(tag)
+    }
+
+    /// Where the resources natives save go, if the context accepts them.
+    fn resource_writer(&mut self) -> Option<&mut dyn ResourceWriter>;
+
+    /// The state an embedder of the VM hands to the natives it registers, if the context carries
+    /// any.
+    fn extensions(&mut self) -> Option<&mut NativeContextExtensions>;
+}
+
+impl dyn NativeContext {
+    /// The context to call natives with outside of a VM, see `MockNativeContext::for_testing`.
+    /// It is called as `<dyn NativeContext>::for_testing()`.
+    pub fn for_testing() -> MockNativeContext {
+        MockNativeContext::for_testing()
+    }
+}
+
+/// State handed to natives by an embedder of the VM, holding at most one value of each type.
+#[derive(Debug, Default)]
+pub struct NativeContextExtensions(HashMap<TypeId, Box<dyn Any>>);
+
+impl NativeContextExtensions {
+    /// Add `value`, returning the value of the same type it replaces.
+    pub fn insert<T: Any>(&mut self, value: T) -> Option<T> {
+        self.0
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .map(|previous| *previous.downcast().unwrap())
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .map(|value| value.downcast_ref().unwrap())
+    }
+
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.0
+            .get_mut(&TypeId::of::<T>())
+            .map(|value| value.downcast_mut().unwrap())
+    }
+}
+
+/// Receives the events emitted by natives.
+pub trait EventSink {
+    fn emit_event(&mut self, event: ContractEvent);
+}
+
+impl EventSink for Vec<ContractEvent> {
+    fn emit_event(&mut self, event: ContractEvent) {
+        self.push(event)
+    }
+}
+
+/// Receives the resources saved by natives.
+pub trait ResourceWriter {
+    /// Save `value_blob`, the LCS serialization of a resource of type `struct_tag`, under
+    /// `address`.
+    fn save_resource(
+        &mut self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+        value_blob: Vec<u8>,
+    );
+}
+
+/// A `ResourceWriter` keeping the saved resources in memory.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryResources(BTreeMap<(AccountAddress, StructTag), Vec<u8>>);
+
+impl InMemoryResources {
+    /// The last blob saved under `address` for the type `struct_tag`.
+    pub fn get(&self, address: AccountAddress, struct_tag: &StructTag) -> Option<&[u8]> {
+        self.0
+            .get(&(address, struct_tag.clone()))
+            .map(Vec::as_slice)
+    }
+}
+
+impl ResourceWriter for InMemoryResources {
+    fn save_resource(
+        &mut self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+        value_blob: Vec<u8>,
+    ) {
+        self.0.insert((address, struct_tag), value_blob);
+    }
+}
+
+/// A frame of the Move call stack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallFrame {
+    pub module: ModuleId,
+    pub function: Identifier,
+    /// The offset of the call instruction the frame is executing
+    pub pc: u16,
+}
+
+/// A `NativeContext` holding fixed values, for calling natives without a VM.
+#[derive(Debug)]
+pub struct MockNativeContext {
+    pub cost_table: CostTable,
+    pub sender: AccountAddress,
+    pub max_gas_amount: GasUnits<GasCarrier>,
+    pub remaining_gas: GasUnits<GasCarrier>,
+    pub block_height: Option<u64>,
+    pub block_timestamp_micros: Option<u64>,
+    pub call_stack: Option<Vec<CallFrame>>,
+    pub max_vector_length: usize,
+    pub events: Option<Vec<ContractEvent>>,
+    pub struct_layouts: BTreeMap<StructTag, StructDef>,
+    pub resources: Option<InMemoryResources>,
+    pub extensions: NativeContextExtensions,
+}
+
+impl MockNativeContext {
+    /// A context without transaction metadata, charging one gas unit per unit of size for every
+    /// native function so that tests can observe how the charged cost grows with the input. Its
+    /// remaining gas is more than any native is charged, so natives never stop for lack of gas.
+    pub fn for_testing() -> Self {
+        let mut cost_table = CostTable::zero();
+        cost_table.native_table = (0..NUMBER_OF_NATIVE_FUNCTIONS)
+            .map(|_| GasCost::new(1, 0))
+            .collect();
+        Self {
+            cost_table,
+            sender: AccountAddress::default(),
+            max_gas_amount: GasUnits::new(0),
+            remaining_gas: GasUnits::new(GasCarrier::max_value()),
+            block_height: None,
+            block_timestamp_micros: None,
+            call_stack: None,
+            max_vector_length: DEFAULT_MAX_VECTOR_LENGTH,
+            events: None,
+            struct_layouts: BTreeMap::new(),
+            resources: None,
+            extensions: NativeContextExtensions::default(),
+        }
+    }
+
+    pub fn new(sender: AccountAddress) -> Self {
+        Self {
+            sender,
+            ..Self::for_testing()
+        }
+    }
+
+    pub fn with_cost_table(mut self, cost_table: CostTable) -> Self {
+        self.cost_table = cost_table;
+        self
+    }
+
+    pub fn with_max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+        self.max_gas_amount = GasUnits::new(max_gas_amount);
+        self
+    }
+
+    pub fn with_remaining_gas(mut self, remaining_gas: u64) -> Self {
+        self.remaining_gas = GasUnits::new(remaining_gas);
+        self
+    }
+
+    pub fn with_block_height(mut self, block_height: u64) -> Self {
+        self.block_height = Some(block_height);
+        self
+    }
+
+    pub fn with_block_timestamp_micros(mut self, block_timestamp_micros: u64) -> Self {
+        self.block_timestamp_micros = Some(block_timestamp_micros);
+        self
+    }
+
+    pub fn with_call_stack(mut self, call_stack: Vec<CallFrame>) -> Self {
+        self.call_stack = Some(call_stack);
+        self
+    }
+
+    pub fn with_max_vector_length(mut self, max_vector_length: usize) -> Self {
+        self.max_vector_length = max_vector_length;
+        self
+    }
+
+    /// Collect emitted events in `events`.
+    pub fn with_event_sink(mut self) -> Self {
+        self.events = Some(vec![]);
+        self
+    }
+
+    /// Resolve `struct_tag` to the layout `layout`.
+    pub fn with_struct_layout(mut self, struct_tag: StructTag, layout: StructDef) -> Self {
+        self.struct_layouts.insert(struct_tag, layout);
+        self
+    }
+
+    /// Keep saved resources in `resources`.
+    pub fn with_resource_writer(mut self) -> Self {
+        self.resources = Some(InMemoryResources::default());
+        self
+    }
+
+    pub fn with_extension<T: Any>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+}
+
+/// Resolves the structs registered with `with_struct_layout`, and fails on the others.
+impl TypeLayoutResolver for MockNativeContext {
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type> {
+        resolve_layout(tag, &|struct_tag| {
+            self.struct_layouts.get(struct_tag).cloned().ok_or_else(|| {
+                VMStatus::new(StatusCode::TYPE_RESOLUTION_FAILURE)
+                    .with_message(format!("no layout for {:?}", struct_tag))
+            })
+        })
+    }
+}
+
+impl NativeContext for MockNativeContext {
+    fn cost_table(&self) -> &CostTable {
+        &self.cost_table
+    }
+
+    fn sender(&self) -> AccountAddress {
+        self.sender
+    }
+
+    fn max_gas_amount(&self) -> GasUnits<GasCarrier> {
+        self.max_gas_amount
+    }
+
+    fn remaining_gas(&self) -> GasUnits<GasCarrier> {
+        self.remaining_gas
+    }
+
+    fn block_height(&self) -> Option<u64> {
+        self.block_height
+    }
+
+    fn block_timestamp_micros(&self) -> Option<u64> {
+        self.block_timestamp_micros
+    }
+
+    fn call_stack(&self) -> Option<Vec<CallFrame>> {
+        self.call_stack.clone()
+    }
+
+    fn max_vector_length(&self) -> usize {
+        self.max_vector_length
+    }
+
+    fn event_sink(&mut self) -> Option<&mut dyn EventSink> {
+        self.events
+            .as_mut()
+            .map(|events| events as &mut dyn EventSink)
+    }
+
+    fn layout_resolver(&self) -> &dyn TypeLayoutResolver {
+        self
+    }
+
+    fn resource_writer(&mut self) -> Option<&mut dyn ResourceWriter> {
+        self.resources
+            .as_mut()
+            .map(|resources| resources as &mut dyn ResourceWriter)
+    }
+
+    fn extensions(&mut self) -> Option<&mut NativeContextExtensions> {
+        Some(&mut self.extensions)
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Natives for debugging Move code, which only resolve when this crate is built with the
+//! `debug_natives` feature. They cost no gas and write to a per-thread sink, which is the debug
+//! log unless replaced with `set_sink`.
+
+use crate::{
+    loaded_data::types::Type,
+    native_functions::{
+        arguments::Arguments,
+        context::{CallFrame, NativeContext},
+        dispatch::NativeResult,
+    },
+    values::Value,
+};
+use libra_logger::prelude::*;
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::{cell::RefCell, fmt::Write};
+use vm::{
+    errors::VMResult,
+    gas_schedule::{GasAlgebra, GasUnits},
+};
+
+thread_local! {
+    static SINK: RefCell<Option<Box<dyn FnMut(&str)>>> = RefCell::new(None);
+}
+
+/// Send the output of the debug natives called on this thread to `sink`, or back to the debug
+/// log if `sink` is `None`.
+pub fn set_sink(sink: Option<Box<dyn FnMut(&str)>>) {
+    SINK.with(|current| *current.borrow_mut() = sink);
+}
+
+fn emit(line: &str) {
+    SINK.with(|sink| match &mut *sink.borrow_mut() {
+        Some(sink) => sink(line),
+        None => debug!("{}", line),
+    })
+}
+
+/// Print the value behind a `&T`, rendered by `Value::pretty_string`.
+pub fn native_print(
+    _context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut ty_layouts: Vec<Type>,
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let layout = ty_layouts.pop().unwrap();
+    let reference = arguments.pop_ref()?;
+    arguments.finish()?;
+
+    let rendered = reference
+        .read_ref()?
+        .pretty_string(&layout)
+        .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))?;
+    emit(&format!("[debug] {}", rendered));
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
+
+/// Print the Move call stack of the caller, as rendered by `format_stack_trace`.
+pub fn native_print_stack_trace(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+    emit(&format_stack_trace(context.call_stack().as_deref()));
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
+
+/// Render the call stack `frames`, given outermost first, with one line per frame and the
+/// innermost frame first:
+/// `#<depth> 0x<address>::<module>::<function> at pc <pc>`. Unknown call stacks are rendered as
+/// `<unavailable>`.
+pub fn format_stack_trace(frames: Option<&[CallFrame]>) -> String {
+    let mut out = String::from("[debug] stack trace:");
+    match frames {
+        Some(frames) => {
+            for (depth, frame) in frames.iter().rev().enumerate() {
+                // Writing to a String cannot fail.
+                write!(
+                    out,
+                    "\n  #{} 0x{}::{}::{} at pc {}",
+                    depth,
+                    frame.module.address(),
+                    frame.module.name(),
+                    frame.function,
+                    frame.pc
+                )
+                .unwrap();
+            }
+        }
+        None => out.push_str("\n  <unavailable>"),
+    }
+    out
+}
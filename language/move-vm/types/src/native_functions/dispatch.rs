@@ -1,15 +1,21 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{hash, primitive_helpers, signature};
-use crate::values::{vector, Value};
+use super::{arguments::Arguments, context::NativeContext};
+use crate::{loaded_data::types::Type, values::Value};
+use anyhow::{ensure, format_err, Error};
+use libra_logger::prelude::*;
 use libra_types::{
+    account_address::AccountAddress,
     account_config::CORE_CODE_ADDRESS,
-    identifier::IdentStr,
+    identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, TypeTag},
     vm_error::{StatusCode, VMStatus},
 };
-use std::collections::VecDeque;
+use once_cell::sync::Lazy;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use smallvec::SmallVec;
+use std::{collections::HashMap, fmt, mem, str::FromStr};
 use vm::{
     access::ModuleAccess,
     errors::VMResult,
@@ -22,26 +28,85 @@ use vm::{
 
 /// Result of a native function execution that requires charges for execution cost.
 ///
-/// An execution that causes an invariant violation would not return a `NativeResult` but
-/// return a `VMResult` error directly.
-/// All native functions must return a `VMResult<NativeResult>` where an `Err` is returned
-/// when an error condition is met that should not charge for the execution. A common example
-/// is a VM invariant violation which should have been forbidden by the verifier.
-/// Errors (typically user errors and aborts) that are logically part of the function execution
-/// must be expressed in a `NativeResult` via a cost and a VMStatus.
+/// All native functions return a `VMResult<NativeResult>`. Errors (typically user errors and
+/// aborts) that are logically part of the function execution must be expressed in a
+/// `NativeResult` via a cost and a VMStatus. A native may still return an `Err` directly for an
+/// invariant violation, which should have been forbidden by the verifier, but it is not free:
+/// `dispatch` turns it into a `NativeResult` charging the base cost of the native, since the
+/// native may have done work before detecting it.
 pub struct NativeResult {
     /// The cost for running that function, whether successfully or not.
     pub cost: GasUnits<GasCarrier>,
+    /// The part of `cost` given back, for natives that charge for work they may not do before
+    /// knowing whether they will do it. It is never more than `cost`.
+    pub refund: GasUnits<GasCarrier>,
     /// Result of execution. This is either the return values or the error to report.
     pub result: VMResult<Vec<Value>>,
+    /// Events emitted by the execution, in the order they were emitted. `dispatch` does not
+    /// interpret them: emitting them is up to its caller.
+    pub events: Vec<NativeEvent>,
+    /// How `cost` was computed, for natives that record it. It is informational: `cost` is what
+    /// the execution is charged.
+    pub cost_breakdown: Option<NativeCostBreakdown>,
 }
 
+/// The components of the cost of a native function execution, for calibrating the cost table: a
+/// cost of `base + per_unit * units`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NativeCostBreakdown {
+    /// The part of the cost that does not depend on the size of the input.
+    pub base: GasUnits<GasCarrier>,
+    /// The cost of each unit of size.
+    pub per_unit: GasUnits<GasCarrier>,
+    /// The size of the input, in the unit the native is charged by.
+    pub units: GasCarrier,
+}
+
+/// An event emitted by a native function: the key of the event stream it is emitted to, its
+/// sequence number in that stream, its type and its value.
+pub type NativeEvent = (Vec<u8>, u64, TypeTag, Value);
+
 impl NativeResult {
     /// Return values of a successful execution.
     pub fn ok(cost: GasUnits<GasCarrier>, values: Vec<Value>) -> Self {
         NativeResult {
             cost,
+            refund: GasUnits::new(0),
+            result: Ok(values),
+            events: vec![],
+            cost_breakdown: None,
+        }
+    }
+
+    /// Return values of a successful execution which was charged `cost` up front and gives
+    /// `refund` of it back.
+    pub fn ok_with_refund(
+        cost: GasUnits<GasCarrier>,
+        refund: GasUnits<GasCarrier>,
+        values: Vec<Value>,
+    ) -> Self {
+        debug_assert_refund(cost, refund);
+        NativeResult {
+            cost,
+            refund,
+            result: Ok(values),
+            events: vec![],
+            cost_breakdown: None,
+        }
+    }
+
+    /// Return values of a successful execution which emitted `events`, in that order.
+    pub fn ok_with_events(
+        cost: GasUnits<GasCarrier>,
+        values: Vec<Value>,
+        events: Vec<NativeEvent>,
+    ) -> Self {
+        NativeResult {
+            cost,
+            refund: GasUnits::new(0),
             result: Ok(values),
+            events,
+            cost_breakdown: None,
         }
     }
 
@@ -50,124 +115,689 @@ impl NativeResult {
     pub fn err(cost: GasUnits<GasCarrier>, err: VMStatus) -> Self {
         NativeResult {
             cost,
+            refund: GasUnits::new(0),
+            result: Err(err),
+            events: vec![],
+            cost_breakdown: None,
+        }
+    }
+
+    /// `VMStatus` of a failed execution which was charged `cost` up front and gives `refund` of
+    /// it back, such as an execution failing before the work it was charged for.
+    pub fn err_with_refund(
+        cost: GasUnits<GasCarrier>,
+        refund: GasUnits<GasCarrier>,
+        err: VMStatus,
+    ) -> Self {
+        debug_assert_refund(cost, refund);
+        NativeResult {
+            cost,
+            refund,
             result: Err(err),
+            events: vec![],
+            cost_breakdown: None,
+        }
+    }
+
+    /// A failure with the major status `status_code` and the sub-status `sub_status`, one of the
+    /// sub-statuses of the native's module in `errors`.
+    pub fn err_with_sub_status(
+        cost: GasUnits<GasCarrier>,
+        status_code: StatusCode,
+        sub_status: u64,
+    ) -> Self {
+        Self::err(cost, VMStatus::new(status_code).with_sub_status(sub_status))
+    }
+
+    /// A user error of the function, such as an argument out of its domain: an `ABORTED` status
+    /// with `abort_code` as its sub-status, as if the Move caller had aborted with it. Codes are
+    /// taken from the namespace of the native's module in `abort_codes`.
+    pub fn abort(cost: GasUnits<GasCarrier>, abort_code: u64) -> Self {
+        Self::err_with_sub_status(cost, StatusCode::ABORTED, abort_code)
+    }
+
+    /// `abort`, with a message telling more about the user error than its code.
+    pub fn abort_with_message(
+        cost: GasUnits<GasCarrier>,
+        abort_code: u64,
+        message: String,
+    ) -> Self {
+        Self::err(
+            cost,
+            VMStatus::new(StatusCode::ABORTED)
+                .with_sub_status(abort_code)
+                .with_message(message),
+        )
+    }
+
+    /// The result, recording that its cost was computed as `breakdown`.
+    pub fn with_cost_breakdown(mut self, breakdown: NativeCostBreakdown) -> Self {
+        self.cost_breakdown = Some(breakdown);
+        self
+    }
+
+    /// The gas the execution is charged: its cost, less its refund.
+    pub fn charged(&self) -> GasUnits<GasCarrier> {
+        self.cost.sub(self.refund)
+    }
+
+    /// The events the execution emitted, in the order they were emitted.
+    pub fn events(&self) -> &[NativeEvent] {
+        &self.events
+    }
+
+    /// Takes the events the execution emitted, in the order they were emitted, leaving none.
+    pub fn take_events(&mut self) -> Vec<NativeEvent> {
+        mem::take(&mut self.events)
+    }
+}
+
+fn debug_assert_refund(cost: GasUnits<GasCarrier>, refund: GasUnits<GasCarrier>) {
+    debug_assert!(
+        refund.get() <= cost.get(),
+        "refund of {} exceeds the cost of {}",
+        refund.get(),
+        cost.get()
+    );
+}
+
+/// A summary of a native function execution, for recording native calls: the native function,
+/// what it was charged and how it ended, without the values it returned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeResultSummary {
+    pub function: NativeFunction,
+    pub cost: GasUnits<GasCarrier>,
+    /// `EXECUTED` if the native function succeeded, the major status of its failure otherwise.
+    pub status: StatusCode,
+    /// The sub status of the failure, if it has one.
+    pub sub_status: Option<u64>,
+}
+
+impl NativeResultSummary {
+    /// Summarizes the execution of `function` which produced `result`.
+    pub fn new(function: NativeFunction, result: &NativeResult) -> Self {
+        let (status, sub_status) = match &result.result {
+            Ok(_) => (StatusCode::EXECUTED, None),
+            Err(err) => (err.major_status, err.sub_status),
+        };
+        Self {
+            function,
+            cost: result.charged(),
+            status,
+            sub_status,
         }
     }
 }
 
+/// The arguments of a native function call, in the order the function declares them. Natives take
+/// few arguments, so they are held inline rather than in a fresh allocation for every call.
+/// Built-in natives are passed them as `Arguments`, which they pop through.
+pub type NativeArguments = SmallVec<[Value; 4]>;
+
 pub fn native_gas(table: &CostTable, key: NativeCostIndex, size: usize) -> GasUnits<GasCarrier> {
+    native_gas_detailed(table, key, size).0
+}
+
+/// `native_gas`, with how the cost was computed. Natives are charged linearly in the size of their
+/// input, so the base of the breakdown is zero.
+pub fn native_gas_detailed(
+    table: &CostTable,
+    key: NativeCostIndex,
+    size: usize,
+) -> (GasUnits<GasCarrier>, NativeCostBreakdown) {
     let gas_amt = table.native_cost(key);
     let memory_size = AbstractMemorySize::new(size as GasCarrier);
-    gas_amt.total().mul(memory_size)
+    let breakdown = NativeCostBreakdown {
+        base: GasUnits::new(0),
+        per_unit: gas_amt.total(),
+        units: memory_size.get(),
+    };
+    (gas_amt.total().mul(memory_size), breakdown)
+}
+
+/// Checks that the transaction can pay `cost`, what the native has to be charged so far, before
+/// the native does the work that cost pays for. Fails with `OUT_OF_GAS` otherwise, for which
+/// `dispatch` charges the native the whole remaining budget.
+pub fn charge_or_stop(context: &dyn NativeContext, cost: GasUnits<GasCarrier>) -> VMResult<()> {
+    if cost.get() > context.remaining_gas().get() {
+        Err(VMStatus::new(StatusCode::OUT_OF_GAS))
+    } else {
+        Ok(())
+    }
+}
+
+/// The `NativeResult` of a native that returned `result`: an `Err` it returned is the error of a
+/// `NativeResult` charging `base_cost`, or the remaining gas of `context` if the native stopped
+/// with `OUT_OF_GAS`.
+pub(crate) fn charge_errors(
+    context: &dyn NativeContext,
+    base_cost: GasUnits<GasCarrier>,
+    result: VMResult<NativeResult>,
+) -> NativeResult {
+    match result {
+        Ok(result) => result,
+        Err(err) if err.major_status == StatusCode::OUT_OF_GAS => {
+            NativeResult::err(context.remaining_gas(), err)
+        }
+        Err(err) => NativeResult::err(base_cost, err),
+    }
+}
+
+/// Finds the struct handle named `name` in the module declaring a native function, for
+/// signatures that refer to the structs of that module.
+pub type StructHandleLookup<'a> = &'a dyn Fn(&str) -> Option<StructHandleIndex>;
+
+/// The implementation of a native function: the cost table entry it is charged by, the number of
+/// arguments it takes, its signature and how it executes. Each `NativeFunction` delegates to the
+/// implementation its variant is given in `builtins`.
+pub trait NativeFunctionImpl: Sync {
+    /// The index of the function in the cost table, or `None` if it is free.
+    fn cost_index(&self) -> Option<NativeCostIndex>;
+
+    /// The number of arguments the function takes.
+    fn arity(&self) -> usize;
+
+    /// The signature of the function, or `None` if it refers to a struct `struct_handle` does not
+    /// find.
+    fn signature(&self, struct_handle: StructHandleLookup) -> Option<FunctionSignature>;
+
+    /// Executes the function, see `NativeFunction::dispatch`.
+    fn execute(
+        &self,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: Arguments,
+    ) -> VMResult<NativeResult>;
+}
+
+/// The address and module name of the module declaring a native function, and its function name
+/// there.
+type NativeFunctionKey = (AccountAddress, &'static str, &'static str);
+
+/// The first protocol version a native function is available from, if declared with `since`.
+macro_rules! native_min_version {
+    () => {
+        0
+    };
+    ($version:literal) => {
+        $version
+    };
 }
 
+/// Declares `NativeFunction` with a variant per native. A variant declared with `cfg` attributes
+/// only exists in builds those attributes hold in: the attributes are applied to the variant and
+/// to every place that names it, so such natives are neither resolved nor listed by `all` in other
+/// builds.
 macro_rules! decl_native_function_enum {
-    {$($variant:ident = $pat:pat),*} => {
+    {$(
+        $(#[cfg($cfg:meta)])*
+        $variant:ident = ($id:literal, &$address:ident, $module:literal, $name:literal)
+        $(since $version:literal)?
+    ),*} => {
         /// Enum representing a native function known by the VM
-        #[derive(Debug, Clone, Copy)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         pub enum NativeFunction {
-            $($variant,)*
+            $($(#[cfg($cfg)])* $variant,)*
         }
 
+        /// Every native function, in declaration order.
+        static ALL_NATIVE_FUNCTIONS: Lazy<Vec<NativeFunction>> = Lazy::new(|| {
+            let mut natives = vec![];
+            $(
+                $(#[cfg($cfg)])*
+                natives.push(NativeFunction::$variant);
+            )*
+            natives
+        });
+
+        /// Every native function `NativeFunction::resolve` resolves to, by the address and module
+        /// name of the module declaring it and its function name there.
+        static NATIVE_FUNCTIONS: Lazy<HashMap<NativeFunctionKey, NativeFunction>> =
+            Lazy::new(|| {
+                NativeFunction::all()
+                    .iter()
+                    .map(|native| {
+                        let key = (native.address(), native.module_name(), native.function_name());
+                        (key, *native)
+                    })
+                    .collect()
+            });
+
         impl NativeFunction {
             /// Looks up the expected native function definition from the module id
             // (address and module) and function name where it was expected to be declared.
             pub fn resolve(module: &ModuleId, function_name: &IdentStr) -> Option<Self> {
+                // Looking up borrowed names, rather than the `'static` ones the table holds
+                let natives: &HashMap<(AccountAddress, &str, &str), Self> = &NATIVE_FUNCTIONS;
+                let key = (*module.address(), module.name().as_str(), function_name.as_str());
+                natives.get(&key).copied()
+            }
+
+            /// `resolve` as a chain of comparisons, which the lookup in `NATIVE_FUNCTIONS` must
+            /// agree with.
+            #[cfg(test)]
+            pub(crate) fn resolve_by_match(
+                module: &ModuleId,
+                function_name: &IdentStr,
+            ) -> Option<Self> {
                 let case = (module.address(), module.name().as_str(), function_name.as_str());
                 match case {
-                    $($pat => Some(Self::$variant), )*
+                    $($(#[cfg($cfg)])* (&$address, $module, $name) => Some(Self::$variant), )*
                     _ => None
                 }
             }
+
+            /// Every native function the VM knows about, which are the ones `resolve` resolves
+            /// to.
+            pub fn all() -> &'static [Self] {
+                &ALL_NATIVE_FUNCTIONS
+            }
+
+            /// The address of the module declaring the native function.
+            pub fn address(self) -> AccountAddress {
+                match self {
+                    $($(#[cfg($cfg)])* Self::$variant => $address, )*
+                }
+            }
+
+            /// The name of the module declaring the native function.
+            pub fn module_name(self) -> &'static str {
+                match self {
+                    $($(#[cfg($cfg)])* Self::$variant => $module, )*
+                }
+            }
+
+            /// The name of the native function in its declaring module.
+            pub fn function_name(self) -> &'static str {
+                match self {
+                    $($(#[cfg($cfg)])* Self::$variant => $name, )*
+                }
+            }
+
+            /// The number identifying the native function, which unlike its position in the enum
+            /// never changes as natives are added or removed. Ids of removed natives are not
+            /// reused.
+            pub fn stable_id(self) -> u16 {
+                match self {
+                    $($(#[cfg($cfg)])* Self::$variant => $id, )*
+                }
+            }
+
+            /// The first protocol version the native function is available from.
+            pub fn min_version(self) -> u64 {
+                match self {
+                    $($(#[cfg($cfg)])* Self::$variant => native_min_version!($($version)?), )*
+                }
+            }
+
+            /// The native function identified by `id`, if any.
+            pub fn from_stable_id(id: u16) -> Option<Self> {
+                match id {
+                    $($(#[cfg($cfg)])* $id => Some(Self::$variant), )*
+                    _ => None,
+                }
+            }
+        }
+
+        /// Renders the native function as `address::Module::function`, with the address in hex
+        /// without its leading zeros, such as `0x0::Vector::push_back`.
+        impl fmt::Display for NativeFunction {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let address = format!("{:x}", self.address());
+                let address = match address.trim_start_matches('0') {
+                    "" => "0",
+                    trimmed => trimmed,
+                };
+                write!(f, "0x{}::{}::{}", address, self.module_name(), self.function_name())
+            }
+        }
+
+        /// Parses a native function rendered by `Display`, with or without the leading zeros of
+        /// its address. Fails if the string is malformed or names no native function.
+        impl FromStr for NativeFunction {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Error> {
+                let parts: Vec<_> = s.split("::").collect();
+                ensure!(
+                    parts.len() == 3,
+                    "{} is not of the form address::Module::function",
+                    s
+                );
+                let address = AccountAddress::from_hex_literal(parts[0])?;
+                let module = ModuleId::new(address, Identifier::new(parts[1])?);
+                let function_name = IdentStr::new(parts[2])?;
+                Self::resolve(&module, function_name)
+                    .ok_or_else(|| format_err!("{} is not a native function", s))
+            }
         }
     }
 }
 
+/// The protocol version a VM runs natives at unless configured otherwise: natives declared `since`
+/// a later version are not available until the network moves to it.
+pub const DEFAULT_NATIVE_VERSION: u64 = 0;
+
+/// The latest protocol version a native is declared `since`. Every native the binary contains is
+/// available from it on.
+pub const LATEST_NATIVE_VERSION: u64 = 1;
+
+// Every native is declared with its stable id. New natives take the next unused id and are added
+// to `unit_tests/native_function_ids.txt`, which also keeps the ids of removed natives from being
+// reused.
+// Natives rolled out behind a protocol version are declared `since` that version, and are only
+// resolved by `resolve_with_version` from it on. The others are available from version 0.
 decl_native_function_enum! {
-    HashSha2_256 = (&CORE_CODE_ADDRESS, "Hash", "sha2_256"),
-    HashSha3_256 = (&CORE_CODE_ADDRESS, "Hash", "sha3_256"),
-    SigED25519Verify = (&CORE_CODE_ADDRESS, "Signature", "ed25519_verify"),
-    SigED25519ThresholdVerify = (&CORE_CODE_ADDRESS, "Signature", "ed25519_threshold_verify"),
-    AddrUtilToBytes = (&CORE_CODE_ADDRESS, "AddressUtil", "address_to_bytes"),
-    U64UtilToBytes = (&CORE_CODE_ADDRESS, "U64Util", "u64_to_bytes"),
-    BytearrayConcat = (&CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_concat"),
-    VectorLength = (&CORE_CODE_ADDRESS, "Vector", "length"),
-    VectorEmpty = (&CORE_CODE_ADDRESS, "Vector", "empty"),
-    VectorBorrow = (&CORE_CODE_ADDRESS, "Vector", "borrow"),
-    VectorBorrowMut = (&CORE_CODE_ADDRESS, "Vector", "borrow_mut"),
-    VectorPushBack = (&CORE_CODE_ADDRESS, "Vector", "push_back"),
-    VectorPopBack = (&CORE_CODE_ADDRESS, "Vector", "pop_back"),
-    VectorDestroyEmpty = (&CORE_CODE_ADDRESS, "Vector", "destroy_empty"),
-    VectorSwap = (&CORE_CODE_ADDRESS, "Vector", "swap"),
-    AccountWriteEvent = (&CORE_CODE_ADDRESS, "LibraAccount", "write_to_event_store"),
-    AccountSaveAccount = (&CORE_CODE_ADDRESS, "LibraAccount", "save_account")
+    HashSha2_256 = (0, &CORE_CODE_ADDRESS, "Hash", "sha2_256"),
+    HashSha3_256 = (1, &CORE_CODE_ADDRESS, "Hash", "sha3_256"),
+    HashKeccak256 = (2, &CORE_CODE_ADDRESS, "Hash", "keccak_256") since 1,
+    HashSha2_512 = (3, &CORE_CODE_ADDRESS, "Hash", "sha2_512") since 1,
+    HashRipemd160 = (4, &CORE_CODE_ADDRESS, "Hash", "ripemd_160") since 1,
+    HashBlake2b256 = (5, &CORE_CODE_ADDRESS, "Hash", "blake2b_256") since 1,
+    HashBlake3 = (6, &CORE_CODE_ADDRESS, "Hash", "blake3") since 1,
+    HashSha3_512 = (7, &CORE_CODE_ADDRESS, "Hash", "sha3_512") since 1,
+    HashHmacSha256 = (8, &CORE_CODE_ADDRESS, "Hash", "hmac_sha256") since 1,
+    HashHkdfSha256 = (9, &CORE_CODE_ADDRESS, "Hash", "hkdf_sha256") since 1,
+    HashSha3_256WithDomain = (10, &CORE_CODE_ADDRESS, "Hash", "sha3_256_with_domain") since 1,
+    SigED25519Verify = (11, &CORE_CODE_ADDRESS, "Signature", "ed25519_verify"),
+    SigED25519ThresholdVerify = (12, &CORE_CODE_ADDRESS, "Signature", "ed25519_threshold_verify"),
+    SigSecp256k1Recover = (13, &CORE_CODE_ADDRESS, "Signature", "secp256k1_recover") since 1,
+    SigBls12381AggregateVerify =
+        (14, &CORE_CODE_ADDRESS, "Signature", "bls12381_aggregate_verify") since 1,
+    SigMultiEd25519Verify = (15, &CORE_CODE_ADDRESS, "Signature", "multi_ed25519_verify") since 1,
+    SigEcvrfVerify = (16, &CORE_CODE_ADDRESS, "Signature", "ecvrf_verify") since 1,
+    SigSchnorrVerify = (17, &CORE_CODE_ADDRESS, "Signature", "schnorr_verify") since 1,
+    RistrettoPointAdd = (18, &CORE_CODE_ADDRESS, "Ristretto255", "point_add") since 1,
+    RistrettoScalarMul = (19, &CORE_CODE_ADDRESS, "Ristretto255", "scalar_mul") since 1,
+    RistrettoPointIsValid = (20, &CORE_CODE_ADDRESS, "Ristretto255", "point_is_valid") since 1,
+    CryptoPedersenVerify = (21, &CORE_CODE_ADDRESS, "Crypto", "pedersen_verify") since 1,
+    AddrUtilToBytes = (22, &CORE_CODE_ADDRESS, "AddressUtil", "address_to_bytes"),
+    AddrUtilFromBytes = (23, &CORE_CODE_ADDRESS, "AddressUtil", "address_from_bytes") since 1,
+    AccountAddressFromAuthKey =
+        (24, &CORE_CODE_ADDRESS, "AccountUtil", "address_from_auth_key") since 1,
+    EventDeriveGuid = (25, &CORE_CODE_ADDRESS, "EventUtil", "guid") since 1,
+    U64UtilToBytes = (26, &CORE_CODE_ADDRESS, "U64Util", "u64_to_bytes"),
+    U64UtilToBytesBE = (27, &CORE_CODE_ADDRESS, "U64Util", "u64_to_bytes_be") since 1,
+    U64UtilFromBytes = (28, &CORE_CODE_ADDRESS, "U64Util", "bytes_to_u64") since 1,
+    U64UtilFromDecimalString =
+        (29, &CORE_CODE_ADDRESS, "U64Util", "u64_from_decimal_string") since 1,
+    U64UtilToDecimalString = (30, &CORE_CODE_ADDRESS, "U64Util", "u64_to_decimal_string") since 1,
+    U128UtilToBytes = (31, &CORE_CODE_ADDRESS, "U128Util", "u128_to_bytes") since 1,
+    U128UtilFromBytes = (32, &CORE_CODE_ADDRESS, "U128Util", "u128_from_bytes") since 1,
+    U64UtilCheckedAdd = (33, &CORE_CODE_ADDRESS, "U64Util", "checked_add") since 1,
+    U64UtilCheckedMul = (34, &CORE_CODE_ADDRESS, "U64Util", "checked_mul") since 1,
+    U128UtilCheckedAdd = (35, &CORE_CODE_ADDRESS, "U128Util", "checked_add") since 1,
+    U128UtilCheckedMul = (36, &CORE_CODE_ADDRESS, "U128Util", "checked_mul") since 1,
+    U128UtilToU64 = (37, &CORE_CODE_ADDRESS, "U128Util", "to_u64") since 1,
+    MathPowU64 = (38, &CORE_CODE_ADDRESS, "Math", "pow_u64") since 1,
+    MathSqrtU128 = (39, &CORE_CODE_ADDRESS, "Math", "sqrt_u128") since 1,
+    MathLog2FloorU64 = (40, &CORE_CODE_ADDRESS, "Math", "log2_floor_u64") since 1,
+    MathLeadingZerosU64 = (41, &CORE_CODE_ADDRESS, "Math", "leading_zeros_u64") since 1,
+    MathPopcountU64 = (42, &CORE_CODE_ADDRESS, "Math", "popcount_u64") since 1,
+    MathAddModU128 = (43, &CORE_CODE_ADDRESS, "Math", "add_mod_u128") since 1,
+    MathMulModU128 = (44, &CORE_CODE_ADDRESS, "Math", "mul_mod_u128") since 1,
+    MathDivModU128 = (45, &CORE_CODE_ADDRESS, "Math", "div_mod_u128") since 1,
+    MathMulDivU64 = (46, &CORE_CODE_ADDRESS, "Math", "mul_div_u64") since 1,
+    FixedPoint32CreateFromRational =
+        (47, &CORE_CODE_ADDRESS, "FixedPoint32", "create_from_rational") since 1,
+    FixedPoint32Multiply = (48, &CORE_CODE_ADDRESS, "FixedPoint32", "multiply_u64") since 1,
+    FixedPoint32Divide = (49, &CORE_CODE_ADDRESS, "FixedPoint32", "divide_u64") since 1,
+    BigUintAdd = (50, &CORE_CODE_ADDRESS, "BigUint", "add") since 1,
+    BigUintMul = (51, &CORE_CODE_ADDRESS, "BigUint", "mul") since 1,
+    BigUintCmp = (52, &CORE_CODE_ADDRESS, "BigUint", "cmp") since 1,
+    BigUintMod = (53, &CORE_CODE_ADDRESS, "BigUint", "mod") since 1,
+    BytearrayConcat = (54, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_concat"),
+    BytearraySlice = (55, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_slice") since 1,
+    BytearrayIndexOf = (56, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_index_of") since 1,
+    BytearrayXor = (57, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_xor") since 1,
+    BytearrayAnd = (58, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_and") since 1,
+    BytearrayOr = (59, &CORE_CODE_ADDRESS, "BytearrayUtil", "bytearray_or") since 1,
+    BytearrayConstantTimeEq = (60, &CORE_CODE_ADDRESS, "BytearrayUtil", "constant_time_eq") since 1,
+    Base58Encode = (61, &CORE_CODE_ADDRESS, "Encoding", "base58_encode") since 1,
+    Base58Decode = (62, &CORE_CODE_ADDRESS, "Encoding", "base58_decode") since 1,
+    Bech32Encode = (63, &CORE_CODE_ADDRESS, "Encoding", "bech32_encode") since 1,
+    Bech32Decode = (64, &CORE_CODE_ADDRESS, "Encoding", "bech32_decode") since 1,
+    Base64Encode = (65, &CORE_CODE_ADDRESS, "Encoding", "base64_encode") since 1,
+    Base64Decode = (66, &CORE_CODE_ADDRESS, "Encoding", "base64_decode") since 1,
+    StringIsValidUtf8 = (67, &CORE_CODE_ADDRESS, "StringUtil", "is_valid_utf8") since 1,
+    StringIsAscii = (68, &CORE_CODE_ADDRESS, "StringUtil", "is_ascii") since 1,
+    StringToUppercaseAscii = (69, &CORE_CODE_ADDRESS, "StringUtil", "to_uppercase_ascii") since 1,
+    LcsToBytes = (70, &CORE_CODE_ADDRESS, "LCS", "to_bytes") since 1,
+    LcsSerializedSize = (71, &CORE_CODE_ADDRESS, "LCS", "serialized_size") since 1,
+    LcsFromBytesU64 = (72, &CORE_CODE_ADDRESS, "LCS", "from_bytes_u64") since 1,
+    LcsFromBytesBool = (73, &CORE_CODE_ADDRESS, "LCS", "from_bytes_bool") since 1,
+    LcsFromBytesAddress = (74, &CORE_CODE_ADDRESS, "LCS", "from_bytes_address") since 1,
+    CompareGeneric = (75, &CORE_CODE_ADDRESS, "Compare", "cmp_lcs_bytes") since 1,
+    ReflectTypeOf = (76, &CORE_CODE_ADDRESS, "TypeReflection", "type_of") since 1,
+    ReflectTypeName = (77, &CORE_CODE_ADDRESS, "TypeReflection", "type_name") since 1,
+    ReflectTypesAreEqual = (78, &CORE_CODE_ADDRESS, "TypeReflection", "types_are_equal") since 1,
+    TxnSender = (79, &CORE_CODE_ADDRESS, "TransactionUtil", "sender") since 1,
+    TxnMaxGasAmount = (80, &CORE_CODE_ADDRESS, "TransactionUtil", "max_gas_amount") since 1,
+    TxnGasRemaining = (81, &CORE_CODE_ADDRESS, "TransactionUtil", "gas_remaining") since 1,
+    BlockGetHeight = (82, &CORE_CODE_ADDRESS, "Block", "get_height") since 1,
+    BlockGetTimestampMicros = (83, &CORE_CODE_ADDRESS, "Block", "get_timestamp_micros") since 1,
+    // Only resolved in builds for developing Move code, so validators never execute it
+    #[cfg(feature = "debug_natives")]
+    DebugPrint = (84, &CORE_CODE_ADDRESS, "Debug", "print") since 1,
+    #[cfg(feature = "debug_natives")]
+    DebugPrintStackTrace = (85, &CORE_CODE_ADDRESS, "Debug", "print_stack_trace") since 1,
+    VectorLength = (86, &CORE_CODE_ADDRESS, "Vector", "length"),
+    VectorEmpty = (87, &CORE_CODE_ADDRESS, "Vector", "empty"),
+    VectorSingleton = (88, &CORE_CODE_ADDRESS, "Vector", "singleton") since 1,
+    VectorFill = (89, &CORE_CODE_ADDRESS, "Vector", "fill") since 1,
+    VectorBorrow = (90, &CORE_CODE_ADDRESS, "Vector", "borrow"),
+    VectorBorrowMut = (91, &CORE_CODE_ADDRESS, "Vector", "borrow_mut"),
+    VectorPushBack = (92, &CORE_CODE_ADDRESS, "Vector", "push_back"),
+    VectorPopBack = (93, &CORE_CODE_ADDRESS, "Vector", "pop_back"),
+    VectorDestroyEmpty = (94, &CORE_CODE_ADDRESS, "Vector", "destroy_empty"),
+    VectorSwap = (95, &CORE_CODE_ADDRESS, "Vector", "swap"),
+    VectorInsert = (96, &CORE_CODE_ADDRESS, "Vector", "insert") since 1,
+    VectorTruncate = (97, &CORE_CODE_ADDRESS, "Vector", "truncate") since 1,
+    VectorRotate = (98, &CORE_CODE_ADDRESS, "Vector", "rotate") since 1,
+    VectorSortU64 = (99, &CORE_CODE_ADDRESS, "Vector", "sort_u64") since 1,
+    VectorBinarySearchU64 = (100, &CORE_CODE_ADDRESS, "Vector", "binary_search_u64") since 1,
+    VectorMaxU64 = (101, &CORE_CODE_ADDRESS, "Vector", "max_u64") since 1,
+    VectorMinU64 = (102, &CORE_CODE_ADDRESS, "Vector", "min_u64") since 1,
+    VectorSumU64 = (103, &CORE_CODE_ADDRESS, "Vector", "sum_u64") since 1,
+    AccountWriteEvent = (104, &CORE_CODE_ADDRESS, "LibraAccount", "write_to_event_store"),
+    AccountSaveAccount = (105, &CORE_CODE_ADDRESS, "LibraAccount", "save_account"),
+    // Only compiled in builds for unit testing Move code. Test modules calling `poison` fail to
+    // link, and so cannot be published, against any other build
+    #[cfg(feature = "testing")]
+    UnitTestCreateSigners =
+        (106, &CORE_CODE_ADDRESS, "UnitTest", "create_signers_for_testing") since 1,
+    #[cfg(feature = "testing")]
+    UnitTestPoison = (107, &CORE_CODE_ADDRESS, "UnitTest", "poison") since 1
+}
+
+/// Serializes the native function as its `Display` name rather than its position in the enum, so
+/// that recordings of native calls keep their meaning as natives are added.
+impl Serialize for NativeFunction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NativeFunction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
 }
 
 impl NativeFunction {
+    /// `resolve`, for a VM running at protocol version `version`: natives only available from
+    /// later versions are not resolved.
+    pub fn resolve_with_version(
+        module: &ModuleId,
+        function_name: &IdentStr,
+        version: u64,
+    ) -> Option<Self> {
+        Self::resolve(module, function_name).filter(|native| native.is_available(version))
+    }
+
+    /// Whether the native function exists at protocol version `version`.
+    pub fn is_available(self, version: u64) -> bool {
+        version >= self.min_version()
+    }
+
+    /// Fails with `NATIVE_FUNCTION_UNAVAILABLE`, with the version the native function is
+    /// available from as sub status, if it does not exist at protocol version `version`.
+    pub fn check_available(self, version: u64) -> VMResult<()> {
+        if self.is_available(version) {
+            return Ok(());
+        }
+        let msg = format!(
+            "{} is available from version {}, not {}",
+            self,
+            self.min_version(),
+            version
+        );
+        Err(VMStatus::new(StatusCode::NATIVE_FUNCTION_UNAVAILABLE)
+            .with_sub_status(self.min_version())
+            .with_message(msg))
+    }
+
+    /// The id of the module declaring the native function.
+    pub fn module_id(self) -> ModuleId {
+        ModuleId::new(self.address(), Identifier::new(self.module_name()).unwrap())
+    }
+
+    /// Every native function `resolve` resolves to, with the module id and function name
+    /// it is declared under.
+    pub(crate) fn declarations() -> Vec<(ModuleId, Identifier, Self)> {
+        Self::all()
+            .iter()
+            .map(|native| {
+                let name = Identifier::new(native.function_name()).unwrap();
+                (native.module_id(), name, *native)
+            })
+            .collect()
+    }
+
     /// Given the vector of aguments, it executes the native function.
+    ///
+    /// `context` carries the cost table the native is charged from and metadata about the
+    /// executing transaction, and receives the effects of natives that have some, such as emitted
+    /// events and saved resources.
+    /// `kinds` and `layouts` describe the type actuals `t`, for natives that must not drop
+    /// resources or need to know the shape of the values they are instantiated with.
+    /// Fails with `NATIVE_FUNCTION_INTERNAL_INCONSISTENCY`, before any native code runs, if `v`
+    /// does not hold `self.num_args()` arguments, with the errors of `check_type_args` if `t`
+    /// does not match `self.type_parameter_kinds()`, and with the errors of `complete_layouts`
+    /// if `layouts` does not describe `t`. Natives can therefore rely on their number of
+    /// arguments, type arguments and layouts without checking them. Once the native runs, an
+    /// `Err` it returns is the error of a `NativeResult` charging `self.base_cost(..)`, or the
+    /// remaining gas of `context` if the native stopped with `OUT_OF_GAS`. In debug builds and
+    /// with the `strict_natives` feature, it also fails with
+    /// `NATIVE_FUNCTION_INTERNAL_INCONSISTENCY` if the native returns values that do not match
+    /// its signature.
     pub fn dispatch(
         self,
-        t: Vec<TypeTag>,
-        v: VecDeque<Value>,
-        c: &CostTable,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: NativeArguments,
+    ) -> VMResult<NativeResult> {
+        let implementation = self.implementation();
+        check_num_args(implementation.arity(), v.len())?;
+        check_type_args(&self.type_parameter_kinds(), t, &kinds)?;
+        let layouts = complete_layouts(context, t, layouts)?;
+        let arguments = Arguments::new(self, v);
+        let base_cost = self.base_cost(context.cost_table());
+        let result = charge_errors(
+            context,
+            base_cost,
+            implementation.execute(context, t, kinds, layouts, arguments),
+        );
+        if CHECK_RETURN_VALUES {
+            let return_types = self
+                .declared_signature()
+                .map_or_else(Vec::new, |signature| signature.return_types);
+            check_return_values(format_args!("{}", self), &return_types, t, &result)?;
+        }
+        Ok(result)
+    }
+
+    /// `dispatch`, for a VM running at protocol version `version`. Fails with the error of
+    /// `check_available`, before any native code runs, if the native does not exist at that
+    /// version.
+    pub fn dispatch_with_version(
+        self,
+        version: u64,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: NativeArguments,
     ) -> VMResult<NativeResult> {
+        self.check_available(version)?;
+        self.dispatch(context, t, kinds, layouts, v)
+    }
+
+    /// The least the native function is charged for running: its cost in `table` for a single
+    /// unit of size, or nothing for natives that are not in the cost table.
+    pub fn base_cost(self, table: &CostTable) -> GasUnits<GasCarrier> {
+        self.cost_index()
+            .map_or_else(|| GasUnits::new(0), |index| native_gas(table, index, 1))
+    }
+
+    /// Whether the interpreter executes the native function itself, with access to the data
+    /// store, rather than through `dispatch`. `dispatch` still runs such natives against the
+    /// effects a `NativeContext` records, as test harnesses do.
+    pub fn is_runtime_handled(self) -> bool {
         match self {
-            Self::HashSha2_256 => hash::native_sha2_256(t, v, c),
-            Self::HashSha3_256 => hash::native_sha3_256(t, v, c),
-            Self::SigED25519Verify => signature::native_ed25519_signature_verification(t, v, c),
-            Self::SigED25519ThresholdVerify => {
-                signature::native_ed25519_threshold_signature_verification(t, v, c)
-            }
-            Self::AddrUtilToBytes => primitive_helpers::native_address_to_bytes(t, v, c),
-            Self::U64UtilToBytes => primitive_helpers::native_u64_to_bytes(t, v, c),
-            Self::BytearrayConcat => primitive_helpers::native_bytearray_concat(t, v, c),
-            Self::VectorLength => vector::native_length(t, v, c),
-            Self::VectorEmpty => vector::native_empty(t, v, c),
-            Self::VectorBorrow => vector::native_borrow(t, v, c),
-            Self::VectorBorrowMut => vector::native_borrow(t, v, c),
-            Self::VectorPushBack => vector::native_push_back(t, v, c),
-            Self::VectorPopBack => vector::native_pop(t, v, c),
-            Self::VectorDestroyEmpty => vector::native_destroy_empty(t, v, c),
-            Self::VectorSwap => vector::native_swap(t, v, c),
-            Self::AccountWriteEvent => Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(
-                "write_to_event_store does not have a native implementation".to_string(),
-            )),
-            Self::AccountSaveAccount => Err(VMStatus::new(StatusCode::UNREACHABLE)
-                .with_message("save_account does not have a native implementation".to_string())),
+            Self::AccountWriteEvent | Self::AccountSaveAccount => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the native function reads the metadata of the block the transaction executes in
+    /// from its `NativeContext`. The interpreter only reads that metadata from chain for them.
+    pub fn reads_block_metadata(self) -> bool {
+        match self {
+            Self::BlockGetHeight | Self::BlockGetTimestampMicros => true,
+            _ => false,
         }
     }
 
     /// The number of arguments to the native function,
     /// It is checked at publishing of the module that this matches the expected signature.
     pub fn num_args(self) -> usize {
-        match self {
-            Self::HashSha2_256 => 1,
-            Self::HashSha3_256 => 1,
-            Self::SigED25519Verify => 3,
-            Self::SigED25519ThresholdVerify => 4,
-            Self::AddrUtilToBytes => 1,
-            Self::U64UtilToBytes => 1,
-            Self::BytearrayConcat => 2,
-            Self::VectorLength => 1,
-            Self::VectorEmpty => 0,
-            Self::VectorBorrow => 2,
-            Self::VectorBorrowMut => 2,
-            Self::VectorPushBack => 2,
-            Self::VectorPopBack => 1,
-            Self::VectorDestroyEmpty => 1,
-            Self::VectorSwap => 3,
-            Self::AccountWriteEvent => 3,
-            Self::AccountSaveAccount => 2,
-        }
+        self.implementation().arity()
+    }
+
+    /// The kinds of the type parameters of the native function, as declared by its signature.
+    pub fn type_parameter_kinds(self) -> Vec<Kind> {
+        self.declared_signature()
+            .map_or_else(Vec::new, |signature| signature.type_formals)
+    }
+
+    /// The number of type arguments to the native function.
+    pub fn num_type_args(self) -> usize {
+        self.type_parameter_kinds().len()
+    }
+
+    /// The signature of the native function, with every struct it refers to given the same
+    /// struct handle. Its type formals and the shape of its argument and return types do not
+    /// depend on the structs it refers to.
+    fn declared_signature(self) -> Option<FunctionSignature> {
+        let any_struct = |_: &str| Some(StructHandleIndex::new(0));
+        self.implementation().signature(&any_struct)
     }
 
     /// The signature as defined in it's declaring module.
     /// It should NOT be generally inspected outside of it's declaring module as the various
-    /// struct handle indexes are not remapped into the local context.
+    /// struct handle indexes are not remapped into the local context; `signature_in_context`
+    /// remaps them.
     /// Returns:
     /// - `Err(NATIVE_FUNCTION_INTERNAL_INCONSISTENCY)` if the produced function signature
-    ///   is inconsistent with `self.num_args()`. This only fails if the native function was
-    ///   implemented incorrectly
+    ///   is inconsistent with `self.num_args()`, or refers to a type parameter it does not
+    ///   declare in its type formals. This only fails if the native function was implemented
+    ///   incorrectly
     /// - `Ok(None)` if a function signature could not be generated for the native function with
     ///   the given `ModuleView`, `m`
     /// - `Ok(Some(expected_function_signature))` otherwise
@@ -175,136 +805,251 @@ impl NativeFunction {
         self,
         m: Option<&ModuleView<T>>,
     ) -> VMResult<Option<FunctionSignature>> {
-        let res = match self.signature_(m) {
+        let module = self.module_id();
+        let struct_handle = |name: &str| m.and_then(|m| struct_handle_idx(m, &module, name));
+        let res = match self.implementation().signature(&struct_handle) {
             None => return Ok(None),
             Some(res) => res,
         };
-        if self.num_args() == res.arg_types.len() {
-            Ok(Some(res))
-        } else {
-            Err(
+        if self.num_args() != res.arg_types.len() {
+            return Err(
                 VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(
                     "Invalid native function declaration. Declared number of args does not match \
                      produced signature"
                         .to_owned(),
                 ),
-            )
+            );
         }
+        let type_formals = res.type_formals.len();
+        if !res
+            .arg_types
+            .iter()
+            .chain(res.return_types.iter())
+            .all(|token| type_parameters_declared(token, type_formals))
+        {
+            return Err(
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(
+                    "Invalid native function declaration. Produced signature uses more type \
+                     parameters than it declares"
+                        .to_owned(),
+                ),
+            );
+        }
+        Ok(Some(res))
     }
 
-    fn signature_<T: ModuleAccess>(self, m: Option<&ModuleView<T>>) -> Option<FunctionSignature> {
-        use SignatureToken::*;
-        macro_rules! simple {
-            ($args:expr, $ret:expr) => {{
-                simple!(vec![], $args, $ret)
-            }};
-            ($kinds:expr, $args:expr, $ret:expr) => {{
-                FunctionSignature {
-                    return_types: $ret,
-                    arg_types: $args,
-                    type_formals: $kinds,
-                }
-            }};
-        }
-        Some(match self {
-            Self::HashSha2_256 => simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
-            Self::HashSha3_256 => simple!(vec![Vector(Box::new(U8))], vec![Vector(Box::new(U8))]),
-            Self::SigED25519Verify => simple!(
-                vec![
-                    Vector(Box::new(U8)),
-                    Vector(Box::new(U8)),
-                    Vector(Box::new(U8))
-                ],
-                vec![Bool]
-            ),
-            Self::SigED25519ThresholdVerify => simple!(
-                vec![
-                    Vector(Box::new(U8)),
-                    Vector(Box::new(U8)),
-                    Vector(Box::new(U8)),
-                    Vector(Box::new(U8))
-                ],
-                vec![U64]
-            ),
-            Self::AddrUtilToBytes => simple!(vec![Address], vec![Vector(Box::new(U8))]),
-            Self::U64UtilToBytes => simple!(vec![U64], vec![Vector(Box::new(U8))]),
-            Self::BytearrayConcat => simple!(vec![ByteArray, ByteArray], vec![ByteArray]),
-            Self::VectorLength => simple!(
-                vec![Kind::All],
-                vec![Reference(Box::new(Vector(Box::new(TypeParameter(0)))))],
-                vec![U64]
-            ),
-            Self::VectorEmpty => simple!(
-                vec![Kind::All],
-                vec![],
-                vec![Vector(Box::new(TypeParameter(0)))]
-            ),
-            Self::VectorBorrow => simple!(
-                vec![Kind::All],
-                vec![Reference(Box::new(Vector(Box::new(TypeParameter(0))))), U64],
-                vec![Reference(Box::new(TypeParameter(0)))]
-            ),
-            Self::VectorBorrowMut => simple!(
-                vec![Kind::All],
-                vec![
-                    MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
-                    U64
-                ],
-                vec![MutableReference(Box::new(TypeParameter(0)))]
-            ),
-            Self::VectorPushBack => simple!(
-                vec![Kind::All],
-                vec![
-                    MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
-                    TypeParameter(0),
-                ],
-                vec![]
-            ),
-            Self::VectorPopBack => simple!(
-                vec![Kind::All],
-                vec![MutableReference(Box::new(Vector(Box::new(TypeParameter(
-                    0
-                )))))],
-                vec![TypeParameter(0)]
-            ),
-            Self::VectorDestroyEmpty => simple!(
-                vec![Kind::All],
-                vec![Vector(Box::new(TypeParameter(0)))],
-                vec![]
-            ),
-            Self::VectorSwap => simple!(
-                vec![Kind::All],
-                vec![
-                    MutableReference(Box::new(Vector(Box::new(TypeParameter(0))))),
-                    U64,
-                    U64,
-                ],
-                vec![]
-            ),
-            Self::AccountWriteEvent => simple!(
-                vec![Kind::Unrestricted],
-                vec![Vector(Box::new(U8)), U64, TypeParameter(0)],
-                vec![]
-            ),
-            Self::AccountSaveAccount => {
-                let type_formals = vec![];
-                let t_idx = struct_handle_idx(m?, "T")?;
-                let arg_types = vec![Address, Struct(t_idx, vec![])];
-                let return_types = vec![];
-                FunctionSignature {
-                    type_formals,
-                    arg_types,
-                    return_types,
-                }
+    /// The signature as defined in its declaring module, `declaring`, with its struct handle
+    /// indexes remapped into the struct handles of `caller`, so that it can be compared with a
+    /// declaration of the native function in `caller`.
+    /// Returns:
+    /// - the errors of `signature`
+    /// - `Err(LOOKUP_FAILED)` if the signature refers to a struct `caller` has no handle for
+    /// - `Ok(None)` if a function signature could not be generated for the native function with
+    ///   `declaring`
+    /// - `Ok(Some(expected_function_signature))` otherwise
+    pub fn signature_in_context<T: ModuleAccess>(
+        self,
+        declaring: &ModuleView<T>,
+        caller: &ModuleView<T>,
+    ) -> VMResult<Option<FunctionSignature>> {
+        let res = match self.signature(Some(declaring))? {
+            None => return Ok(None),
+            Some(res) => res,
+        };
+        let remap = |tokens: Vec<SignatureToken>| {
+            tokens
+                .iter()
+                .map(|token| remap_struct_handles(token, declaring, caller))
+                .collect::<VMResult<Vec<_>>>()
+        };
+        Ok(Some(FunctionSignature {
+            return_types: remap(res.return_types)?,
+            arg_types: remap(res.arg_types)?,
+            type_formals: res.type_formals,
+        }))
+    }
+}
+
+/// Helper for checking that a native function taking `expected` arguments is called with `found`
+/// arguments. The bytecode verifier guarantees it, so this only fails if it was bypassed.
+pub(crate) fn check_num_args(expected: usize, found: usize) -> VMResult<()> {
+    if expected == found {
+        return Ok(());
+    }
+    let msg = format!(
+        "native function expects {} arguments but was called with {}",
+        expected, found
+    );
+    Err(VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(msg))
+}
+
+/// Helper for giving a native function called with the type arguments `t` one layout per type
+/// argument. A caller passing no layouts gets them resolved from `context`, as tests and
+/// benchmarks calling natives without a VM do. Fails with `NUMBER_OF_TYPE_ACTUALS_MISMATCH` if
+/// `layouts` is not empty but does not hold as many layouts as `t`, and with the error of
+/// `NativeContext::type_to_layout` if a layout cannot be resolved.
+pub(crate) fn complete_layouts(
+    context: &dyn NativeContext,
+    t: &[TypeTag],
+    layouts: Vec<Type>,
+) -> VMResult<Vec<Type>> {
+    if layouts.is_empty() {
+        return t.iter().map(|tag| context.type_to_layout(tag)).collect();
+    }
+    if layouts.len() != t.len() {
+        let msg = format!(
+            "native function called with {} type arguments but {} layouts",
+            t.len(),
+            layouts.len()
+        );
+        return Err(VMStatus::new(StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH).with_message(msg));
+    }
+    Ok(layouts)
+}
+
+/// Helper for checking that a native function with the type parameters `type_formals` is called
+/// with as many type arguments `t`, of kinds allowed for their parameter. `kinds` are the kinds of
+/// `t` as the caller classified them; a type argument the caller did not classify is let through.
+/// Fails with `NUMBER_OF_TYPE_ACTUALS_MISMATCH` or `CONTRAINT_KIND_MISMATCH`.
+pub(crate) fn check_type_args(
+    type_formals: &[Kind],
+    t: &[TypeTag],
+    kinds: &[Kind],
+) -> VMResult<()> {
+    if type_formals.len() != t.len() {
+        let msg = format!(
+            "native function expects {} type arguments but was called with {}",
+            type_formals.len(),
+            t.len()
+        );
+        return Err(VMStatus::new(StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH).with_message(msg));
+    }
+    for (idx, (type_formal, tag)) in type_formals.iter().zip(t).enumerate() {
+        match kinds.get(idx) {
+            Some(kind) if !kind.is_sub_kind_of(*type_formal) => {
+                let msg = format!(
+                    "type argument {} of kind {:?} given for a parameter of kind {:?}",
+                    idx, kind, type_formal
+                );
+                return Err(VMStatus::new(StatusCode::CONTRAINT_KIND_MISMATCH).with_message(msg));
             }
-        })
+            Some(_) => (),
+            None if *type_formal == Kind::All => (),
+            None => warn!(
+                "cannot check that type argument {:?} is of kind {:?}",
+                tag, type_formal
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Whether the values natives return are checked against their signature, see
+/// `check_return_values`. Only natives implemented incorrectly fail that check, so it is only
+/// made in debug builds and with the `strict_natives` feature.
+pub(crate) const CHECK_RETURN_VALUES: bool =
+    cfg!(any(debug_assertions, feature = "strict_natives"));
+
+/// Helper for checking that the values returned by the native function `name`, if it succeeded,
+/// have the shape of its `return_types` instantiated with the type arguments `t`.
+pub(crate) fn check_return_values(
+    name: fmt::Arguments,
+    return_types: &[SignatureToken],
+    t: &[TypeTag],
+    result: &NativeResult,
+) -> VMResult<()> {
+    let values = match &result.result {
+        Ok(values) => values,
+        Err(_) => return Ok(()),
+    };
+    let msg = if values.len() != return_types.len() {
+        format!(
+            "native function {} returned {} values but declares {}",
+            name,
+            values.len(),
+            return_types.len()
+        )
+    } else if let Some(idx) = values
+        .iter()
+        .zip(return_types)
+        .position(|(value, token)| !value.has_shape_of(token, t))
+    {
+        format!(
+            "native function {} returned {:?} at position {} for a value of type {:?}",
+            name, values[idx], idx, return_types[idx]
+        )
+    } else {
+        return Ok(());
+    };
+    Err(VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(msg))
+}
+
+/// Helper for checking that every type parameter in `token` is one of the first `type_formals`
+pub(crate) fn type_parameters_declared(token: &SignatureToken, type_formals: usize) -> bool {
+    use SignatureToken::*;
+    match token {
+        TypeParameter(idx) => (*idx as usize) < type_formals,
+        Vector(token) | Reference(token) | MutableReference(token) => {
+            type_parameters_declared(token, type_formals)
+        }
+        Struct(_, tokens) => tokens
+            .iter()
+            .all(|token| type_parameters_declared(token, type_formals)),
+        Bool | U8 | U64 | U128 | ByteArray | Address => true,
     }
 }
 
-/// Helper for finding non-native struct handle index
-fn struct_handle_idx<T: ModuleAccess>(m: &ModuleView<T>, name: &str) -> Option<StructHandleIndex> {
+/// Helper for replacing the struct handle indexes in `token`, into the struct handles of `from`,
+/// with the indexes of the handles for the same structs in `to`
+fn remap_struct_handles<T: ModuleAccess>(
+    token: &SignatureToken,
+    from: &ModuleView<T>,
+    to: &ModuleView<T>,
+) -> VMResult<SignatureToken> {
+    use SignatureToken::*;
+    let remap = |token: &SignatureToken| remap_struct_handles(token, from, to);
+    Ok(match token {
+        Struct(idx, tokens) => {
+            let handle = from.struct_handles().nth(idx.0 as usize).ok_or_else(|| {
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(
+                    format!(
+                        "Struct handle {} is not in module {}",
+                        idx,
+                        from.id().name()
+                    ),
+                )
+            })?;
+            let (module, name) = (handle.module_id(), handle.name());
+            let remapped = to
+                .struct_handles()
+                .position(|handle| handle.module_id() == module && handle.name() == name)
+                .ok_or_else(|| {
+                    VMStatus::new(StatusCode::LOOKUP_FAILED).with_message(format!(
+                        "Module {} has no struct handle for {}::{}",
+                        to.id().name(),
+                        module.name(),
+                        name
+                    ))
+                })?;
+            let tokens = tokens.iter().map(remap).collect::<VMResult<_>>()?;
+            Struct(StructHandleIndex::new(remapped as u16), tokens)
+        }
+        Vector(token) => Vector(Box::new(remap(token)?)),
+        Reference(token) => Reference(Box::new(remap(token)?)),
+        MutableReference(token) => MutableReference(Box::new(remap(token)?)),
+        Bool | U8 | U64 | U128 | ByteArray | Address | TypeParameter(_) => token.clone(),
+    })
+}
+
+/// Helper for finding the handle of the struct `name` declared by `module`. Structs of the same
+/// name declared by other modules are not it.
+fn struct_handle_idx<T: ModuleAccess>(
+    m: &ModuleView<T>,
+    module: &ModuleId,
+    name: &str,
+) -> Option<StructHandleIndex> {
     m.struct_handles().enumerate().find_map(|(idx, handle)| {
-        if handle.name().as_str() == name {
+        if handle.name().as_str() == name && &handle.module_id() == module {
             Some(StructHandleIndex::new(idx as u16))
         } else {
             None
@@ -312,9 +1057,23 @@ fn struct_handle_idx<T: ModuleAccess>(m: &ModuleView<T>, name: &str) -> Option<S
     })
 }
 
+/// The error of a native function popping more arguments than it was called with. `dispatch`
+/// checks the number of arguments before natives run, so this is an invariant violation.
+pub fn missing_argument_error() -> VMStatus {
+    VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY)
+        .with_message("Native function popped more arguments than it was called with".to_owned())
+}
+
+/// Pops the last argument in `$arguments` as a `$t`, returning early with
+/// `missing_argument_error` if there is none and with the error of `value_as` if it is not a
+/// `$t`. This is for natives registered with a `NativeFunctionTable`, which are passed
+/// `NativeArguments`: built-in natives pop through `Arguments` instead.
 #[macro_export]
 macro_rules! pop_arg {
     ($arguments:ident, $t:ty) => {{
-        $arguments.pop_back().unwrap().value_as::<$t>()?
+        $arguments
+            .pop()
+            .ok_or_else($crate::native_functions::dispatch::missing_argument_error)?
+            .value_as::<$t>()?
     }};
 }
@@ -0,0 +1,98 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The sub-statuses natives fail with, one module per native module, so that callers can tell
+//! failures apart by their sub-status rather than by their message. User errors are aborts, with
+//! codes from the namespace of the module in `abort_codes`. Other failures carry a sub-status from
+//! the same namespace with another major status, through `NativeResult::err_with_sub_status`.
+//!
+//! Every module lists its sub-statuses in `ALL`, and no two sub-statuses of a module are equal.
+
+/// Declares sub-status constants, and `ALL` listing them with their names.
+macro_rules! sub_statuses {
+    ($($(#[$attr:meta])* $name:ident = $value:expr;)*) => {
+        $(
+            $(#[$attr])*
+            pub const $name: u64 = $value;
+        )*
+
+        /// Every sub-status of the module, with its name.
+        pub const ALL: &[(&str, u64)] = &[$((stringify!($name), $name)),*];
+    };
+}
+
+pub mod signature {
+    use crate::native_functions::abort_codes;
+
+    /// Starting error code number
+    pub const DEFAULT_ERROR_CODE: u64 = abort_codes::SIGNATURE;
+
+    sub_statuses! {
+        /// Batch signature verification failed
+        SIGNATURE_VERIFICATION_FAILURE = DEFAULT_ERROR_CODE + 1;
+        /// Public keys deserialization error
+        PUBLIC_KEY_DESERIALIZATION_FAILURE = DEFAULT_ERROR_CODE + 2;
+        /// Signatures deserialization error
+        SIGNATURE_DESERIALIZATION_FAILURE = DEFAULT_ERROR_CODE + 3;
+        /// Bitmap is all zeros
+        ZERO_BITMAP_FAILURE = DEFAULT_ERROR_CODE + 4;
+        /// Invalid bitmap length
+        INVALID_BITMAP_LENGTH_FAILURE = DEFAULT_ERROR_CODE + 5;
+        /// Mismatch between bitmap's Hamming weight and number or size of signatures
+        SIGNATURE_SIZE_FAILURE = DEFAULT_ERROR_CODE + 6;
+        /// Bitmap points to a non-existent key
+        BITMAP_PUBLIC_KEY_SIZE_FAILURE = DEFAULT_ERROR_CODE + 7;
+        /// Length of bytes of concatenated keys exceeds the maximum allowed
+        OVERSIZED_PUBLIC_KEY_SIZE_FAILURE = DEFAULT_ERROR_CODE + 8;
+        /// Concatenated Ed25519 public keys should be a multiple of 32 bytes
+        INVALID_PUBLIC_KEY_SIZE_FAILURE = DEFAULT_ERROR_CODE + 9;
+        /// No public keys to verify an aggregate signature against
+        EMPTY_PUBLIC_KEYS_FAILURE = DEFAULT_ERROR_CODE + 10;
+        /// More public keys than `signature::MAX_AGGREGATE_PUBLIC_KEYS`
+        TOO_MANY_PUBLIC_KEYS_FAILURE = DEFAULT_ERROR_CODE + 11;
+        /// Schnorr public key is not a 32-byte x-only key
+        INVALID_SCHNORR_PUBLIC_KEY_LENGTH_FAILURE = DEFAULT_ERROR_CODE + 12;
+        /// Schnorr signature is not 64 bytes
+        INVALID_SCHNORR_SIGNATURE_LENGTH_FAILURE = DEFAULT_ERROR_CODE + 13;
+        /// VRF proof deserialization error
+        PROOF_DESERIALIZATION_FAILURE = DEFAULT_ERROR_CODE + 14;
+        /// Message signed by a threshold signature is not a hash
+        INVALID_MESSAGE_LENGTH_FAILURE = DEFAULT_ERROR_CODE + 15;
+    }
+}
+
+pub mod vector {
+    use crate::native_functions::abort_codes;
+    use libra_types::vm_error::sub_status;
+
+    /// Starting error code number. The codes are those of `sub_status::NFE_VECTOR_*`, which abort
+    /// codes keep the offsets of the `NATIVE_FUNCTION_ERROR` sub-statuses they replaced.
+    pub const DEFAULT_ERROR_CODE: u64 = abort_codes::VECTOR;
+
+    sub_statuses! {
+        /// Index is past the end of the vector
+        INDEX_OUT_OF_BOUNDS = sub_status::NFE_VECTOR_INDEX_OUT_OF_BOUNDS;
+        /// Popping from an empty vector
+        POP_EMPTY_VEC = sub_status::NFE_VECTOR_POP_EMPTY;
+        /// Destroying a vector that still has elements
+        DESTROY_NON_EMPTY_VEC = sub_status::NFE_VECTOR_DESTROY_NON_EMPTY;
+        /// Filling a vector with more than `MAX_FILL_LENGTH` or
+        /// `NativeContext::max_vector_length` elements
+        FILL_TOO_LONG = sub_status::NFE_VECTOR_FILL_TOO_LONG;
+        /// Growing a vector past `NativeContext::max_vector_length` elements
+        VECTOR_TOO_LONG = sub_status::NFE_VECTOR_TOO_LONG;
+        /// Truncating a vector of resources, which would destroy them. Reported with a
+        /// `NATIVE_FUNCTION_ERROR`, like the other element types the natives do not support
+        TRUNCATE_RESOURCE_VEC = sub_status::NFE_VECTOR_TRUNCATE_RESOURCE;
+        /// Sorting a vector of another type than `u64`
+        SORT_NON_U64_VEC = sub_status::NFE_VECTOR_SORT_NON_U64;
+        /// Searching a vector of another type than `u64`
+        BINARY_SEARCH_NON_U64_VEC = sub_status::NFE_VECTOR_BINARY_SEARCH_NON_U64;
+        /// Aggregating a vector of another type than `u64`
+        AGGREGATE_NON_U64_VEC = sub_status::NFE_VECTOR_AGGREGATE_NON_U64;
+    }
+}
+
+/// The sub-statuses of every module, with its name.
+pub const MODULES: &[(&str, &[(&str, u64)])] =
+    &[("signature", signature::ALL), ("vector", vector::ALL)];
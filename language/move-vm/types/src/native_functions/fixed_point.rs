@@ -0,0 +1,115 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Arithmetic on FixedPoint32 numbers, exchanged as the raw `u64` of a Q32.32 value: the upper 32
+//! bits hold the integer part and the lower 32 bits the fractional part. Intermediate results are
+//! computed in `u128` so they are exact, and every result is rounded toward zero.
+
+use crate::{
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::convert::TryFrom;
+use vm::{
+    errors::VMResult,
+    gas_schedule::{CostTable, NativeCostIndex},
+};
+
+/// Number of fractional bits in a FixedPoint32 value
+const FRACTIONAL_BITS: u32 = 32;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::FIXED_POINT;
+/// The denominator or divisor is zero
+pub const DIVISION_BY_ZERO: u64 = DEFAULT_ERROR_CODE + 1;
+/// The result does not fit in a `u64`
+pub const RESULT_OVERFLOW: u64 = DEFAULT_ERROR_CODE + 2;
+
+/// Returns the FixedPoint32 value closest to, and not greater than, `numerator / denominator`.
+pub fn native_create_from_rational(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    binary_op(
+        NativeCostIndex::FIXED_POINT32_CREATE_FROM_RATIONAL,
+        arguments,
+        context.cost_table(),
+        |numerator, denominator| {
+            if denominator == 0 {
+                return Err(DIVISION_BY_ZERO);
+            }
+            to_u64((u128::from(numerator) << FRACTIONAL_BITS) / u128::from(denominator))
+        },
+    )
+}
+
+/// Multiplies the integer `value` by the FixedPoint32 `multiplier`, truncating the fractional part
+/// of the product.
+pub fn native_multiply_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    binary_op(
+        NativeCostIndex::FIXED_POINT32_MULTIPLY,
+        arguments,
+        context.cost_table(),
+        |value, multiplier| to_u64((u128::from(value) * u128::from(multiplier)) >> FRACTIONAL_BITS),
+    )
+}
+
+/// Divides the integer `value` by the FixedPoint32 `divisor`, truncating the fractional part of
+/// the quotient.
+pub fn native_divide_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    binary_op(
+        NativeCostIndex::FIXED_POINT32_DIVIDE,
+        arguments,
+        context.cost_table(),
+        |value, divisor| {
+            if divisor == 0 {
+                return Err(DIVISION_BY_ZERO);
+            }
+            to_u64((u128::from(value) << FRACTIONAL_BITS) / u128::from(divisor))
+        },
+    )
+}
+
+fn to_u64(result: u128) -> Result<u64, u64> {
+    u64::try_from(result).map_err(|_| RESULT_OVERFLOW)
+}
+
+/// Applies `op` to two `u64` arguments, turning an `Err` into a charged native error carrying the
+/// returned sub status.
+fn binary_op(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
+    cost_table: &CostTable,
+    op: fn(u64, u64) -> Result<u64, u64>,
+) -> VMResult<NativeResult> {
+    let b = arguments.pop_as::<u64>()?;
+    let a = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, 1);
+    match op(a, b) {
+        Ok(result) => Ok(NativeResult::ok(cost, vec![Value::u64(result)])),
+        Err(sub_status) => Ok(NativeResult::err(
+            cost,
+            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(sub_status),
+        )),
+    }
+}
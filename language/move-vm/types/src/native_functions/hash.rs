@@ -2,55 +2,393 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    native_functions::dispatch::{native_gas, NativeResult},
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{charge_or_stop, native_gas, native_gas_detailed, NativeResult},
+    },
     values::Value,
 };
-use libra_crypto::HashValue;
+use blake2::{
+    digest::{Input, VariableOutput},
+    VarBlake2b,
+};
+use hmac::{Hmac, Mac};
+use libra_crypto::{hkdf::Hkdf, HashValue};
 use libra_types::{
     language_storage::TypeTag,
     vm_error::{StatusCode, VMStatus},
 };
-use sha2::{Digest, Sha256};
-use std::collections::VecDeque;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Keccak256, Sha3_256, Sha3_512};
 use vm::{
     errors::VMResult,
-    gas_schedule::{CostTable, NativeCostIndex},
+    gas_schedule::{GasCarrier, GasUnits, NativeCostIndex},
 };
 
+/// Output length in bytes of the BLAKE2b-256 digest.
+const BLAKE2B_256_OUTPUT_LENGTH: usize = 32;
+
+/// Size in bytes of a BLAKE3 chunk, the unit BLAKE3 gas is charged in.
+pub const BLAKE3_CHUNK_LEN: usize = 1024;
+
+/// Size in bytes of the chunks the other hashes digest their input in. Natives check that the
+/// transaction can pay for their input so far before digesting each chunk.
+pub const HASH_CHUNK_LEN: usize = 1024;
+
+/// Largest output HKDF-SHA256 can derive, as defined in RFC 5869 (255 * HashLen).
+pub const HKDF_SHA256_MAX_OUTPUT_LENGTH: u64 = 255 * 32;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::HASH;
+/// The requested HKDF output length exceeds `HKDF_SHA256_MAX_OUTPUT_LENGTH`
+pub const HKDF_INVALID_OUTPUT_LENGTH: u64 = DEFAULT_ERROR_CODE + 1;
+
 pub fn native_sha2_256(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
-) -> VMResult<NativeResult> {
-    if arguments.len() != 1 {
-        let msg = format!(
-            "wrong number of arguments for sha2_256 expected 1 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
-    }
-    let hash_arg = pop_arg!(arguments, Vec<u8>);
-    let cost = native_gas(cost_table, NativeCostIndex::SHA2_256, hash_arg.len());
-    let hash_vec = Sha256::digest(hash_arg.as_slice()).to_vec();
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SHA2_256,
+        hash_arg.len(),
+    );
+    let mut hasher = Sha256::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::SHA2_256, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
     let return_values = vec![Value::vector_u8(hash_vec)];
-    Ok(NativeResult::ok(cost, return_values))
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
 }
 
 pub fn native_sha3_256(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
-) -> VMResult<NativeResult> {
-    if arguments.len() != 1 {
-        let msg = format!(
-            "wrong number of arguments for sha3_256 expected 1 found {}",
-            arguments.len()
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SHA3_256,
+        hash_arg.len(),
+    );
+    let mut hasher = Sha3_256::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::SHA3_256, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_keccak_256(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::KECCAK_256,
+        hash_arg.len(),
+    );
+    let mut hasher = Keccak256::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::KECCAK_256, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_sha2_512(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SHA2_512,
+        hash_arg.len(),
+    );
+    let mut hasher = Sha512::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::SHA2_512, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_ripemd_160(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::RIPEMD_160,
+        hash_arg.len(),
+    );
+    let mut hasher = Ripemd160::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::RIPEMD_160, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_blake2b_256(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::BLAKE2B_256,
+        hash_arg.len(),
+    );
+    // BLAKE2b-256 encodes the digest length in its parameter block, so it is not a truncation of
+    // BLAKE2b-512 and must be computed with a variable output hasher.
+    let mut hasher = VarBlake2b::new(BLAKE2B_256_OUTPUT_LENGTH).map_err(|_| {
+        VMStatus::new(StatusCode::UNREACHABLE)
+            .with_message("invalid output length for blake2b_256".to_string())
+    })?;
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::BLAKE2B_256, len),
+        |chunk| hasher.input(chunk),
+    )?;
+    let hash_vec = hasher.vec_result();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_blake3(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::BLAKE3,
+        blake3_chunk_count(hash_arg.len()),
+    );
+    let mut hasher = blake3::Hasher::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        BLAKE3_CHUNK_LEN,
+        |len| {
+            native_gas(
+                context.cost_table(),
+                NativeCostIndex::BLAKE3,
+                blake3_chunk_count(len),
+            )
+        },
+        |chunk| {
+            hasher.update(chunk);
+        },
+    )?;
+    let hash_vec = hasher.finalize().as_bytes().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+/// Feeds `input` to `digest` a chunk of `chunk_len` bytes at a time. Before each chunk, checks
+/// that the transaction can pay `cost` of the number of bytes digested once the chunk is.
+fn digest_in_chunks(
+    context: &dyn NativeContext,
+    input: &[u8],
+    chunk_len: usize,
+    cost: impl Fn(usize) -> GasUnits<GasCarrier>,
+    mut digest: impl FnMut(&[u8]),
+) -> VMResult<()> {
+    let mut digested = 0;
+    for chunk in input.chunks(chunk_len) {
+        digested += chunk.len();
+        charge_or_stop(context, cost(digested))?;
+        digest(chunk);
+    }
+    Ok(())
+}
+
+/// Number of chunks BLAKE3 compresses for an input of `len` bytes. The empty input still
+/// compresses one (empty) chunk.
+pub fn blake3_chunk_count(len: usize) -> usize {
+    std::cmp::max(1, (len + BLAKE3_CHUNK_LEN - 1) / BLAKE3_CHUNK_LEN)
+}
+
+pub fn native_sha3_512(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let hash_arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SHA3_512,
+        hash_arg.len(),
+    );
+    let mut hasher = Sha3_512::new();
+    digest_in_chunks(
+        context,
+        &hash_arg,
+        HASH_CHUNK_LEN,
+        |len| native_gas(context.cost_table(), NativeCostIndex::SHA3_512, len),
+        |chunk| Input::input(&mut hasher, chunk),
+    )?;
+    let hash_vec = hasher.result().to_vec();
+    let return_values = vec![Value::vector_u8(hash_vec)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_hmac_sha256(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let message = arguments.pop_vec_of::<u8>()?;
+    let key = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::HMAC_SHA256,
+        key.len() + message.len(),
+    );
+    let message_cost = |len| {
+        native_gas(
+            context.cost_table(),
+            NativeCostIndex::HMAC_SHA256,
+            key.len() + len,
+        )
+    };
+    charge_or_stop(context, message_cost(0))?;
+    // HMAC accepts keys of any length: keys longer than the block size are hashed first.
+    let mut mac = Hmac::<Sha256>::new_varkey(key.as_slice()).map_err(|_| {
+        VMStatus::new(StatusCode::UNREACHABLE)
+            .with_message("invalid key length for hmac_sha256".to_string())
+    })?;
+    digest_in_chunks(context, &message, HASH_CHUNK_LEN, message_cost, |chunk| {
+        mac.input(chunk)
+    })?;
+    let tag = mac.result().code().to_vec();
+    let return_values = vec![Value::vector_u8(tag)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+pub fn native_hkdf_sha256(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let length = arguments.pop_as::<u64>()?;
+    let info = arguments.pop_vec_of::<u8>()?;
+    let salt = arguments.pop_vec_of::<u8>()?;
+    let ikm = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    // The inputs are charged for as well as the output, since extracting hashes all of them.
+    let input_len = ikm.len() + salt.len() + info.len();
+    if length > HKDF_SHA256_MAX_OUTPUT_LENGTH {
+        let (cost, breakdown) = native_gas_detailed(
+            context.cost_table(),
+            NativeCostIndex::HKDF_SHA256,
+            std::cmp::max(input_len, 1),
+        );
+        return Ok(
+            NativeResult::abort(cost, HKDF_INVALID_OUTPUT_LENGTH).with_cost_breakdown(breakdown)
         );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
     }
-    let hash_arg = pop_arg!(arguments, Vec<u8>);
-    let cost = native_gas(cost_table, NativeCostIndex::SHA3_256, hash_arg.len());
-    let hash_vec = HashValue::from_sha3_256(hash_arg.as_slice()).to_vec();
+    let length = length as usize;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::HKDF_SHA256,
+        input_len + length,
+    );
+    // The output is derived in one go, so the budget is checked for all of it up front
+    charge_or_stop(context, cost)?;
+    // RFC 5869 allows an empty output, which `Hkdf` rejects.
+    let okm = if length == 0 {
+        vec![]
+    } else {
+        // An empty salt is equivalent to the RFC 5869 default of HashLen zero bytes, since HMAC
+        // pads its key with zeros to the block size.
+        Hkdf::<Sha256>::extract_then_expand(
+            Some(salt.as_slice()),
+            ikm.as_slice(),
+            Some(info.as_slice()),
+            length,
+        )
+        .map_err(|e| VMStatus::new(StatusCode::UNREACHABLE).with_message(e.to_string()))?
+    };
+    let return_values = vec![Value::vector_u8(okm)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+/// Hash `message` with SHA3-256 under the domain tag `domain`. The digest is taken over
+/// `len(domain) || domain || message`, where `len(domain)` is the length of the tag in bytes
+/// encoded as a little-endian `u64`. The length prefix makes the framing unambiguous, so no two
+/// distinct `(domain, message)` pairs hash the same preimage.
+pub fn native_sha3_256_with_domain(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let message = arguments.pop_vec_of::<u8>()?;
+    let domain = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SHA3_256_WITH_DOMAIN,
+        domain.len() + message.len(),
+    );
+    charge_or_stop(context, cost)?;
+    let mut preimage = Vec::with_capacity(8 + domain.len() + message.len());
+    preimage.extend_from_slice(&(domain.len() as u64).to_le_bytes());
+    preimage.extend_from_slice(&domain);
+    preimage.extend_from_slice(&message);
+    let hash_vec = HashValue::from_sha3_256(preimage.as_slice()).to_vec();
     let return_values = vec![Value::vector_u8(hash_vec)];
-    Ok(NativeResult::ok(cost, return_values))
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
 }
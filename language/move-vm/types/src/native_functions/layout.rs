@@ -0,0 +1,179 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolution of the layouts of types, for natives that serialize or inspect values of the types
+//! they are instantiated with.
+
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_structs::dispatch::resolve_native_struct,
+};
+use libra_types::{
+    language_storage::{ModuleId, StructTag, TypeTag},
+    vm_error::{StatusCode, VMStatus},
+};
+use std::collections::HashMap;
+use vm::{
+    access::ModuleAccess, errors::VMResult, file_format::SignatureToken,
+    views::StructDefinitionView,
+};
+
+/// Turns type tags into the layouts of the values of those types.
+pub trait TypeLayoutResolver {
+    /// The layout of the values of type `tag`, or an error if a struct it refers to cannot be
+    /// resolved.
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type>;
+}
+
+/// Resolves the layout of `tag`, asking `struct_layout` for the layouts of the struct types it
+/// refers to.
+pub fn resolve_layout(
+    tag: &TypeTag,
+    struct_layout: &dyn Fn(&StructTag) -> VMResult<StructDef>,
+) -> VMResult<Type> {
+    Ok(match tag {
+        TypeTag::Bool => Type::Bool,
+        TypeTag::U8 => Type::U8,
+        TypeTag::U64 => Type::U64,
+        TypeTag::U128 => Type::U128,
+        TypeTag::ByteArray => Type::ByteArray,
+        TypeTag::Address => Type::Address,
+        TypeTag::Vector(tag) => Type::Vector(Box::new(resolve_layout(tag, struct_layout)?)),
+        TypeTag::Struct(struct_tag) => Type::Struct(struct_layout(struct_tag)?),
+    })
+}
+
+fn qualified_name(struct_tag: &StructTag) -> String {
+    format!(
+        "{}::{}::{}",
+        struct_tag.address, struct_tag.module, struct_tag.name
+    )
+}
+
+/// A `TypeLayoutResolver` for callers without modules loaded: it resolves the types that do not
+/// refer to structs, and fails on the others.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StubLayoutResolver;
+
+impl TypeLayoutResolver for StubLayoutResolver {
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type> {
+        resolve_layout(tag, &|struct_tag| {
+            let msg = format!(
+                "cannot resolve the layout of {} without modules",
+                qualified_name(struct_tag)
+            );
+            Err(VMStatus::new(StatusCode::TYPE_RESOLUTION_FAILURE).with_message(msg))
+        })
+    }
+}
+
+/// A `TypeLayoutResolver` resolving structs from the modules declaring them.
+pub struct ModuleLayoutResolver<'a, T> {
+    modules: HashMap<ModuleId, &'a T>,
+}
+
+impl<'a, T: ModuleAccess> ModuleLayoutResolver<'a, T> {
+    /// A resolver for the structs declared by `modules`.
+    pub fn new(modules: impl IntoIterator<Item = &'a T>) -> Self {
+        let modules = modules
+            .into_iter()
+            .map(|module| (module.self_id(), module))
+            .collect();
+        Self { modules }
+    }
+
+    fn struct_layout(&self, struct_tag: &StructTag) -> VMResult<StructDef> {
+        let not_found = |what: &str| {
+            let msg = format!("{} of {} not found", what, qualified_name(struct_tag));
+            VMStatus::new(StatusCode::TYPE_RESOLUTION_FAILURE).with_message(msg)
+        };
+        let module_id = ModuleId::new(struct_tag.address, struct_tag.module.clone());
+        let module = *self
+            .modules
+            .get(&module_id)
+            .ok_or_else(|| not_found("module"))?;
+        let struct_def = module
+            .struct_defs()
+            .iter()
+            .map(|def| StructDefinitionView::new(module, def))
+            .find(|view| view.name() == struct_tag.name.as_ident_str())
+            .ok_or_else(|| not_found("struct"))?;
+        if struct_def.type_formals().len() != struct_tag.type_params.len() {
+            let msg = format!(
+                "{} takes {} type arguments but {} were given",
+                qualified_name(struct_tag),
+                struct_def.type_formals().len(),
+                struct_tag.type_params.len()
+            );
+            return Err(
+                VMStatus::new(StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH).with_message(msg)
+            );
+        }
+
+        match struct_def.fields() {
+            None => {
+                let native = resolve_native_struct(&module_id, &struct_tag.name)
+                    .ok_or_else(|| VMStatus::new(StatusCode::LINKER_ERROR))?;
+                Ok(StructDef::Native(native.struct_type.clone()))
+            }
+            Some(fields) => {
+                let field_types = fields
+                    .map(|field| {
+                        let tag =
+                            token_to_tag(module, field.signature_token(), &struct_tag.type_params)?;
+                        self.type_to_layout(&tag)
+                    })
+                    .collect::<VMResult<_>>()?;
+                Ok(StructDef::new(field_types))
+            }
+        }
+    }
+}
+
+impl<T: ModuleAccess> TypeLayoutResolver for ModuleLayoutResolver<'_, T> {
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type> {
+        resolve_layout(tag, &|struct_tag| self.struct_layout(struct_tag))
+    }
+}
+
+/// The type tag of the field type `token` of a struct of `module` instantiated with
+/// `type_actuals`.
+fn token_to_tag<T: ModuleAccess>(
+    module: &T,
+    token: &SignatureToken,
+    type_actuals: &[TypeTag],
+) -> VMResult<TypeTag> {
+    Ok(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::ByteArray => TypeTag::ByteArray,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Vector(token) => {
+            TypeTag::Vector(Box::new(token_to_tag(module, token, type_actuals)?))
+        }
+        SignatureToken::Struct(idx, tokens) => {
+            let handle = module.struct_handle_at(*idx);
+            let declaring_module = module.module_handle_at(handle.module);
+            let type_params = tokens
+                .iter()
+                .map(|token| token_to_tag(module, token, type_actuals))
+                .collect::<VMResult<_>>()?;
+            TypeTag::Struct(StructTag {
+                address: *module.address_at(declaring_module.address),
+                module: module.identifier_at(declaring_module.name).to_owned(),
+                name: module.identifier_at(handle.name).to_owned(),
+                type_params,
+            })
+        }
+        SignatureToken::TypeParameter(idx) => type_actuals
+            .get(*idx as usize)
+            .cloned()
+            .ok_or_else(|| VMStatus::new(StatusCode::TYPE_RESOLUTION_FAILURE))?,
+        SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => {
+            return Err(VMStatus::new(StatusCode::TYPE_RESOLUTION_FAILURE)
+                .with_message("struct fields cannot be references".to_owned()))
+        }
+    })
+}
@@ -0,0 +1,197 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    loaded_data::types::Type,
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{charge_or_stop, native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use serde::de::DeserializeOwned;
+use std::cmp::Ordering;
+use vm::{
+    errors::VMResult,
+    gas_schedule::{CostTable, NativeCostIndex},
+};
+
+/// Largest value, in LCS bytes, the natives serialize. They charge for the size of a value and
+/// check it against this limit before serializing it.
+pub const MAX_SERIALIZED_SIZE: usize = 64 * 1024;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::LCS;
+/// The value is more than `MAX_SERIALIZED_SIZE` bytes once serialized
+pub const VALUE_TOO_LARGE: u64 = DEFAULT_ERROR_CODE + 1;
+
+/// `cmp_lcs_bytes` result when the first value is less than the second
+pub const LESS: u64 = 0;
+/// `cmp_lcs_bytes` result when the values are equal
+pub const EQUAL: u64 = 1;
+/// `cmp_lcs_bytes` result when the first value is greater than the second
+pub const GREATER: u64 = 2;
+
+/// Serialize the value behind a `&T` into its canonical LCS bytes, using the layout of the type
+/// `T` the native was instantiated with.
+pub fn native_to_bytes(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut ty_layouts: Vec<Type>,
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let layout = ty_layouts.pop().unwrap();
+    let reference = arguments.pop_ref()?;
+    arguments.finish()?;
+
+    let value = reference.read_ref()?;
+    let size = serialized_size(&value, &layout)?;
+    let cost = native_gas(context.cost_table(), NativeCostIndex::LCS_TO_BYTES, size);
+    charge_or_stop(context, cost)?;
+    if size > MAX_SERIALIZED_SIZE {
+        return Ok(NativeResult::abort(cost, VALUE_TOO_LARGE));
+    }
+    let serialized = value
+        .simple_serialize(&layout)
+        .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))?;
+    let return_values = vec![Value::vector_u8(serialized)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Compute the length of the LCS bytes `to_bytes` would return for the value behind a `&T`,
+/// without serializing it.
+pub fn native_serialized_size(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut ty_layouts: Vec<Type>,
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let layout = ty_layouts.pop().unwrap();
+    let reference = arguments.pop_ref()?;
+    arguments.finish()?;
+
+    let size = serialized_size(&reference.read_ref()?, &layout)?;
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::LCS_SERIALIZED_SIZE,
+        size,
+    );
+    charge_or_stop(context, cost)?;
+    let return_values = vec![Value::u64(size as u64)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Compare the values behind two `&T`s by their canonical LCS bytes, lexicographically. This is a
+/// total order over values of any type, but not their natural order: integers are little-endian,
+/// so `256 < 1`, and vectors are prefixed with their length, so shorter vectors come first.
+/// Returns `LESS`, `EQUAL` or `GREATER`.
+pub fn native_compare(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut ty_layouts: Vec<Type>,
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let layout = ty_layouts.pop().unwrap();
+    let right = arguments.pop_ref()?.read_ref()?;
+    let left = arguments.pop_ref()?.read_ref()?;
+    arguments.finish()?;
+
+    let left_size = serialized_size(&left, &layout)?;
+    let right_size = serialized_size(&right, &layout)?;
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::COMPARE_GENERIC,
+        left_size + right_size,
+    );
+    charge_or_stop(context, cost)?;
+    if left_size > MAX_SERIALIZED_SIZE || right_size > MAX_SERIALIZED_SIZE {
+        return Ok(NativeResult::abort(cost, VALUE_TOO_LARGE));
+    }
+    let serialize = |value: Value| {
+        value
+            .simple_serialize(&layout)
+            .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))
+    };
+    let left = serialize(left)?;
+    let right = serialize(right)?;
+    let ordering = match left.cmp(&right) {
+        Ordering::Less => LESS,
+        Ordering::Equal => EQUAL,
+        Ordering::Greater => GREATER,
+    };
+    let return_values = vec![Value::u64(ordering)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// The length of the LCS bytes of `value`, computed without serializing it. Fails with
+/// `DATA_FORMAT_ERROR` if `value` does not have the shape of `layout`.
+fn serialized_size(value: &Value, layout: &Type) -> VMResult<usize> {
+    value
+        .serialized_size(layout)
+        .ok_or_else(|| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))
+}
+
+pub fn native_from_bytes_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    from_bytes(
+        NativeCostIndex::LCS_FROM_BYTES_U64,
+        arguments,
+        context.cost_table(),
+        Value::u64,
+    )
+}
+
+pub fn native_from_bytes_bool(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    from_bytes(
+        NativeCostIndex::LCS_FROM_BYTES_BOOL,
+        arguments,
+        context.cost_table(),
+        Value::bool,
+    )
+}
+
+pub fn native_from_bytes_address(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    from_bytes(
+        NativeCostIndex::LCS_FROM_BYTES_ADDRESS,
+        arguments,
+        context.cost_table(),
+        Value::address,
+    )
+}
+
+/// Deserialize a `T` from LCS bytes, returning whether the bytes were exactly one canonical
+/// encoding of a `T` together with the decoded value, which is `T::default()` when they were not.
+/// Trailing bytes after a valid encoding are rejected.
+fn from_bytes<T: DeserializeOwned + Default>(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
+    cost_table: &CostTable,
+    into_value: fn(T) -> Value,
+) -> VMResult<NativeResult> {
+    let bytes = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, bytes.len());
+    let return_values = match ::lcs::from_bytes::<T>(&bytes) {
+        Ok(value) => vec![Value::bool(true), into_value(value)],
+        Err(_) => vec![Value::bool(false), into_value(T::default())],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
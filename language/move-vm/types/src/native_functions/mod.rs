@@ -3,6 +3,28 @@
 
 #[macro_use]
 pub mod dispatch;
+pub mod abort_codes;
+pub mod account;
+pub mod arguments;
+pub mod biguint;
+pub mod block;
+pub mod builtins;
+pub mod context;
+pub mod debug;
+pub mod errors;
+pub mod fixed_point;
 pub mod hash;
+pub mod layout;
+pub mod lcs;
+pub mod pedersen;
 pub mod primitive_helpers;
+pub mod reflection;
+pub mod ristretto255;
 pub mod signature;
+pub mod table;
+pub mod transaction;
+#[cfg(feature = "testing")]
+pub mod unit_test;
+
+#[cfg(test)]
+mod unit_tests;
@@ -0,0 +1,83 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pedersen commitments over Ristretto255: `commitment = value * G + blinding * H`, where `G` is
+//! the Ristretto255 base point and `H` is derived from it by hashing its encoding with SHA3-512,
+//! the same pair of generators as the `bulletproofs` crate.
+
+use crate::{
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+        ristretto255::{canonical_scalar, decompress_point},
+    },
+    values::Value,
+};
+use curve25519_dalek::{
+    constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT},
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::MultiscalarMul,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use once_cell::sync::Lazy;
+use sha3::Sha3_512;
+use vm::{errors::VMResult, gas_schedule::NativeCostIndex};
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::PEDERSEN;
+/// Commitment is not the canonical encoding of a Ristretto255 point
+pub const INVALID_COMMITMENT_ENCODING: u64 = DEFAULT_ERROR_CODE + 1;
+/// Blinding factor is not the canonical encoding of a scalar
+pub const INVALID_BLINDING_ENCODING: u64 = DEFAULT_ERROR_CODE + 2;
+
+/// The blinding generator `H`.
+pub static BLINDING_GENERATOR: Lazy<RistrettoPoint> = Lazy::new(|| {
+    RistrettoPoint::hash_from_bytes::<Sha3_512>(RISTRETTO_BASEPOINT_COMPRESSED.as_bytes())
+});
+
+pub fn native_pedersen_verify(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let blinding = arguments.pop_vec_of::<u8>()?;
+    let value = arguments.pop_as::<u64>()?;
+    let commitment = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    // Charged up front, so that malformed inputs pay the same base cost as well formed ones.
+    let cost = native_gas(context.cost_table(), NativeCostIndex::PEDERSEN_VERIFY, 1);
+    let commitment = match decompress_point(&commitment) {
+        Some(commitment) => commitment,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(INVALID_COMMITMENT_ENCODING),
+            ));
+        }
+    };
+    let blinding = match canonical_scalar(&blinding) {
+        Some(blinding) => blinding,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(INVALID_BLINDING_ENCODING),
+            ));
+        }
+    };
+
+    let expected = RistrettoPoint::multiscalar_mul(
+        &[Scalar::from(value), blinding],
+        &[RISTRETTO_BASEPOINT_POINT, *BLINDING_GENERATOR],
+    );
+    let return_values = vec![Value::bool(commitment == expected)];
+    Ok(NativeResult::ok(cost, return_values))
+}
@@ -2,40 +2,74 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    native_functions::dispatch::{native_gas, NativeResult},
-    values::Value,
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::{VMValueCast, Value},
 };
+use bech32::{FromBase32, ToBase32};
+use libra_crypto::HashValue;
 use libra_types::{
-    account_address::AccountAddress,
+    account_address::{AccountAddress, ADDRESS_LENGTH},
     byte_array::ByteArray,
+    event::EventKey,
     language_storage::TypeTag,
     vm_error::{StatusCode, VMStatus},
 };
-use std::collections::VecDeque;
+use std::{cmp, convert::TryFrom};
+use subtle::ConstantTimeEq;
 use vm::{
     errors::VMResult,
     gas_schedule::{CostTable, NativeCostIndex},
 };
 
+/// Maximum length of a bech32 string, as defined in BIP-173.
+pub const BECH32_MAX_LENGTH: usize = 90;
+/// Length of the bech32 checksum in characters.
+const BECH32_CHECKSUM_LENGTH: usize = 6;
+
+/// Base64 encodes every 3 bytes as a group of 4 characters, padding the last group with '='.
+const BASE64_GROUP_LENGTH: usize = 4;
+/// Number of decimal digits in `u64::MAX`.
+const U64_MAX_DECIMAL_LENGTH: usize = 20;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::PRIMITIVE_HELPERS;
+/// Byte vector does not have the length of the integer it is decoded into
+pub const INVALID_BYTES_LENGTH_FAILURE: u64 = DEFAULT_ERROR_CODE + 1;
+/// Byte vector is not `ADDRESS_LENGTH` bytes long
+pub const INVALID_ADDRESS_LENGTH_FAILURE: u64 = DEFAULT_ERROR_CODE + 2;
+/// Slice range is out of bounds or its start is past its end
+pub const INVALID_SLICE_RANGE_FAILURE: u64 = DEFAULT_ERROR_CODE + 3;
+/// Byte arrays combined bytewise do not have the same length
+pub const BYTEARRAY_LENGTH_MISMATCH_FAILURE: u64 = DEFAULT_ERROR_CODE + 4;
+/// A string contains a byte outside the ASCII range
+pub const NON_ASCII_FAILURE: u64 = DEFAULT_ERROR_CODE + 5;
+/// The logarithm of zero is undefined
+pub const LOG2_OF_ZERO_FAILURE: u64 = DEFAULT_ERROR_CODE + 6;
+/// Modular arithmetic with a modulus of zero
+pub const MODULUS_OF_ZERO_FAILURE: u64 = DEFAULT_ERROR_CODE + 7;
+/// Division with a divisor of zero
+pub const DIVISION_BY_ZERO_FAILURE: u64 = DEFAULT_ERROR_CODE + 8;
+/// Authentication key is not `HashValue::LENGTH` bytes long
+pub const INVALID_AUTH_KEY_LENGTH_FAILURE: u64 = DEFAULT_ERROR_CODE + 9;
+
 pub fn native_bytearray_concat(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
 ) -> VMResult<NativeResult> {
-    if arguments.len() != 2 {
-        let msg = format!(
-            "wrong number of arguments for bytearray_concat expected 2 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
-    }
-    let arg2 = pop_arg!(arguments, ByteArray);
-    let arg1 = pop_arg!(arguments, ByteArray);
+    let arg2 = arguments.pop_as::<ByteArray>()?;
+    let arg1 = arguments.pop_as::<ByteArray>()?;
+    arguments.finish()?;
     let mut return_val = arg1.as_bytes().to_vec();
     return_val.extend_from_slice(arg2.as_bytes());
 
     let cost = native_gas(
-        cost_table,
+        context.cost_table(),
         NativeCostIndex::BYTEARRAY_CONCAT,
         return_val.len(),
     );
@@ -43,23 +77,158 @@ pub fn native_bytearray_concat(
     Ok(NativeResult::ok(cost, return_values))
 }
 
-pub fn native_address_to_bytes(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
+/// Copy the bytes in `[start, end)` into a new byte array.
+pub fn native_bytearray_slice(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let end = arguments.pop_as::<u64>()?;
+    let start = arguments.pop_as::<u64>()?;
+    let bytes = arguments.pop_as::<ByteArray>()?;
+    arguments.finish()?;
+
+    if start > end || end > bytes.len() as u64 {
+        let cost = native_gas(context.cost_table(), NativeCostIndex::BYTEARRAY_SLICE, 1);
+        return Ok(NativeResult::abort(cost, INVALID_SLICE_RANGE_FAILURE));
+    }
+    let slice = bytes.as_bytes()[start as usize..end as usize].to_vec();
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BYTEARRAY_SLICE,
+        slice.len(),
+    );
+    let return_values = vec![Value::byte_array(ByteArray::new(slice))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Find the first occurrence of `needle` in `haystack`, returning whether it was found together
+/// with its index, which is 0 when it was not. An empty needle is found at index 0.
+pub fn native_bytearray_index_of(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let needle = arguments.pop_as::<ByteArray>()?;
+    let haystack = arguments.pop_as::<ByteArray>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BYTEARRAY_INDEX_OF,
+        haystack.len(),
+    );
+    let index = if needle.is_empty() {
+        Some(0)
+    } else {
+        haystack
+            .as_bytes()
+            .windows(needle.len())
+            .position(|window| window == needle.as_bytes())
+    };
+    let return_values = match index {
+        Some(index) => vec![Value::bool(true), Value::u64(index as u64)],
+        None => vec![Value::bool(false), Value::u64(0)],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_bytearray_xor(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    bytewise(
+        NativeCostIndex::BYTEARRAY_XOR,
+        arguments,
+        context.cost_table(),
+        |a, b| a ^ b,
+    )
+}
+
+pub fn native_bytearray_and(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    bytewise(
+        NativeCostIndex::BYTEARRAY_AND,
+        arguments,
+        context.cost_table(),
+        |a, b| a & b,
+    )
+}
+
+pub fn native_bytearray_or(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    bytewise(
+        NativeCostIndex::BYTEARRAY_OR,
+        arguments,
+        context.cost_table(),
+        |a, b| a | b,
+    )
+}
+
+/// Combine two byte arrays of equal length byte by byte with `op`. Gas is charged over the longer
+/// of the two, including when their lengths differ.
+fn bytewise(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
     cost_table: &CostTable,
+    op: fn(u8, u8) -> u8,
 ) -> VMResult<NativeResult> {
-    if arguments.len() != 1 {
-        let msg = format!(
-            "wrong number of arguments for address_to_bytes expected 1 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
+    let arg2 = arguments.pop_as::<ByteArray>()?;
+    let arg1 = arguments.pop_as::<ByteArray>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, cmp::max(arg1.len(), arg2.len()));
+    if arg1.len() != arg2.len() {
+        return Ok(NativeResult::abort(cost, BYTEARRAY_LENGTH_MISMATCH_FAILURE));
     }
-    let arg = pop_arg!(arguments, AccountAddress);
+    let return_val = arg1
+        .as_bytes()
+        .iter()
+        .zip(arg2.as_bytes())
+        .map(|(a, b)| op(*a, *b))
+        .collect();
+    let return_values = vec![Value::byte_array(ByteArray::new(return_val))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Compare two byte arrays in time that depends only on their lengths. Gas is charged over the
+/// longer of the two, so arrays of different lengths cost the same as a match of the longer one.
+pub fn native_bytearray_constant_time_eq(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg2 = arguments.pop_as::<ByteArray>()?;
+    let arg1 = arguments.pop_as::<ByteArray>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BYTEARRAY_CONSTANT_TIME_EQ,
+        cmp::max(arg1.len(), arg2.len()),
+    );
+    let return_values = vec![Value::bool(arg1.as_bytes().ct_eq(arg2.as_bytes()).into())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_address_to_bytes(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<AccountAddress>()?;
+    arguments.finish()?;
     let return_val = arg.to_vec();
 
     let cost = native_gas(
-        cost_table,
+        context.cost_table(),
         NativeCostIndex::ADDRESS_TO_BYTES,
         return_val.len(),
     );
@@ -67,22 +236,799 @@ pub fn native_address_to_bytes(
     Ok(NativeResult::ok(cost, return_values))
 }
 
+pub fn native_address_from_bytes(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::ADDRESS_FROM_BYTES,
+        arg.len(),
+    );
+    if arg.len() != ADDRESS_LENGTH {
+        return Ok(NativeResult::abort(cost, INVALID_ADDRESS_LENGTH_FAILURE));
+    }
+    let address = AccountAddress::try_from(arg)
+        .map_err(|e| VMStatus::new(StatusCode::UNREACHABLE).with_message(e.to_string()))?;
+    let return_values = vec![Value::address(address)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
 pub fn native_u64_to_bytes(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
 ) -> VMResult<NativeResult> {
-    if arguments.len() != 1 {
-        let msg = format!(
-            "wrong number of arguments for u64_to_bytes expected 1 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
+    let arg = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+    let return_val: Vec<u8> = arg.to_le_bytes().to_vec();
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U64_TO_BYTES,
+        return_val.len(),
+    );
+    let return_values = vec![Value::vector_u8(return_val)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_u64_to_bytes_be(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+    let return_val: Vec<u8> = arg.to_be_bytes().to_vec();
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U64_TO_BYTES_BE,
+        return_val.len(),
+    );
+    let return_values = vec![Value::vector_u8(return_val)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_bytes_to_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BYTES_TO_U64,
+        arg.len(),
+    );
+    let mut bytes = [0u8; 8];
+    if arg.len() != bytes.len() {
+        return Ok(NativeResult::abort(cost, INVALID_BYTES_LENGTH_FAILURE));
     }
-    let arg = pop_arg!(arguments, u64);
+    bytes.copy_from_slice(&arg);
+    let return_values = vec![Value::u64(u64::from_le_bytes(bytes))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Parse a string of ASCII decimal digits, returning whether it is a valid `u64` together with its
+/// value, which is 0 when it is not. Empty strings, non-digit bytes and values above `u64::MAX`
+/// are rejected, as are strings longer than `U64_MAX_DECIMAL_LENGTH` even if they only differ in
+/// leading zeros.
+pub fn native_u64_from_decimal_string(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    // Longer strings are rejected without being read, so gas is capped at the maximum length.
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U64_FROM_DECIMAL_STRING,
+        cmp::min(arg.len(), U64_MAX_DECIMAL_LENGTH),
+    );
+    let parsed = if arg.is_empty() || arg.len() > U64_MAX_DECIMAL_LENGTH {
+        None
+    } else {
+        arg.iter().try_fold(0u64, |value, byte| {
+            if byte.is_ascii_digit() {
+                value.checked_mul(10)?.checked_add(u64::from(byte - b'0'))
+            } else {
+                None
+            }
+        })
+    };
+    let return_values = match parsed {
+        Some(value) => vec![Value::bool(true), Value::u64(value)],
+        None => vec![Value::bool(false), Value::u64(0)],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Format a `u64` as ASCII decimal digits without leading zeros.
+pub fn native_u64_to_decimal_string(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U64_TO_DECIMAL_STRING,
+        1,
+    );
+    let return_values = vec![Value::vector_u8(arg.to_string().into_bytes())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_u128_to_bytes(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u128>()?;
+    arguments.finish()?;
     let return_val: Vec<u8> = arg.to_le_bytes().to_vec();
 
-    let cost = native_gas(cost_table, NativeCostIndex::U64_TO_BYTES, return_val.len());
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U128_TO_BYTES,
+        return_val.len(),
+    );
+    let return_values = vec![Value::vector_u8(return_val)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_u128_from_bytes(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::U128_FROM_BYTES,
+        arg.len(),
+    );
+    let mut bytes = [0u8; 16];
+    if arg.len() != bytes.len() {
+        return Ok(NativeResult::abort(cost, INVALID_BYTES_LENGTH_FAILURE));
+    }
+    bytes.copy_from_slice(&arg);
+    let return_values = vec![Value::u128(u128::from_le_bytes(bytes))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Narrows a u128 to a u64, returning whether the value fits together with the value, or with zero
+/// when it is above u64::MAX.
+pub fn native_u128_to_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u128>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::U128_TO_U64, 1);
+    let (success, result) = match u64::try_from(arg) {
+        Ok(result) => (true, result),
+        Err(_) => (false, 0),
+    };
+    let return_values = vec![Value::bool(success), Value::u64(result)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_u64_checked_add(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    checked(
+        NativeCostIndex::U64_CHECKED_ADD,
+        arguments,
+        context.cost_table(),
+        u64::checked_add,
+        Value::u64,
+    )
+}
+
+pub fn native_u64_checked_mul(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    checked(
+        NativeCostIndex::U64_CHECKED_MUL,
+        arguments,
+        context.cost_table(),
+        u64::checked_mul,
+        Value::u64,
+    )
+}
+
+pub fn native_u128_checked_add(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    checked(
+        NativeCostIndex::U128_CHECKED_ADD,
+        arguments,
+        context.cost_table(),
+        u128::checked_add,
+        Value::u128,
+    )
+}
+
+pub fn native_u128_checked_mul(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    checked(
+        NativeCostIndex::U128_CHECKED_MUL,
+        arguments,
+        context.cost_table(),
+        u128::checked_mul,
+        Value::u128,
+    )
+}
+
+/// Raises base to the power exp by square-and-multiply, returning whether the result fits in a u64
+/// together with the result, or with zero on overflow. 0^0 is defined as 1. Gas is charged per bit
+/// of the exponent.
+pub fn native_pow_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let exp = arguments.pop_as::<u64>()?;
+    let base = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let exp_bits = 64 - exp.leading_zeros() as usize;
+    let cost = native_gas(context.cost_table(), NativeCostIndex::POW_U64, 1 + exp_bits);
+    let (success, result) = match checked_pow(base, exp) {
+        Some(result) => (true, result),
+        None => (false, 0),
+    };
+    let return_values = vec![Value::bool(success), Value::u64(result)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+fn checked_pow(mut base: u64, mut exp: u64) -> Option<u64> {
+    let mut result: u64 = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+        exp >>= 1;
+        // The square is only needed, and can only overflow the result, if a higher bit is set.
+        if exp > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+/// Computes the floor of the square root of a u128.
+pub fn native_sqrt_u128(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u128>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::SQRT_U128, 1);
+    let return_values = vec![Value::u128(isqrt(arg))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Digit-by-digit integer square root, which takes at most 64 iterations and is exact over the
+/// whole u128 range.
+fn isqrt(n: u128) -> u128 {
+    let mut rem = n;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > n {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+pub fn native_add_mod_u128(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    modular(
+        NativeCostIndex::ADD_MOD_U128,
+        arguments,
+        context.cost_table(),
+        add_mod,
+    )
+}
+
+pub fn native_mul_mod_u128(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    modular(
+        NativeCostIndex::MUL_MOD_U128,
+        arguments,
+        context.cost_table(),
+        mul_mod,
+    )
+}
+
+fn modular(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
+    cost_table: &CostTable,
+    op: fn(u128, u128, u128) -> u128,
+) -> VMResult<NativeResult> {
+    let modulus = arguments.pop_as::<u128>()?;
+    let b = arguments.pop_as::<u128>()?;
+    let a = arguments.pop_as::<u128>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, 1);
+    if modulus == 0 {
+        return Ok(NativeResult::abort(cost, MODULUS_OF_ZERO_FAILURE));
+    }
+    let return_values = vec![Value::u128(op(a, b, modulus))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Reduces the 129-bit value `carry * 2^128 + value`, known to be less than `2 * modulus`.
+fn reduce_once(value: u128, carry: bool, modulus: u128) -> u128 {
+    if carry || value >= modulus {
+        value.wrapping_sub(modulus)
+    } else {
+        value
+    }
+}
+
+/// Computes `(a + b) mod modulus` exactly.
+fn add_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    let (sum, carry) = (a % modulus).overflowing_add(b % modulus);
+    reduce_once(sum, carry, modulus)
+}
+
+/// Computes `(a * b) mod modulus` exactly, by forming the full 256-bit product and reducing it one
+/// bit at a time, most significant first.
+fn mul_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    let (high, low) = widening_mul(a, b);
+    let mut rem = 0u128;
+    for &word in &[high, low] {
+        for i in (0..128).rev() {
+            let carry = rem >> 127 == 1;
+            rem = (rem << 1) | ((word >> i) & 1);
+            rem = reduce_once(rem, carry, modulus);
+        }
+    }
+    rem
+}
+
+/// Returns the high and low halves of the 256-bit product `a * b`.
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = std::u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let high_high = a_high * b_high;
+
+    // Sum of the middle terms and the carry out of the low word, each at most 64 bits wide
+    let middle = (low_low >> 64) + (low_high & MASK) + (high_low & MASK);
+    let low = (low_low & MASK) | (middle << 64);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// Returns the quotient and the remainder of dividing dividend by divisor.
+pub fn native_div_mod_u128(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let divisor = arguments.pop_as::<u128>()?;
+    let dividend = arguments.pop_as::<u128>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::DIV_MOD_U128, 1);
+    if divisor == 0 {
+        return Ok(NativeResult::abort(cost, DIVISION_BY_ZERO_FAILURE));
+    }
+    let return_values = vec![
+        Value::u128(dividend / divisor),
+        Value::u128(dividend % divisor),
+    ];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Computes a * b / c rounded down, with the product held in a u128 so no precision is lost.
+/// Returns whether c is non-zero and the quotient fits in a u64 together with the quotient, or
+/// with zero otherwise.
+pub fn native_mul_div_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let c = arguments.pop_as::<u64>()?;
+    let b = arguments.pop_as::<u64>()?;
+    let a = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::MUL_DIV_U64, 1);
+    let quotient = (u128::from(a) * u128::from(b)).checked_div(u128::from(c));
+    let (success, result) = match quotient.map(u64::try_from) {
+        Some(Ok(result)) => (true, result),
+        _ => (false, 0),
+    };
+    let return_values = vec![Value::bool(success), Value::u64(result)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_log2_floor_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::LOG2_FLOOR_U64, 1);
+    if arg == 0 {
+        return Ok(NativeResult::abort(cost, LOG2_OF_ZERO_FAILURE));
+    }
+    let return_values = vec![Value::u64(u64::from(63 - arg.leading_zeros()))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_leading_zeros_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    count_bits(
+        NativeCostIndex::LEADING_ZEROS_U64,
+        arguments,
+        context.cost_table(),
+        u64::leading_zeros,
+    )
+}
+
+pub fn native_popcount_u64(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    count_bits(
+        NativeCostIndex::POPCOUNT_U64,
+        arguments,
+        context.cost_table(),
+        u64::count_ones,
+    )
+}
+
+fn count_bits(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
+    cost_table: &CostTable,
+    op: fn(u64) -> u32,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, 1);
+    let return_values = vec![Value::u64(u64::from(op(arg)))];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Applies a checked arithmetic operation, returning whether it succeeded together with the
+/// result, or with zero on overflow.
+fn checked<T: Default>(
+    cost_index: NativeCostIndex,
+    mut arguments: Arguments,
+    cost_table: &CostTable,
+    op: fn(T, T) -> Option<T>,
+    to_value: fn(T) -> Value,
+) -> VMResult<NativeResult>
+where
+    Value: VMValueCast<T>,
+{
+    let arg2 = arguments.pop_as::<T>()?;
+    let arg1 = arguments.pop_as::<T>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(cost_table, cost_index, 1);
+    let (success, result) = match op(arg1, arg2) {
+        Some(result) => (true, result),
+        None => (false, T::default()),
+    };
+    let return_values = vec![Value::bool(success), to_value(result)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_base58_encode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    // Base58 encoding is a change of radix of the whole input, which is quadratic in its length.
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BASE58_ENCODE,
+        arg.len().saturating_mul(arg.len()),
+    );
+    let return_values = vec![Value::vector_u8(bs58::encode(arg).into_vec())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Decode a Base58 string, returning whether it only contained Base58 characters together with
+/// the decoded bytes, which are empty when it did not.
+pub fn native_base58_decode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BASE58_DECODE,
+        arg.len(),
+    );
+    let return_values = match bs58::decode(arg).into_vec() {
+        Ok(decoded) => vec![Value::bool(true), Value::vector_u8(decoded)],
+        Err(_) => vec![Value::bool(false), Value::vector_u8(vec![])],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Encode `payload` as a BIP-173 bech32 string with human-readable part `hrp`, returning whether
+/// the encoding succeeded together with the lowercase encoding, which is empty when `hrp` is not a
+/// valid human-readable part or the encoding would exceed `BECH32_MAX_LENGTH` characters.
+pub fn native_bech32_encode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let payload = arguments.pop_vec_of::<u8>()?;
+    let hrp = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    // One character for the separator, one per 5 bits of payload, and the checksum.
+    let encoded_len = hrp.len() + 1 + (payload.len() * 8 + 4) / 5 + BECH32_CHECKSUM_LENGTH;
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BECH32_ENCODE,
+        encoded_len,
+    );
+    let encoded = if encoded_len > BECH32_MAX_LENGTH {
+        None
+    } else {
+        std::str::from_utf8(&hrp)
+            .ok()
+            .and_then(|hrp| bech32::encode(hrp, payload.to_base32()).ok())
+    };
+    let return_values = match encoded {
+        Some(encoded) => vec![Value::bool(true), Value::vector_u8(encoded.into_bytes())],
+        None => vec![Value::bool(false), Value::vector_u8(vec![])],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Decode the BIP-173 bech32 string `encoded`, returning whether it is a valid bech32 string
+/// with human-readable part `hrp` together with its 8-bit payload, which is empty when it is not.
+/// Mixed-case strings and strings longer than `BECH32_MAX_LENGTH` characters are rejected.
+pub fn native_bech32_decode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let encoded = arguments.pop_vec_of::<u8>()?;
+    let hrp = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BECH32_DECODE,
+        encoded.len(),
+    );
+    let decoded = if encoded.len() > BECH32_MAX_LENGTH {
+        None
+    } else {
+        std::str::from_utf8(&encoded)
+            .ok()
+            .and_then(|encoded| bech32::decode(encoded).ok())
+            // The decoded human-readable part is lowercase; it is case-insensitive.
+            .filter(|(decoded_hrp, _)| decoded_hrp.as_bytes().eq_ignore_ascii_case(&hrp))
+            .and_then(|(_, data)| Vec::<u8>::from_base32(&data).ok())
+    };
+    let return_values = match decoded {
+        Some(payload) => vec![Value::bool(true), Value::vector_u8(payload)],
+        None => vec![Value::bool(false), Value::vector_u8(vec![])],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+pub fn native_base64_encode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BASE64_ENCODE,
+        arg.len(),
+    );
+    let return_values = vec![Value::vector_u8(base64::encode(&arg).into_bytes())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Decode a padded, standard alphabet Base64 string, returning whether it was well formed
+/// together with the decoded bytes, which are empty when it was not.
+pub fn native_base64_decode(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::BASE64_DECODE,
+        arg.len(),
+    );
+    // The decoder accepts unpadded input, so padding is enforced by requiring whole groups.
+    let decoded = if arg.len() % BASE64_GROUP_LENGTH == 0 {
+        base64::decode(&arg).ok()
+    } else {
+        None
+    };
+    let return_values = match decoded {
+        Some(decoded) => vec![Value::bool(true), Value::vector_u8(decoded)],
+        None => vec![Value::bool(false), Value::vector_u8(vec![])],
+    };
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Check whether a byte vector is well formed UTF-8, rejecting overlong encodings, encoded
+/// surrogates and code points beyond U+10FFFF.
+pub fn native_string_is_valid_utf8(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::STRING_IS_VALID_UTF8,
+        arg.len(),
+    );
+    let return_values = vec![Value::bool(std::str::from_utf8(&arg).is_ok())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Check whether every byte is in the ASCII range.
+pub fn native_string_is_ascii(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::STRING_IS_ASCII,
+        arg.len(),
+    );
+    let return_values = vec![Value::bool(arg.is_ascii())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Map the ASCII lowercase letters to uppercase, leaving other ASCII bytes unchanged. Fails with
+/// `NON_ASCII_FAILURE` if any byte is outside the ASCII range.
+pub fn native_string_to_uppercase_ascii(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let mut arg = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::STRING_TO_UPPERCASE_ASCII,
+        arg.len(),
+    );
+    if !arg.is_ascii() {
+        return Ok(NativeResult::abort(cost, NON_ASCII_FAILURE));
+    }
+    arg.make_ascii_uppercase();
+    let return_values = vec![Value::vector_u8(arg)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Return the GUID of the event stream created by `account` with its counter at `counter`:
+/// the counter as 8 little-endian bytes followed by the address, as in `EventKey`.
+pub fn native_event_guid(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let account = arguments.pop_as::<AccountAddress>()?;
+    let counter = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+    let return_val = EventKey::new_from_address(&account, counter).to_vec();
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::EVENT_GUID,
+        return_val.len(),
+    );
     let return_values = vec![Value::vector_u8(return_val)];
     Ok(NativeResult::ok(cost, return_values))
 }
+
+/// Return the address of the account with authentication key `auth_key`. The authentication key
+/// is the SHA3-256 hash of the account's public key, and the address is that whole hash, as in
+/// `AccountAddress::from_public_key`.
+pub fn native_address_from_auth_key(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let auth_key = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::ACCOUNT_ADDRESS_FROM_AUTH_KEY,
+        auth_key.len(),
+    );
+    if auth_key.len() != HashValue::LENGTH {
+        return Ok(NativeResult::abort(cost, INVALID_AUTH_KEY_LENGTH_FAILURE));
+    }
+    let hash = HashValue::from_slice(&auth_key)
+        .map_err(|e| VMStatus::new(StatusCode::UNREACHABLE).with_message(e.to_string()))?;
+    let return_values = vec![Value::address(AccountAddress::new(*hash.as_ref()))];
+    Ok(NativeResult::ok(cost, return_values))
+}
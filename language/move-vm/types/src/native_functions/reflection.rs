@@ -0,0 +1,123 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reflection on the types a generic native function is instantiated with, which the dispatcher
+//! hands over as `TypeTag`s.
+
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::fmt::Write;
+use vm::{errors::VMResult, gas_schedule::NativeCostIndex};
+
+/// Return the canonical LCS serialization of the `TypeTag` of the type `T` the native was
+/// instantiated with.
+pub fn native_type_of(
+    context: &dyn NativeContext,
+    ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let tag = &ty_args[0];
+
+    let serialized =
+        lcs::to_bytes(tag).map_err(|_| VMStatus::new(StatusCode::DATA_FORMAT_ERROR))?;
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::REFLECT_TYPE_OF,
+        serialized.len(),
+    );
+    let return_values = vec![Value::vector_u8(serialized)];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Return the name of the type `T` the native was instantiated with, as ASCII bytes. The
+/// rendering is stable, as modules may store or compare it:
+/// - primitive types are `bool`, `u8`, `u64`, `u128`, `bytearray` and `address`
+/// - vectors are `vector<T>`
+/// - structs are `0x<address>::<Module>::<Name>`, where the address is written as 64 lowercase
+///   hex digits, followed by `<T1, T2, ...>` if the struct has type arguments
+pub fn native_type_name(
+    context: &dyn NativeContext,
+    ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let tag = &ty_args[0];
+
+    let mut name = String::new();
+    write_type_name(&mut name, tag);
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::REFLECT_TYPE_NAME,
+        name.len(),
+    );
+    let return_values = vec![Value::vector_u8(name.into_bytes())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Return whether the two types `T1` and `T2` the native was instantiated with are the same type,
+/// comparing struct type arguments as well.
+pub fn native_types_are_equal(
+    context: &dyn NativeContext,
+    ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::REFLECT_TYPES_ARE_EQUAL,
+        1,
+    );
+    let return_values = vec![Value::bool(ty_args[0] == ty_args[1])];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+fn write_type_name(out: &mut String, tag: &TypeTag) {
+    match tag {
+        TypeTag::Bool => out.push_str("bool"),
+        TypeTag::U8 => out.push_str("u8"),
+        TypeTag::U64 => out.push_str("u64"),
+        TypeTag::U128 => out.push_str("u128"),
+        TypeTag::ByteArray => out.push_str("bytearray"),
+        TypeTag::Address => out.push_str("address"),
+        TypeTag::Vector(tag) => {
+            out.push_str("vector<");
+            write_type_name(out, tag);
+            out.push('>');
+        }
+        TypeTag::Struct(tag) => {
+            out.push_str("0x");
+            for byte in tag.address.as_ref() {
+                // Writing to a String cannot fail.
+                write!(out, "{:02x}", byte).unwrap();
+            }
+            out.push_str("::");
+            out.push_str(tag.module.as_str());
+            out.push_str("::");
+            out.push_str(tag.name.as_str());
+            if !tag.type_params.is_empty() {
+                out.push('<');
+                for (i, param) in tag.type_params.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_type_name(out, param);
+                }
+                out.push('>');
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Group operations on Ristretto255 points and scalars, all exchanged as their canonical 32-byte
+//! encodings.
+
+use crate::{
+    native_functions::{
+        abort_codes,
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use vm::{errors::VMResult, gas_schedule::NativeCostIndex};
+
+/// Length of the encoding of a Ristretto255 point or scalar
+const ENCODING_LENGTH: usize = 32;
+
+/// Starting error code number
+pub const DEFAULT_ERROR_CODE: u64 = abort_codes::RISTRETTO255;
+/// Point is not the canonical encoding of a Ristretto255 point
+pub const INVALID_POINT_ENCODING: u64 = DEFAULT_ERROR_CODE + 1;
+/// Scalar is not the canonical encoding of a scalar modulo the group order
+pub const INVALID_SCALAR_ENCODING: u64 = DEFAULT_ERROR_CODE + 2;
+
+pub fn native_point_add(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let b = arguments.pop_vec_of::<u8>()?;
+    let a = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::RISTRETTO_POINT_ADD,
+        1,
+    );
+    let (a, b) = match (decompress_point(&a), decompress_point(&b)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(INVALID_POINT_ENCODING),
+            ));
+        }
+    };
+    let sum = (a + b).compress().to_bytes().to_vec();
+    Ok(NativeResult::ok(cost, vec![Value::vector_u8(sum)]))
+}
+
+pub fn native_scalar_mul(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let scalar = arguments.pop_vec_of::<u8>()?;
+    let point = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::RISTRETTO_SCALAR_MUL,
+        1,
+    );
+    let point = match decompress_point(&point) {
+        Some(point) => point,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(INVALID_POINT_ENCODING),
+            ));
+        }
+    };
+    let scalar = match canonical_scalar(&scalar) {
+        Some(scalar) => scalar,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
+                    .with_sub_status(INVALID_SCALAR_ENCODING),
+            ));
+        }
+    };
+    let product = (point * scalar).compress().to_bytes().to_vec();
+    Ok(NativeResult::ok(cost, vec![Value::vector_u8(product)]))
+}
+
+pub fn native_point_is_valid(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let point = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let cost = native_gas(
+        context.cost_table(),
+        NativeCostIndex::RISTRETTO_POINT_IS_VALID,
+        1,
+    );
+    let is_valid = decompress_point(&point).is_some();
+    Ok(NativeResult::ok(cost, vec![Value::bool(is_valid)]))
+}
+
+/// Decodes a canonical point encoding, failing on any other input (including wrong lengths).
+pub(crate) fn decompress_point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    if bytes.len() != ENCODING_LENGTH {
+        return None;
+    }
+    CompressedRistretto::from_slice(bytes).decompress()
+}
+
+/// Decodes a canonical scalar encoding, failing on any other input (including wrong lengths).
+pub(crate) fn canonical_scalar(bytes: &[u8]) -> Option<Scalar> {
+    if bytes.len() != ENCODING_LENGTH {
+        return None;
+    }
+    let mut buf = [0u8; ENCODING_LENGTH];
+    buf.copy_from_slice(bytes);
+    Scalar::from_canonical_bytes(buf)
+}
@@ -2,20 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    native_functions::dispatch::{native_gas, NativeResult},
+    native_functions::{
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{charge_or_stop, native_gas, native_gas_detailed, NativeResult},
+        errors::signature::*,
+    },
     values::Value,
 };
 use bit_vec::BitVec;
 use libra_crypto::{
+    bls12381::{BLS12381PublicKey, BLS12381Signature},
     ed25519::{self, Ed25519PublicKey, Ed25519Signature},
     traits::*,
+    vrf::ecvrf::{Output, Proof, VRFPublicKey},
     HashValue,
 };
 use libra_types::{
     language_storage::TypeTag,
     vm_error::{StatusCode, VMStatus},
 };
-use std::{collections::VecDeque, convert::TryFrom};
+use secp256k1::{
+    curve::{Affine, Field, Jacobian, Scalar, ECMULT_CONTEXT},
+    Message, RecoveryId,
+};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
 use vm::{
     errors::VMResult,
     gas_schedule::{CostTable, NativeCostIndex},
@@ -23,99 +35,389 @@ use vm::{
 
 const BITMAP_SIZE: usize = 32;
 
-/// Starting error code number
-const DEFAULT_ERROR_CODE: u64 = 0x0ED2_5519;
-/// Batch signature verification failed
-const SIGNATURE_VERIFICATION_FAILURE: u64 = DEFAULT_ERROR_CODE + 1;
-/// Public keys deserialization error
-const PUBLIC_KEY_DESERIALIZATION_FAILURE: u64 = DEFAULT_ERROR_CODE + 2;
-/// Signatures deserialization error
-const SIGNATURE_DESERIALIZATION_FAILURE: u64 = DEFAULT_ERROR_CODE + 3;
-/// Bitmap is all zeros
-const ZERO_BITMAP_FAILURE: u64 = DEFAULT_ERROR_CODE + 4;
-/// Invalid bitmap length
-const INVALID_BITMAP_LENGTH_FAILURE: u64 = DEFAULT_ERROR_CODE + 5;
-/// Mismatch between bitmap's Hamming weight and number or size of signatures
-const SIGNATURE_SIZE_FAILURE: u64 = DEFAULT_ERROR_CODE + 6;
-/// Bitmap points to a non-existent key
-const BITMAP_PUBLIC_KEY_SIZE_FAILURE: u64 = DEFAULT_ERROR_CODE + 7;
-/// Length of bytes of concatenated keys exceeds the maximum allowed
-const OVERSIZED_PUBLIC_KEY_SIZE_FAILURE: u64 = DEFAULT_ERROR_CODE + 8;
-/// Concatenated Ed25519 public keys should be a multiple of 32 bytes
-const INVALID_PUBLIC_KEY_SIZE_FAILURE: u64 = DEFAULT_ERROR_CODE + 9;
+/// Length of the message hash accepted by secp256k1 public key recovery
+const SECP256K1_MESSAGE_LENGTH: usize = 32;
+/// Length of a compact (r || s) secp256k1 signature
+const SECP256K1_SIGNATURE_LENGTH: usize = 64;
+
+/// Length of a BIP-340 x-only public key
+const SCHNORR_PUBLIC_KEY_LENGTH: usize = 32;
+/// Length of a BIP-340 (r || s) signature
+const SCHNORR_SIGNATURE_LENGTH: usize = 64;
+/// Tag of the BIP-340 hash the challenge of a signature is computed with
+const SCHNORR_CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+/// Maximum number of public keys a BLS12-381 aggregate signature can be verified against.
+pub const MAX_AGGREGATE_PUBLIC_KEYS: usize = 256;
 
 pub fn native_ed25519_signature_verification(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
 ) -> VMResult<NativeResult> {
-    if arguments.len() != 3 {
-        let msg = format!(
-            "wrong number of arguments for ed25519_signature_verification expected 3 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
-    }
-    let msg = pop_arg!(arguments, Vec<u8>);
-    let pubkey = pop_arg!(arguments, Vec<u8>);
-    let signature = pop_arg!(arguments, Vec<u8>);
+    let msg = arguments.pop_vec_of::<u8>()?;
+    let pubkey = arguments.pop_vec_of::<u8>()?;
+    let signature = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
 
-    let cost = native_gas(cost_table, NativeCostIndex::ED25519_VERIFY, msg.len());
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::ED25519_VERIFY,
+        msg.len(),
+    );
 
     let sig = match ed25519::Ed25519Signature::try_from(signature.as_slice()) {
         Ok(sig) => sig,
         Err(_) => {
-            return Ok(NativeResult::err(
-                cost,
-                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                    .with_sub_status(DEFAULT_ERROR_CODE),
-            ));
+            return Ok(NativeResult::abort(cost, SIGNATURE_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown));
         }
     };
     let pk = match ed25519::Ed25519PublicKey::try_from(pubkey.as_slice()) {
         Ok(pk) => pk,
         Err(_) => {
-            return Ok(NativeResult::err(
-                cost,
-                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                    .with_sub_status(DEFAULT_ERROR_CODE),
-            ));
+            return Ok(
+                NativeResult::abort(cost, PUBLIC_KEY_DESERIALIZATION_FAILURE)
+                    .with_cost_breakdown(breakdown),
+            );
         }
     };
 
     let bool_value = sig.verify_arbitrary_msg(msg.as_slice(), &pk).is_ok();
     let return_values = vec![Value::bool(bool_value)];
-    Ok(NativeResult::ok(cost, return_values))
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+/// Recover the secp256k1 public key that signed a 32-byte message hash (Ethereum's `ecrecover`).
+///
+/// Returns the 64-byte uncompressed public key without its 0x04 prefix and `true`, or an empty
+/// vector and `false` when the inputs are malformed or no key can be recovered. Signatures with a
+/// high `s` value are rejected so that every message has a single valid signature per key.
+pub fn native_secp256k1_recover(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let signature = arguments.pop_vec_of::<u8>()?;
+    let recovery_id = arguments.pop_as::<u8>()?;
+    let hash = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SECP256K1_RECOVER,
+        hash.len(),
+    );
+
+    let return_values = match secp256k1_recover(&hash, recovery_id, &signature) {
+        Some(pubkey) => vec![Value::vector_u8(pubkey), Value::bool(true)],
+        None => vec![Value::vector_u8(vec![]), Value::bool(false)],
+    };
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+fn secp256k1_recover(hash: &[u8], recovery_id: u8, signature: &[u8]) -> Option<Vec<u8>> {
+    if hash.len() != SECP256K1_MESSAGE_LENGTH || signature.len() != SECP256K1_SIGNATURE_LENGTH {
+        return None;
+    }
+    let message = Message::parse_slice(hash).ok()?;
+    let recovery_id = RecoveryId::parse(recovery_id).ok()?;
+    let r = secp256k1_scalar(&signature[..32])?;
+    let s = secp256k1_scalar(&signature[32..])?;
+    if s.is_high() {
+        return None;
+    }
+    let pubkey = secp256k1::recover(&message, &secp256k1::Signature { r, s }, &recovery_id).ok()?;
+    Some(pubkey.serialize()[1..].to_vec())
+}
+
+/// Parse a 32-byte big-endian scalar, rejecting values not below the group order rather than
+/// reducing them like `secp256k1::Signature::parse` does.
+fn secp256k1_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let mut scalar = Scalar::default();
+    if bool::from(scalar.set_b32(&bytes32(bytes))) {
+        return None;
+    }
+    Some(scalar)
+}
+
+fn bytes32(bytes: &[u8]) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(bytes);
+    buf
+}
+
+/// Verify a BIP-340 Schnorr signature over secp256k1 on a message of any length.
+///
+/// The public key is a 32-byte x-only key and the signature is `r || s`. A public key that is not
+/// 32 bytes or a signature that is not 64 bytes is a charged abort, with a sub-status telling the
+/// two apart; keys and signatures of the right length that are not valid return `false`.
+pub fn native_schnorr_signature_verification(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let msg = arguments.pop_vec_of::<u8>()?;
+    let signature = arguments.pop_vec_of::<u8>()?;
+    let pubkey = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::SCHNORR_VERIFY,
+        msg.len(),
+    );
+    if pubkey.len() != SCHNORR_PUBLIC_KEY_LENGTH {
+        return Ok(
+            NativeResult::abort(cost, INVALID_SCHNORR_PUBLIC_KEY_LENGTH_FAILURE)
+                .with_cost_breakdown(breakdown),
+        );
+    }
+    if signature.len() != SCHNORR_SIGNATURE_LENGTH {
+        return Ok(
+            NativeResult::abort(cost, INVALID_SCHNORR_SIGNATURE_LENGTH_FAILURE)
+                .with_cost_breakdown(breakdown),
+        );
+    }
+
+    let bool_value = schnorr_verify(&pubkey, &signature, &msg);
+    let return_values = vec![Value::bool(bool_value)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
+}
+
+/// Verify a BIP-340 signature of `msg` by `pubkey`, both of their expected length.
+fn schnorr_verify(pubkey: &[u8], signature: &[u8], msg: &[u8]) -> bool {
+    // The public key is the point with x-coordinate `pubkey` and an even y-coordinate.
+    let mut x = Field::default();
+    let mut key = Affine::default();
+    if !x.set_b32(&bytes32(pubkey)) || !key.set_xo_var(&x, false) {
+        return false;
+    }
+    let mut r = Field::default();
+    if !r.set_b32(&bytes32(&signature[..32])) {
+        return false;
+    }
+    let s = match secp256k1_scalar(&signature[32..]) {
+        Some(s) => s,
+        None => return false,
+    };
+
+    // R = s * G - e * P must not be infinity, and have an even y-coordinate and x-coordinate r.
+    let mut challenge = Scalar::default();
+    // The challenge is reduced modulo the group order, overflowing or not.
+    let _ = challenge.set_b32(&schnorr_challenge(&signature[..32], pubkey, msg));
+    let mut point = Jacobian::default();
+    ECMULT_CONTEXT.ecmult(&mut point, &Jacobian::from_ge(&key), &-challenge, &s);
+    if point.is_infinity() {
+        return false;
+    }
+    let mut point = Affine::from_gej(&point);
+    point.x.normalize_var();
+    point.y.normalize_var();
+    !point.y.is_odd() && point.x.eq_var(&r)
+}
+
+/// The BIP-340 challenge `hash_BIP0340/challenge(r || pubkey || msg)`, where the tagged hash
+/// `hash_tag(x)` is `SHA256(SHA256(tag) || SHA256(tag) || x)`.
+fn schnorr_challenge(r: &[u8], pubkey: &[u8], msg: &[u8]) -> [u8; 32] {
+    let tag = Sha256::digest(SCHNORR_CHALLENGE_TAG);
+    let mut hasher = Sha256::new();
+    hasher.input(&tag);
+    hasher.input(&tag);
+    hasher.input(r);
+    hasher.input(pubkey);
+    hasher.input(msg);
+    bytes32(&hasher.result())
+}
+
+/// Verify a BLS12-381 aggregate signature by several public keys on a single message.
+///
+/// The caller is responsible for only passing keys whose possession has been proven, as is the
+/// case for validator keys; otherwise a rogue key can forge the aggregate.
+pub fn native_bls12381_aggregate_signature_verification(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let msg = arguments.pop_vec_of::<u8>()?;
+    let signature = arguments.pop_vec_of::<u8>()?;
+    let pubkeys = arguments.pop_vec_of::<Vec<u8>>()?;
+    arguments.finish()?;
+
+    // Charge per public key, with the empty list paying for one.
+    let num_pubkeys = pubkeys.len();
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::BLS12381_AGGREGATE_VERIFY,
+        std::cmp::max(num_pubkeys, 1),
+    );
+    if num_pubkeys == 0 {
+        return Ok(
+            NativeResult::abort(cost, EMPTY_PUBLIC_KEYS_FAILURE).with_cost_breakdown(breakdown)
+        );
+    }
+    if num_pubkeys > MAX_AGGREGATE_PUBLIC_KEYS {
+        return Ok(
+            NativeResult::abort(cost, TOO_MANY_PUBLIC_KEYS_FAILURE).with_cost_breakdown(breakdown)
+        );
+    }
+
+    let sig = match BLS12381Signature::try_from(signature.as_slice()) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return Ok(NativeResult::abort(cost, SIGNATURE_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown));
+        }
+    };
+    let pks: Result<Vec<_>, _> = pubkeys
+        .iter()
+        .map(|pk| BLS12381PublicKey::try_from(pk.as_slice()))
+        .collect();
+    let pks = match pks {
+        Ok(pks) => pks,
+        Err(_) => {
+            return Ok(
+                NativeResult::abort(cost, PUBLIC_KEY_DESERIALIZATION_FAILURE)
+                    .with_cost_breakdown(breakdown),
+            );
+        }
+    };
+
+    // Keys cancelling out to the identity do not verify anything.
+    let bool_value = match BLS12381PublicKey::aggregate(&pks) {
+        Ok(aggregate_pk) => sig
+            .verify_arbitrary_msg(msg.as_slice(), &aggregate_pk)
+            .is_ok(),
+        Err(_) => false,
+    };
+    let return_values = vec![Value::bool(bool_value)];
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
 }
 
 /// Batch verify a collection of signatures using a bitmap for matching signatures to keys.
 pub fn native_ed25519_threshold_signature_verification(
-    _ty_args: Vec<TypeTag>,
-    mut arguments: VecDeque<Value>,
-    cost_table: &CostTable,
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
 ) -> VMResult<NativeResult> {
-    if arguments.len() != 4 {
-        let msg = format!(
-            "wrong number of arguments for ed25519_threshold_signature_verification expected 4 found {}",
-            arguments.len()
-        );
-        return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
+    let message = arguments.pop_vec_of::<u8>()?;
+    let public_keys = arguments.pop_vec_of::<u8>()?;
+    let signatures = arguments.pop_vec_of::<u8>()?;
+    let bitmap = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    ed25519_threshold_signature_verification(&bitmap, &signatures, &public_keys, &message, context)
+}
+
+fn ed25519_threshold_signature_verification(
+    bitmap: &[u8],
+    signatures: &[u8],
+    public_keys: &[u8],
+    message: &[u8],
+    context: &dyn NativeContext,
+) -> VMResult<NativeResult> {
+    let cost_table = context.cost_table();
+    let bitvec = BitVec::from_bytes(bitmap);
+
+    let num_of_sigs = match sanity_check(
+        &bitvec,
+        &signatures,
+        &public_keys,
+        NativeCostIndex::ED25519_THRESHOLD_VERIFY,
+        cost_table,
+    ) {
+        Ok(sig_count) => sig_count,
+        Err(result) => return Ok(result),
+    };
+    let (cost, breakdown) = native_gas_detailed(
+        cost_table,
+        NativeCostIndex::ED25519_THRESHOLD_VERIFY,
+        num_of_sigs as usize * message.len(),
+    );
+
+    // Signatures are charged one at a time, so that a transaction that cannot pay for all of them
+    // stops at the first one it cannot pay for.
+    let mut sigs = Vec::with_capacity(num_of_sigs as usize);
+    for (i, chunk) in signatures.chunks(64).enumerate() {
+        charge_or_stop(
+            context,
+            native_gas(
+                cost_table,
+                NativeCostIndex::ED25519_THRESHOLD_VERIFY,
+                (i + 1) * message.len(),
+            ),
+        )?;
+        match Ed25519Signature::try_from(chunk) {
+            Ok(signature) => sigs.push(signature),
+            // Signature deserialization error
+            Err(_) => {
+                return Ok(NativeResult::abort(cost, SIGNATURE_DESERIALIZATION_FAILURE)
+                    .with_cost_breakdown(breakdown))
+            }
+        }
     }
-    let message = pop_arg!(arguments, Vec<u8>);
-    let public_keys = pop_arg!(arguments, Vec<u8>);
-    let signatures = pop_arg!(arguments, Vec<u8>);
-    let bitmap = pop_arg!(arguments, Vec<u8>);
 
-    Ok(ed25519_threshold_signature_verification(
+    let key_chunks: ::std::result::Result<Vec<_>, _> = public_keys
+        .chunks(32)
+        .map(Ed25519PublicKey::try_from)
+        .collect();
+
+    Ok(match key_chunks {
+        Ok(keys) => {
+            let keys_and_signatures = matching_keys_and_signatures(num_of_sigs, bitvec, sigs, keys);
+            let hash_value = match HashValue::from_slice(message) {
+                Err(_) => {
+                    return Ok(NativeResult::abort(cost, INVALID_MESSAGE_LENGTH_FAILURE)
+                        .with_cost_breakdown(breakdown))
+                }
+                Ok(hash_value) => hash_value,
+            };
+            match Ed25519Signature::batch_verify_signatures(&hash_value, keys_and_signatures) {
+                Ok(()) => NativeResult::ok(cost, vec![Value::u64(num_of_sigs)])
+                    .with_cost_breakdown(breakdown),
+                Err(_) =>
+                // Batch verification failed
+                {
+                    NativeResult::abort(cost, SIGNATURE_VERIFICATION_FAILURE)
+                        .with_cost_breakdown(breakdown)
+                }
+            }
+        }
+        Err(_) =>
+        // Key deserialization error
+        {
+            NativeResult::abort(cost, PUBLIC_KEY_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown)
+        }
+    })
+}
+
+/// Verify a multi-signature in which a bitmap selects the signing subset of the public keys.
+///
+/// Only the signatures of the set bits are included, in the order of their keys. Returns whether
+/// every included signature is valid for its key; a bitmap that does not match the signatures and
+/// keys is a charged error.
+pub fn native_multi_ed25519_signature_verification(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let message = arguments.pop_vec_of::<u8>()?;
+    let public_keys = arguments.pop_vec_of::<u8>()?;
+    let signatures = arguments.pop_vec_of::<u8>()?;
+    let bitmap = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    Ok(multi_ed25519_signature_verification(
         &bitmap,
         &signatures,
         &public_keys,
         &message,
-        cost_table,
+        context.cost_table(),
     ))
 }
 
-fn ed25519_threshold_signature_verification(
+fn multi_ed25519_signature_verification(
     bitmap: &[u8],
     signatures: &[u8],
     public_keys: &[u8],
@@ -124,79 +426,94 @@ fn ed25519_threshold_signature_verification(
 ) -> NativeResult {
     let bitvec = BitVec::from_bytes(bitmap);
 
-    let num_of_sigs = match sanity_check(&bitvec, &signatures, &public_keys, cost_table) {
+    let num_of_sigs = match sanity_check(
+        &bitvec,
+        &signatures,
+        &public_keys,
+        NativeCostIndex::MULTI_ED25519_VERIFY,
+        cost_table,
+    ) {
         Ok(sig_count) => sig_count,
         Err(result) => return result,
     };
-    let cost = native_gas(
+    let (cost, breakdown) = native_gas_detailed(
         cost_table,
-        NativeCostIndex::ED25519_THRESHOLD_VERIFY,
-        num_of_sigs as usize * message.len(),
+        NativeCostIndex::MULTI_ED25519_VERIFY,
+        num_of_sigs as usize,
     );
 
-    let sig_chunks: ::std::result::Result<Vec<_>, _> = signatures
+    let signatures: ::std::result::Result<Vec<_>, _> = signatures
         .chunks(64)
         .map(Ed25519Signature::try_from)
         .collect();
+    let signatures = match signatures {
+        Ok(signatures) => signatures,
+        Err(_) => {
+            return NativeResult::abort(cost, SIGNATURE_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown)
+        }
+    };
+    let keys: ::std::result::Result<Vec<_>, _> = public_keys
+        .chunks(32)
+        .map(Ed25519PublicKey::try_from)
+        .collect();
+    let keys = match keys {
+        Ok(keys) => keys,
+        Err(_) => {
+            return NativeResult::abort(cost, PUBLIC_KEY_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown)
+        }
+    };
 
-    match sig_chunks {
-        Ok(signatures) => {
-            let key_chunks: ::std::result::Result<Vec<_>, _> = public_keys
-                .chunks(32)
-                .map(Ed25519PublicKey::try_from)
-                .collect();
-
-            match key_chunks {
-                Ok(keys) => {
-                    let keys_and_signatures =
-                        matching_keys_and_signatures(num_of_sigs, bitvec, signatures, keys);
-                    let hash_value = match HashValue::from_slice(message) {
-                        Err(_) => {
-                            return NativeResult::err(
-                                cost,
-                                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                                    .with_sub_status(DEFAULT_ERROR_CODE),
-                            )
-                        }
-                        Ok(hash_value) => hash_value,
-                    };
-                    match Ed25519Signature::batch_verify_signatures(
-                        &hash_value,
-                        keys_and_signatures,
-                    ) {
-                        Ok(()) => NativeResult::ok(cost, vec![Value::u64(num_of_sigs)]),
-                        Err(_) =>
-                        // Batch verification failed
-                        {
-                            NativeResult::err(
-                                cost,
-                                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                                    .with_sub_status(SIGNATURE_VERIFICATION_FAILURE),
-                            )
-                        }
-                    }
-                }
-                Err(_) =>
-                // Key deserialization error
-                {
-                    NativeResult::err(
-                        cost,
-                        VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                            .with_sub_status(PUBLIC_KEY_DESERIALIZATION_FAILURE),
-                    )
-                }
-            }
+    let bool_value = matching_keys_and_signatures(num_of_sigs, bitvec, signatures, keys)
+        .iter()
+        .all(|(key, signature)| signature.verify_arbitrary_msg(message, key).is_ok());
+    NativeResult::ok(cost, vec![Value::bool(bool_value)]).with_cost_breakdown(breakdown)
+}
+
+/// Verify an ECVRF proof of `input` under `pubkey`, returning whether it is valid together with
+/// the VRF output hash, which is empty when the proof does not verify.
+pub fn native_ecvrf_verify(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let input = arguments.pop_vec_of::<u8>()?;
+    let proof = arguments.pop_vec_of::<u8>()?;
+    let pubkey = arguments.pop_vec_of::<u8>()?;
+    arguments.finish()?;
+
+    let (cost, breakdown) = native_gas_detailed(
+        context.cost_table(),
+        NativeCostIndex::ECVRF_VERIFY,
+        input.len(),
+    );
+
+    let key = match VRFPublicKey::try_from(pubkey.as_slice()) {
+        Ok(key) => key,
+        Err(_) => {
+            return Ok(
+                NativeResult::abort(cost, PUBLIC_KEY_DESERIALIZATION_FAILURE)
+                    .with_cost_breakdown(breakdown),
+            );
         }
-        Err(_) =>
-        // Signature deserialization error
-        {
-            NativeResult::err(
-                cost,
-                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                    .with_sub_status(SIGNATURE_DESERIALIZATION_FAILURE),
-            )
+    };
+    let proof = match Proof::try_from(proof.as_slice()) {
+        Ok(proof) => proof,
+        Err(_) => {
+            return Ok(NativeResult::abort(cost, PROOF_DESERIALIZATION_FAILURE)
+                .with_cost_breakdown(breakdown));
         }
-    }
+    };
+
+    let return_values = match key.verify(&proof, input.as_slice()) {
+        Ok(()) => vec![
+            Value::bool(true),
+            Value::vector_u8(Output::from(&proof).to_bytes().to_vec()),
+        ],
+        Err(_) => vec![Value::bool(false), Value::vector_u8(vec![])],
+    };
+    Ok(NativeResult::ok(cost, return_values).with_cost_breakdown(breakdown))
 }
 
 fn matching_keys_and_signatures(
@@ -230,26 +547,34 @@ fn sanity_check(
     bitmap: &BitVec<u32>,
     signatures: &[u8],
     pubkeys: &[u8],
+    cost_index: NativeCostIndex,
     cost_table: &CostTable,
 ) -> std::result::Result<u64, NativeResult> {
     let bitmap_len = bitmap.len();
     let signatures_len = signatures.len();
     let public_keys_len = pubkeys.len();
 
-    let cost = native_gas(
+    let (cost, breakdown) = native_gas_detailed(
         cost_table,
-        NativeCostIndex::ED25519_THRESHOLD_VERIFY,
+        cost_index,
         bitmap_len + signatures_len + public_keys_len,
     );
 
+    // Malformed inputs are rejected before any signature is verified, so the cost of the
+    // signatures is refunded.
+    let reject = |sub_status| {
+        NativeResult::err_with_refund(
+            cost,
+            native_gas(cost_table, cost_index, signatures_len),
+            VMStatus::new(StatusCode::ABORTED).with_sub_status(sub_status),
+        )
+        .with_cost_breakdown(breakdown)
+    };
+
     // Ensure a BITMAP_SIZE bitmap.
     if bitmap_len != BITMAP_SIZE {
         // Invalid bitmap length
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                .with_sub_status(INVALID_BITMAP_LENGTH_FAILURE),
-        ));
+        return Err(reject(INVALID_BITMAP_LENGTH_FAILURE));
     }
 
     let mut bitmap_last_bit_set: usize = 0; // This is fine as we expect at least one set bit.
@@ -262,46 +587,27 @@ fn sanity_check(
     }
     if bitmap_count_ones == 0 {
         // Bitmap is all zeros
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(ZERO_BITMAP_FAILURE),
-        ));
+        return Err(reject(ZERO_BITMAP_FAILURE));
     }
     // Ensure we have as many signatures as the number of set bits in bitmap.
     if bitmap_count_ones * 64 != signatures_len {
         // Mismatch between Bitmap Hamming weight and number of signatures
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                .with_sub_status(SIGNATURE_SIZE_FAILURE),
-        ));
+        return Err(reject(SIGNATURE_SIZE_FAILURE));
     }
     // Ensure that we have at least as many keys as the index of the last set bit in bitmap.
     if public_keys_len < 32 * (bitmap_last_bit_set + 1) {
         // Bitmap points to a non-existent key
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                .with_sub_status(BITMAP_PUBLIC_KEY_SIZE_FAILURE),
-        ));
+        return Err(reject(BITMAP_PUBLIC_KEY_SIZE_FAILURE));
     }
     // Ensure no more than BITMAP_SIZE keys.
     if public_keys_len > 32 * BITMAP_SIZE {
         // Length of bytes of concatenated keys exceeds the maximum allowed
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                .with_sub_status(OVERSIZED_PUBLIC_KEY_SIZE_FAILURE),
-        ));
+        return Err(reject(OVERSIZED_PUBLIC_KEY_SIZE_FAILURE));
     }
     // Ensure ByteArray for keys is a multiple of 32 bytes.
     if public_keys_len % 32 != 0 {
         // Concatenated Ed25519 public keys should be a multiple of 32 bytes
-        return Err(NativeResult::err(
-            cost,
-            VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                .with_sub_status(INVALID_PUBLIC_KEY_SIZE_FAILURE),
-        ));
+        return Err(reject(INVALID_PUBLIC_KEY_SIZE_FAILURE));
     }
     Ok(bitmap_count_ones as u64)
 }
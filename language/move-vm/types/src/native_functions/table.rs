@@ -0,0 +1,263 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A registry of the native functions the VM can call: the built-in ones, and the ones an
+//! embedder of the VM registers for modules of its own.
+
+use crate::{
+    loaded_data::types::Type,
+    native_functions::{
+        context::NativeContext,
+        dispatch::{
+            charge_errors, check_num_args, check_return_values, check_type_args, complete_layouts,
+            NativeArguments, NativeFunction, NativeResult, CHECK_RETURN_VALUES,
+        },
+    },
+};
+use libra_types::{
+    account_config::CORE_CODE_ADDRESS,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    vm_error::{StatusCode, VMStatus},
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use vm::{
+    access::ModuleAccess,
+    errors::VMResult,
+    file_format::{FunctionSignature, Kind},
+    gas_schedule::{GasAlgebra, GasUnits},
+    views::ModuleView,
+};
+
+/// The implementation of a registered native function. It is called with the same arguments as
+/// `NativeFunction::dispatch`.
+pub type NativeFunctionFn = Box<
+    dyn Fn(
+            &mut dyn NativeContext,
+            &[TypeTag],
+            Vec<Kind>,
+            Vec<Type>,
+            NativeArguments,
+        ) -> VMResult<NativeResult>
+        + Send
+        + Sync,
+>;
+
+/// A native function held by a `NativeFunctionTable`.
+pub enum NativeFunctionEntry {
+    /// A native function built into the VM.
+    Builtin(NativeFunction),
+    /// A native function registered with `NativeFunctionTable::register`.
+    Registered {
+        name: String,
+        num_args: usize,
+        signature: FunctionSignature,
+        function: NativeFunctionFn,
+    },
+}
+
+impl NativeFunctionEntry {
+    /// Executes the native function, see `NativeFunction::dispatch`. Registered functions are
+    /// only called with as many arguments as they were registered with, and with the type
+    /// arguments their signature declares and their layouts. In debug builds, the values they
+    /// return are checked against their signature as well. An `Err` they return is charged as
+    /// for built-in functions, from their base cost, which is none as they are not in the cost
+    /// table.
+    pub fn dispatch(
+        &self,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: NativeArguments,
+    ) -> VMResult<NativeResult> {
+        match self {
+            Self::Builtin(native) => native.dispatch(context, t, kinds, layouts, v),
+            Self::Registered {
+                name,
+                num_args,
+                signature,
+                function,
+            } => {
+                check_num_args(*num_args, v.len())?;
+                check_type_args(&signature.type_formals, t, &kinds)?;
+                let layouts = complete_layouts(context, t, layouts)?;
+                let result = charge_errors(
+                    context,
+                    GasUnits::new(0),
+                    function(context, t, kinds, layouts, v),
+                );
+                if CHECK_RETURN_VALUES {
+                    check_return_values(
+                        format_args!("{}", name),
+                        &signature.return_types,
+                        t,
+                        &result,
+                    )?;
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// `dispatch`, for a VM running at protocol version `version`. Built-in functions fail with
+    /// the error of `NativeFunction::check_available`, before any native code runs, if they do
+    /// not exist at that version. Registered functions exist at every version.
+    pub fn dispatch_with_version(
+        &self,
+        version: u64,
+        context: &mut dyn NativeContext,
+        t: &[TypeTag],
+        kinds: Vec<Kind>,
+        layouts: Vec<Type>,
+        v: NativeArguments,
+    ) -> VMResult<NativeResult> {
+        self.check_available(version)?;
+        self.dispatch(context, t, kinds, layouts, v)
+    }
+
+    /// Whether the native function exists at protocol version `version`.
+    pub fn is_available(&self, version: u64) -> bool {
+        match self {
+            Self::Builtin(native) => native.is_available(version),
+            Self::Registered { .. } => true,
+        }
+    }
+
+    /// Fails with the error of `NativeFunction::check_available` if the native function does not
+    /// exist at protocol version `version`.
+    pub fn check_available(&self, version: u64) -> VMResult<()> {
+        match self {
+            Self::Builtin(native) => native.check_available(version),
+            Self::Registered { .. } => Ok(()),
+        }
+    }
+
+    /// The number of arguments to the native function.
+    pub fn num_args(&self) -> usize {
+        match self {
+            Self::Builtin(native) => native.num_args(),
+            Self::Registered { num_args, .. } => *num_args,
+        }
+    }
+
+    /// The signature of the native function, see `NativeFunction::signature`. The signature of
+    /// a registered function is the one it was registered with, whatever the module.
+    pub fn signature<T: ModuleAccess>(
+        &self,
+        m: Option<&ModuleView<T>>,
+    ) -> VMResult<Option<FunctionSignature>> {
+        match self {
+            Self::Builtin(native) => native.signature(m),
+            Self::Registered { signature, .. } => Ok(Some(signature.clone())),
+        }
+    }
+}
+
+/// The native functions known to a VM, by the module declaring them and their name there.
+pub struct NativeFunctionTable(HashMap<ModuleId, HashMap<Identifier, NativeFunctionEntry>>);
+
+impl NativeFunctionTable {
+    /// A table holding the built-in native functions.
+    pub fn new() -> Self {
+        let mut table = Self(HashMap::new());
+        for (module, name, native) in NativeFunction::declarations() {
+            table.insert(module, name, NativeFunctionEntry::Builtin(native));
+        }
+        table
+    }
+
+    /// The table holding only the built-in native functions, for the VMs and verifiers that are
+    /// given no other table.
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: Lazy<NativeFunctionTable> = Lazy::new(NativeFunctionTable::new);
+        &BUILTIN
+    }
+
+    /// Registers `function` as the native function `name` of `module`, taking `num_args`
+    /// arguments and with the signature `signature`.
+    ///
+    /// Fails with `DUPLICATE_ELEMENT` if the table already holds a native function under that
+    /// name, built in or not, and with `NATIVE_FUNCTION_INTERNAL_INCONSISTENCY` if `signature`
+    /// does not take `num_args` arguments.
+    pub fn register(
+        &mut self,
+        module: ModuleId,
+        name: Identifier,
+        num_args: usize,
+        signature: FunctionSignature,
+        function: NativeFunctionFn,
+    ) -> VMResult<()> {
+        let qualified_name = format!("{}::{}::{}", module.address(), module.name(), name);
+        if let Some(entry) = self.resolve(&module, &name) {
+            let msg = match entry {
+                NativeFunctionEntry::Builtin(_) => {
+                    format!("{} shadows a built-in native function", qualified_name)
+                }
+                NativeFunctionEntry::Registered { .. } => {
+                    format!("{} is already registered", qualified_name)
+                }
+            };
+            return Err(VMStatus::new(StatusCode::DUPLICATE_ELEMENT).with_message(msg));
+        }
+        if module.address() == &CORE_CODE_ADDRESS {
+            let msg = format!(
+                "{} is declared by a core module, whose natives are built in",
+                qualified_name
+            );
+            return Err(VMStatus::new(StatusCode::DUPLICATE_ELEMENT).with_message(msg));
+        }
+        if num_args != signature.arg_types.len() {
+            let msg = format!(
+                "{} is registered with {} arguments but its signature takes {}",
+                qualified_name,
+                num_args,
+                signature.arg_types.len()
+            );
+            return Err(
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(msg),
+            );
+        }
+
+        let entry = NativeFunctionEntry::Registered {
+            name: qualified_name,
+            num_args,
+            signature,
+            function,
+        };
+        self.insert(module, name, entry);
+        Ok(())
+    }
+
+    /// Looks up the native function `function_name` declared by `module`.
+    pub fn resolve(
+        &self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+    ) -> Option<&NativeFunctionEntry> {
+        self.0.get(module)?.get(function_name)
+    }
+
+    /// `resolve`, for a VM running at protocol version `version`: built-in functions only
+    /// available from later versions are not resolved.
+    pub fn resolve_with_version(
+        &self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        version: u64,
+    ) -> Option<&NativeFunctionEntry> {
+        self.resolve(module, function_name)
+            .filter(|entry| entry.is_available(version))
+    }
+
+    fn insert(&mut self, module: ModuleId, name: Identifier, entry: NativeFunctionEntry) {
+        self.0.entry(module).or_default().insert(name, entry);
+    }
+}
+
+impl Default for NativeFunctionTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Metadata about the executing transaction, read from the `NativeContext`.
+
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::language_storage::TypeTag;
+use vm::{
+    errors::VMResult,
+    gas_schedule::{GasAlgebra, NativeCostIndex},
+};
+
+/// Return the address of the account that sent the transaction.
+pub fn native_sender(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::TXN_SENDER, 1);
+    let return_values = vec![Value::address(context.sender())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Return the maximum number of gas units the transaction may spend.
+pub fn native_max_gas_amount(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::TXN_MAX_GAS_AMOUNT, 1);
+    let return_values = vec![Value::u64(context.max_gas_amount().get())];
+    Ok(NativeResult::ok(cost, return_values))
+}
+
+/// Return the number of gas units left to the transaction once this call has been charged, so a
+/// module can stop before it runs out of gas.
+pub fn native_gas_remaining(
+    context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+
+    let cost = native_gas(context.cost_table(), NativeCostIndex::TXN_GAS_REMAINING, 1);
+    // If the call cannot be paid for the transaction runs out of gas and the value is never seen
+    let remaining = context.remaining_gas().get().saturating_sub(cost.get());
+    let return_values = vec![Value::u64(remaining)];
+    Ok(NativeResult::ok(cost, return_values))
+}
@@ -0,0 +1,54 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Natives of the `UnitTest` module, for unit testing Move code. They are only compiled when this
+//! crate is built with the `testing` feature, as they would let any code act as any account. They
+//! cost no gas.
+
+use crate::{
+    native_functions::{arguments::Arguments, context::NativeContext, dispatch::NativeResult},
+    values::Value,
+};
+use libra_crypto::HashValue;
+use libra_types::{account_address::AccountAddress, language_storage::TypeTag};
+use vm::{
+    errors::VMResult,
+    gas_schedule::{GasAlgebra, GasUnits},
+};
+
+/// Prefix of the preimage of the addresses `create_signers_for_testing` creates.
+const SIGNER_SEED: &[u8] = b"UnitTest::create_signers_for_testing";
+
+/// The `i`-th address created by `create_signers_for_testing`: the SHA3-256 hash of
+/// `SIGNER_SEED` followed by `i` as a little-endian `u64`. It is the same in every run, and does
+/// not collide with the addresses of the core modules.
+pub fn test_signer(i: u64) -> AccountAddress {
+    let mut preimage = SIGNER_SEED.to_vec();
+    preimage.extend_from_slice(&i.to_le_bytes());
+    AccountAddress::new(*HashValue::from_sha3_256(&preimage).as_ref())
+}
+
+/// Create the addresses of `num_signers` accounts for a test to act as, `test_signer(0)` up to
+/// `test_signer(num_signers - 1)`.
+pub fn native_create_signers_for_testing(
+    _context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    mut arguments: Arguments,
+) -> VMResult<NativeResult> {
+    let num_signers = arguments.pop_as::<u64>()?;
+    arguments.finish()?;
+
+    let signers = Value::vector_address((0..num_signers).map(test_signer));
+    Ok(NativeResult::ok(GasUnits::new(0), vec![signers]))
+}
+
+/// Does nothing. Modules compiled for unit tests call it so that they fail to link, and so cannot
+/// be published, against a VM built without the `testing` feature.
+pub fn native_poison(
+    _context: &dyn NativeContext,
+    _ty_args: &[TypeTag],
+    arguments: Arguments,
+) -> VMResult<NativeResult> {
+    arguments.finish()?;
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
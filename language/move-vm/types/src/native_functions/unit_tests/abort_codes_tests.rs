@@ -0,0 +1,80 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::native_functions::{
+    abort_codes::{self, NAMESPACE_SIZE},
+    dispatch::NativeResult,
+    errors::{signature, vector},
+    hash, lcs, primitive_helpers,
+};
+use libra_types::vm_error::StatusCode;
+use vm::gas_schedule::{GasAlgebra, GasUnits};
+
+#[test]
+fn abort_is_aborted_with_the_code_as_sub_status() {
+    let result = NativeResult::abort(GasUnits::new(7), hash::HKDF_INVALID_OUTPUT_LENGTH);
+    assert_eq!(result.cost.get(), 7);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(hash::HKDF_INVALID_OUTPUT_LENGTH));
+    assert_eq!(status.message, None);
+}
+
+#[test]
+fn abort_with_message_keeps_the_message() {
+    let result = NativeResult::abort_with_message(
+        GasUnits::new(7),
+        vector::DESTROY_NON_EMPTY_VEC,
+        "length 3".to_string(),
+    );
+    assert_eq!(result.cost.get(), 7);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(vector::DESTROY_NON_EMPTY_VEC));
+    assert_eq!(status.message.as_deref(), Some("length 3"));
+}
+
+#[test]
+fn namespaces_do_not_overlap() {
+    for (i, (module, namespace)) in abort_codes::ALL.iter().enumerate() {
+        for (other_module, other_namespace) in &abort_codes::ALL[i + 1..] {
+            let distance = if namespace > other_namespace {
+                namespace - other_namespace
+            } else {
+                other_namespace - namespace
+            };
+            assert!(
+                distance >= NAMESPACE_SIZE,
+                "{} and {} have overlapping namespaces",
+                module,
+                other_module
+            );
+        }
+    }
+}
+
+#[test]
+fn abort_codes_are_in_their_module_namespace() {
+    let in_namespace = |code: u64, namespace: u64| {
+        assert!(code > namespace && code < namespace + NAMESPACE_SIZE);
+    };
+    in_namespace(hash::HKDF_INVALID_OUTPUT_LENGTH, abort_codes::HASH);
+    in_namespace(lcs::VALUE_TOO_LARGE, abort_codes::LCS);
+    in_namespace(
+        primitive_helpers::INVALID_AUTH_KEY_LENGTH_FAILURE,
+        abort_codes::PRIMITIVE_HELPERS,
+    );
+    in_namespace(
+        signature::PROOF_DESERIALIZATION_FAILURE,
+        abort_codes::SIGNATURE,
+    );
+    for code in &[
+        vector::INDEX_OUT_OF_BOUNDS,
+        vector::POP_EMPTY_VEC,
+        vector::DESTROY_NON_EMPTY_VEC,
+        vector::FILL_TOO_LONG,
+        vector::VECTOR_TOO_LONG,
+    ] {
+        in_namespace(*code, abort_codes::VECTOR);
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::{
+        account,
+        context::{InMemoryResources, MockNativeContext},
+        dispatch::{NativeFunction, NativeResult},
+    },
+    values::{Struct, Value},
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_config,
+    contract_event::ContractEvent,
+    event::EventKey,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    vm_error::StatusCode,
+};
+use vm::{file_format::Kind, gas_schedule::GasAlgebra};
+
+fn payment_tag() -> TypeTag {
+    TypeTag::Struct(StructTag {
+        address: AccountAddress::default(),
+        module: Identifier::new("LibraAccount").unwrap(),
+        name: Identifier::new("SentPaymentEvent").unwrap(),
+        type_params: vec![],
+    })
+}
+
+fn payment_layout() -> Type {
+    Type::Struct(StructDef::new(vec![Type::U64, Type::Address]))
+}
+
+fn payment(amount: u64, payee: AccountAddress) -> Value {
+    Value::struct_(Struct::pack(vec![
+        Value::u64(amount),
+        Value::address(payee),
+    ]))
+}
+
+fn write_to_event_store(
+    context: &mut MockNativeContext,
+    guid: EventKey,
+    count: u64,
+    msg: Value,
+) -> NativeResult {
+    NativeFunction::AccountWriteEvent
+        .dispatch(
+            context,
+            &[payment_tag()],
+            vec![Kind::Unrestricted],
+            vec![payment_layout()],
+            args(vec![
+                Value::vector_u8(guid.to_vec()),
+                Value::u64(count),
+                msg,
+            ]),
+        )
+        .unwrap()
+}
+
+#[test]
+fn write_to_event_store_emits_to_the_sink() {
+    let mut context = MockNativeContext::for_testing().with_event_sink();
+    let guid = EventKey::new_from_address(&AccountAddress::new([1; 32]), 0);
+    let payee = AccountAddress::new([2; 32]);
+    for (count, amount) in [10, 20].iter().enumerate() {
+        let result =
+            write_to_event_store(&mut context, guid, count as u64, payment(*amount, payee));
+        // The payload is a u64 followed by an address
+        assert_eq!(result.cost.get(), 40);
+        assert!(result.result.unwrap().is_empty());
+    }
+
+    let payload = |amount: u64| {
+        let mut payload = amount.to_le_bytes().to_vec();
+        payload.extend_from_slice(payee.as_ref());
+        payload
+    };
+    assert_eq!(
+        context.events.unwrap(),
+        vec![
+            ContractEvent::new(guid, 0, payment_tag(), payload(10)),
+            ContractEvent::new(guid, 1, payment_tag(), payload(20)),
+        ]
+    );
+}
+
+#[test]
+fn write_to_event_store_without_sink() {
+    let mut context = MockNativeContext::for_testing();
+    let guid = EventKey::new_from_address(&AccountAddress::new([1; 32]), 0);
+    let result = write_to_event_store(
+        &mut context,
+        guid,
+        0,
+        payment(10, AccountAddress::default()),
+    );
+    assert_eq!(result.cost.get(), 40);
+    let status = result.result.err().unwrap();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(account::NO_EVENT_SINK));
+}
+
+#[test]
+fn write_to_event_store_checks_type_argument_kinds() {
+    let guid = EventKey::new_from_address(&AccountAddress::new([1; 32]), 0);
+    let write = |context: &mut MockNativeContext, kinds| {
+        NativeFunction::AccountWriteEvent.dispatch(
+            context,
+            &[payment_tag()],
+            kinds,
+            vec![payment_layout()],
+            args(vec![
+                Value::vector_u8(guid.to_vec()),
+                Value::u64(0),
+                payment(10, AccountAddress::default()),
+            ]),
+        )
+    };
+
+    // Events cannot carry resources
+    let mut context = MockNativeContext::for_testing().with_event_sink();
+    let status = write(&mut context, vec![Kind::Resource]).err().unwrap();
+    assert_eq!(status.major_status, StatusCode::CONTRAINT_KIND_MISMATCH);
+    assert!(context.events.unwrap().is_empty());
+
+    // A type argument of unknown kind is let through
+    let mut context = MockNativeContext::for_testing().with_event_sink();
+    assert!(write(&mut context, vec![]).unwrap().result.is_ok());
+    assert_eq!(context.events.unwrap().len(), 1);
+}
+
+#[test]
+fn write_to_event_store_rejects_bad_guids() {
+    let mut context = MockNativeContext::for_testing().with_event_sink();
+    let status = account::native_write_to_event_store(
+        &mut context,
+        &[TypeTag::U64],
+        vec![Type::U64],
+        args(vec![
+            Value::vector_u8(vec![0; 3]),
+            Value::u64(0),
+            Value::u64(1),
+        ]),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(status.major_status, StatusCode::EVENT_KEY_MISMATCH);
+    assert!(context.events.unwrap().is_empty());
+}
+
+fn account_layout() -> StructDef {
+    StructDef::new(vec![Type::U64, Type::Bool])
+}
+
+fn account_resource(balance: u64) -> Value {
+    Value::struct_(Struct::pack(vec![Value::u64(balance), Value::bool(true)]))
+}
+
+fn save_account(
+    context: &mut MockNativeContext,
+    address: AccountAddress,
+    account: Value,
+) -> NativeResult {
+    NativeFunction::AccountSaveAccount
+        .dispatch(
+            context,
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::address(address), account]),
+        )
+        .unwrap()
+}
+
+fn saved_account(resources: &InMemoryResources, address: AccountAddress) -> Option<&[u8]> {
+    resources.get(address, &account_config::account_struct_tag())
+}
+
+#[test]
+fn save_account_round_trip() {
+    let mut context = MockNativeContext::for_testing()
+        .with_struct_layout(account_config::account_struct_tag(), account_layout())
+        .with_resource_writer();
+    let alice = AccountAddress::new([1; 32]);
+    let bob = AccountAddress::new([2; 32]);
+    for &(address, balance) in &[(alice, 10), (bob, 20), (alice, 30)] {
+        let result = save_account(&mut context, address, account_resource(balance));
+        assert_eq!(result.cost.get(), 1);
+        assert!(result.result.unwrap().is_empty());
+    }
+
+    let resources = context.resources.unwrap();
+    for &(address, balance) in &[(alice, 30), (bob, 20)] {
+        let blob = saved_account(&resources, address).unwrap();
+        let layout = Type::Struct(account_layout());
+        let account = Value::simple_deserialize(blob, layout.clone()).unwrap();
+        assert!(account.equals(&account_resource(balance)).unwrap());
+        assert_eq!(account.simple_serialize(&layout).unwrap(), blob);
+    }
+    assert!(saved_account(&resources, AccountAddress::new([3; 32])).is_none());
+}
+
+#[test]
+fn save_account_without_resource_writer() {
+    let mut context = MockNativeContext::for_testing()
+        .with_struct_layout(account_config::account_struct_tag(), account_layout());
+    let result = save_account(
+        &mut context,
+        AccountAddress::new([1; 32]),
+        account_resource(10),
+    );
+    assert_eq!(result.cost.get(), 1);
+    let status = result.result.err().unwrap();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(account::NO_RESOURCE_WRITER));
+}
+
+#[test]
+fn save_account_without_layout() {
+    let mut context = MockNativeContext::for_testing().with_resource_writer();
+    let address = AccountAddress::new([1; 32]);
+    let result = save_account(&mut context, address, account_resource(10));
+    assert_eq!(result.cost.get(), 1);
+    let status = result.result.err().unwrap();
+    assert_eq!(status.sub_status, Some(account::NO_ACCOUNT_LAYOUT));
+    assert!(status.message.unwrap().contains("TYPE_RESOLUTION_FAILURE"));
+    assert!(saved_account(&context.resources.unwrap(), address).is_none());
+}
+
+#[test]
+fn save_account_rejects_the_core_address() {
+    let mut context = MockNativeContext::for_testing()
+        .with_struct_layout(account_config::account_struct_tag(), account_layout())
+        .with_resource_writer();
+    let status = account::native_save_account(
+        &mut context,
+        &[],
+        args(vec![
+            Value::address(account_config::CORE_CODE_ADDRESS),
+            account_resource(10),
+        ]),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(status.major_status, StatusCode::CREATE_NULL_ACCOUNT);
+}
@@ -0,0 +1,124 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        arguments::Arguments, context::MockNativeContext, dispatch::NativeFunction,
+    },
+    values::{Locals, Value},
+};
+use libra_types::vm_error::{StatusCode, VMStatus};
+use vm::{errors::VMResult, gas_schedule::GasAlgebra};
+
+/// Dispatches `native`, which takes no type arguments, with `arguments` and returns its error.
+/// The error is found by running the native, so it is charged for.
+fn dispatch_err(native: NativeFunction, arguments: Vec<Value>) -> VMStatus {
+    let mut context = MockNativeContext::for_testing();
+    let result = native
+        .dispatch(&mut context, &[], vec![], vec![], args(arguments))
+        .unwrap();
+    assert!(result.cost.get() > 0);
+    assert_eq!(result.cost, native.base_cost(&context.cost_table));
+    result.result.unwrap_err()
+}
+
+#[test]
+fn pop_as_pops_from_the_last_argument() {
+    let mut arguments = Arguments::new(
+        NativeFunction::MathPowU64,
+        args(vec![Value::u64(2), Value::bool(true)]),
+    );
+    assert_eq!(arguments.remaining(), 2);
+    assert!(arguments.pop_as::<bool>().unwrap());
+    assert_eq!(arguments.pop_as::<u64>().unwrap(), 2);
+    assert_eq!(arguments.remaining(), 0);
+
+    let status = arguments.pop_as::<u64>().unwrap_err();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+    let msg = status.message.unwrap();
+    assert!(msg.contains("0x0::Math::pow_u64 popped a missing argument as u64"));
+}
+
+#[test]
+fn pop_ref_and_pop_vec_of() {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::u64(7)).unwrap();
+    let mut arguments: Arguments = args(vec![
+        locals.borrow_loc(0).unwrap(),
+        Value::vector_u8(vec![1, 2]),
+        Value::bool(true),
+    ]);
+    assert!(arguments.pop().unwrap().equals(&Value::bool(true)).unwrap());
+    assert_eq!(arguments.pop_vec_of::<u8>().unwrap(), vec![1, 2]);
+    let reference = arguments.pop_ref().unwrap();
+    assert!(reference
+        .read_ref()
+        .unwrap()
+        .equals(&Value::u64(7))
+        .unwrap());
+    arguments.finish().unwrap();
+}
+
+#[test]
+fn mistyped_arguments_are_reported() {
+    let status = dispatch_err(NativeFunction::HashSha3_256, vec![Value::u64(0)]);
+    assert_eq!(status.major_status, StatusCode::INTERNAL_TYPE_ERROR);
+    let msg = status.message.unwrap();
+    assert!(msg.starts_with("0x0::Hash::sha3_256 popped argument 0 as "));
+    assert!(msg.contains("Vec<u8>"));
+
+    // The position is the one the native function declares, not the order of popping
+    let status = dispatch_err(
+        NativeFunction::SigSecp256k1Recover,
+        vec![
+            Value::vector_u8(vec![]),
+            Value::u64(0),
+            Value::vector_u8(vec![]),
+        ],
+    );
+    assert_eq!(status.major_status, StatusCode::INTERNAL_TYPE_ERROR);
+    let msg = status.message.unwrap();
+    assert!(msg.starts_with("0x0::Signature::secp256k1_recover popped argument 1 as u8"));
+
+    // Natives sharing an implementation report their own name
+    let status = dispatch_err(
+        NativeFunction::BytearrayXor,
+        vec![Value::vector_u8(vec![]), Value::bool(false)],
+    );
+    let msg = status.message.unwrap();
+    assert!(msg.starts_with("0x0::BytearrayUtil::bytearray_xor popped argument 1 as "));
+    assert!(msg.contains("ByteArray"));
+
+    // Natives called directly do not know their name
+    let mut arguments: Arguments = args(vec![Value::bool(false)]);
+    let msg = arguments.pop_as::<u64>().unwrap_err().message.unwrap();
+    assert!(msg.starts_with("Native function popped argument 0 as u64"));
+}
+
+#[test]
+fn unused_arguments_fail_loudly() {
+    // A `pow_u64` that forgets to pop its base
+    fn forgetful_pow(mut arguments: Arguments) -> VMResult<u64> {
+        let exp = arguments.pop_as::<u64>()?;
+        arguments.finish()?;
+        Ok(exp)
+    }
+    let status = forgetful_pow(Arguments::new(
+        NativeFunction::MathPowU64,
+        args(vec![Value::u64(2), Value::u64(3)]),
+    ))
+    .unwrap_err();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+    assert_eq!(
+        status.message.unwrap(),
+        "0x0::Math::pow_u64 left 1 of its arguments unused"
+    );
+    assert_eq!(forgetful_pow(args(vec![Value::u64(3)])).unwrap(), 3);
+}
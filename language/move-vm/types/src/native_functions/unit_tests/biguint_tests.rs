@@ -0,0 +1,194 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, single_bytes};
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        biguint,
+        context::{MockNativeContext, NativeContext},
+        dispatch::NativeResult,
+    },
+    values::Value,
+};
+use libra_types::{language_storage::TypeTag, vm_error::StatusCode};
+use num_bigint::BigUint;
+use proptest::prelude::*;
+use vm::{errors::VMResult, gas_schedule::GasAlgebra};
+
+type Native = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+fn call(native: Native, a: &[u8], b: &[u8]) -> NativeResult {
+    native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(a.to_vec()),
+            Value::vector_u8(b.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn add(a: &[u8], b: &[u8]) -> Vec<u8> {
+    single_bytes(call(biguint::native_add, a, b))
+}
+
+fn mul(a: &[u8], b: &[u8]) -> Vec<u8> {
+    single_bytes(call(biguint::native_mul, a, b))
+}
+
+fn modulo(a: &[u8], modulus: &[u8]) -> Vec<u8> {
+    single_bytes(call(biguint::native_mod, a, modulus))
+}
+
+fn cmp(a: &[u8], b: &[u8]) -> u64 {
+    let mut values = call(biguint::native_cmp, a, b)
+        .result
+        .expect("cmp should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+fn assert_sub_status(result: NativeResult, sub_status: u64) {
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+/// The canonical encoding of `n`, which for zero is the empty vector rather than `[0]`.
+fn canonical(n: BigUint) -> Vec<u8> {
+    if n == BigUint::from(0u8) {
+        vec![]
+    } else {
+        n.to_bytes_be()
+    }
+}
+
+fn big(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+#[test]
+fn outputs_are_canonical() {
+    assert_eq!(add(&[], &[]), Vec::<u8>::new());
+    assert_eq!(add(&[0, 0], &[0]), Vec::<u8>::new());
+    assert_eq!(add(&[0, 0, 5], &[0, 3]), vec![8]);
+    assert_eq!(mul(&[0, 0, 5], &[]), Vec::<u8>::new());
+    assert_eq!(mul(&[0, 2], &[0, 0, 3]), vec![6]);
+    assert_eq!(modulo(&[0, 7], &[0, 7]), Vec::<u8>::new());
+    assert_eq!(modulo(&[0, 0, 9], &[0, 4]), vec![1]);
+}
+
+#[test]
+fn add_carries() {
+    assert_eq!(add(&[0xff], &[0x01]), vec![0x01, 0x00]);
+    assert_eq!(
+        add(&[0xff, 0xff, 0xff], &[0x01]),
+        vec![0x01, 0x00, 0x00, 0x00]
+    );
+    assert_eq!(add(&[0x01], &[0xff, 0xff]), vec![0x01, 0x00, 0x00]);
+}
+
+#[test]
+fn mul_carries() {
+    assert_eq!(
+        mul(&[0xff, 0xff], &[0xff, 0xff]),
+        vec![0xff, 0xfe, 0x00, 0x01]
+    );
+    assert_eq!(mul(&[0x01, 0x00], &[0x01, 0x00]), vec![0x01, 0x00, 0x00]);
+}
+
+#[test]
+fn cmp_ignores_leading_zeros() {
+    assert_eq!(cmp(&[1], &[2]), 0);
+    assert_eq!(cmp(&[0, 0, 2], &[2]), 1);
+    assert_eq!(cmp(&[], &[0]), 1);
+    assert_eq!(cmp(&[1, 0], &[0xff]), 2);
+    assert_eq!(cmp(&[0, 0xff], &[1, 0]), 0);
+}
+
+#[test]
+fn mod_by_zero() {
+    assert_sub_status(
+        call(biguint::native_mod, &[1], &[]),
+        biguint::DIVISION_BY_ZERO,
+    );
+    assert_sub_status(
+        call(biguint::native_mod, &[1], &[0, 0]),
+        biguint::DIVISION_BY_ZERO,
+    );
+    assert_sub_status(
+        call(biguint::native_mod, &[], &[0]),
+        biguint::DIVISION_BY_ZERO,
+    );
+}
+
+#[test]
+fn operand_length_cap() {
+    let max = vec![0xab; biguint::MAX_OPERAND_LENGTH];
+    let too_long = vec![0xab; biguint::MAX_OPERAND_LENGTH + 1];
+    assert_eq!(mul(&max, &max), canonical(big(&max) * big(&max)));
+    assert_eq!(
+        modulo(&max, &max[1..]),
+        canonical(big(&max) % big(&max[1..]))
+    );
+    for &native in &[
+        biguint::native_add as Native,
+        biguint::native_mul,
+        biguint::native_cmp,
+        biguint::native_mod,
+    ] {
+        for (a, b) in &[(&too_long, &max), (&max, &too_long)] {
+            let result = call(native, a, b);
+            assert_eq!(result.cost.get() as usize, 1 + a.len() + b.len());
+            assert_sub_status(result, biguint::OPERAND_TOO_LONG);
+        }
+    }
+}
+
+#[test]
+fn gas_scales_with_operand_lengths() {
+    let a = vec![1u8; 40];
+    let b = vec![2u8; 30];
+    assert_eq!(call(biguint::native_add, &a, &b).cost.get(), 71);
+    assert_eq!(call(biguint::native_cmp, &a, &b).cost.get(), 71);
+    assert_eq!(call(biguint::native_mul, &a, &b).cost.get(), 1201);
+    assert_eq!(call(biguint::native_mod, &a, &b).cost.get(), 1201);
+    assert_eq!(call(biguint::native_mul, &a, &a).cost.get(), 1601);
+}
+
+proptest! {
+    #[test]
+    fn add_matches_num_bigint(
+        a in prop::collection::vec(any::<u8>(), 0..64),
+        b in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        prop_assert_eq!(add(&a, &b), canonical(big(&a) + big(&b)));
+    }
+
+    #[test]
+    fn mul_matches_num_bigint(
+        a in prop::collection::vec(any::<u8>(), 0..64),
+        b in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        prop_assert_eq!(mul(&a, &b), canonical(big(&a) * big(&b)));
+    }
+
+    #[test]
+    fn cmp_matches_num_bigint(
+        a in prop::collection::vec(any::<u8>(), 0..16),
+        b in prop::collection::vec(any::<u8>(), 0..16),
+    ) {
+        prop_assert_eq!(cmp(&a, &b), (big(&a).cmp(&big(&b)) as i64 + 1) as u64);
+    }
+
+    #[test]
+    fn mod_matches_num_bigint(
+        a in prop::collection::vec(any::<u8>(), 0..64),
+        modulus in prop::collection::vec(any::<u8>(), 1..32),
+    ) {
+        prop_assume!(big(&modulus) != BigUint::from(0u8));
+        prop_assert_eq!(modulo(&a, &modulus), canonical(big(&a) % big(&modulus)));
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        block,
+        context::MockNativeContext,
+        dispatch::{NativeFunction, NativeResult},
+    },
+    values::Value,
+};
+use libra_types::vm_error::StatusCode;
+use vm::gas_schedule::GasAlgebra;
+
+fn call(native: NativeFunction, context: &mut MockNativeContext) -> NativeResult {
+    native
+        .dispatch(context, &[], vec![], vec![], args(vec![]))
+        .unwrap()
+}
+
+fn single_u64(result: NativeResult) -> u64 {
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("native function should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+fn assert_no_block_metadata(result: NativeResult) {
+    assert_eq!(result.cost.get(), 1);
+    let status = result.result.err().unwrap();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(block::NO_BLOCK_METADATA));
+}
+
+#[test]
+fn block_metadata_reads_the_context() {
+    let mut context = MockNativeContext::for_testing()
+        .with_block_height(42)
+        .with_block_timestamp_micros(1_580_000_000_000_000);
+    assert_eq!(
+        single_u64(call(NativeFunction::BlockGetHeight, &mut context)),
+        42
+    );
+    assert_eq!(
+        single_u64(call(NativeFunction::BlockGetTimestampMicros, &mut context)),
+        1_580_000_000_000_000
+    );
+
+    // Zero is a value like any other when the context has it
+    let mut context = MockNativeContext::for_testing()
+        .with_block_height(0)
+        .with_block_timestamp_micros(0);
+    assert_eq!(
+        single_u64(call(NativeFunction::BlockGetHeight, &mut context)),
+        0
+    );
+    assert_eq!(
+        single_u64(call(NativeFunction::BlockGetTimestampMicros, &mut context)),
+        0
+    );
+}
+
+#[test]
+fn block_metadata_missing_is_a_charged_error() {
+    let mut context = MockNativeContext::for_testing();
+    assert_no_block_metadata(call(NativeFunction::BlockGetHeight, &mut context));
+    assert_no_block_metadata(call(NativeFunction::BlockGetTimestampMicros, &mut context));
+
+    // Each field is checked on its own
+    let mut context = MockNativeContext::for_testing().with_block_height(7);
+    assert_eq!(
+        single_u64(call(NativeFunction::BlockGetHeight, &mut context)),
+        7
+    );
+    assert_no_block_metadata(call(NativeFunction::BlockGetTimestampMicros, &mut context));
+}
+
+#[test]
+fn block_metadata_takes_no_arguments() {
+    let context = MockNativeContext::for_testing().with_block_height(1);
+    let status = block::native_get_height(&context, &[], args(vec![Value::u64(1)]))
+        .err()
+        .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+}
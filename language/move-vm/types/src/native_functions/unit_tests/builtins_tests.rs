@@ -0,0 +1,711 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{
+            type_parameters_declared, NativeArguments, NativeFunction, NativeResultSummary,
+            DEFAULT_NATIVE_VERSION, LATEST_NATIVE_VERSION,
+        },
+        errors,
+    },
+    values::{Locals, Value},
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    vm_error::StatusCode,
+};
+use std::collections::HashSet;
+use vm::{
+    access::ModuleAccess,
+    errors::VMResult,
+    file_format::{
+        empty_module, AddressPoolIndex, CompiledModule, FunctionSignature, FunctionSignatureIndex,
+        IdentifierIndex, Kind, ModuleHandle, ModuleHandleIndex, SignatureToken, StructHandle,
+        StructHandleIndex,
+    },
+    gas_schedule::GasAlgebra,
+    views::ModuleView,
+};
+
+#[test]
+fn builtin_arities_match_signatures() {
+    // Any struct a signature refers to is found, so every builtin produces a signature
+    let struct_handle = |_: &str| Some(StructHandleIndex::new(0));
+    for (module, name, native) in NativeFunction::declarations() {
+        let implementation = native.implementation();
+        let signature = implementation
+            .signature(&struct_handle)
+            .unwrap_or_else(|| panic!("{}::{} has no signature", module.name(), name));
+        assert_eq!(
+            implementation.arity(),
+            signature.arg_types.len(),
+            "{}::{} takes as many arguments as its signature",
+            module.name(),
+            name
+        );
+        assert_eq!(native.num_args(), implementation.arity());
+        assert_eq!(native.type_parameter_kinds(), signature.type_formals);
+        assert_eq!(native.num_type_args(), signature.type_formals.len());
+
+        let type_formals = signature.type_formals.len();
+        let mut tokens = signature
+            .arg_types
+            .iter()
+            .chain(signature.return_types.iter());
+        assert!(
+            tokens.all(|token| type_parameters_declared(token, type_formals)),
+            "{}::{} declares the type parameters it uses",
+            module.name(),
+            name
+        );
+    }
+}
+
+#[test]
+fn builtin_signatures_without_module() {
+    // Only the natives referring to structs of their module need it to produce a signature
+    for (_, _, native) in NativeFunction::declarations() {
+        let signature = native.signature::<CompiledModule>(None).unwrap();
+        match native {
+            NativeFunction::AccountSaveAccount => assert!(signature.is_none()),
+            _ => assert!(signature.is_some()),
+        }
+    }
+}
+
+#[test]
+fn dispatch_rejects_wrong_number_of_arguments() {
+    for (module, name, native) in NativeFunction::declarations() {
+        let expected = native.num_args();
+        let mut counts = vec![expected + 1, expected + 2];
+        if expected > 0 {
+            counts.push(expected - 1);
+        }
+        for found in counts {
+            let arguments = args((0..found).map(|_| Value::u64(0)).collect());
+            let status = native
+                .dispatch(
+                    &mut MockNativeContext::for_testing(),
+                    &[],
+                    vec![],
+                    vec![],
+                    arguments,
+                )
+                .err()
+                .unwrap_or_else(|| {
+                    panic!("{}::{} ran with {} arguments", module.name(), name, found)
+                });
+            assert_eq!(
+                status.major_status,
+                StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+            );
+            let msg = status.message.unwrap();
+            assert!(msg.contains(&format!("expects {} arguments", expected)));
+            assert!(msg.contains(&format!("called with {}", found)));
+        }
+    }
+}
+
+#[test]
+fn dispatch_rejects_wrong_number_of_type_arguments() {
+    let status = NativeFunction::VectorLength
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &[TypeTag::U64, TypeTag::U64],
+            vec![Kind::Unrestricted, Kind::Unrestricted],
+            vec![],
+            args(vec![Value::u64(0)]),
+        )
+        .err()
+        .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH
+    );
+
+    // `Vector::empty<T>` takes no arguments, but one type argument
+    let empty = |t: Vec<TypeTag>, kinds: Vec<Kind>| {
+        NativeFunction::VectorEmpty.dispatch(
+            &mut MockNativeContext::for_testing(),
+            &t,
+            kinds,
+            vec![],
+            args(vec![]),
+        )
+    };
+    let result = empty(vec![TypeTag::U64], vec![Kind::Unrestricted]).unwrap();
+    assert_eq!(result.result.unwrap().len(), 1);
+    let status = empty(vec![], vec![]).err().unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH
+    );
+}
+
+#[test]
+fn natives_have_a_base_cost() {
+    // Only the natives that do not run in production are free
+    let table = MockNativeContext::for_testing().cost_table;
+    for (module, name, native) in NativeFunction::declarations() {
+        let free = match module.name().as_str() {
+            "Debug" | "UnitTest" => true,
+            _ => false,
+        };
+        assert_eq!(
+            native.cost_index().is_none(),
+            free,
+            "{}::{} is in the cost table",
+            module.name(),
+            name
+        );
+        assert_eq!(native.base_cost(&table).get(), if free { 0 } else { 1 });
+    }
+}
+
+#[test]
+fn resolve_agrees_with_match() {
+    for (module, name, native) in NativeFunction::declarations() {
+        assert_eq!(NativeFunction::resolve(&module, &name), Some(native));
+        assert_eq!(
+            NativeFunction::resolve_by_match(&module, &name),
+            Some(native)
+        );
+    }
+
+    let module = |address, name| ModuleId::new(address, Identifier::new(name).unwrap());
+    let other_address = AccountAddress::new([0xab; 32]);
+    let misses = vec![
+        // Unknown function of a module declaring natives
+        (module(CORE_CODE_ADDRESS, "Hash"), "sha1"),
+        // Known function of another module
+        (module(CORE_CODE_ADDRESS, "Signature"), "sha3_256"),
+        // Known module and function at another address
+        (module(other_address, "Hash"), "sha3_256"),
+        (module(other_address, "Vector"), "length"),
+        // Names only differing in case
+        (module(CORE_CODE_ADDRESS, "hash"), "sha3_256"),
+        (module(CORE_CODE_ADDRESS, "Vector"), "Length"),
+    ];
+    for (module, name) in misses {
+        let name = IdentStr::new(name).unwrap();
+        assert_eq!(NativeFunction::resolve(&module, name), None);
+        assert_eq!(NativeFunction::resolve_by_match(&module, name), None);
+    }
+}
+
+#[test]
+fn all_natives_resolve_from_their_names() {
+    assert_eq!(
+        NativeFunction::all().len(),
+        NativeFunction::declarations().len()
+    );
+    for native in NativeFunction::all() {
+        let module = native.module_id();
+        assert_eq!(module.address(), &native.address());
+        assert_eq!(module.name().as_str(), native.module_name());
+        let name = IdentStr::new(native.function_name()).unwrap();
+        assert_eq!(NativeFunction::resolve(&module, name), Some(*native));
+    }
+
+    let native = NativeFunction::VectorPushBack;
+    assert_eq!(native.address(), CORE_CODE_ADDRESS);
+    assert_eq!(native.module_name(), "Vector");
+    assert_eq!(native.function_name(), "push_back");
+}
+
+#[test]
+fn natives_display_and_parse() {
+    assert_eq!(
+        NativeFunction::VectorPushBack.to_string(),
+        "0x0::Vector::push_back"
+    );
+    for native in NativeFunction::all() {
+        assert_eq!(
+            native.to_string().parse::<NativeFunction>().unwrap(),
+            *native
+        );
+    }
+    // The address may be written in full
+    let full = format!("{}::Hash::sha3_256", CORE_CODE_ADDRESS);
+    assert_eq!(
+        full.parse::<NativeFunction>().unwrap(),
+        NativeFunction::HashSha3_256
+    );
+
+    for malformed in &[
+        "",
+        "Vector::push_back",
+        "0x0::Vector",
+        "0x0::Vector::push_back::extra",
+        "0::Vector::push_back",
+        "0xzz::Vector::push_back",
+        "0x0::Vec tor::push_back",
+        "0x0::Vector::",
+    ] {
+        assert!(
+            malformed.parse::<NativeFunction>().is_err(),
+            "{}",
+            malformed
+        );
+    }
+    for unknown in &[
+        "0x0::Vector::push_front",
+        "0x0::Hash::push_back",
+        "0x1::Vector::push_back",
+    ] {
+        assert!(unknown.parse::<NativeFunction>().is_err(), "{}", unknown);
+    }
+}
+
+#[test]
+fn natives_serialize_as_names() {
+    for native in NativeFunction::all() {
+        let json = serde_json::to_string(native).unwrap();
+        assert_eq!(json, format!("\"{}\"", native));
+        assert_eq!(
+            serde_json::from_str::<NativeFunction>(&json).unwrap(),
+            *native
+        );
+
+        let bytes = lcs::to_bytes(native).unwrap();
+        assert_eq!(bytes, lcs::to_bytes(&native.to_string()).unwrap());
+        assert_eq!(lcs::from_bytes::<NativeFunction>(&bytes).unwrap(), *native);
+    }
+    assert!(serde_json::from_str::<NativeFunction>("\"0x0::Vector::push_front\"").is_err());
+    assert!(serde_json::from_str::<NativeFunction>("3").is_err());
+}
+
+#[test]
+fn native_result_summaries_round_trip() {
+    let mut locals = Locals::new(2);
+    locals
+        .store_loc(0, Value::vector_u64(vec![1, 2, 3]))
+        .unwrap();
+    locals.store_loc(1, Value::vector_u64(vec![])).unwrap();
+    let mut context = MockNativeContext::for_testing();
+    let mut summarize = |native: NativeFunction, t: &[TypeTag], arguments| {
+        let result = native
+            .dispatch(
+                &mut context,
+                t,
+                vec![Kind::Unrestricted; t.len()],
+                vec![],
+                arguments,
+            )
+            .unwrap();
+        NativeResultSummary::new(native, &result)
+    };
+    let succeeded = summarize(
+        NativeFunction::VectorLength,
+        &[TypeTag::U64],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    );
+    assert_eq!(succeeded.status, StatusCode::EXECUTED);
+    assert_eq!(succeeded.sub_status, None);
+    let failed = summarize(
+        NativeFunction::VectorPopBack,
+        &[TypeTag::U64],
+        args(vec![locals.borrow_loc(1).unwrap()]),
+    );
+    assert_eq!(failed.status, StatusCode::ABORTED);
+    assert_eq!(failed.sub_status, Some(errors::vector::POP_EMPTY_VEC));
+
+    for summary in &[succeeded, failed] {
+        let json = serde_json::to_string(summary).unwrap();
+        assert_eq!(
+            &serde_json::from_str::<NativeResultSummary>(&json).unwrap(),
+            summary
+        );
+        let bytes = lcs::to_bytes(summary).unwrap();
+        assert_eq!(
+            &lcs::from_bytes::<NativeResultSummary>(&bytes).unwrap(),
+            summary
+        );
+    }
+}
+
+#[test]
+fn native_result_summaries_survive_new_natives() {
+    // Recorded when `Hash::sha3_256` was the second native of the enum and `Vector::length` the
+    // 88th; natives added since do not change what the recording refers to
+    let recording = r#"[
+        {"function":"0x0::Hash::sha3_256","cost":32,"status":4001,"sub_status":null},
+        {"function":"0x0::Vector::length","cost":1,"status":4001,"sub_status":null}
+    ]"#;
+    let summaries: Vec<NativeResultSummary> = serde_json::from_str(recording).unwrap();
+    assert_eq!(summaries[0].function, NativeFunction::HashSha3_256);
+    assert_eq!(summaries[0].cost.get(), 32);
+    assert_eq!(summaries[0].status, StatusCode::EXECUTED);
+    assert_eq!(summaries[1].function, NativeFunction::VectorLength);
+}
+
+#[test]
+fn stable_ids_match_golden_file() {
+    let golden: Vec<(u16, &str)> = include_str!("native_function_ids.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split(' ');
+            let id = parts.next().unwrap().parse().unwrap();
+            let name = parts.next().unwrap();
+            assert_eq!(parts.next(), None, "malformed line {}", line);
+            (id, name)
+        })
+        .collect();
+    // Increasing ids are unique, and leave new natives only the ids after the last one
+    for window in golden.windows(2) {
+        assert!(window[0].0 < window[1].0, "{:?} is out of order", window[1]);
+    }
+    let names: HashSet<_> = golden.iter().map(|(_, name)| name).collect();
+    assert_eq!(names.len(), golden.len(), "a name is listed twice");
+
+    for native in NativeFunction::all() {
+        let id = native.stable_id();
+        let name = native.to_string();
+        assert!(
+            golden.contains(&(id, name.as_str())),
+            "{} {} is not in native_function_ids.txt",
+            id,
+            name
+        );
+        assert_eq!(NativeFunction::from_stable_id(id), Some(*native));
+    }
+    let last = golden.last().unwrap().0;
+    assert_eq!(NativeFunction::from_stable_id(last + 1), None);
+}
+
+#[test]
+fn every_native_is_available_at_the_latest_version() {
+    for native in NativeFunction::all() {
+        assert!(native.min_version() <= LATEST_NATIVE_VERSION, "{}", native);
+    }
+    assert!(NativeFunction::all()
+        .iter()
+        .any(|native| native.min_version() == LATEST_NATIVE_VERSION));
+}
+
+#[test]
+fn only_the_original_natives_are_available_at_the_default_version() {
+    use NativeFunction::*;
+    let available: Vec<_> = NativeFunction::all()
+        .iter()
+        .cloned()
+        .filter(|native| native.is_available(DEFAULT_NATIVE_VERSION))
+        .collect();
+    assert_eq!(
+        available,
+        vec![
+            HashSha2_256,
+            HashSha3_256,
+            SigED25519Verify,
+            SigED25519ThresholdVerify,
+            AddrUtilToBytes,
+            U64UtilToBytes,
+            BytearrayConcat,
+            VectorLength,
+            VectorEmpty,
+            VectorBorrow,
+            VectorBorrowMut,
+            VectorPushBack,
+            VectorPopBack,
+            VectorDestroyEmpty,
+            VectorSwap,
+            AccountWriteEvent,
+            AccountSaveAccount,
+        ]
+    );
+}
+
+#[test]
+fn gated_natives_resolve_from_their_version() {
+    let module = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Hash").unwrap());
+    let name = IdentStr::new("keccak_256").unwrap();
+    assert_eq!(NativeFunction::HashKeccak256.min_version(), 1);
+    assert_eq!(NativeFunction::resolve_with_version(&module, name, 0), None);
+    assert_eq!(
+        NativeFunction::resolve_with_version(&module, name, 1),
+        Some(NativeFunction::HashKeccak256)
+    );
+    // Unversioned resolution finds every native the binary contains
+    assert_eq!(
+        NativeFunction::resolve(&module, name),
+        Some(NativeFunction::HashKeccak256)
+    );
+
+    for native in NativeFunction::all() {
+        let (module, name) = (native.module_id(), native.function_name());
+        let name = IdentStr::new(name).unwrap();
+        let version = native.min_version();
+        assert_eq!(
+            NativeFunction::resolve_with_version(&module, name, version),
+            Some(*native)
+        );
+        assert_eq!(
+            NativeFunction::resolve_with_version(&module, name, std::u64::MAX),
+            Some(*native)
+        );
+        if version > 0 {
+            assert_eq!(
+                NativeFunction::resolve_with_version(&module, name, version - 1),
+                None
+            );
+        }
+    }
+}
+
+#[test]
+fn gated_natives_are_not_dispatched_before_their_version() {
+    let mut context = MockNativeContext::for_testing();
+    let native = NativeFunction::HashKeccak256;
+    let status = native
+        .dispatch_with_version(
+            0,
+            &mut context,
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::vector_u8(vec![])]),
+        )
+        .unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_UNAVAILABLE);
+    assert_eq!(status.sub_status, Some(1));
+    assert!(status.message.unwrap().contains("version 1"));
+
+    let result = native
+        .dispatch_with_version(
+            1,
+            &mut context,
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::vector_u8(vec![])]),
+        )
+        .unwrap();
+    assert!(result.result.is_ok());
+}
+
+/// A module `id` with a handle for each of `structs`, in order, and a function signature taking
+/// `args`.
+fn module_with_struct_handles(
+    id: &ModuleId,
+    structs: &[(&ModuleId, &str)],
+    args: Vec<SignatureToken>,
+) -> CompiledModule {
+    let mut m = empty_module();
+    m.address_pool = vec![*id.address()];
+    m.identifiers = vec![id.name().to_owned()];
+    for (module, name) in structs {
+        let module = if *module == id {
+            ModuleHandleIndex::new(0)
+        } else {
+            m.address_pool.push(*module.address());
+            m.identifiers.push(module.name().to_owned());
+            m.module_handles.push(ModuleHandle {
+                address: AddressPoolIndex::new(m.address_pool.len() as u16 - 1),
+                name: IdentifierIndex::new(m.identifiers.len() as u16 - 1),
+            });
+            ModuleHandleIndex::new(m.module_handles.len() as u16 - 1)
+        };
+        m.identifiers.push(Identifier::new(*name).unwrap());
+        m.struct_handles.push(StructHandle {
+            module,
+            name: IdentifierIndex::new(m.identifiers.len() as u16 - 1),
+            is_nominal_resource: true,
+            type_formals: vec![],
+        });
+    }
+    m.function_signatures.push(FunctionSignature {
+        return_types: vec![],
+        arg_types: args,
+        type_formals: vec![],
+    });
+    m.freeze().unwrap()
+}
+
+#[test]
+fn signature_in_context_remaps_struct_handles() {
+    let libra_account = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("LibraAccount").unwrap());
+    let caller_id = ModuleId::new(
+        AccountAddress::new([0xab; 32]),
+        Identifier::new("Caller").unwrap(),
+    );
+    let declaring = module_with_struct_handles(&libra_account, &[(&libra_account, "T")], vec![]);
+    // `LibraAccount::T` is the caller's second struct handle, after a struct of its own
+    let caller_module = module_with_struct_handles(
+        &caller_id,
+        &[(&caller_id, "T"), (&libra_account, "T")],
+        vec![
+            SignatureToken::Address,
+            SignatureToken::Struct(StructHandleIndex::new(1), vec![]),
+        ],
+    );
+    let declared = caller_module.function_signature_at(FunctionSignatureIndex::new(0));
+    let (declaring, caller) = (ModuleView::new(&declaring), ModuleView::new(&caller_module));
+
+    let native = NativeFunction::AccountSaveAccount;
+    let in_declaring = native.signature(Some(&declaring)).unwrap().unwrap();
+    assert_eq!(
+        in_declaring.arg_types[1],
+        SignatureToken::Struct(StructHandleIndex::new(0), vec![])
+    );
+    let in_caller = native
+        .signature_in_context(&declaring, &caller)
+        .unwrap()
+        .unwrap();
+    assert_eq!(&in_caller, declared);
+
+    // Natives referring to no struct have the same signature in every module
+    for native in NativeFunction::all() {
+        if *native != NativeFunction::AccountSaveAccount {
+            assert_eq!(
+                native.signature_in_context(&declaring, &caller).unwrap(),
+                native.signature(Some(&declaring)).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn signature_in_context_requires_caller_to_import_structs() {
+    let libra_account = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("LibraAccount").unwrap());
+    let caller_id = ModuleId::new(
+        AccountAddress::new([0xab; 32]),
+        Identifier::new("Caller").unwrap(),
+    );
+    let declaring = module_with_struct_handles(&libra_account, &[(&libra_account, "T")], vec![]);
+    // A struct of the same name from another module is not `LibraAccount::T`
+    let caller = module_with_struct_handles(&caller_id, &[(&caller_id, "T")], vec![]);
+    let (declaring, caller) = (ModuleView::new(&declaring), ModuleView::new(&caller));
+
+    let status = NativeFunction::AccountSaveAccount
+        .signature_in_context(&declaring, &caller)
+        .unwrap_err();
+    assert_eq!(status.major_status, StatusCode::LOOKUP_FAILED);
+    assert!(status.message.unwrap().contains("LibraAccount::T"));
+}
+
+#[test]
+fn signature_finds_structs_of_the_declaring_module() {
+    let libra_account = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("LibraAccount").unwrap());
+    let decoy = ModuleId::new(
+        AccountAddress::new([0xab; 32]),
+        Identifier::new("Decoy").unwrap(),
+    );
+    let native = NativeFunction::AccountSaveAccount;
+
+    // A struct `T` of another module, before the struct `T` of `LibraAccount`
+    let module = module_with_struct_handles(
+        &libra_account,
+        &[(&decoy, "T"), (&libra_account, "T")],
+        vec![],
+    );
+    let signature = native
+        .signature(Some(&ModuleView::new(&module)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        signature.arg_types[1],
+        SignatureToken::Struct(StructHandleIndex::new(1), vec![])
+    );
+
+    // Only a struct `T` of another module
+    let module = module_with_struct_handles(&libra_account, &[(&decoy, "T")], vec![]);
+    assert_eq!(
+        native.signature(Some(&ModuleView::new(&module))).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn natives_handled_by_the_interpreter() {
+    // The natives of `LibraAccount` write to the data store, which only the interpreter has
+    for native in NativeFunction::all() {
+        let expected = native.module_name() == "LibraAccount";
+        assert_eq!(native.is_runtime_handled(), expected, "{}", native);
+    }
+}
+
+#[test]
+fn natives_reading_the_block_metadata() {
+    for native in NativeFunction::all() {
+        let expected = native.module_name() == "Block";
+        assert_eq!(native.reads_block_metadata(), expected, "{}", native);
+    }
+}
+
+/// The natives of the `UnitTest` module `resolve` finds.
+fn resolved_unit_test_natives() -> Vec<NativeFunction> {
+    let module = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("UnitTest").unwrap());
+    ["create_signers_for_testing", "poison"]
+        .iter()
+        .filter_map(|name| NativeFunction::resolve(&module, IdentStr::new(name).unwrap()))
+        .collect()
+}
+
+#[test]
+fn unit_test_natives_resolve_only_with_testing() {
+    let listed: Vec<_> = NativeFunction::all()
+        .iter()
+        .copied()
+        .filter(|native| native.module_name() == "UnitTest")
+        .collect();
+    assert_eq!(resolved_unit_test_natives(), listed);
+    assert_eq!(listed.is_empty(), !cfg!(feature = "testing"));
+}
+
+#[cfg(not(feature = "testing"))]
+#[test]
+fn unit_test_natives_are_compiled_out() {
+    assert!(resolved_unit_test_natives().is_empty());
+    assert!(NativeFunction::all()
+        .iter()
+        .all(|native| native.module_name() != "UnitTest"));
+    // Their ids are reserved, but name no native
+    assert_eq!(NativeFunction::from_stable_id(106), None);
+    assert_eq!(NativeFunction::from_stable_id(107), None);
+    assert!("0x0::UnitTest::poison".parse::<NativeFunction>().is_err());
+}
+
+#[test]
+fn pop_arg_fails_on_missing_arguments() {
+    fn pop_two(mut arguments: NativeArguments) -> VMResult<(u64, bool)> {
+        let b = pop_arg!(arguments, bool);
+        let a = pop_arg!(arguments, u64);
+        Ok((a, b))
+    }
+    assert_eq!(
+        pop_two(args(vec![Value::u64(1), Value::bool(true)])).unwrap(),
+        (1, true)
+    );
+    for arguments in vec![vec![], vec![Value::bool(true)]] {
+        let status = pop_two(args(arguments)).unwrap_err();
+        assert_eq!(
+            status.major_status,
+            StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+        );
+    }
+    // Arguments of the wrong type are still type errors
+    let status = pop_two(args(vec![Value::bool(true), Value::u64(1)])).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::INTERNAL_TYPE_ERROR);
+
+    // Natives dispatched without their arguments fail instead of panicking
+    let status = NativeFunction::HashSha3_256
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(vec![]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+}
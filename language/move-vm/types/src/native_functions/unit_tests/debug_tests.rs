@@ -0,0 +1,173 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::{
+        context::{CallFrame, MockNativeContext},
+        debug,
+        dispatch::NativeFunction,
+    },
+    values::{Locals, Struct, Value},
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    byte_array::ByteArray,
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+};
+use std::{cell::RefCell, rc::Rc};
+use vm::gas_schedule::GasAlgebra;
+
+/// Runs `f` with the debug sink of this thread capturing its output, and returns the lines.
+fn capture(f: impl FnOnce()) -> Vec<String> {
+    let lines = Rc::new(RefCell::new(vec![]));
+    let sink_lines = Rc::clone(&lines);
+    debug::set_sink(Some(Box::new(move |line| {
+        sink_lines.borrow_mut().push(line.to_owned())
+    })));
+    f();
+    debug::set_sink(None);
+    Rc::try_unwrap(lines).unwrap().into_inner()
+}
+
+/// Calls `print` on a reference to `value`, checking it succeeds for free.
+fn print(value: Value, layout: Type) {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, value).unwrap();
+    let result = debug::native_print(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![layout],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 0);
+    assert!(result.result.unwrap().is_empty());
+}
+
+#[test]
+fn print_primitives() {
+    let lines = capture(|| {
+        print(Value::u8(7), Type::U8);
+        print(Value::u64(7), Type::U64);
+        print(Value::u128(7), Type::U128);
+        print(Value::bool(false), Type::Bool);
+        print(
+            Value::byte_array(ByteArray::new(vec![0xca, 0xfe])),
+            Type::ByteArray,
+        );
+        print(
+            Value::vector_u64(vec![1, 2, 3]),
+            Type::Vector(Box::new(Type::U64)),
+        );
+        print(Value::vector_u8(vec![]), Type::Vector(Box::new(Type::U8)));
+    });
+    assert_eq!(
+        lines,
+        vec![
+            "[debug] 7u8",
+            "[debug] 7",
+            "[debug] 7u128",
+            "[debug] false",
+            "[debug] b\"cafe\"",
+            "[debug] [1, 2, 3]",
+            "[debug] []",
+        ]
+    );
+}
+
+#[test]
+fn print_nested_struct() {
+    let inner_layout = Type::Struct(StructDef::new(vec![Type::Bool, Type::Address]));
+    let outer_layout = Type::Struct(StructDef::new(vec![
+        Type::U64,
+        inner_layout,
+        Type::Vector(Box::new(Type::U8)),
+    ]));
+    let inner = Value::struct_(Struct::pack(vec![
+        Value::bool(true),
+        Value::address(AccountAddress::new([0xab; 32])),
+    ]));
+    let outer = Value::struct_(Struct::pack(vec![
+        Value::u64(100),
+        inner,
+        Value::vector_u8(vec![1, 2]),
+    ]));
+
+    let lines = capture(|| print(outer, outer_layout));
+    assert_eq!(
+        lines,
+        vec![
+            "[debug] {100, {true, \
+             0xabababababababababababababababababababababababababababababababab}, [1u8, 2u8]}"
+        ]
+    );
+}
+
+#[test]
+fn print_layout_mismatch() {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::u64(1)).unwrap();
+    let lines = capture(|| {
+        assert!(debug::native_print(
+            &MockNativeContext::for_testing(),
+            &[],
+            vec![Type::Bool],
+            args(vec![locals.borrow_loc(0).unwrap()])
+        )
+        .is_err())
+    });
+    assert!(lines.is_empty());
+}
+
+#[test]
+fn print_resolves_only_with_debug_natives() {
+    let module = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Debug").unwrap());
+    let resolved = NativeFunction::resolve(&module, IdentStr::new("print").unwrap());
+    assert_eq!(resolved.is_some(), cfg!(feature = "debug_natives"));
+}
+
+fn frame(module: &str, function: &str, pc: u16) -> CallFrame {
+    CallFrame {
+        module: ModuleId::new(
+            AccountAddress::from_hex_literal("0x1").unwrap(),
+            Identifier::new(module).unwrap(),
+        ),
+        function: Identifier::new(function).unwrap(),
+        pc,
+    }
+}
+
+fn print_stack_trace(context: &MockNativeContext) {
+    let result = debug::native_print_stack_trace(context, &[], args(vec![])).unwrap();
+    assert_eq!(result.cost.get(), 0);
+    assert!(result.result.unwrap().is_empty());
+}
+
+#[test]
+fn print_stack_trace_innermost_first() {
+    let context = MockNativeContext::for_testing().with_call_stack(vec![
+        frame("Main", "main", 4),
+        frame("Vault", "withdraw", 12),
+        frame("Coin", "split", 0),
+    ]);
+    let address = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let expected = format!(
+        "[debug] stack trace:\n  #0 {0}::Coin::split at pc 0\n  #1 {0}::Vault::withdraw at pc 12\n  \
+         #2 {0}::Main::main at pc 4",
+        address
+    );
+    assert_eq!(capture(|| print_stack_trace(&context)), vec![expected]);
+}
+
+#[test]
+fn print_stack_trace_without_call_stack() {
+    assert_eq!(
+        capture(|| print_stack_trace(&MockNativeContext::for_testing())),
+        vec!["[debug] stack trace:\n  <unavailable>"]
+    );
+    assert_eq!(debug::format_stack_trace(Some(&[])), "[debug] stack trace:");
+}
@@ -0,0 +1,75 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::native_functions::{
+    abort_codes::{self, NAMESPACE_SIZE},
+    dispatch::NativeResult,
+    errors,
+};
+use libra_types::vm_error::{sub_status, StatusCode};
+use std::collections::HashSet;
+use vm::gas_schedule::{GasAlgebra, GasUnits};
+
+#[test]
+fn sub_statuses_are_distinct_within_modules() {
+    for (module, sub_statuses) in errors::MODULES {
+        let mut names = HashSet::new();
+        let mut values = HashSet::new();
+        for (name, value) in *sub_statuses {
+            assert!(names.insert(name), "{}::{} is listed twice", module, name);
+            assert!(
+                values.insert(value),
+                "{}::{} has the value of another sub-status",
+                module,
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn err_with_sub_status_sets_both_statuses() {
+    let result = NativeResult::err_with_sub_status(
+        GasUnits::new(3),
+        StatusCode::NATIVE_FUNCTION_ERROR,
+        errors::vector::SORT_NON_U64_VEC,
+    );
+    assert_eq!(result.cost.get(), 3);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(errors::vector::SORT_NON_U64_VEC));
+    assert_eq!(status.message, None);
+}
+
+#[test]
+fn vector_sub_statuses_are_in_the_vector_namespace() {
+    assert_eq!(sub_status::NFE_VECTOR_ERROR_BASE, abort_codes::VECTOR);
+    // Aborts and `NATIVE_FUNCTION_ERROR` sub-statuses alike
+    for (name, value) in errors::vector::ALL {
+        assert!(
+            *value > abort_codes::VECTOR && *value < abort_codes::VECTOR + NAMESPACE_SIZE,
+            "vector::{} is out of the vector namespace",
+            name
+        );
+    }
+}
+
+#[test]
+fn vector_abort_codes_keep_the_offsets_of_the_sub_statuses_they_replaced() {
+    // Formerly reported as `NATIVE_FUNCTION_ERROR` with the sub-status of the same offset
+    let offset = |code: u64| code - errors::vector::DEFAULT_ERROR_CODE;
+    assert_eq!(offset(errors::vector::INDEX_OUT_OF_BOUNDS), 1);
+    assert_eq!(offset(errors::vector::POP_EMPTY_VEC), 2);
+    assert_eq!(offset(errors::vector::DESTROY_NON_EMPTY_VEC), 3);
+    assert_eq!(offset(errors::vector::FILL_TOO_LONG), 5);
+    assert_eq!(offset(errors::vector::VECTOR_TOO_LONG), 8);
+}
+
+#[test]
+fn vector_native_function_errors_keep_their_offsets() {
+    let offset = |code: u64| code - errors::vector::DEFAULT_ERROR_CODE;
+    assert_eq!(offset(errors::vector::TRUNCATE_RESOURCE_VEC), 4);
+    assert_eq!(offset(errors::vector::SORT_NON_U64_VEC), 6);
+    assert_eq!(offset(errors::vector::BINARY_SEARCH_NON_U64_VEC), 7);
+    assert_eq!(offset(errors::vector::AGGREGATE_NON_U64_VEC), 9);
+}
@@ -0,0 +1,134 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        context::{MockNativeContext, NativeContext},
+        dispatch::NativeResult,
+        fixed_point,
+    },
+    values::Value,
+};
+use libra_types::{language_storage::TypeTag, vm_error::StatusCode};
+use vm::{errors::VMResult, gas_schedule::GasAlgebra};
+
+type Native = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+/// FixedPoint32 encoding of one
+const ONE: u64 = 1 << 32;
+
+fn call(native: Native, a: u64, b: u64) -> NativeResult {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(a), Value::u64(b)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    result
+}
+
+fn value(native: Native, a: u64, b: u64) -> u64 {
+    let mut values = call(native, a, b)
+        .result
+        .expect("fixed point native should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+fn assert_sub_status(result: NativeResult, sub_status: u64) {
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+#[test]
+fn create_from_rational_rounds_down() {
+    let create = fixed_point::native_create_from_rational;
+    assert_eq!(value(create, 1, 1), ONE);
+    assert_eq!(value(create, 1, 2), ONE / 2);
+    assert_eq!(value(create, 0, 7), 0);
+    // 1/3 and 2/3 are not representable and truncate to the value just below
+    assert_eq!(value(create, 1, 3), 0x5555_5555);
+    assert_eq!(value(create, 2, 3), 0xAAAA_AAAA);
+    // Ratios smaller than 2^-32 truncate to zero
+    assert_eq!(value(create, 1, ONE + 1), 0);
+    assert_eq!(value(create, std::u64::MAX, std::u64::MAX), ONE);
+}
+
+#[test]
+fn create_from_rational_overflow_boundary() {
+    let create = fixed_point::native_create_from_rational;
+    assert_eq!(
+        value(create, std::u64::MAX >> 32, 1),
+        std::u64::MAX - (ONE - 1)
+    );
+    assert_eq!(value(create, std::u64::MAX, ONE), std::u64::MAX);
+    assert_sub_status(call(create, ONE, 1), fixed_point::RESULT_OVERFLOW);
+    assert_sub_status(
+        call(create, std::u64::MAX, ONE - 1),
+        fixed_point::RESULT_OVERFLOW,
+    );
+    assert_sub_status(call(create, 1, 0), fixed_point::DIVISION_BY_ZERO);
+    assert_sub_status(call(create, 0, 0), fixed_point::DIVISION_BY_ZERO);
+}
+
+#[test]
+fn multiply_u64_truncates() {
+    let multiply = fixed_point::native_multiply_u64;
+    assert_eq!(value(multiply, 10, ONE / 2), 5);
+    assert_eq!(value(multiply, 11, ONE / 2), 5);
+    assert_eq!(value(multiply, 7, ONE), 7);
+    assert_eq!(value(multiply, 7, 0), 0);
+    assert_eq!(value(multiply, 0, std::u64::MAX), 0);
+    // 3 * 0x5555_5555 is just below one, so the product truncates to zero
+    assert_eq!(value(multiply, 3, 0x5555_5555), 0);
+    assert_eq!(value(multiply, 3, 0x5555_5556), 1);
+}
+
+#[test]
+fn multiply_u64_overflow_boundary() {
+    let multiply = fixed_point::native_multiply_u64;
+    assert_eq!(value(multiply, std::u64::MAX, ONE), std::u64::MAX);
+    assert_eq!(value(multiply, ONE - 1, std::u64::MAX), std::u64::MAX - ONE);
+    assert_sub_status(
+        call(multiply, std::u64::MAX, ONE + 1),
+        fixed_point::RESULT_OVERFLOW,
+    );
+    assert_eq!(value(multiply, 2 * ONE, (1 << 63) - 1), std::u64::MAX - 1);
+    assert_sub_status(
+        call(multiply, 2 * ONE, 1 << 63),
+        fixed_point::RESULT_OVERFLOW,
+    );
+}
+
+#[test]
+fn divide_u64_truncates() {
+    let divide = fixed_point::native_divide_u64;
+    assert_eq!(value(divide, 10, ONE / 2), 20);
+    assert_eq!(value(divide, 7, ONE), 7);
+    assert_eq!(value(divide, 7, 2 * ONE), 3);
+    assert_eq!(value(divide, 0, 1), 0);
+    // 1 / 0xAAAA_AAAA is just above 1.5
+    assert_eq!(value(divide, 1, 0xAAAA_AAAA), 1);
+    assert_eq!(value(divide, 3, 2 * ONE + 1), 1);
+    assert_sub_status(call(divide, 1, 0), fixed_point::DIVISION_BY_ZERO);
+    assert_sub_status(call(divide, 0, 0), fixed_point::DIVISION_BY_ZERO);
+}
+
+#[test]
+fn divide_u64_overflow_boundary() {
+    let divide = fixed_point::native_divide_u64;
+    assert_eq!(value(divide, std::u64::MAX, ONE), std::u64::MAX);
+    assert_eq!(
+        value(divide, std::u64::MAX >> 32, 1),
+        std::u64::MAX - (ONE - 1)
+    );
+    assert_sub_status(
+        call(divide, std::u64::MAX, ONE - 1),
+        fixed_point::RESULT_OVERFLOW,
+    );
+    assert_sub_status(call(divide, ONE, 1), fixed_point::RESULT_OVERFLOW);
+}
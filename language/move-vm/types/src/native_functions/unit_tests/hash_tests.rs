@@ -0,0 +1,579 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, assert_cost_breakdown, context_with_native_cost, single_bytes};
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{NativeFunction, NativeResult},
+        hash,
+    },
+    values::Value,
+};
+use blake2::{
+    digest::{Input, VariableOutput},
+    Blake2b, Digest, VarBlake2s,
+};
+use libra_crypto::HashValue;
+use libra_types::vm_error::StatusCode;
+use sha2::Sha256;
+use vm::gas_schedule::{GasAlgebra, GasUnits};
+
+#[test]
+fn keccak_256_empty_input() {
+    let result = hash::native_keccak_256(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(vec![])]),
+    )
+    .unwrap();
+    assert_eq!(
+        single_bytes(result),
+        hex::decode("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470").unwrap()
+    );
+}
+
+#[test]
+fn keccak_256_known_vector() {
+    let result = hash::native_keccak_256(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(b"abc".to_vec())]),
+    )
+    .unwrap();
+    let digest = single_bytes(result);
+    assert_eq!(digest.len(), 32);
+    assert_eq!(
+        digest,
+        hex::decode("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45").unwrap()
+    );
+}
+
+#[test]
+fn keccak_256_gas_grows_with_input() {
+    let context = MockNativeContext::for_testing();
+    let small =
+        hash::native_keccak_256(&context, &[], args(vec![Value::vector_u8(vec![0xab; 32])]))
+            .unwrap();
+    let large = hash::native_keccak_256(
+        &context,
+        &[],
+        args(vec![Value::vector_u8(vec![0xab; 4096])]),
+    )
+    .unwrap();
+    assert_eq!(small.cost.get(), 32);
+    assert_eq!(large.cost.get(), 4096);
+}
+
+fn sha2_512(input: &[u8]) -> Vec<u8> {
+    let result = hash::native_sha2_512(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input.to_vec())]),
+    )
+    .unwrap();
+    single_bytes(result)
+}
+
+#[test]
+fn sha2_512_nist_vectors() {
+    assert_eq!(
+        sha2_512(b""),
+        hex::decode(
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        sha2_512(b"abc"),
+        hex::decode(
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        sha2_512(
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+              hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+        ),
+        hex::decode(
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn sha2_512_long_input() {
+    // NIST long message: one million repetitions of 'a'.
+    let input = vec![b'a'; 1_000_000];
+    let result = hash::native_sha2_512(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1_000_000);
+    assert_eq!(
+        single_bytes(result),
+        hex::decode(
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+             de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+        )
+        .unwrap()
+    );
+}
+
+fn ripemd_160(input: &[u8]) -> Vec<u8> {
+    let result = hash::native_ripemd_160(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input.to_vec())]),
+    )
+    .unwrap();
+    single_bytes(result)
+}
+
+#[test]
+fn ripemd_160_empty_input() {
+    assert_eq!(
+        ripemd_160(b""),
+        hex::decode("9c1185a5c5e9fc54612808977ee8f548b2258d31").unwrap()
+    );
+}
+
+#[test]
+fn ripemd_160_known_vectors() {
+    assert_eq!(
+        ripemd_160(b"abc"),
+        hex::decode("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc").unwrap()
+    );
+    assert_eq!(
+        ripemd_160(b"message digest"),
+        hex::decode("5d0689ef49d2fae572b881b123a85ffa21595f36").unwrap()
+    );
+}
+
+fn blake2b_256(input: &[u8]) -> Vec<u8> {
+    let result = hash::native_blake2b_256(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input.to_vec())]),
+    )
+    .unwrap();
+    single_bytes(result)
+}
+
+#[test]
+fn blake2b_256_known_vectors() {
+    assert_eq!(
+        blake2b_256(b""),
+        hex::decode("0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8").unwrap()
+    );
+    assert_eq!(
+        blake2b_256(b"abc"),
+        hex::decode("bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319").unwrap()
+    );
+}
+
+#[test]
+fn blake2b_256_parameterization() {
+    let input = b"parameterization check";
+    let digest = blake2b_256(input);
+    assert_eq!(digest.len(), 32);
+
+    // Same length, different function: BLAKE2s-256.
+    let mut blake2s = VarBlake2s::new(32).unwrap();
+    blake2s.input(&input[..]);
+    assert_ne!(digest, blake2s.vec_result());
+
+    // Not a truncated BLAKE2b-512 either.
+    assert_ne!(digest[..], Blake2b::digest(input)[..32]);
+}
+
+fn blake3_result(input: &[u8]) -> NativeResult {
+    hash::native_blake3(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input.to_vec())]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn blake3_known_vectors() {
+    assert_eq!(
+        single_bytes(blake3_result(b"")),
+        hex::decode("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262").unwrap()
+    );
+    assert_eq!(
+        single_bytes(blake3_result(b"abc")),
+        hex::decode("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85").unwrap()
+    );
+}
+
+#[test]
+fn blake3_large_input() {
+    // Larger than 1 MiB and not a multiple of the chunk size.
+    let input: Vec<u8> = (0..(1 << 20) + 1025).map(|i| (i % 251) as u8).collect();
+    let result = blake3_result(&input);
+    assert_eq!(result.cost.get(), 1026);
+    assert_eq!(
+        single_bytes(result),
+        blake3::hash(&input).as_bytes().to_vec()
+    );
+}
+
+#[test]
+fn blake3_gas_is_per_chunk() {
+    assert_eq!(hash::blake3_chunk_count(0), 1);
+    assert_eq!(hash::blake3_chunk_count(1), 1);
+    assert_eq!(hash::blake3_chunk_count(1024), 1);
+    assert_eq!(hash::blake3_chunk_count(1025), 2);
+    assert_eq!(blake3_result(&[0u8; 1024]).cost, blake3_result(b"").cost);
+    assert!(blake3_result(&[0u8; 1025]).cost.get() > blake3_result(&[0u8; 1024]).cost.get());
+}
+
+fn sha3_512(input: &[u8]) -> Vec<u8> {
+    let result = hash::native_sha3_512(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(input.to_vec())]),
+    )
+    .unwrap();
+    single_bytes(result)
+}
+
+#[test]
+fn sha3_512_known_vectors() {
+    let empty = sha3_512(b"");
+    assert_eq!(empty.len(), 64);
+    assert_eq!(
+        empty,
+        hex::decode(
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+             15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+        )
+        .unwrap()
+    );
+    let abc = sha3_512(b"abc");
+    assert_eq!(abc.len(), 64);
+    assert_eq!(
+        abc,
+        hex::decode(
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        )
+        .unwrap()
+    );
+}
+
+fn hmac_sha256_result(key: &[u8], message: &[u8]) -> NativeResult {
+    hash::native_hmac_sha256(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(key.to_vec()),
+            Value::vector_u8(message.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    single_bytes(hmac_sha256_result(key, message))
+}
+
+#[test]
+fn hmac_sha256_short_key() {
+    // RFC 4231 test case 2.
+    assert_eq!(
+        hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+        hex::decode("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843").unwrap()
+    );
+}
+
+#[test]
+fn hmac_sha256_key_longer_than_block_size() {
+    let key = [0xaau8; 131];
+    // RFC 4231 test case 6.
+    assert_eq!(
+        hmac_sha256(
+            &key,
+            b"Test Using Larger Than Block-Size Key - Hash Key First"
+        ),
+        hex::decode("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54").unwrap()
+    );
+    // RFC 4231 test case 7.
+    assert_eq!(
+        hmac_sha256(
+            &key,
+            b"This is a test using a larger than block-size key and a larger than block-size \
+              data. The key needs to be hashed before being used by the HMAC algorithm."
+        ),
+        hex::decode("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2").unwrap()
+    );
+}
+
+#[test]
+fn hmac_sha256_long_key_is_hashed_first() {
+    // A key one byte over the 64 byte block size is equivalent to its SHA2-256 digest.
+    let key = [b'x'; 65];
+    let hashed_key = Sha256::digest(&key);
+    assert_eq!(
+        hmac_sha256(&key, b"msg"),
+        hmac_sha256(hashed_key.as_slice(), b"msg")
+    );
+    // A key of exactly the block size is used as is.
+    let block_key = [b'x'; 64];
+    assert_ne!(
+        hmac_sha256(&block_key, b"msg"),
+        hmac_sha256(Sha256::digest(&block_key).as_slice(), b"msg")
+    );
+}
+
+#[test]
+fn hmac_sha256_gas_covers_key_and_message() {
+    let result = hmac_sha256_result(&[0u8; 100], &[0u8; 28]);
+    assert_eq!(result.cost.get(), 128);
+}
+
+fn hkdf_sha256_result(ikm: &[u8], salt: &[u8], info: &[u8], length: u64) -> NativeResult {
+    hash::native_hkdf_sha256(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(ikm.to_vec()),
+            Value::vector_u8(salt.to_vec()),
+            Value::vector_u8(info.to_vec()),
+            Value::u64(length),
+        ]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn hkdf_sha256_rfc5869_vectors() {
+    // Test case 1: basic test case.
+    let result = hkdf_sha256_result(
+        &[0x0b; 22],
+        &hex::decode("000102030405060708090a0b0c").unwrap(),
+        &hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+        42,
+    );
+    // The 22 bytes of key material, 13 of salt and 10 of info, and the 42 of output
+    assert_eq!(result.cost.get(), 22 + 13 + 10 + 42);
+    assert_eq!(
+        single_bytes(result),
+        hex::decode(
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+             34007208d5b887185865"
+        )
+        .unwrap()
+    );
+
+    // Test case 2: longer inputs and outputs.
+    let ikm: Vec<u8> = (0x00..=0x4f).collect();
+    let salt: Vec<u8> = (0x60..=0xaf).collect();
+    let info: Vec<u8> = (0xb0..=0xff).collect();
+    assert_eq!(
+        single_bytes(hkdf_sha256_result(&ikm, &salt, &info, 82)),
+        hex::decode(
+            "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+             59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+             cc30c58179ec3e87c14c01d5c1f3434f1d87"
+        )
+        .unwrap()
+    );
+
+    // Test case 3: zero-length salt and info.
+    assert_eq!(
+        single_bytes(hkdf_sha256_result(&[0x0b; 22], &[], &[], 42)),
+        hex::decode(
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+             9d201395faa4b61a96c8"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn hkdf_sha256_output_length_bounds() {
+    let max = hash::HKDF_SHA256_MAX_OUTPUT_LENGTH;
+    assert_eq!(
+        single_bytes(hkdf_sha256_result(b"ikm", b"salt", b"info", max)).len(),
+        max as usize
+    );
+    let result = hkdf_sha256_result(b"ikm", b"salt", b"info", 0);
+    assert_eq!(result.cost.get(), 11);
+    assert_eq!(single_bytes(result), Vec::<u8>::new());
+    for length in &[max + 1, u64::max_value()] {
+        let result = hkdf_sha256_result(b"ikm", b"salt", b"info", *length);
+        let status = result.result.unwrap_err();
+        assert_eq!(status.major_status, StatusCode::ABORTED);
+        assert_eq!(status.sub_status, Some(hash::HKDF_INVALID_OUTPUT_LENGTH));
+        assert_eq!(result.cost.get(), 11);
+    }
+    let result = hkdf_sha256_result(&[], &[], &[], max + 1);
+    assert_eq!(result.cost.get(), 1);
+}
+
+fn sha3_256_with_domain_result(domain: &[u8], message: &[u8]) -> NativeResult {
+    hash::native_sha3_256_with_domain(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(domain.to_vec()),
+            Value::vector_u8(message.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn sha3_256_with_domain(domain: &[u8], message: &[u8]) -> Vec<u8> {
+    single_bytes(sha3_256_with_domain_result(domain, message))
+}
+
+#[test]
+fn sha3_256_with_domain_framing() {
+    let mut preimage = 2u64.to_le_bytes().to_vec();
+    preimage.extend_from_slice(b"ABC");
+    assert_eq!(
+        sha3_256_with_domain(b"AB", b"C"),
+        HashValue::from_sha3_256(&preimage).to_vec()
+    );
+}
+
+#[test]
+fn sha3_256_with_domain_separates_domains() {
+    assert_ne!(
+        sha3_256_with_domain(b"AB", b"C"),
+        sha3_256_with_domain(b"A", b"BC")
+    );
+    assert_ne!(
+        sha3_256_with_domain(b"", b"ABC"),
+        sha3_256_with_domain(b"ABC", b"")
+    );
+}
+
+#[test]
+fn sha3_256_with_domain_gas_covers_domain_and_message() {
+    let result = sha3_256_with_domain_result(&[0u8; 10], &[0u8; 22]);
+    assert_eq!(result.cost.get(), 32);
+}
+
+/// Dispatches the hash `native` on `input` with `budget` gas left to the transaction.
+fn hash_with_budget(native: NativeFunction, input: Vec<u8>, budget: u64) -> NativeResult {
+    let mut context = MockNativeContext {
+        remaining_gas: GasUnits::new(budget),
+        ..MockNativeContext::for_testing()
+    };
+    native
+        .dispatch(
+            &mut context,
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::vector_u8(input)]),
+        )
+        .unwrap()
+}
+
+fn assert_out_of_gas(result: NativeResult, budget: u64) {
+    assert_eq!(result.cost.get(), budget);
+    assert_eq!(
+        result.result.unwrap_err().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+}
+
+#[test]
+fn hashes_stop_when_out_of_gas() {
+    for native in &[
+        NativeFunction::HashSha2_256,
+        NativeFunction::HashSha3_256,
+        NativeFunction::HashKeccak256,
+        NativeFunction::HashSha2_512,
+        NativeFunction::HashRipemd160,
+        NativeFunction::HashBlake2b256,
+        NativeFunction::HashSha3_512,
+    ] {
+        let input = vec![0xab; 4 * hash::HASH_CHUNK_LEN];
+        assert_out_of_gas(hash_with_budget(*native, input, 10), 10);
+
+        // Within the budget, the hash is computed
+        let result = hash_with_budget(*native, vec![0xab; 10], 10);
+        assert_eq!(result.cost.get(), 10);
+        assert_eq!(result.result.unwrap().len(), 1);
+    }
+
+    // BLAKE3 is charged per chunk: the budget covers ten of the eleven chunks
+    let input = vec![0xab; 11 * hash::BLAKE3_CHUNK_LEN];
+    assert_out_of_gas(hash_with_budget(NativeFunction::HashBlake3, input, 10), 10);
+}
+
+#[test]
+fn chunked_hashes_match_one_shot_hashes() {
+    let input: Vec<u8> = (0..3 * hash::HASH_CHUNK_LEN + 7).map(|i| i as u8).collect();
+    let result = hash_with_budget(
+        NativeFunction::HashSha3_256,
+        input.clone(),
+        input.len() as u64,
+    );
+    assert_eq!(
+        single_bytes(result),
+        HashValue::from_sha3_256(&input).to_vec()
+    );
+    let result = hash_with_budget(
+        NativeFunction::HashSha2_256,
+        input.clone(),
+        input.len() as u64,
+    );
+    assert_eq!(single_bytes(result), Sha256::digest(&input).to_vec());
+}
+
+#[test]
+fn hashes_record_their_cost_breakdown() {
+    let context = context_with_native_cost(3, 2);
+    let input = || args(vec![Value::vector_u8(vec![0xab; 100])]);
+    for native in &[
+        hash::native_sha2_256,
+        hash::native_sha3_256,
+        hash::native_keccak_256,
+        hash::native_sha2_512,
+        hash::native_ripemd_160,
+        hash::native_blake2b_256,
+        hash::native_sha3_512,
+    ] {
+        let result = native(&context, &[], input()).unwrap();
+        assert_cost_breakdown(&result);
+        let breakdown = result.cost_breakdown.unwrap();
+        assert_eq!(breakdown.base.get(), 0);
+        assert_eq!(breakdown.per_unit.get(), 5);
+        assert_eq!(breakdown.units, 100);
+    }
+
+    // BLAKE3 is charged per chunk
+    let input = vec![0xab; 2 * hash::BLAKE3_CHUNK_LEN + 1];
+    let result = hash::native_blake3(&context, &[], args(vec![Value::vector_u8(input)])).unwrap();
+    assert_cost_breakdown(&result);
+    assert_eq!(result.cost_breakdown.unwrap().units, 3);
+
+    let result = hash::native_hkdf_sha256(
+        &context,
+        &[],
+        args(vec![
+            Value::vector_u8(b"ikm".to_vec()),
+            Value::vector_u8(vec![]),
+            Value::vector_u8(vec![]),
+            Value::u64(0),
+        ]),
+    )
+    .unwrap();
+    assert_cost_breakdown(&result);
+    assert_eq!(result.cost_breakdown.unwrap().units, 3);
+    assert_eq!(result.cost.get(), 15);
+}
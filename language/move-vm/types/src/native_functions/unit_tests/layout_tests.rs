@@ -0,0 +1,139 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::layout::{ModuleLayoutResolver, StubLayoutResolver, TypeLayoutResolver},
+};
+use libra_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    vm_error::StatusCode,
+};
+use vm::file_format::{
+    empty_module, CompiledModule, FieldDefinition, FieldDefinitionIndex, IdentifierIndex, Kind,
+    ModuleHandleIndex, SignatureToken, StructDefinition, StructFieldInformation, StructHandle,
+    StructHandleIndex, TypeSignature, TypeSignatureIndex,
+};
+
+/// The module
+/// // module M {
+/// //     struct Inner<T> { value: T, count: u64 }
+/// //     struct Outer<T> { inner: Inner<T>, items: vector<T> }
+/// // }
+fn generic_module() -> CompiledModule {
+    let mut m = empty_module();
+    m.identifiers = ["M", "Inner", "Outer", "value", "count", "inner", "items"]
+        .iter()
+        .map(|name| Identifier::new(*name).unwrap())
+        .collect();
+    m.struct_handles = [1, 2]
+        .iter()
+        .map(|&name| StructHandle {
+            module: ModuleHandleIndex::new(0),
+            name: IdentifierIndex::new(name),
+            is_nominal_resource: false,
+            type_formals: vec![Kind::All],
+        })
+        .collect();
+    let type_parameter = SignatureToken::TypeParameter(0);
+    m.type_signatures = vec![
+        TypeSignature(type_parameter.clone()),
+        TypeSignature(SignatureToken::U64),
+        TypeSignature(SignatureToken::Struct(
+            StructHandleIndex::new(0),
+            vec![type_parameter.clone()],
+        )),
+        TypeSignature(SignatureToken::Vector(Box::new(type_parameter))),
+    ];
+    m.field_defs = [(0, 3, 0), (0, 4, 1), (1, 5, 2), (1, 6, 3)]
+        .iter()
+        .map(|&(struct_, name, signature)| FieldDefinition {
+            struct_: StructHandleIndex::new(struct_),
+            name: IdentifierIndex::new(name),
+            signature: TypeSignatureIndex::new(signature),
+        })
+        .collect();
+    m.struct_defs = [0, 1]
+        .iter()
+        .map(|&idx| StructDefinition {
+            struct_handle: StructHandleIndex::new(idx),
+            field_information: StructFieldInformation::Declared {
+                field_count: 2,
+                fields: FieldDefinitionIndex::new(2 * idx),
+            },
+        })
+        .collect();
+    m.freeze().unwrap()
+}
+
+fn struct_tag(name: &str, type_params: Vec<TypeTag>) -> StructTag {
+    StructTag {
+        address: AccountAddress::default(),
+        module: Identifier::new("M").unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params,
+    }
+}
+
+#[test]
+fn resolve_nested_generic_struct() {
+    let module = generic_module();
+    let resolver = ModuleLayoutResolver::new(vec![&module]);
+    let outer = TypeTag::Struct(struct_tag("Outer", vec![TypeTag::Address]));
+    let inner = Type::Struct(StructDef::new(vec![Type::Address, Type::U64]));
+    let expected = Type::Struct(StructDef::new(vec![
+        inner,
+        Type::Vector(Box::new(Type::Address)),
+    ]));
+    assert_eq!(resolver.type_to_layout(&outer).unwrap(), expected);
+    assert_eq!(
+        resolver
+            .type_to_layout(&TypeTag::Vector(Box::new(outer)))
+            .unwrap(),
+        Type::Vector(Box::new(expected))
+    );
+}
+
+#[test]
+fn resolve_struct_with_wrong_type_arguments() {
+    let module = generic_module();
+    let resolver = ModuleLayoutResolver::new(vec![&module]);
+    let status = resolver
+        .type_to_layout(&TypeTag::Struct(struct_tag("Inner", vec![])))
+        .err()
+        .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH
+    );
+}
+
+#[test]
+fn resolve_unknown_struct() {
+    let module = generic_module();
+    let resolver = ModuleLayoutResolver::new(vec![&module]);
+    let unknown = struct_tag("Missing", vec![]);
+    let mut other_module = unknown.clone();
+    other_module.module = Identifier::new("N").unwrap();
+    for tag in vec![unknown, other_module] {
+        let status = resolver
+            .type_to_layout(&TypeTag::Struct(tag))
+            .err()
+            .unwrap();
+        assert_eq!(status.major_status, StatusCode::TYPE_RESOLUTION_FAILURE);
+    }
+}
+
+#[test]
+fn stub_resolves_only_types_without_structs() {
+    let vector = TypeTag::Vector(Box::new(TypeTag::U64));
+    assert_eq!(
+        StubLayoutResolver.type_to_layout(&vector).unwrap(),
+        Type::Vector(Box::new(Type::U64))
+    );
+    let structs = TypeTag::Vector(Box::new(TypeTag::Struct(struct_tag("Inner", vec![]))));
+    let status = StubLayoutResolver.type_to_layout(&structs).err().unwrap();
+    assert_eq!(status.major_status, StatusCode::TYPE_RESOLUTION_FAILURE);
+}
@@ -0,0 +1,666 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, single_bytes};
+use crate::{
+    loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::{
+        arguments::Arguments,
+        context::{MockNativeContext, NativeContext},
+        dispatch::{NativeFunction, NativeResult},
+        lcs as lcs_natives,
+    },
+    values::{Locals, Struct, Value},
+};
+use libra_types::{
+    account_address::AccountAddress, byte_array::ByteArray, language_storage::TypeTag,
+    vm_error::StatusCode,
+};
+use proptest::prelude::*;
+use serde::Serialize;
+use vm::{errors::VMResult, file_format::Kind, gas_schedule::GasAlgebra};
+
+/// Calls `to_bytes` on a reference to `value`, which is stored in a local for the duration of the
+/// call.
+fn to_bytes_result(value: Value, layout: Type) -> NativeResult {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, value).unwrap();
+    lcs_natives::native_to_bytes(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![layout],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+    .unwrap()
+}
+
+fn to_bytes(value: Value, layout: Type) -> Vec<u8> {
+    single_bytes(to_bytes_result(value, layout))
+}
+
+#[derive(Serialize)]
+struct Inner {
+    flag: bool,
+    owner: AccountAddress,
+}
+
+#[derive(Serialize)]
+struct Outer {
+    amount: u64,
+    inner: Inner,
+    memo: Vec<u8>,
+}
+
+fn inner_layout() -> Type {
+    Type::Struct(StructDef::new(vec![Type::Bool, Type::Address]))
+}
+
+fn outer_layout() -> Type {
+    Type::Struct(StructDef::new(vec![
+        Type::U64,
+        inner_layout(),
+        Type::Vector(Box::new(Type::U8)),
+    ]))
+}
+
+#[test]
+fn to_bytes_primitives() {
+    let address = AccountAddress::new([0xab; 32]);
+    let byte_array = ByteArray::new(vec![1, 2, 3]);
+    assert_eq!(
+        to_bytes(Value::bool(true), Type::Bool),
+        lcs::to_bytes(&true).unwrap()
+    );
+    assert_eq!(
+        to_bytes(Value::u8(7), Type::U8),
+        lcs::to_bytes(&7u8).unwrap()
+    );
+    assert_eq!(
+        to_bytes(Value::u64(0x0102_0304), Type::U64),
+        lcs::to_bytes(&0x0102_0304u64).unwrap()
+    );
+    assert_eq!(
+        to_bytes(Value::u128(std::u128::MAX), Type::U128),
+        lcs::to_bytes(&std::u128::MAX).unwrap()
+    );
+    assert_eq!(
+        to_bytes(Value::address(address), Type::Address),
+        lcs::to_bytes(&address).unwrap()
+    );
+    assert_eq!(
+        to_bytes(Value::byte_array(byte_array.clone()), Type::ByteArray),
+        lcs::to_bytes(&byte_array).unwrap()
+    );
+    assert_eq!(
+        to_bytes(
+            Value::vector_u8(vec![4, 5, 6]),
+            Type::Vector(Box::new(Type::U8))
+        ),
+        lcs::to_bytes(&vec![4u8, 5, 6]).unwrap()
+    );
+}
+
+#[test]
+fn to_bytes_nested_struct() {
+    let address = AccountAddress::new([0x11; 32]);
+    let value = Value::struct_(Struct::pack(vec![
+        Value::u64(42),
+        Value::struct_(Struct::pack(vec![
+            Value::bool(true),
+            Value::address(address),
+        ])),
+        Value::vector_u8(b"memo".to_vec()),
+    ]));
+    let expected = lcs::to_bytes(&Outer {
+        amount: 42,
+        inner: Inner {
+            flag: true,
+            owner: address,
+        },
+        memo: b"memo".to_vec(),
+    })
+    .unwrap();
+
+    let result = to_bytes_result(value, outer_layout());
+    assert_eq!(result.cost.get(), expected.len() as u64);
+    assert_eq!(single_bytes(result), expected);
+}
+
+#[test]
+fn to_bytes_vector_of_structs() {
+    let outers = vec![
+        Outer {
+            amount: 1,
+            inner: Inner {
+                flag: false,
+                owner: AccountAddress::new([1; 32]),
+            },
+            memo: vec![],
+        },
+        Outer {
+            amount: std::u64::MAX,
+            inner: Inner {
+                flag: true,
+                owner: AccountAddress::new([2; 32]),
+            },
+            memo: vec![0xff; 3],
+        },
+    ];
+    let expected = lcs::to_bytes(&outers).unwrap();
+    let layout = Type::Vector(Box::new(outer_layout()));
+    // Vectors of structs can only be built by the VM, e.g. by deserializing them.
+    let value = Value::simple_deserialize(&expected, layout.clone()).unwrap();
+    assert_eq!(to_bytes(value, layout), expected);
+}
+
+#[test]
+fn to_bytes_layout_mismatch() {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::u64(1)).unwrap();
+    assert!(lcs_natives::native_to_bytes(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![Type::Bool],
+        args(vec![locals.borrow_loc(0).unwrap()])
+    )
+    .is_err());
+}
+
+/// Dispatches `to_bytes` on a reference to the `u64` 9 with the layouts `layouts`.
+fn dispatch_to_bytes(layouts: Vec<Type>) -> VMResult<NativeResult> {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::u64(9)).unwrap();
+    NativeFunction::LcsToBytes.dispatch(
+        &mut MockNativeContext::for_testing(),
+        &[TypeTag::U64],
+        vec![Kind::Unrestricted],
+        layouts,
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+}
+
+#[test]
+fn to_bytes_dispatches_with_layouts() {
+    let result = dispatch_to_bytes(vec![Type::U64]).unwrap();
+    assert_eq!(single_bytes(result), lcs::to_bytes(&9u64).unwrap());
+
+    // Layouts the caller does not pass are resolved from the context
+    let result = dispatch_to_bytes(vec![]).unwrap();
+    assert_eq!(single_bytes(result), lcs::to_bytes(&9u64).unwrap());
+
+    let status = dispatch_to_bytes(vec![Type::U64, Type::U64]).err().unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH
+    );
+}
+
+/// Dispatches `native` on references to `values`, of type `vector<u8>`, with a transaction that
+/// can pay `budget`.
+fn dispatch_with_budget(native: NativeFunction, values: Vec<Value>, budget: u64) -> NativeResult {
+    let mut locals = Locals::new(values.len());
+    for (idx, value) in values.into_iter().enumerate() {
+        locals.store_loc(idx, value).unwrap();
+    }
+    let references = (0..native.num_args())
+        .map(|idx| locals.borrow_loc(idx).unwrap())
+        .collect();
+    native
+        .dispatch(
+            &mut MockNativeContext::for_testing().with_remaining_gas(budget),
+            &[TypeTag::Vector(Box::new(TypeTag::U8))],
+            vec![Kind::Unrestricted],
+            vec![Type::Vector(Box::new(Type::U8))],
+            args(references),
+        )
+        .unwrap()
+}
+
+#[test]
+fn to_bytes_stops_before_serializing_when_out_of_gas() {
+    let value = Value::vector_u8(vec![7; 100]);
+    let result = dispatch_with_budget(NativeFunction::LcsToBytes, vec![value.copy_value()], 103);
+    assert_eq!(result.cost.get(), 103);
+    assert_eq!(
+        result.result.unwrap_err().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+
+    let result = dispatch_with_budget(NativeFunction::LcsToBytes, vec![value], 104);
+    assert_eq!(result.cost.get(), 104);
+    assert_eq!(single_bytes(result).len(), 104);
+}
+
+#[test]
+fn to_bytes_rejects_oversized_values() {
+    let max = lcs_natives::MAX_SERIALIZED_SIZE;
+    // The length prefix takes 4 bytes
+    let result = to_bytes_result(
+        Value::vector_u8(vec![0; max - 4]),
+        Type::Vector(Box::new(Type::U8)),
+    );
+    assert_eq!(single_bytes(result).len(), max);
+
+    let result = to_bytes_result(
+        Value::vector_u8(vec![0; max - 3]),
+        Type::Vector(Box::new(Type::U8)),
+    );
+    assert_eq!(result.cost.get(), max as u64 + 1);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(lcs_natives::VALUE_TOO_LARGE));
+}
+
+fn serialized_size_result(value: Value, layout: Type) -> VMResult<NativeResult> {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, value).unwrap();
+    lcs_natives::native_serialized_size(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![layout],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+}
+
+#[test]
+fn serialized_size_stops_when_out_of_gas() {
+    let value = Value::vector_u8(vec![7; 100]);
+    let result = dispatch_with_budget(
+        NativeFunction::LcsSerializedSize,
+        vec![value.copy_value()],
+        103,
+    );
+    assert_eq!(result.cost.get(), 103);
+    assert_eq!(
+        result.result.unwrap_err().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+
+    let result = dispatch_with_budget(NativeFunction::LcsSerializedSize, vec![value], 104);
+    assert_eq!(result.cost.get(), 104);
+    let size = result
+        .result
+        .unwrap()
+        .pop()
+        .unwrap()
+        .value_as::<u64>()
+        .unwrap();
+    assert_eq!(size, 104);
+}
+
+/// Checks that `serialized_size` agrees with the length of `to_bytes`, and returns it.
+fn assert_serialized_size(value: Value, layout: Type) -> usize {
+    let expected = to_bytes(value.copy_value(), layout.clone()).len();
+    let result = serialized_size_result(value, layout).unwrap();
+    assert_eq!(result.cost.get(), expected as u64);
+    let mut values = result.result.expect("serialized_size should succeed");
+    assert_eq!(values.len(), 1);
+    let size = values.pop().unwrap().value_as::<u64>().unwrap();
+    assert_eq!(size, expected as u64);
+    expected
+}
+
+#[test]
+fn serialized_size_primitives() {
+    assert_eq!(assert_serialized_size(Value::bool(false), Type::Bool), 1);
+    assert_eq!(assert_serialized_size(Value::u8(1), Type::U8), 1);
+    assert_eq!(assert_serialized_size(Value::u64(1), Type::U64), 8);
+    assert_eq!(assert_serialized_size(Value::u128(1), Type::U128), 16);
+    assert_eq!(
+        assert_serialized_size(Value::address(AccountAddress::default()), Type::Address),
+        32
+    );
+    assert_eq!(
+        assert_serialized_size(
+            Value::byte_array(ByteArray::new(vec![1, 2, 3])),
+            Type::ByteArray
+        ),
+        7
+    );
+    assert_eq!(
+        assert_serialized_size(Value::vector_u8(vec![]), Type::Vector(Box::new(Type::U8))),
+        4
+    );
+}
+
+#[test]
+fn serialized_size_nested_structures() {
+    let value = Value::struct_(Struct::pack(vec![
+        Value::u64(42),
+        Value::struct_(Struct::pack(vec![
+            Value::bool(true),
+            Value::address(AccountAddress::new([0x11; 32])),
+        ])),
+        Value::vector_u8(b"memo".to_vec()),
+    ]));
+    assert_serialized_size(value, outer_layout());
+
+    let outers = vec![
+        Outer {
+            amount: 1,
+            inner: Inner {
+                flag: false,
+                owner: AccountAddress::new([1; 32]),
+            },
+            memo: vec![],
+        },
+        Outer {
+            amount: 2,
+            inner: Inner {
+                flag: true,
+                owner: AccountAddress::new([2; 32]),
+            },
+            memo: vec![0xff; 300],
+        },
+    ];
+    let layout = Type::Vector(Box::new(outer_layout()));
+    let value =
+        Value::simple_deserialize(&lcs::to_bytes(&outers).unwrap(), layout.clone()).unwrap();
+    assert_serialized_size(value, layout);
+
+    let nested = vec![vec![], vec![1u64, 2, 3], vec![std::u64::MAX]];
+    let layout = Type::Vector(Box::new(Type::Vector(Box::new(Type::U64))));
+    let value =
+        Value::simple_deserialize(&lcs::to_bytes(&nested).unwrap(), layout.clone()).unwrap();
+    assert_eq!(
+        assert_serialized_size(value, layout),
+        4 + 4 + (4 + 24) + (4 + 8)
+    );
+
+    let byte_arrays = vec![ByteArray::new(vec![]), ByteArray::new(vec![7; 5])];
+    let layout = Type::Vector(Box::new(Type::ByteArray));
+    let value =
+        Value::simple_deserialize(&lcs::to_bytes(&byte_arrays).unwrap(), layout.clone()).unwrap();
+    assert_serialized_size(value, layout);
+}
+
+#[test]
+fn serialized_size_layout_mismatch() {
+    assert!(serialized_size_result(Value::u64(1), Type::Bool).is_err());
+    assert!(serialized_size_result(
+        Value::struct_(Struct::pack(vec![Value::bool(true)])),
+        inner_layout()
+    )
+    .is_err());
+    assert!(
+        serialized_size_result(Value::vector_u8(vec![1]), Type::Vector(Box::new(Type::U64)))
+            .is_err()
+    );
+}
+
+type Native = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+/// Calls one of the `from_bytes_*` natives, returning whether decoding succeeded and the value.
+fn from_bytes(native: Native, bytes: &[u8]) -> (bool, Value) {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), bytes.len() as u64);
+    let mut values = result.result.expect("from_bytes should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (valid, value)
+}
+
+fn from_bytes_u64(bytes: &[u8]) -> (bool, u64) {
+    let (valid, value) = from_bytes(lcs_natives::native_from_bytes_u64, bytes);
+    (valid, value.value_as::<u64>().unwrap())
+}
+
+fn from_bytes_bool(bytes: &[u8]) -> (bool, bool) {
+    let (valid, value) = from_bytes(lcs_natives::native_from_bytes_bool, bytes);
+    (valid, value.value_as::<bool>().unwrap())
+}
+
+fn from_bytes_address(bytes: &[u8]) -> (bool, AccountAddress) {
+    let (valid, value) = from_bytes(lcs_natives::native_from_bytes_address, bytes);
+    (valid, value.value_as::<AccountAddress>().unwrap())
+}
+
+#[test]
+fn from_bytes_rejects_trailing_bytes() {
+    let mut bytes = lcs::to_bytes(&7u64).unwrap();
+    bytes.push(0);
+    assert_eq!(from_bytes_u64(&bytes), (false, 0));
+    assert_eq!(from_bytes_bool(&[1, 1]), (false, false));
+    let mut bytes = lcs::to_bytes(&AccountAddress::new([3; 32])).unwrap();
+    bytes.push(3);
+    assert_eq!(
+        from_bytes_address(&bytes),
+        (false, AccountAddress::default())
+    );
+}
+
+#[test]
+fn from_bytes_rejects_truncated_input() {
+    assert_eq!(from_bytes_u64(&[]), (false, 0));
+    assert_eq!(from_bytes_u64(&[1; 7]), (false, 0));
+    assert_eq!(from_bytes_bool(&[]), (false, false));
+    assert_eq!(
+        from_bytes_address(&[3; 31]),
+        (false, AccountAddress::default())
+    );
+}
+
+#[test]
+fn from_bytes_bool_is_canonical() {
+    assert_eq!(from_bytes_bool(&[0]), (true, false));
+    assert_eq!(from_bytes_bool(&[1]), (true, true));
+    assert_eq!(from_bytes_bool(&[2]), (false, false));
+}
+
+proptest! {
+    #[test]
+    fn from_bytes_u64_round_trip(value in any::<u64>()) {
+        let bytes = to_bytes(Value::u64(value), Type::U64);
+        prop_assert_eq!(from_bytes_u64(&bytes), (true, value));
+    }
+
+    #[test]
+    fn from_bytes_bool_round_trip(value in any::<bool>()) {
+        let bytes = to_bytes(Value::bool(value), Type::Bool);
+        prop_assert_eq!(from_bytes_bool(&bytes), (true, value));
+    }
+
+    #[test]
+    fn from_bytes_address_round_trip(address in any::<AccountAddress>()) {
+        let bytes = to_bytes(Value::address(address), Type::Address);
+        prop_assert_eq!(from_bytes_address(&bytes), (true, address));
+    }
+
+    #[test]
+    fn serialized_size_matches_to_bytes(
+        memos in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..40), 0..8),
+    ) {
+        let outers: Vec<_> = memos
+            .into_iter()
+            .enumerate()
+            .map(|(i, memo)| Outer {
+                amount: i as u64,
+                inner: Inner {
+                    flag: i % 2 == 0,
+                    owner: AccountAddress::new([i as u8; 32]),
+                },
+                memo,
+            })
+            .collect();
+        let bytes = lcs::to_bytes(&outers).unwrap();
+        let layout = Type::Vector(Box::new(outer_layout()));
+        let value = Value::simple_deserialize(&bytes, layout.clone()).unwrap();
+        prop_assert_eq!(assert_serialized_size(value, layout), bytes.len());
+    }
+}
+
+/// Calls `cmp_lcs_bytes` on references to `left` and `right`, checking it is charged for both
+/// serializations.
+fn compare(left: Value, right: Value, layout: Type) -> u64 {
+    let size = to_bytes(left.copy_value(), layout.clone()).len()
+        + to_bytes(right.copy_value(), layout.clone()).len();
+    let mut locals = Locals::new(2);
+    locals.store_loc(0, left).unwrap();
+    locals.store_loc(1, right).unwrap();
+    let result = lcs_natives::native_compare(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![layout],
+        args(vec![
+            locals.borrow_loc(0).unwrap(),
+            locals.borrow_loc(1).unwrap(),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), size as u64);
+    let mut values = result.result.expect("cmp_lcs_bytes should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+#[test]
+fn compare_primitives() {
+    use lcs_natives::{EQUAL, GREATER, LESS};
+    assert_eq!(compare(Value::u64(1), Value::u64(2), Type::U64), LESS);
+    assert_eq!(compare(Value::u64(2), Value::u64(2), Type::U64), EQUAL);
+    assert_eq!(
+        compare(Value::bool(true), Value::bool(false), Type::Bool),
+        GREATER
+    );
+    // Integers are compared by their little-endian bytes, not numerically
+    assert_eq!(compare(Value::u64(256), Value::u64(1), Type::U64), LESS);
+}
+
+#[test]
+fn compare_vectors_of_different_lengths() {
+    use lcs_natives::{EQUAL, GREATER, LESS};
+    let layout = Type::Vector(Box::new(Type::U64));
+    let vector = Value::vector_u64;
+    // The length prefix comes first, so a shorter vector is less whatever its elements
+    assert_eq!(
+        compare(vector(vec![9]), vector(vec![1, 1]), layout.clone()),
+        LESS
+    );
+    assert_eq!(
+        compare(vector(vec![]), vector(vec![0]), layout.clone()),
+        LESS
+    );
+    assert_eq!(
+        compare(vector(vec![1, 2]), vector(vec![1, 3]), layout.clone()),
+        LESS
+    );
+    assert_eq!(
+        compare(vector(vec![1, 2]), vector(vec![1, 2]), layout.clone()),
+        EQUAL
+    );
+    assert_eq!(
+        compare(vector(vec![0, 0, 0]), vector(vec![5, 5]), layout),
+        GREATER
+    );
+}
+
+#[test]
+fn compare_nested_structs() {
+    use lcs_natives::{EQUAL, GREATER, LESS};
+    let outer = |amount: u64, flag: bool, memo: Vec<u8>| {
+        let outer = Outer {
+            amount,
+            inner: Inner {
+                flag,
+                owner: AccountAddress::new([0xab; 32]),
+            },
+            memo,
+        };
+        Value::simple_deserialize(&lcs::to_bytes(&outer).unwrap(), outer_layout()).unwrap()
+    };
+    // Fields are compared in declaration order
+    assert_eq!(
+        compare(
+            outer(1, true, vec![]),
+            outer(2, false, vec![]),
+            outer_layout()
+        ),
+        LESS
+    );
+    assert_eq!(
+        compare(
+            outer(1, true, vec![]),
+            outer(1, false, vec![7]),
+            outer_layout()
+        ),
+        GREATER
+    );
+    assert_eq!(
+        compare(
+            outer(1, false, vec![]),
+            outer(1, false, vec![7]),
+            outer_layout()
+        ),
+        LESS
+    );
+    assert_eq!(
+        compare(
+            outer(3, true, vec![7]),
+            outer(3, true, vec![7]),
+            outer_layout()
+        ),
+        EQUAL
+    );
+}
+
+#[test]
+fn compare_layout_mismatch() {
+    let mut locals = Locals::new(2);
+    locals.store_loc(0, Value::u64(1)).unwrap();
+    locals.store_loc(1, Value::u64(2)).unwrap();
+    assert!(lcs_natives::native_compare(
+        &MockNativeContext::for_testing(),
+        &[],
+        vec![Type::Bool],
+        args(vec![
+            locals.borrow_loc(0).unwrap(),
+            locals.borrow_loc(1).unwrap(),
+        ])
+    )
+    .is_err());
+}
+
+#[test]
+fn compare_stops_before_serializing_when_out_of_gas() {
+    let values = || {
+        vec![
+            Value::vector_u8(vec![1; 100]),
+            Value::vector_u8(vec![2; 100]),
+        ]
+    };
+    let result = dispatch_with_budget(NativeFunction::CompareGeneric, values(), 207);
+    assert_eq!(result.cost.get(), 207);
+    assert_eq!(
+        result.result.unwrap_err().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+
+    let result = dispatch_with_budget(NativeFunction::CompareGeneric, values(), 208);
+    assert_eq!(result.cost.get(), 208);
+    let ordering = result
+        .result
+        .unwrap()
+        .pop()
+        .unwrap()
+        .value_as::<u64>()
+        .unwrap();
+    assert_eq!(ordering, lcs_natives::LESS);
+}
+
+#[test]
+fn compare_rejects_oversized_values() {
+    let max = lcs_natives::MAX_SERIALIZED_SIZE;
+    // The length prefix takes 4 bytes
+    let values = vec![Value::vector_u8(vec![0; max - 3]), Value::vector_u8(vec![])];
+    let result = dispatch_with_budget(NativeFunction::CompareGeneric, values, u64::max_value());
+    assert_eq!(result.cost.get(), max as u64 + 5);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(lcs_natives::VALUE_TOO_LARGE));
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod abort_codes_tests;
+mod account_tests;
+mod arguments_tests;
+mod biguint_tests;
+mod block_tests;
+mod builtins_tests;
+mod debug_tests;
+mod errors_tests;
+mod fixed_point_tests;
+mod hash_tests;
+mod layout_tests;
+mod lcs_tests;
+mod native_result_tests;
+mod pedersen_tests;
+mod primitive_helpers_tests;
+mod reflection_tests;
+mod ristretto255_tests;
+mod signature_tests;
+mod table_tests;
+mod transaction_tests;
+#[cfg(feature = "testing")]
+mod unit_test_tests;
+mod vector_tests;
+
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{NativeArguments, NativeResult},
+    },
+    values::Value,
+};
+use vm::gas_schedule::{CostTable, GasAlgebra, GasCost};
+
+/// The cost table of `MockNativeContext::for_testing`, charging one gas unit per unit of size for
+/// every native function.
+pub(crate) fn unit_cost_table() -> CostTable {
+    MockNativeContext::for_testing().cost_table
+}
+
+/// A `MockNativeContext::for_testing` whose natives all cost `instruction_gas` and `memory_gas`
+/// per unit of size, for tests telling the components of a cost apart.
+pub(crate) fn context_with_native_cost(instruction_gas: u64, memory_gas: u64) -> MockNativeContext {
+    let mut cost_table = unit_cost_table();
+    for cost in cost_table.native_table.iter_mut() {
+        *cost = GasCost::new(instruction_gas, memory_gas);
+    }
+    MockNativeContext::for_testing().with_cost_table(cost_table)
+}
+
+/// Asserts that `result` records how its cost was computed, and that the breakdown adds up to it.
+pub(crate) fn assert_cost_breakdown(result: &NativeResult) {
+    let breakdown = result
+        .cost_breakdown
+        .expect("native function should record its cost breakdown");
+    assert_eq!(
+        breakdown.base.get() + breakdown.per_unit.get() * breakdown.units,
+        result.cost.get()
+    );
+}
+
+/// The arguments `values`, as `NativeArguments` or as `Arguments` for natives called directly.
+pub(crate) fn args<A: From<NativeArguments>>(values: Vec<Value>) -> A {
+    A::from(values.into_iter().collect::<NativeArguments>())
+}
+
+/// Unwraps the single `vector<u8>` returned by a successful native call.
+pub(crate) fn single_bytes(result: NativeResult) -> Vec<u8> {
+    let mut values = result.result.expect("native function should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<Vec<u8>>().unwrap()
+}
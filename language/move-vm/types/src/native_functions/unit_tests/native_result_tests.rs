@@ -0,0 +1,113 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::unit_cost_table;
+use crate::{
+    native_functions::dispatch::{
+        native_gas_detailed, NativeEvent, NativeFunction, NativeResult, NativeResultSummary,
+    },
+    values::Value,
+};
+use libra_types::{
+    language_storage::TypeTag,
+    vm_error::{StatusCode, VMStatus},
+};
+use vm::gas_schedule::{GasAlgebra, GasUnits, NativeCostIndex};
+
+/// Events `0..n` of the stream `key`, each with its sequence number as its value.
+fn events(key: &[u8], n: u64) -> Vec<NativeEvent> {
+    (0..n)
+        .map(|i| (key.to_vec(), i, TypeTag::U64, Value::u64(i)))
+        .collect()
+}
+
+fn assert_events(actual: &[NativeEvent], expected: &[NativeEvent]) {
+    assert_eq!(actual.len(), expected.len());
+    for ((key, seq_num, tag, value), (exp_key, exp_seq_num, exp_tag, exp_value)) in
+        actual.iter().zip(expected)
+    {
+        assert_eq!(key, exp_key);
+        assert_eq!(seq_num, exp_seq_num);
+        assert_eq!(tag, exp_tag);
+        assert!(value.equals(exp_value).unwrap());
+    }
+}
+
+#[test]
+fn results_have_no_events_by_default() {
+    assert!(NativeResult::ok(GasUnits::new(1), vec![])
+        .events()
+        .is_empty());
+    assert!(
+        NativeResult::err(GasUnits::new(1), VMStatus::new(StatusCode::ABORTED))
+            .events()
+            .is_empty()
+    );
+    assert!(NativeResult::abort(GasUnits::new(1), 0).events().is_empty());
+}
+
+/// Events of two streams, interleaved.
+fn interleaved_events() -> Vec<NativeEvent> {
+    let mut interleaved = events(b"first", 2);
+    interleaved.extend(events(b"second", 2));
+    interleaved.push((b"first".to_vec(), 2, TypeTag::Bool, Value::bool(true)));
+    interleaved
+}
+
+#[test]
+fn events_keep_their_order() {
+    let mut result =
+        NativeResult::ok_with_events(GasUnits::new(5), vec![Value::u64(7)], interleaved_events());
+    assert_eq!(result.cost.get(), 5);
+    assert_eq!(result.result.as_ref().unwrap().len(), 1);
+    assert_events(result.events(), &interleaved_events());
+
+    assert_events(&result.take_events(), &interleaved_events());
+    assert!(result.events().is_empty());
+}
+
+#[test]
+fn refunds_are_deducted_from_the_charge() {
+    let result = NativeResult::ok(GasUnits::new(10), vec![]);
+    assert_eq!(result.refund.get(), 0);
+    assert_eq!(result.charged().get(), 10);
+
+    let result = NativeResult::ok_with_refund(GasUnits::new(10), GasUnits::new(4), vec![]);
+    assert_eq!(result.cost.get(), 10);
+    assert_eq!(result.charged().get(), 6);
+    let summary = NativeResultSummary::new(NativeFunction::HashSha3_256, &result);
+    assert_eq!(summary.cost.get(), 6);
+
+    let result = NativeResult::err_with_refund(
+        GasUnits::new(10),
+        GasUnits::new(10),
+        VMStatus::new(StatusCode::ABORTED),
+    );
+    assert_eq!(result.charged().get(), 0);
+    assert_eq!(result.result.unwrap_err().major_status, StatusCode::ABORTED);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "refund of 2 exceeds the cost of 1")]
+fn refunds_exceeding_the_cost_are_rejected() {
+    NativeResult::err_with_refund(
+        GasUnits::new(1),
+        GasUnits::new(2),
+        VMStatus::new(StatusCode::ABORTED),
+    );
+}
+
+#[test]
+fn cost_breakdowns_are_recorded_on_request() {
+    assert!(NativeResult::ok(GasUnits::new(1), vec![])
+        .cost_breakdown
+        .is_none());
+
+    let (cost, breakdown) = native_gas_detailed(&unit_cost_table(), NativeCostIndex::SHA3_256, 12);
+    assert_eq!(cost.get(), 12);
+    assert_eq!(breakdown.units, 12);
+    let result = NativeResult::abort(cost, 0).with_cost_breakdown(breakdown);
+    assert_eq!(result.cost_breakdown, Some(breakdown));
+    assert_eq!(result.cost.get(), 12);
+}
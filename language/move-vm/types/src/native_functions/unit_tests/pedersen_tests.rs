@@ -0,0 +1,118 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{context::MockNativeContext, dispatch::NativeResult, pedersen},
+    values::Value,
+};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use libra_types::vm_error::StatusCode;
+use vm::gas_schedule::GasAlgebra;
+
+fn pedersen_verify(commitment: &[u8], value: u64, blinding: &[u8]) -> NativeResult {
+    pedersen::native_pedersen_verify(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(commitment.to_vec()),
+            Value::u64(value),
+            Value::vector_u8(blinding.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn verified(result: NativeResult) -> bool {
+    let mut values = result.result.expect("pedersen_verify should not fail");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+fn assert_sub_status(result: NativeResult, sub_status: u64) {
+    assert_eq!(result.cost.get(), 1);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+fn commit(value: u64, blinding: Scalar) -> Vec<u8> {
+    (RISTRETTO_BASEPOINT_POINT * Scalar::from(value) + *pedersen::BLINDING_GENERATOR * blinding)
+        .compress()
+        .to_bytes()
+        .to_vec()
+}
+
+#[test]
+fn blinding_generator_matches_bulletproofs() {
+    assert_eq!(
+        pedersen::BLINDING_GENERATOR.compress().to_bytes().to_vec(),
+        hex::decode("8c9240b456a9e6dc65c377a1048d745f94a08cdb7f44cbcd7b46f34048871134").unwrap()
+    );
+}
+
+#[test]
+fn pedersen_verify_known_commitment() {
+    // value = 42 and blinding = 7 under the bulletproofs generators.
+    let commitment =
+        hex::decode("a69ed12fb9c42f06a8c6ff8b535a781b613f46c7944d013c078eb0b5f3745c44").unwrap();
+    assert_eq!(commitment, commit(42, Scalar::from(7u64)));
+    let blinding = Scalar::from(7u64);
+    assert!(verified(pedersen_verify(
+        &commitment,
+        42,
+        blinding.as_bytes()
+    )));
+    assert!(!verified(pedersen_verify(
+        &commitment,
+        43,
+        blinding.as_bytes()
+    )));
+    assert!(!verified(pedersen_verify(
+        &commitment,
+        42,
+        Scalar::from(8u64).as_bytes()
+    )));
+}
+
+#[test]
+fn pedersen_verify_random_blinding() {
+    let blinding = Scalar::from(0xdead_beef_u64) * Scalar::from(0x0bad_f00d_u64);
+    let commitment = commit(u64::max_value(), blinding);
+    assert!(verified(pedersen_verify(
+        &commitment,
+        u64::max_value(),
+        blinding.as_bytes()
+    )));
+    // Swapping the roles of value and blinding does not verify.
+    assert!(!verified(pedersen_verify(
+        &(RISTRETTO_BASEPOINT_POINT * blinding
+            + *pedersen::BLINDING_GENERATOR * Scalar::from(u64::max_value()))
+        .compress()
+        .to_bytes(),
+        u64::max_value(),
+        blinding.as_bytes()
+    )));
+}
+
+#[test]
+fn pedersen_verify_malformed_inputs() {
+    let blinding = Scalar::from(7u64);
+    let commitment = commit(42, blinding);
+    assert_sub_status(
+        pedersen_verify(&[0xff; 32], 42, blinding.as_bytes()),
+        pedersen::INVALID_COMMITMENT_ENCODING,
+    );
+    assert_sub_status(
+        pedersen_verify(&commitment[..31], 42, blinding.as_bytes()),
+        pedersen::INVALID_COMMITMENT_ENCODING,
+    );
+    assert_sub_status(
+        pedersen_verify(&commitment, 42, &[0xff; 32]),
+        pedersen::INVALID_BLINDING_ENCODING,
+    );
+    assert_sub_status(
+        pedersen_verify(&commitment, 42, &[7]),
+        pedersen::INVALID_BLINDING_ENCODING,
+    );
+}
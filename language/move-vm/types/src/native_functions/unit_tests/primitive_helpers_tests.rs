@@ -0,0 +1,1544 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, single_bytes};
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        context::{MockNativeContext, NativeContext},
+        dispatch::NativeResult,
+        primitive_helpers,
+    },
+    values::Value,
+};
+use libra_crypto::{ed25519::Ed25519PrivateKey, traits::*, HashValue};
+use libra_types::{
+    account_address::{AccountAddress, ADDRESS_LENGTH},
+    byte_array::ByteArray,
+    event::{EventKey, EVENT_KEY_LENGTH},
+    language_storage::TypeTag,
+    vm_error::StatusCode,
+};
+use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use vm::{errors::VMResult, gas_schedule::GasAlgebra};
+
+fn bytearray_slice_result(bytes: &[u8], start: u64, end: u64) -> NativeResult {
+    primitive_helpers::native_bytearray_slice(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::byte_array(ByteArray::new(bytes.to_vec())),
+            Value::u64(start),
+            Value::u64(end),
+        ]),
+    )
+    .unwrap()
+}
+
+fn bytearray_slice(bytes: &[u8], start: u64, end: u64) -> Vec<u8> {
+    let result = bytearray_slice_result(bytes, start, end);
+    assert_eq!(result.cost.get(), end - start);
+    let mut values = result.result.expect("bytearray_slice should succeed");
+    assert_eq!(values.len(), 1);
+    values
+        .pop()
+        .unwrap()
+        .value_as::<ByteArray>()
+        .unwrap()
+        .as_bytes()
+        .to_vec()
+}
+
+#[test]
+fn bytearray_slice_ranges() {
+    let bytes = b"libra";
+    assert_eq!(bytearray_slice(bytes, 0, 0), b"".to_vec());
+    assert_eq!(bytearray_slice(bytes, 0, 5), bytes.to_vec());
+    assert_eq!(bytearray_slice(bytes, 5, 5), b"".to_vec());
+    assert_eq!(bytearray_slice(bytes, 1, 4), b"ibr".to_vec());
+    assert_eq!(bytearray_slice(b"", 0, 0), b"".to_vec());
+}
+
+#[test]
+fn bytearray_slice_invalid_ranges() {
+    for (start, end) in &[(3, 2), (0, 6), (6, 6), (std::u64::MAX, std::u64::MAX)] {
+        let result = bytearray_slice_result(b"libra", *start, *end);
+        assert_eq!(result.cost.get(), 1);
+        assert_aborted(result, primitive_helpers::INVALID_SLICE_RANGE_FAILURE);
+    }
+}
+
+fn bytearray_index_of(haystack: &[u8], needle: &[u8]) -> (bool, u64) {
+    let result = primitive_helpers::native_bytearray_index_of(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::byte_array(ByteArray::new(haystack.to_vec())),
+            Value::byte_array(ByteArray::new(needle.to_vec())),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), haystack.len() as u64);
+    let mut values = result.result.expect("bytearray_index_of should succeed");
+    assert_eq!(values.len(), 2);
+    let index = values.pop().unwrap().value_as::<u64>().unwrap();
+    let found = values.pop().unwrap().value_as::<bool>().unwrap();
+    (found, index)
+}
+
+#[test]
+fn bytearray_index_of_finds_first_occurrence() {
+    assert_eq!(bytearray_index_of(b"key=value;key", b"key"), (true, 0));
+    assert_eq!(bytearray_index_of(b"key=value;key", b";"), (true, 9));
+    assert_eq!(bytearray_index_of(b"key=value", b"value"), (true, 4));
+    assert_eq!(bytearray_index_of(b"key=value", b"values"), (false, 0));
+    assert_eq!(bytearray_index_of(b"key=value", b"x"), (false, 0));
+}
+
+#[test]
+fn bytearray_index_of_overlapping_patterns() {
+    assert_eq!(bytearray_index_of(b"aaab", b"aab"), (true, 1));
+    assert_eq!(bytearray_index_of(b"abababc", b"ababc"), (true, 2));
+    assert_eq!(bytearray_index_of(b"aaaa", b"aa"), (true, 0));
+}
+
+#[test]
+fn bytearray_index_of_edge_cases() {
+    assert_eq!(bytearray_index_of(b"abc", b""), (true, 0));
+    assert_eq!(bytearray_index_of(b"", b""), (true, 0));
+    assert_eq!(bytearray_index_of(b"", b"a"), (false, 0));
+    assert_eq!(bytearray_index_of(b"ab", b"abc"), (false, 0));
+    assert_eq!(bytearray_index_of(b"abc", b"abc"), (true, 0));
+}
+
+type Native = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+fn bytewise_result(native: Native, a: &[u8], b: &[u8]) -> NativeResult {
+    native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::byte_array(ByteArray::new(a.to_vec())),
+            Value::byte_array(ByteArray::new(b.to_vec())),
+        ]),
+    )
+    .unwrap()
+}
+
+fn bytewise(native: Native, a: &[u8], b: &[u8]) -> Vec<u8> {
+    let result = bytewise_result(native, a, b);
+    assert_eq!(result.cost.get(), a.len() as u64);
+    let mut values = result.result.expect("bytewise natives should succeed");
+    assert_eq!(values.len(), 1);
+    values
+        .pop()
+        .unwrap()
+        .value_as::<ByteArray>()
+        .unwrap()
+        .as_bytes()
+        .to_vec()
+}
+
+#[test]
+fn bytearray_bitwise_ops() {
+    let a = [0b1100_1010, 0xff, 0x00];
+    let b = [0b1010_0110, 0x0f, 0x00];
+    assert_eq!(
+        bytewise(primitive_helpers::native_bytearray_xor, &a, &b),
+        vec![0b0110_1100, 0xf0, 0x00]
+    );
+    assert_eq!(
+        bytewise(primitive_helpers::native_bytearray_and, &a, &b),
+        vec![0b1000_0010, 0x0f, 0x00]
+    );
+    assert_eq!(
+        bytewise(primitive_helpers::native_bytearray_or, &a, &b),
+        vec![0b1110_1110, 0xff, 0x00]
+    );
+}
+
+#[test]
+fn bytearray_bitwise_ops_empty_inputs() {
+    for native in &[
+        primitive_helpers::native_bytearray_xor as Native,
+        primitive_helpers::native_bytearray_and,
+        primitive_helpers::native_bytearray_or,
+    ] {
+        assert_eq!(bytewise(*native, &[], &[]), Vec::<u8>::new());
+    }
+}
+
+#[test]
+fn bytearray_bitwise_ops_length_mismatch() {
+    for native in &[
+        primitive_helpers::native_bytearray_xor as Native,
+        primitive_helpers::native_bytearray_and,
+        primitive_helpers::native_bytearray_or,
+    ] {
+        let result = bytewise_result(*native, &[1, 2, 3], &[1, 2]);
+        assert_eq!(result.cost.get(), 3);
+        assert_aborted(result, primitive_helpers::BYTEARRAY_LENGTH_MISMATCH_FAILURE);
+        let result = bytewise_result(*native, &[], &[1]);
+        assert_eq!(result.cost.get(), 1);
+        assert_aborted(result, primitive_helpers::BYTEARRAY_LENGTH_MISMATCH_FAILURE);
+    }
+}
+
+fn constant_time_eq_result(a: &[u8], b: &[u8]) -> NativeResult {
+    primitive_helpers::native_bytearray_constant_time_eq(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::byte_array(ByteArray::new(a.to_vec())),
+            Value::byte_array(ByteArray::new(b.to_vec())),
+        ]),
+    )
+    .unwrap()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut values = constant_time_eq_result(a, b)
+        .result
+        .expect("constant_time_eq should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+#[test]
+fn constant_time_eq_known_inputs() {
+    assert!(constant_time_eq(b"", b""));
+    assert!(constant_time_eq(b"tag", b"tag"));
+    assert!(!constant_time_eq(b"tag", b"taG"));
+    assert!(!constant_time_eq(b"tag", b"tags"));
+}
+
+#[test]
+fn constant_time_eq_gas_is_max_length() {
+    assert_eq!(
+        constant_time_eq_result(&[1u8; 32], &[1u8; 32]).cost.get(),
+        32
+    );
+    // A length mismatch costs as much as comparing two arrays of the longer length.
+    assert_eq!(
+        constant_time_eq_result(&[1u8; 4], &[1u8; 32]).cost.get(),
+        32
+    );
+    assert_eq!(constant_time_eq_result(&[1u8; 32], &[]).cost.get(), 32);
+}
+
+fn address_to_bytes(address: AccountAddress) -> Vec<u8> {
+    single_bytes(
+        primitive_helpers::native_address_to_bytes(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![Value::address(address)]),
+        )
+        .unwrap(),
+    )
+}
+
+fn address_from_bytes_result(bytes: &[u8]) -> NativeResult {
+    primitive_helpers::native_address_from_bytes(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap()
+}
+
+fn address_from_bytes(bytes: &[u8]) -> AccountAddress {
+    let mut values = address_from_bytes_result(bytes)
+        .result
+        .expect("address_from_bytes should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<AccountAddress>().unwrap()
+}
+
+#[test]
+fn address_from_bytes_round_trip() {
+    for address in &[
+        AccountAddress::new([0u8; ADDRESS_LENGTH]),
+        AccountAddress::new([0xffu8; ADDRESS_LENGTH]),
+    ] {
+        assert_eq!(address_from_bytes(&address_to_bytes(*address)), *address);
+    }
+}
+
+#[test]
+fn address_from_bytes_wrong_length() {
+    for len in &[0, ADDRESS_LENGTH - 1, ADDRESS_LENGTH + 1] {
+        let result = address_from_bytes_result(&vec![0u8; *len]);
+        assert_eq!(result.cost.get(), *len as u64);
+        assert_aborted(result, primitive_helpers::INVALID_ADDRESS_LENGTH_FAILURE);
+    }
+}
+
+fn address_from_auth_key_result(auth_key: &[u8]) -> NativeResult {
+    primitive_helpers::native_address_from_auth_key(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(auth_key.to_vec())]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn address_from_auth_key_matches_public_key_derivation() {
+    let mut rng = StdRng::from_seed([5u8; 32]);
+    for _ in 0..16 {
+        let public_key = Ed25519PrivateKey::generate_for_testing(&mut rng).public_key();
+        let auth_key = HashValue::from_sha3_256(&public_key.to_bytes()).to_vec();
+        let result = address_from_auth_key_result(&auth_key);
+        assert_eq!(result.cost.get(), HashValue::LENGTH as u64);
+        let mut values = result.result.expect("address_from_auth_key should succeed");
+        assert_eq!(values.len(), 1);
+        assert_eq!(
+            values.pop().unwrap().value_as::<AccountAddress>().unwrap(),
+            AccountAddress::from_public_key(&public_key)
+        );
+    }
+}
+
+#[test]
+fn address_from_auth_key_wrong_length() {
+    for len in &[0, HashValue::LENGTH - 1, HashValue::LENGTH + 1] {
+        let result = address_from_auth_key_result(&vec![0u8; *len]);
+        assert_eq!(result.cost.get(), *len as u64);
+        assert_aborted(result, primitive_helpers::INVALID_AUTH_KEY_LENGTH_FAILURE);
+    }
+}
+
+fn u64_to_bytes(value: u64) -> Vec<u8> {
+    single_bytes(
+        primitive_helpers::native_u64_to_bytes(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![Value::u64(value)]),
+        )
+        .unwrap(),
+    )
+}
+
+fn u64_to_bytes_be(value: u64) -> Vec<u8> {
+    single_bytes(
+        primitive_helpers::native_u64_to_bytes_be(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![Value::u64(value)]),
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn u64_to_bytes_be_reverses_byte_order() {
+    assert_eq!(u64_to_bytes_be(1), vec![0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(u64_to_bytes(1), vec![1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        u64_to_bytes_be(0x0102_0304_0506_0708),
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    );
+}
+
+fn bytes_to_u64_result(bytes: &[u8]) -> NativeResult {
+    primitive_helpers::native_bytes_to_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap()
+}
+
+fn bytes_to_u64(bytes: &[u8]) -> u64 {
+    let mut values = bytes_to_u64_result(bytes)
+        .result
+        .expect("bytes_to_u64 should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+fn u64_from_decimal_string(bytes: &[u8]) -> (bool, u64) {
+    let result = primitive_helpers::native_u64_from_decimal_string(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), bytes.len().min(20) as u64);
+    let mut values = result
+        .result
+        .expect("u64_from_decimal_string should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u64>().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (valid, value)
+}
+
+fn u64_to_decimal_string(value: u64) -> Vec<u8> {
+    let result = primitive_helpers::native_u64_to_decimal_string(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(value)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    single_bytes(result)
+}
+
+fn assert_aborted(result: NativeResult, sub_status: u64) {
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+#[test]
+fn bytes_to_u64_little_endian() {
+    assert_eq!(bytes_to_u64(&[1, 0, 0, 0, 0, 0, 0, 0]), 1);
+    assert_eq!(bytes_to_u64(&[0xff; 8]), std::u64::MAX);
+}
+
+#[test]
+fn bytes_to_u64_wrong_length() {
+    for len in &[0, 7, 9, 16] {
+        let result = bytes_to_u64_result(&vec![0u8; *len]);
+        assert_eq!(result.cost.get(), *len as u64);
+        assert_aborted(result, primitive_helpers::INVALID_BYTES_LENGTH_FAILURE);
+    }
+}
+
+fn u128_to_bytes(value: u128) -> Vec<u8> {
+    single_bytes(
+        primitive_helpers::native_u128_to_bytes(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![Value::u128(value)]),
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn u128_to_bytes_little_endian() {
+    assert_eq!(u128_to_bytes(0), vec![0u8; 16]);
+    let mut expected = vec![0u8; 16];
+    expected[0] = 1;
+    expected[15] = 0x80;
+    assert_eq!(u128_to_bytes((1u128 << 127) | 1), expected);
+}
+
+fn u128_from_bytes_result(bytes: &[u8]) -> NativeResult {
+    primitive_helpers::native_u128_from_bytes(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap()
+}
+
+fn u128_from_bytes(bytes: &[u8]) -> u128 {
+    let mut values = u128_from_bytes_result(bytes)
+        .result
+        .expect("u128_from_bytes should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u128>().unwrap()
+}
+
+#[test]
+fn u128_from_bytes_round_trip_bounds() {
+    assert_eq!(u128_from_bytes(&u128_to_bytes(0)), 0);
+    assert_eq!(
+        u128_from_bytes(&u128_to_bytes(std::u128::MAX)),
+        std::u128::MAX
+    );
+    assert_eq!(u128_from_bytes(&[0xff; 16]), std::u128::MAX);
+}
+
+#[test]
+fn u128_from_bytes_wrong_length() {
+    for len in &[0, 8, 15, 17] {
+        let result = u128_from_bytes_result(&vec![0u8; *len]);
+        assert_eq!(result.cost.get(), *len as u64);
+        assert_aborted(result, primitive_helpers::INVALID_BYTES_LENGTH_FAILURE);
+    }
+}
+
+fn base58_encode_result(bytes: &[u8]) -> NativeResult {
+    primitive_helpers::native_base58_encode(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap()
+}
+
+fn base58_encode(bytes: &[u8]) -> Vec<u8> {
+    single_bytes(base58_encode_result(bytes))
+}
+
+fn base58_decode(encoded: &[u8]) -> (bool, Vec<u8>) {
+    let mut values = primitive_helpers::native_base58_decode(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(encoded.to_vec())]),
+    )
+    .unwrap()
+    .result
+    .expect("base58_decode should succeed");
+    assert_eq!(values.len(), 2);
+    let decoded = values.pop().unwrap().value_as::<Vec<u8>>().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (valid, decoded)
+}
+
+#[test]
+fn u64_from_decimal_string_bounds() {
+    assert_eq!(u64_from_decimal_string(b"0"), (true, 0));
+    assert_eq!(u64_from_decimal_string(b"42"), (true, 42));
+    assert_eq!(
+        u64_from_decimal_string(b"18446744073709551615"),
+        (true, std::u64::MAX)
+    );
+    assert_eq!(u64_from_decimal_string(b"18446744073709551616"), (false, 0));
+    assert_eq!(u64_from_decimal_string(b"99999999999999999999"), (false, 0));
+    assert_eq!(
+        u64_from_decimal_string(b"184467440737095516150"),
+        (false, 0)
+    );
+}
+
+#[test]
+fn u64_from_decimal_string_leading_zeros() {
+    assert_eq!(u64_from_decimal_string(b"007"), (true, 7));
+    assert_eq!(u64_from_decimal_string(b"00000000000000000000"), (true, 0));
+    assert_eq!(u64_from_decimal_string(b"00000000000000000001"), (true, 1));
+    // Past the length cap even leading zeros are rejected.
+    assert_eq!(
+        u64_from_decimal_string(b"000000000000000000001"),
+        (false, 0)
+    );
+}
+
+#[test]
+fn u64_from_decimal_string_invalid() {
+    for bytes in &[
+        &b""[..],
+        b"-1",
+        b"+1",
+        b" 1",
+        b"1 ",
+        b"1_000",
+        b"0x10",
+        b"1.5",
+    ] {
+        assert_eq!(u64_from_decimal_string(bytes), (false, 0));
+    }
+    // Gas stops growing once input exceeds the length cap.
+    u64_from_decimal_string(&[b'1'; 1000]);
+}
+
+#[test]
+fn u64_to_decimal_string_bounds() {
+    assert_eq!(u64_to_decimal_string(0), b"0".to_vec());
+    assert_eq!(u64_to_decimal_string(7), b"7".to_vec());
+    assert_eq!(u64_to_decimal_string(1000), b"1000".to_vec());
+    assert_eq!(
+        u64_to_decimal_string(std::u64::MAX),
+        b"18446744073709551615".to_vec()
+    );
+    for value in &[0, 1, 10, std::u64::MAX - 1, std::u64::MAX] {
+        assert_eq!(
+            u64_from_decimal_string(&u64_to_decimal_string(*value)),
+            (true, *value)
+        );
+    }
+}
+
+#[test]
+fn base58_known_vectors() {
+    assert_eq!(base58_encode(b""), b"".to_vec());
+    assert_eq!(base58_encode(b"hello world"), b"StV1DL6CwTryKyV".to_vec());
+    assert_eq!(
+        base58_decode(b"StV1DL6CwTryKyV"),
+        (true, b"hello world".to_vec())
+    );
+}
+
+#[test]
+fn base58_leading_zeros() {
+    // Every leading zero byte is encoded as a leading '1'.
+    assert_eq!(base58_encode(&[0, 0, 0]), b"111".to_vec());
+    assert_eq!(base58_encode(&[0, 0, 1]), b"112".to_vec());
+    assert_eq!(base58_decode(b"112"), (true, vec![0, 0, 1]));
+    assert_eq!(base58_decode(b"1"), (true, vec![0]));
+}
+
+#[test]
+fn base58_decode_invalid_characters() {
+    // '0', 'O', 'I' and 'l' are not in the Base58 alphabet.
+    for encoded in &[&b"0"[..], b"StV1DL6CwTryKyO", b"Il", b"+"] {
+        assert_eq!(base58_decode(encoded), (false, vec![]));
+    }
+}
+
+#[test]
+fn base58_encode_gas_is_quadratic() {
+    assert_eq!(base58_encode_result(&[1u8; 10]).cost.get(), 100);
+    assert_eq!(base58_encode_result(&[1u8; 20]).cost.get(), 400);
+}
+
+fn bech32_call(native: Native, hrp: &[u8], data: &[u8]) -> (u64, bool, Vec<u8>) {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(hrp.to_vec()),
+            Value::vector_u8(data.to_vec()),
+        ]),
+    )
+    .unwrap();
+    let cost = result.cost.get();
+    let mut values = result.result.expect("bech32 natives should succeed");
+    assert_eq!(values.len(), 2);
+    let bytes = values.pop().unwrap().value_as::<Vec<u8>>().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (cost, valid, bytes)
+}
+
+fn bech32_encode(hrp: &[u8], payload: &[u8]) -> (bool, Vec<u8>) {
+    let (_, valid, encoded) = bech32_call(primitive_helpers::native_bech32_encode, hrp, payload);
+    (valid, encoded)
+}
+
+fn bech32_decode(hrp: &[u8], encoded: &[u8]) -> (bool, Vec<u8>) {
+    let (cost, valid, payload) = bech32_call(primitive_helpers::native_bech32_decode, hrp, encoded);
+    assert_eq!(cost, encoded.len() as u64);
+    (valid, payload)
+}
+
+/// Splits a bech32 string at its last separator, returning the human-readable part.
+fn bech32_hrp(encoded: &str) -> &[u8] {
+    encoded
+        .rfind('1')
+        .map_or(b"", |sep| encoded[..sep].as_bytes())
+}
+
+const ABCDEF_PAYLOAD: &str = "00443214c74254b635cf84653a56d7c675be77df";
+
+#[test]
+fn bech32_bip173_valid_vectors() {
+    for (encoded, payload) in &[
+        ("A12UEL5L", vec![]),
+        ("a12uel5l", vec![]),
+        (
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            vec![],
+        ),
+        (
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            hex::decode(ABCDEF_PAYLOAD).unwrap(),
+        ),
+        (
+            "11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqc8247j",
+            vec![0u8; 51],
+        ),
+        ("?1ezyfcl", vec![]),
+    ] {
+        assert_eq!(
+            bech32_decode(bech32_hrp(encoded), encoded.as_bytes()),
+            (true, payload.clone()),
+            "{}",
+            encoded
+        );
+    }
+}
+
+#[test]
+fn bech32_bip173_invalid_vectors() {
+    for encoded in &[
+        // HRP character out of range.
+        " 1nwldj5",
+        "\x7f1axkwrx",
+        // Overall max length exceeded.
+        "an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx",
+        // No separator character.
+        "pzry9x0s0muk",
+        // Empty HRP.
+        "1pzry9x0s0muk",
+        "10a06t8",
+        "1qzzfhee",
+        // Invalid data character.
+        "x1b4n0q5v",
+        // Too short checksum.
+        "li1dgmt3",
+        // Checksum calculated with uppercase form of HRP.
+        "A1G7SGD8",
+        // Mixed case.
+        "a12UEL5L",
+    ] {
+        assert_eq!(
+            bech32_decode(bech32_hrp(encoded), encoded.as_bytes()),
+            (false, vec![]),
+            "{}",
+            encoded
+        );
+    }
+}
+
+#[test]
+fn bech32_decode_checks_hrp() {
+    assert_eq!(bech32_decode(b"a", b"a12uel5l"), (true, vec![]));
+    assert_eq!(bech32_decode(b"A", b"a12uel5l"), (true, vec![]));
+    assert_eq!(bech32_decode(b"b", b"a12uel5l"), (false, vec![]));
+    assert_eq!(bech32_decode(b"", b"a12uel5l"), (false, vec![]));
+}
+
+#[test]
+fn bech32_encode_bip173_vectors() {
+    assert_eq!(bech32_encode(b"a", &[]), (true, b"a12uel5l".to_vec()));
+    assert_eq!(bech32_encode(b"A", &[]), (true, b"a12uel5l".to_vec()));
+    assert_eq!(
+        bech32_encode(b"abcdef", &hex::decode(ABCDEF_PAYLOAD).unwrap()),
+        (
+            true,
+            b"abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw".to_vec()
+        )
+    );
+    let (cost, valid, encoded) =
+        bech32_call(primitive_helpers::native_bech32_encode, b"1", &[0u8; 51]);
+    assert!(valid);
+    assert_eq!(encoded.len(), primitive_helpers::BECH32_MAX_LENGTH);
+    assert_eq!(cost, encoded.len() as u64);
+}
+
+#[test]
+fn bech32_encode_invalid_inputs() {
+    assert_eq!(bech32_encode(b"", &[]), (false, vec![]));
+    assert_eq!(bech32_encode(b"a b", &[]), (false, vec![]));
+    assert_eq!(bech32_encode(b"aB", &[]), (false, vec![]));
+    assert_eq!(bech32_encode(&[0xff], &[]), (false, vec![]));
+    // One byte past what fits in BECH32_MAX_LENGTH characters.
+    assert_eq!(bech32_encode(b"1", &[0u8; 52]), (false, vec![]));
+}
+
+fn base64_encode(bytes: &[u8]) -> Vec<u8> {
+    single_bytes(
+        primitive_helpers::native_base64_encode(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![Value::vector_u8(bytes.to_vec())]),
+        )
+        .unwrap(),
+    )
+}
+
+fn base64_decode(encoded: &[u8]) -> (bool, Vec<u8>) {
+    let result = primitive_helpers::native_base64_decode(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(encoded.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), encoded.len() as u64);
+    let mut values = result.result.expect("base64_decode should succeed");
+    assert_eq!(values.len(), 2);
+    let decoded = values.pop().unwrap().value_as::<Vec<u8>>().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (valid, decoded)
+}
+
+#[test]
+fn base64_rfc4648_vectors() {
+    for (decoded, encoded) in &[
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ] {
+        assert_eq!(base64_encode(decoded.as_bytes()), encoded.as_bytes());
+        assert_eq!(
+            base64_decode(encoded.as_bytes()),
+            (true, decoded.as_bytes().to_vec())
+        );
+    }
+    assert_eq!(base64_encode(&[0xfb, 0xff]), b"+/8=".to_vec());
+}
+
+#[test]
+fn base64_decode_malformed_padding() {
+    for encoded in &[
+        // Missing padding.
+        &b"Zg"[..],
+        b"Zm8",
+        b"Zm9vYg",
+        // Too much padding.
+        b"Zm8==",
+        b"Zg===",
+        // Padding in the middle.
+        b"Zg==Zm9v",
+        b"Z===",
+        // Non-zero bits discarded by the last character.
+        b"Zh==",
+        // Characters outside the standard alphabet.
+        b"-_8=",
+        b"Zm9v Zm9v",
+    ] {
+        assert_eq!(base64_decode(encoded), (false, vec![]));
+    }
+}
+
+fn is_valid_utf8(bytes: &[u8]) -> bool {
+    let result = primitive_helpers::native_string_is_valid_utf8(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), bytes.len() as u64);
+    let mut values = result.result.expect("is_valid_utf8 should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+#[test]
+fn utf8_valid_sequences() {
+    for bytes in &[
+        &b""[..],
+        b"libra",
+        // U+00E9, U+20AC and U+1F600: two, three and four byte sequences.
+        "\u{e9}\u{20ac}\u{1f600}".as_bytes(),
+        // Boundaries of each sequence length.
+        &[0x7f],
+        &[0xc2, 0x80],
+        &[0xdf, 0xbf],
+        &[0xe0, 0xa0, 0x80],
+        &[0xef, 0xbf, 0xbf],
+        &[0xf0, 0x90, 0x80, 0x80],
+        &[0xf4, 0x8f, 0xbf, 0xbf],
+        // U+FFFD and U+D7FF, U+E000 on either side of the surrogate range.
+        &[0xef, 0xbf, 0xbd],
+        &[0xed, 0x9f, 0xbf],
+        &[0xee, 0x80, 0x80],
+        // An embedded NUL is valid UTF-8.
+        b"a\0b",
+    ] {
+        assert!(is_valid_utf8(bytes), "{:?} should be valid", bytes);
+    }
+}
+
+#[test]
+fn utf8_overlong_encodings() {
+    for bytes in &[
+        // '/' encoded in two, three and four bytes.
+        &[0xc0, 0xaf][..],
+        &[0xe0, 0x80, 0xaf],
+        &[0xf0, 0x80, 0x80, 0xaf],
+        // Overlong NUL, as used by modified UTF-8.
+        &[0xc0, 0x80],
+        // Largest overlong sequence of each length.
+        &[0xc1, 0xbf],
+        &[0xe0, 0x9f, 0xbf],
+        &[0xf0, 0x8f, 0xbf, 0xbf],
+    ] {
+        assert!(!is_valid_utf8(bytes), "{:?} should be invalid", bytes);
+    }
+}
+
+#[test]
+fn utf8_cesu8_surrogates() {
+    for bytes in &[
+        // Lone high and low surrogates U+D800 and U+DFFF.
+        &[0xed, 0xa0, 0x80][..],
+        &[0xed, 0xbf, 0xbf],
+        // U+1F600 encoded as a CESU-8 surrogate pair.
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80],
+    ] {
+        assert!(!is_valid_utf8(bytes), "{:?} should be invalid", bytes);
+    }
+}
+
+#[test]
+fn utf8_malformed_sequences() {
+    for bytes in &[
+        // Unexpected continuation bytes.
+        &[0x80][..],
+        &[0x61, 0xbf],
+        // Truncated sequences.
+        &[0xc3],
+        &[0xe2, 0x82],
+        &[0xf0, 0x9f, 0x98],
+        // Code points beyond U+10FFFF and bytes that never appear in UTF-8.
+        &[0xf4, 0x90, 0x80, 0x80],
+        &[0xf5, 0x80, 0x80, 0x80],
+        &[0xfe],
+        &[0xff],
+    ] {
+        assert!(!is_valid_utf8(bytes), "{:?} should be invalid", bytes);
+    }
+}
+
+fn is_ascii(bytes: &[u8]) -> bool {
+    let result = primitive_helpers::native_string_is_ascii(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), bytes.len() as u64);
+    let mut values = result.result.expect("is_ascii should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+fn to_uppercase_ascii_result(bytes: &[u8]) -> NativeResult {
+    let result = primitive_helpers::native_string_to_uppercase_ascii(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(bytes.to_vec())]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), bytes.len() as u64);
+    result
+}
+
+fn to_uppercase_ascii(bytes: &[u8]) -> Vec<u8> {
+    single_bytes(to_uppercase_ascii_result(bytes))
+}
+
+#[test]
+fn ascii_check() {
+    assert!(is_ascii(b""));
+    assert!(is_ascii(b"LBR"));
+    assert!(is_ascii(&[0x00, 0x7f]));
+    assert!(!is_ascii(&[0x80]));
+    assert!(!is_ascii(b"caf\xc3\xa9"));
+    assert!(!is_ascii(&[b'A', 0xff]));
+}
+
+#[test]
+fn ascii_to_uppercase() {
+    assert_eq!(to_uppercase_ascii(b""), Vec::<u8>::new());
+    assert_eq!(to_uppercase_ascii(b"lbr"), b"LBR".to_vec());
+    assert_eq!(to_uppercase_ascii(b"LbR_coin-1"), b"LBR_COIN-1".to_vec());
+    // Only a-z change; the neighbouring '`' and '{' are left alone.
+    assert_eq!(to_uppercase_ascii(b"`az{"), b"`AZ{".to_vec());
+    let upper = to_uppercase_ascii(b"MiXeD cAsE");
+    assert_eq!(to_uppercase_ascii(&upper), upper);
+}
+
+#[test]
+fn ascii_to_uppercase_rejects_non_ascii() {
+    for bytes in &[&[0x80][..], b"caf\xc3\xa9", &[b'a', 0xff]] {
+        assert_aborted(
+            to_uppercase_ascii_result(bytes),
+            primitive_helpers::NON_ASCII_FAILURE,
+        );
+    }
+}
+
+fn checked_u64(native: Native, a: u64, b: u64) -> (bool, u64) {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(a), Value::u64(b)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("checked arithmetic should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u64>().unwrap();
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    (success, value)
+}
+
+fn checked_u128(native: Native, a: u128, b: u128) -> (bool, u128) {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u128(a), Value::u128(b)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("checked arithmetic should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u128>().unwrap();
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    (success, value)
+}
+
+#[test]
+fn u64_checked_add_bounds() {
+    let add = primitive_helpers::native_u64_checked_add;
+    assert_eq!(checked_u64(add, 1, 2), (true, 3));
+    assert_eq!(
+        checked_u64(add, std::u64::MAX - 1, 1),
+        (true, std::u64::MAX)
+    );
+    assert_eq!(checked_u64(add, std::u64::MAX, 0), (true, std::u64::MAX));
+    assert_eq!(checked_u64(add, std::u64::MAX, 1), (false, 0));
+    assert_eq!(checked_u64(add, std::u64::MAX, std::u64::MAX), (false, 0));
+}
+
+#[test]
+fn u64_checked_mul_bounds() {
+    let mul = primitive_helpers::native_u64_checked_mul;
+    let half = std::u64::MAX / 2;
+    assert_eq!(checked_u64(mul, 0, std::u64::MAX), (true, 0));
+    assert_eq!(checked_u64(mul, std::u64::MAX, 1), (true, std::u64::MAX));
+    assert_eq!(checked_u64(mul, half, 2), (true, std::u64::MAX - 1));
+    assert_eq!(checked_u64(mul, half + 1, 2), (false, 0));
+    assert_eq!(checked_u64(mul, std::u64::MAX, 2), (false, 0));
+}
+
+#[test]
+fn u128_checked_add_bounds() {
+    let add = primitive_helpers::native_u128_checked_add;
+    assert_eq!(checked_u128(add, 1, 2), (true, 3));
+    assert_eq!(
+        checked_u128(add, std::u128::MAX - 1, 1),
+        (true, std::u128::MAX)
+    );
+    assert_eq!(checked_u128(add, std::u128::MAX, 1), (false, 0));
+    assert_eq!(
+        checked_u128(add, std::u128::MAX, std::u128::MAX),
+        (false, 0)
+    );
+}
+
+#[test]
+fn u128_checked_mul_bounds() {
+    let mul = primitive_helpers::native_u128_checked_mul;
+    let half = std::u128::MAX / 2;
+    assert_eq!(checked_u128(mul, 0, std::u128::MAX), (true, 0));
+    assert_eq!(checked_u128(mul, std::u128::MAX, 1), (true, std::u128::MAX));
+    assert_eq!(checked_u128(mul, half, 2), (true, std::u128::MAX - 1));
+    assert_eq!(checked_u128(mul, half + 1, 2), (false, 0));
+    assert_eq!(checked_u128(mul, std::u128::MAX, 2), (false, 0));
+}
+
+fn pow_u64(base: u64, exp: u64) -> (bool, u64) {
+    let result = primitive_helpers::native_pow_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(base), Value::u64(exp)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1 + 64 - u64::from(exp.leading_zeros()));
+    let mut values = result.result.expect("pow_u64 should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u64>().unwrap();
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    (success, value)
+}
+
+#[test]
+fn pow_u64_edge_cases() {
+    assert_eq!(pow_u64(0, 0), (true, 1));
+    assert_eq!(pow_u64(7, 0), (true, 1));
+    assert_eq!(pow_u64(0, 5), (true, 0));
+    assert_eq!(pow_u64(1, std::u64::MAX), (true, 1));
+    assert_eq!(pow_u64(0, std::u64::MAX), (true, 0));
+    assert_eq!(pow_u64(3, 4), (true, 81));
+    assert_eq!(pow_u64(10, 19), (true, 10_000_000_000_000_000_000));
+}
+
+#[test]
+fn pow_u64_overflow() {
+    assert_eq!(pow_u64(2, 63), (true, 1 << 63));
+    assert_eq!(pow_u64(2, 64), (false, 0));
+    assert_eq!(pow_u64(10, 20), (false, 0));
+    assert_eq!(pow_u64(2, std::u64::MAX), (false, 0));
+    assert_eq!(pow_u64(std::u64::MAX, 2), (false, 0));
+    // The final square is never computed, so a base whose square overflows still works.
+    assert_eq!(pow_u64(1 << 32, 1), (true, 1 << 32));
+}
+
+fn sqrt_u128(value: u128) -> u128 {
+    let result = primitive_helpers::native_sqrt_u128(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u128(value)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("sqrt_u128 should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u128>().unwrap()
+}
+
+#[test]
+fn sqrt_u128_small_values() {
+    assert_eq!(sqrt_u128(0), 0);
+    assert_eq!(sqrt_u128(1), 1);
+    assert_eq!(sqrt_u128(2), 1);
+    assert_eq!(sqrt_u128(3), 1);
+    assert_eq!(sqrt_u128(4), 2);
+    assert_eq!(sqrt_u128(15), 3);
+    assert_eq!(sqrt_u128(16), 4);
+    assert_eq!(sqrt_u128(17), 4);
+}
+
+#[test]
+fn sqrt_u128_around_perfect_squares() {
+    for &root in &[10u128, 1 << 20, 3_037_000_499, 1 << 32, (1 << 64) - 1] {
+        let square = root * root;
+        assert_eq!(sqrt_u128(square - 1), root - 1);
+        assert_eq!(sqrt_u128(square), root);
+        assert_eq!(sqrt_u128(square + 1), root);
+    }
+}
+
+#[test]
+fn sqrt_u128_max() {
+    let root = std::u64::MAX as u128;
+    assert_eq!(sqrt_u128(std::u128::MAX), root);
+    // (2^64 - 1)^2 is the largest perfect square below u128::MAX.
+    assert_eq!(sqrt_u128(root * root - 1), root - 1);
+}
+
+fn bits_result(native: Native, value: u64) -> NativeResult {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(value)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    result
+}
+
+fn bits(native: Native, value: u64) -> u64 {
+    let mut values = bits_result(native, value)
+        .result
+        .expect("bit native should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+#[test]
+fn log2_floor_u64_values() {
+    let log2 = primitive_helpers::native_log2_floor_u64;
+    for i in 0..64 {
+        assert_eq!(bits(log2, 1 << i), i);
+    }
+    assert_eq!(bits(log2, 3), 1);
+    assert_eq!(bits(log2, (1 << 10) + 1), 10);
+    assert_eq!(bits(log2, std::u64::MAX), 63);
+    assert_aborted(
+        bits_result(log2, 0),
+        primitive_helpers::LOG2_OF_ZERO_FAILURE,
+    );
+}
+
+#[test]
+fn leading_zeros_u64_values() {
+    let leading_zeros = primitive_helpers::native_leading_zeros_u64;
+    for i in 0..64 {
+        assert_eq!(bits(leading_zeros, 1 << i), 63 - i);
+    }
+    assert_eq!(bits(leading_zeros, 0), 64);
+    assert_eq!(bits(leading_zeros, std::u64::MAX), 0);
+}
+
+#[test]
+fn popcount_u64_values() {
+    let popcount = primitive_helpers::native_popcount_u64;
+    for i in 0..64 {
+        assert_eq!(bits(popcount, 1 << i), 1);
+    }
+    assert_eq!(bits(popcount, 0), 0);
+    assert_eq!(bits(popcount, 0b1011), 3);
+    assert_eq!(bits(popcount, std::u64::MAX), 64);
+}
+
+fn modular_result(native: Native, a: u128, b: u128, modulus: u128) -> NativeResult {
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u128(a), Value::u128(b), Value::u128(modulus)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    result
+}
+
+fn modular(native: Native, a: u128, b: u128, modulus: u128) -> u128 {
+    let mut values = modular_result(native, a, b, modulus)
+        .result
+        .expect("modular native should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u128>().unwrap()
+}
+
+fn add_mod_u128(a: u128, b: u128, modulus: u128) -> u128 {
+    modular(primitive_helpers::native_add_mod_u128, a, b, modulus)
+}
+
+fn mul_mod_u128(a: u128, b: u128, modulus: u128) -> u128 {
+    modular(primitive_helpers::native_mul_mod_u128, a, b, modulus)
+}
+
+/// 2^127 - 1, a prime
+const M127: u128 = (1 << 127) - 1;
+
+#[test]
+fn add_mod_u128_values() {
+    const MAX: u128 = std::u128::MAX;
+    assert_eq!(add_mod_u128(2, 3, 4), 1);
+    assert_eq!(add_mod_u128(9, 9, 1), 0);
+    assert_eq!(add_mod_u128(MAX, MAX, MAX), 0);
+    assert_eq!(add_mod_u128(MAX, 1, MAX), 1);
+    // The sum of these does not fit in a u128
+    assert_eq!(add_mod_u128(MAX - 1, MAX - 1, MAX), MAX - 2);
+    assert_eq!(add_mod_u128(MAX, MAX, MAX - 1), 2);
+    assert_eq!(add_mod_u128(1 << 127, 1 << 127, MAX), 1);
+    assert_eq!(add_mod_u128(M127 - 1, M127 - 1, M127), M127 - 2);
+}
+
+#[test]
+fn mul_mod_u128_values() {
+    const MAX: u128 = std::u128::MAX;
+    assert_eq!(mul_mod_u128(3, 5, 7), 1);
+    assert_eq!(mul_mod_u128(MAX, MAX, 1), 0);
+    assert_eq!(mul_mod_u128(0, MAX, MAX), 0);
+    // The products of these do not fit in a u128
+    assert_eq!(mul_mod_u128(MAX, MAX, MAX), 0);
+    assert_eq!(mul_mod_u128(MAX - 1, MAX - 1, MAX), 1);
+    assert_eq!(mul_mod_u128(MAX, MAX, MAX - 1), 1);
+    assert_eq!(mul_mod_u128(1 << 64, 1 << 64, MAX), 1);
+    assert_eq!(mul_mod_u128(1 << 127, 1 << 127, MAX), 1 << 126);
+    assert_eq!(mul_mod_u128(M127 - 1, M127 - 1, M127), 1);
+    assert_eq!(mul_mod_u128(MAX, MAX, M127), 1);
+    // Fermat: a^(p - 1) = 1 mod p, computed by square-and-multiply on top of mul_mod
+    let (mut base, mut exp, mut acc) = (MAX - 12345, M127 - 1, 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul_mod_u128(acc, base, M127);
+        }
+        base = mul_mod_u128(base, base, M127);
+        exp >>= 1;
+    }
+    assert_eq!(acc, 1);
+}
+
+#[test]
+fn modular_u128_zero_modulus() {
+    for &native in &[
+        primitive_helpers::native_add_mod_u128 as Native,
+        primitive_helpers::native_mul_mod_u128,
+    ] {
+        assert_aborted(
+            modular_result(native, 1, 2, 0),
+            primitive_helpers::MODULUS_OF_ZERO_FAILURE,
+        );
+    }
+}
+
+fn div_mod_u128_result(dividend: u128, divisor: u128) -> NativeResult {
+    let result = primitive_helpers::native_div_mod_u128(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u128(dividend), Value::u128(divisor)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    result
+}
+
+fn div_mod_u128(dividend: u128, divisor: u128) -> (u128, u128) {
+    let mut values = div_mod_u128_result(dividend, divisor)
+        .result
+        .expect("div_mod_u128 should succeed");
+    assert_eq!(values.len(), 2);
+    let remainder = values.pop().unwrap().value_as::<u128>().unwrap();
+    let quotient = values.pop().unwrap().value_as::<u128>().unwrap();
+    (quotient, remainder)
+}
+
+#[test]
+fn div_mod_u128_values() {
+    const MAX: u128 = std::u128::MAX;
+    assert_eq!(div_mod_u128(7, 2), (3, 1));
+    assert_eq!(div_mod_u128(6, 3), (2, 0));
+    assert_eq!(div_mod_u128(0, 5), (0, 0));
+    assert_eq!(div_mod_u128(4, 5), (0, 4));
+    assert_eq!(div_mod_u128(MAX, 1), (MAX, 0));
+    assert_eq!(div_mod_u128(MAX, MAX), (1, 0));
+    assert_eq!(div_mod_u128(MAX - 1, MAX), (0, MAX - 1));
+    assert_eq!(div_mod_u128(MAX, 1 << 64), ((1 << 64) - 1, (1 << 64) - 1));
+}
+
+#[test]
+fn div_mod_u128_by_zero() {
+    for &dividend in &[0, 1, std::u128::MAX] {
+        assert_aborted(
+            div_mod_u128_result(dividend, 0),
+            primitive_helpers::DIVISION_BY_ZERO_FAILURE,
+        );
+    }
+}
+
+fn u128_to_u64(value: u128) -> (bool, u64) {
+    let result = primitive_helpers::native_u128_to_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u128(value)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("u128_to_u64 should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u64>().unwrap();
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    (success, value)
+}
+
+#[test]
+fn u128_to_u64_boundaries() {
+    const MAX: u128 = std::u64::MAX as u128;
+    assert_eq!(u128_to_u64(0), (true, 0));
+    assert_eq!(u128_to_u64(42), (true, 42));
+    assert_eq!(u128_to_u64(MAX - 1), (true, std::u64::MAX - 1));
+    assert_eq!(u128_to_u64(MAX), (true, std::u64::MAX));
+    assert_eq!(u128_to_u64(MAX + 1), (false, 0));
+    assert_eq!(u128_to_u64(std::u128::MAX), (false, 0));
+}
+
+fn mul_div_u64(a: u64, b: u64, c: u64) -> (bool, u64) {
+    let result = primitive_helpers::native_mul_div_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(a), Value::u64(b), Value::u64(c)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("mul_div_u64 should succeed");
+    assert_eq!(values.len(), 2);
+    let value = values.pop().unwrap().value_as::<u64>().unwrap();
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    (success, value)
+}
+
+#[test]
+fn mul_div_u64_rounds_down() {
+    assert_eq!(mul_div_u64(7, 1, 2), (true, 3));
+    assert_eq!(mul_div_u64(2, 3, 7), (true, 0));
+    assert_eq!(mul_div_u64(10, 10, 3), (true, 33));
+    assert_eq!(mul_div_u64(0, std::u64::MAX, 1), (true, 0));
+}
+
+#[test]
+fn mul_div_u64_fee_calculation() {
+    const BPS: u64 = 10_000;
+    // 0.3% of a trillion
+    assert_eq!(
+        mul_div_u64(1_000_000_000_000, 30, BPS),
+        (true, 3_000_000_000)
+    );
+    // amount * fee_bps overflows a u64 here, but the fee itself fits
+    let amount = std::u64::MAX / 3;
+    assert_eq!(
+        mul_div_u64(amount, 25, BPS),
+        (true, (u128::from(amount) * 25 / 10_000) as u64)
+    );
+    // A fee of 100% returns the amount itself
+    assert_eq!(mul_div_u64(std::u64::MAX, BPS, BPS), (true, std::u64::MAX));
+    // Fees on tiny amounts round down to zero
+    assert_eq!(mul_div_u64(333, 30, BPS), (true, 0));
+}
+
+#[test]
+fn mul_div_u64_failures() {
+    assert_eq!(mul_div_u64(1, 1, 0), (false, 0));
+    assert_eq!(mul_div_u64(0, 0, 0), (false, 0));
+    assert_eq!(mul_div_u64(std::u64::MAX, 2, 1), (false, 0));
+    assert_eq!(
+        mul_div_u64(std::u64::MAX, std::u64::MAX, std::u64::MAX - 1),
+        (false, 0)
+    );
+    assert_eq!(
+        mul_div_u64(std::u64::MAX, std::u64::MAX, std::u64::MAX),
+        (true, std::u64::MAX)
+    );
+}
+
+fn event_guid(counter: u64, account: AccountAddress) -> Vec<u8> {
+    let result = primitive_helpers::native_event_guid(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(counter), Value::address(account)]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), EVENT_KEY_LENGTH as u64);
+    single_bytes(result)
+}
+
+#[test]
+fn event_guid_layout() {
+    let account = AccountAddress::new([0xab; ADDRESS_LENGTH]);
+    let guid = event_guid(0x0102_0304_0506_0708, account);
+    assert_eq!(guid.len(), EVENT_KEY_LENGTH);
+    assert_eq!(&guid[..8], &[8, 7, 6, 5, 4, 3, 2, 1]);
+    assert_eq!(&guid[8..], account.as_ref());
+    assert_ne!(event_guid(0, account), event_guid(1, account));
+}
+
+proptest! {
+    #[test]
+    fn constant_time_eq_matches_equality(
+        a in prop::collection::vec(any::<u8>(), 0..64),
+        b in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        prop_assert_eq!(constant_time_eq(&a, &b), a == b);
+        prop_assert!(constant_time_eq(&a, &a.clone()));
+    }
+
+    #[test]
+    fn constant_time_eq_detects_single_byte_flip(
+        a in prop::collection::vec(any::<u8>(), 1..64),
+        index in any::<prop::sample::Index>(),
+        flip in 1u8..,
+    ) {
+        let mut b = a.clone();
+        let i = index.index(b.len());
+        b[i] ^= flip;
+        prop_assert!(!constant_time_eq(&a, &b));
+    }
+
+    #[test]
+    fn u128_to_bytes_matches_lcs(value in any::<u128>()) {
+        prop_assert_eq!(u128_to_bytes(value), lcs::to_bytes(&value).unwrap());
+    }
+
+    #[test]
+    fn u64_to_bytes_be_sorts_like_integers(a in any::<u64>(), b in any::<u64>()) {
+        prop_assert_eq!(u64_to_bytes_be(a).cmp(&u64_to_bytes_be(b)), a.cmp(&b));
+    }
+
+    #[test]
+    fn bytes_to_u64_round_trip(value in any::<u64>()) {
+        prop_assert_eq!(bytes_to_u64(&u64_to_bytes(value)), value);
+    }
+
+    #[test]
+    fn u64_to_bytes_round_trip(bytes in prop::array::uniform8(any::<u8>())) {
+        prop_assert_eq!(u64_to_bytes(bytes_to_u64(&bytes)), bytes.to_vec());
+    }
+
+    #[test]
+    fn u128_from_bytes_round_trip(value in any::<u128>()) {
+        prop_assert_eq!(u128_from_bytes(&u128_to_bytes(value)), value);
+    }
+
+    #[test]
+    fn base58_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assert_eq!(base58_decode(&base58_encode(&bytes)), (true, bytes));
+    }
+
+    #[test]
+    fn bech32_round_trip(payload in prop::collection::vec(any::<u8>(), 0..40)) {
+        let (valid, encoded) = bech32_encode(b"libra", &payload);
+        prop_assert!(valid);
+        prop_assert_eq!(bech32_decode(b"libra", &encoded), (true, payload));
+    }
+
+    #[test]
+    fn base64_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assert_eq!(base64_decode(&base64_encode(&bytes)), (true, bytes));
+    }
+
+    #[test]
+    fn utf8_strings_are_valid(string in ".*") {
+        prop_assert!(is_valid_utf8(string.as_bytes()));
+    }
+
+    #[test]
+    fn utf8_matches_std(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assert_eq!(is_valid_utf8(&bytes), String::from_utf8(bytes).is_ok());
+    }
+
+    #[test]
+    fn ascii_to_uppercase_matches_std(string in "[ -~]*") {
+        let upper = to_uppercase_ascii(string.as_bytes());
+        prop_assert!(is_ascii(&upper));
+        prop_assert_eq!(upper, string.to_ascii_uppercase().into_bytes());
+    }
+
+    #[test]
+    fn div_mod_u128_recombines(dividend in any::<u128>(), divisor in 1..=std::u128::MAX) {
+        let (quotient, remainder) = div_mod_u128(dividend, divisor);
+        prop_assert!(remainder < divisor);
+        prop_assert_eq!(quotient * divisor + remainder, dividend);
+    }
+
+    #[test]
+    fn mul_div_u64_matches_u128_arithmetic(a in any::<u64>(), b in any::<u64>(), c in 1..=std::u64::MAX) {
+        let expected = u128::from(a) * u128::from(b) / u128::from(c);
+        let expected = if expected > u128::from(std::u64::MAX) {
+            (false, 0)
+        } else {
+            (true, expected as u64)
+        };
+        prop_assert_eq!(mul_div_u64(a, b, c), expected);
+    }
+
+    #[test]
+    fn add_mod_u128_matches_u128_arithmetic(
+        a in any::<u128>(),
+        b in any::<u128>(),
+        modulus in 1..=(1u128 << 127),
+    ) {
+        prop_assert_eq!(add_mod_u128(a, b, modulus), (a % modulus + b % modulus) % modulus);
+    }
+
+    #[test]
+    fn mul_mod_u128_matches_u128_arithmetic(
+        a in any::<u128>(),
+        b in any::<u128>(),
+        modulus in 1..=(1u128 << 64),
+    ) {
+        prop_assert_eq!(mul_mod_u128(a, b, modulus), (a % modulus) * (b % modulus) % modulus);
+    }
+
+    #[test]
+    fn u64_from_decimal_string_matches_std(value in any::<u64>()) {
+        prop_assert_eq!(
+            u64_from_decimal_string(value.to_string().as_bytes()),
+            (true, value)
+        );
+    }
+
+    #[test]
+    fn u64_decimal_string_round_trip(value in any::<u64>()) {
+        let formatted = u64_to_decimal_string(value);
+        prop_assert!(formatted == b"0" || formatted[0] != b'0');
+        prop_assert_eq!(u64_from_decimal_string(&formatted), (true, value));
+    }
+
+    #[test]
+    fn pow_u64_matches_std(base in 0u64..1000, exp in 0u32..100) {
+        let expected = match base.checked_pow(exp) {
+            Some(result) => (true, result),
+            None => (false, 0),
+        };
+        prop_assert_eq!(pow_u64(base, u64::from(exp)), expected);
+    }
+
+    #[test]
+    fn sqrt_u128_is_floor(value in any::<u128>()) {
+        let root = sqrt_u128(value);
+        prop_assert!(root * root <= value);
+        prop_assert!((root + 1).checked_mul(root + 1).map_or(true, |next| next > value));
+    }
+
+    #[test]
+    fn event_guid_matches_event_key(counter in any::<u64>(), account in any::<AccountAddress>()) {
+        prop_assert_eq!(
+            event_guid(counter, account),
+            EventKey::new_from_address(&account, counter).to_vec()
+        );
+    }
+}
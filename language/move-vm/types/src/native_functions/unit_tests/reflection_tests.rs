@@ -0,0 +1,212 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, single_bytes};
+use crate::native_functions::{
+    context::MockNativeContext,
+    dispatch::{NativeFunction, NativeResult},
+    reflection,
+};
+use libra_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    vm_error::StatusCode,
+};
+use vm::{
+    file_format::{CompiledModule, Kind},
+    gas_schedule::GasAlgebra,
+};
+
+fn struct_tag(module: &str, name: &str, type_params: Vec<TypeTag>) -> TypeTag {
+    TypeTag::Struct(StructTag {
+        address: AccountAddress::new([0xab; 32]),
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params,
+    })
+}
+
+fn type_of_result(tag: TypeTag) -> NativeResult {
+    reflection::native_type_of(&MockNativeContext::for_testing(), &[tag], args(vec![])).unwrap()
+}
+
+/// Calls `type_of` on `tag`, checking the result deserializes back to `tag` and is charged per
+/// byte.
+fn type_of(tag: TypeTag) -> Vec<u8> {
+    let result = type_of_result(tag.clone());
+    let cost = result.cost.get();
+    let bytes = single_bytes(result);
+    assert_eq!(cost, bytes.len() as u64);
+    assert_eq!(lcs::from_bytes::<TypeTag>(&bytes).unwrap(), tag);
+    bytes
+}
+
+#[test]
+fn type_of_primitives() {
+    for tag in vec![
+        TypeTag::Bool,
+        TypeTag::U8,
+        TypeTag::U64,
+        TypeTag::U128,
+        TypeTag::ByteArray,
+        TypeTag::Address,
+        TypeTag::Vector(Box::new(TypeTag::U8)),
+    ] {
+        assert_eq!(type_of(tag.clone()), lcs::to_bytes(&tag).unwrap());
+    }
+    assert_ne!(type_of(TypeTag::U64), type_of(TypeTag::U128));
+}
+
+#[test]
+fn type_of_nested_structs() {
+    let coin = struct_tag("LBR", "T", vec![]);
+    let inner = struct_tag("Vault", "Slot", vec![coin.clone(), TypeTag::U64]);
+    let outer = struct_tag(
+        "Registry",
+        "Entry",
+        vec![TypeTag::Vector(Box::new(inner.clone())), coin.clone()],
+    );
+    assert_eq!(type_of(outer.clone()), lcs::to_bytes(&outer).unwrap());
+
+    // Type arguments are part of the result, so instantiations of a struct differ
+    let other = struct_tag("Vault", "Slot", vec![TypeTag::U64, coin]);
+    assert_ne!(type_of(inner.clone()), type_of(other));
+    assert!(type_of(outer).len() > type_of(inner).len());
+}
+
+/// Dispatches `native` with the type arguments `ty_args`, which it is not declared with, and
+/// checks that dispatch rejects them before the native runs.
+fn assert_wrong_type_arity(native: NativeFunction, ty_args: Vec<TypeTag>) {
+    let status = native
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &ty_args,
+            vec![],
+            vec![],
+            args(vec![]),
+        )
+        .err()
+        .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NUMBER_OF_TYPE_ACTUALS_MISMATCH
+    );
+}
+
+#[test]
+fn type_of_wrong_type_arity() {
+    for ty_args in vec![vec![], vec![TypeTag::U64, TypeTag::U64]] {
+        assert_wrong_type_arity(NativeFunction::ReflectTypeOf, ty_args);
+    }
+}
+
+fn type_name(tag: TypeTag) -> String {
+    let result =
+        reflection::native_type_name(&MockNativeContext::for_testing(), &[tag], args(vec![]))
+            .unwrap();
+    let cost = result.cost.get();
+    let name = String::from_utf8(single_bytes(result)).unwrap();
+    assert_eq!(cost, name.len() as u64);
+    name
+}
+
+#[test]
+fn type_name_primitives() {
+    assert_eq!(type_name(TypeTag::Bool), "bool");
+    assert_eq!(type_name(TypeTag::U8), "u8");
+    assert_eq!(type_name(TypeTag::U64), "u64");
+    assert_eq!(type_name(TypeTag::U128), "u128");
+    assert_eq!(type_name(TypeTag::ByteArray), "bytearray");
+    assert_eq!(type_name(TypeTag::Address), "address");
+    assert_eq!(
+        type_name(TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(
+            TypeTag::U8
+        ))))),
+        "vector<vector<u8>>"
+    );
+}
+
+#[test]
+fn type_name_nested_structs() {
+    // The rendering is part of the on-chain interface: do not change these strings.
+    let lbr = TypeTag::Struct(StructTag {
+        address: AccountAddress::default(),
+        module: Identifier::new("LBR").unwrap(),
+        name: Identifier::new("T").unwrap(),
+        type_params: vec![],
+    });
+    assert_eq!(
+        type_name(lbr.clone()),
+        "0x0000000000000000000000000000000000000000000000000000000000000000::LBR::T"
+    );
+    let inner = struct_tag("Vault", "Slot", vec![lbr, TypeTag::U64]);
+    let outer = struct_tag(
+        "Registry",
+        "Entry",
+        vec![TypeTag::Vector(Box::new(inner)), TypeTag::Address],
+    );
+    assert_eq!(
+        type_name(outer),
+        "0xabababababababababababababababababababababababababababababababab::Registry::Entry<\
+         vector<0xabababababababababababababababababababababababababababababababab::Vault::Slot<\
+         0x0000000000000000000000000000000000000000000000000000000000000000::LBR::T, u64>>, \
+         address>"
+    );
+}
+
+fn types_are_equal(a: TypeTag, b: TypeTag) -> bool {
+    let result = reflection::native_types_are_equal(
+        &MockNativeContext::for_testing(),
+        &[a, b],
+        args(vec![]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("types_are_equal should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+#[test]
+fn types_are_equal_compares_structurally() {
+    let coin = struct_tag("LBR", "T", vec![]);
+    assert!(types_are_equal(TypeTag::U64, TypeTag::U64));
+    assert!(!types_are_equal(TypeTag::U64, TypeTag::U128));
+    assert!(types_are_equal(coin.clone(), coin.clone()));
+    assert!(!types_are_equal(
+        coin.clone(),
+        struct_tag("LBR", "U", vec![])
+    ));
+    assert!(!types_are_equal(
+        TypeTag::Vector(Box::new(TypeTag::U8)),
+        TypeTag::ByteArray
+    ));
+
+    // The same struct instantiated with different type arguments is a different type
+    let a = struct_tag("Vault", "Slot", vec![coin.clone(), TypeTag::U64]);
+    let b = struct_tag("Vault", "Slot", vec![TypeTag::U64, coin.clone()]);
+    let c = struct_tag("Vault", "Slot", vec![coin, TypeTag::U64]);
+    assert!(!types_are_equal(a.clone(), b));
+    assert!(types_are_equal(a, c));
+}
+
+#[test]
+fn types_are_equal_wrong_type_arity() {
+    for ty_args in vec![vec![], vec![TypeTag::U64], vec![TypeTag::U64; 3]] {
+        assert_wrong_type_arity(NativeFunction::ReflectTypesAreEqual, ty_args);
+    }
+}
+
+#[test]
+fn reflection_signatures_declare_their_type_formals() {
+    for &(native, type_formals) in &[
+        (NativeFunction::ReflectTypeOf, 1),
+        (NativeFunction::ReflectTypeName, 1),
+        (NativeFunction::ReflectTypesAreEqual, 2),
+    ] {
+        let signature = native.signature::<CompiledModule>(None).unwrap().unwrap();
+        assert_eq!(signature.type_formals, vec![Kind::All; type_formals]);
+        assert!(signature.arg_types.is_empty());
+    }
+}
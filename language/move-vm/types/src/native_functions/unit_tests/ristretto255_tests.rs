@@ -0,0 +1,112 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, single_bytes};
+use crate::{
+    native_functions::{context::MockNativeContext, dispatch::NativeResult, ristretto255},
+    values::Value,
+};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use libra_types::vm_error::StatusCode;
+use vm::gas_schedule::GasAlgebra;
+
+fn encode(point: RistrettoPoint) -> Vec<u8> {
+    point.compress().to_bytes().to_vec()
+}
+
+fn point_add(a: &[u8], b: &[u8]) -> NativeResult {
+    ristretto255::native_point_add(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(a.to_vec()),
+            Value::vector_u8(b.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn scalar_mul(point: &[u8], scalar: &[u8]) -> NativeResult {
+    ristretto255::native_scalar_mul(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(point.to_vec()),
+            Value::vector_u8(scalar.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn point_is_valid(point: &[u8]) -> bool {
+    let result = ristretto255::native_point_is_valid(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::vector_u8(point.to_vec())]),
+    )
+    .unwrap();
+    let mut values = result.result.unwrap();
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+fn assert_sub_status(result: NativeResult, sub_status: u64) {
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+#[test]
+fn point_add_matches_dalek() {
+    let a = RISTRETTO_BASEPOINT_POINT * Scalar::from(3u64);
+    let b = RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64);
+    assert_eq!(
+        single_bytes(point_add(&encode(a), &encode(b))),
+        encode(a + b)
+    );
+    assert_eq!(
+        single_bytes(point_add(&encode(a), &encode(b))),
+        encode(RISTRETTO_BASEPOINT_POINT * Scalar::from(8u64))
+    );
+}
+
+#[test]
+fn scalar_mul_matches_dalek() {
+    let scalar = Scalar::from(0x1234_5678_9abc_def0u64) * Scalar::from(0x0fed_cba9_8765_4321u64);
+    assert_eq!(
+        single_bytes(scalar_mul(
+            &encode(RISTRETTO_BASEPOINT_POINT),
+            scalar.as_bytes()
+        )),
+        encode(RISTRETTO_BASEPOINT_POINT * scalar)
+    );
+}
+
+#[test]
+fn point_is_valid_checks_encoding() {
+    assert!(point_is_valid(&encode(RISTRETTO_BASEPOINT_POINT)));
+    assert!(!point_is_valid(&[0xff; 32]));
+    assert!(!point_is_valid(&encode(RISTRETTO_BASEPOINT_POINT)[..31]));
+}
+
+#[test]
+fn invalid_encodings_are_charged_errors() {
+    let base = encode(RISTRETTO_BASEPOINT_POINT);
+    let result = point_add(&base, &[0xff; 32]);
+    assert_eq!(result.cost.get(), 1);
+    assert_sub_status(result, ristretto255::INVALID_POINT_ENCODING);
+    assert_sub_status(
+        point_add(&base[..31], &base),
+        ristretto255::INVALID_POINT_ENCODING,
+    );
+    assert_sub_status(
+        scalar_mul(&[0xff; 32], Scalar::one().as_bytes()),
+        ristretto255::INVALID_POINT_ENCODING,
+    );
+    // 2^256 - 1 is not reduced modulo the group order.
+    assert_sub_status(
+        scalar_mul(&base, &[0xff; 32]),
+        ristretto255::INVALID_SCALAR_ENCODING,
+    );
+}
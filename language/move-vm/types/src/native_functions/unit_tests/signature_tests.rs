@@ -0,0 +1,842 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, assert_cost_breakdown, context_with_native_cost};
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{NativeFunction, NativeResult},
+        errors, signature,
+    },
+    values::Value,
+};
+use libra_crypto::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    traits::*,
+    vrf::ecvrf::{Output, VRFPrivateKey, VRFPublicKey, PROOF_LENGTH},
+    HashValue,
+};
+use libra_types::vm_error::StatusCode;
+use rand::{rngs::StdRng, SeedableRng};
+use secp256k1::{Message, RecoveryId, SecretKey};
+use sha3::{Digest, Keccak256};
+use vm::{
+    file_format::CompiledModule,
+    gas_schedule::{GasAlgebra, GasUnits},
+};
+
+/// secp256k1 generator point, i.e. the public key for the secret key 1.
+const GENERATOR: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+                         483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+fn secp256k1_recover(hash: &[u8], recovery_id: u8, signature: &[u8]) -> (Vec<u8>, bool) {
+    let result = signature::native_secp256k1_recover(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(hash.to_vec()),
+            Value::u8(recovery_id),
+            Value::vector_u8(signature.to_vec()),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(result.cost.get(), hash.len() as u64);
+    let mut values = result.result.expect("secp256k1_recover should not fail");
+    assert_eq!(values.len(), 2);
+    let success = values.pop().unwrap().value_as::<bool>().unwrap();
+    let pubkey = values.pop().unwrap().value_as::<Vec<u8>>().unwrap();
+    (pubkey, success)
+}
+
+/// Signs `keccak256(msg)` with the secret key 1, returning the hash, the compact signature and
+/// the recovery id.
+fn sign_with_key_one(msg: &[u8]) -> (Vec<u8>, Vec<u8>, u8) {
+    let mut key = [0u8; 32];
+    key[31] = 1;
+    let secret_key = SecretKey::parse(&key).unwrap();
+    let hash = Keccak256::digest(msg).to_vec();
+    let (sig, recovery_id) = secp256k1::sign(&Message::parse_slice(&hash).unwrap(), &secret_key);
+    (hash, sig.serialize().to_vec(), recovery_id.serialize())
+}
+
+#[test]
+fn secp256k1_recover_signer() {
+    let (hash, sig, recovery_id) = sign_with_key_one(b"hello");
+    assert_eq!(
+        secp256k1_recover(&hash, recovery_id, &sig),
+        (hex::decode(GENERATOR).unwrap(), true)
+    );
+}
+
+#[test]
+fn secp256k1_recover_rejects_high_s() {
+    let (hash, sig, recovery_id) = sign_with_key_one(b"hello");
+    let mut high_s = secp256k1::Signature::parse_slice(&sig).unwrap();
+    high_s.s = -high_s.s;
+    assert!(high_s.s.is_high());
+    let flipped_id = recovery_id ^ 1;
+
+    // The malleated signature recovers the same key when the check is skipped...
+    let recovered = secp256k1::recover(
+        &Message::parse_slice(&hash).unwrap(),
+        &high_s,
+        &RecoveryId::parse(flipped_id).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        recovered.serialize()[1..].to_vec(),
+        hex::decode(GENERATOR).unwrap()
+    );
+    // ...but the native refuses it.
+    assert_eq!(
+        secp256k1_recover(&hash, flipped_id, &high_s.serialize()),
+        (vec![], false)
+    );
+}
+
+#[test]
+fn secp256k1_recover_invalid_recovery_id() {
+    let (hash, sig, recovery_id) = sign_with_key_one(b"hello");
+    // Out of range, including the Ethereum RPC encoding.
+    for id in &[4, 27, 28, 255] {
+        assert_eq!(secp256k1_recover(&hash, *id, &sig), (vec![], false));
+    }
+    // In range but wrong: recovers some other key, or none at all.
+    let (pubkey, _) = secp256k1_recover(&hash, recovery_id ^ 1, &sig);
+    assert_ne!(pubkey, hex::decode(GENERATOR).unwrap());
+}
+
+#[test]
+fn secp256k1_recover_malformed_inputs() {
+    let (hash, sig, recovery_id) = sign_with_key_one(b"hello");
+    assert_eq!(
+        secp256k1_recover(&hash[..31], recovery_id, &sig),
+        (vec![], false)
+    );
+    assert_eq!(
+        secp256k1_recover(&hash, recovery_id, &sig[..63]),
+        (vec![], false)
+    );
+    // s not below the group order.
+    let mut overflow = sig.clone();
+    overflow[32..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(
+        secp256k1_recover(&hash, recovery_id, &overflow),
+        (vec![], false)
+    );
+    // r of zero.
+    let mut zero_r = sig;
+    zero_r[..32].copy_from_slice(&[0; 32]);
+    assert_eq!(
+        secp256k1_recover(&hash, recovery_id, &zero_r),
+        (vec![], false)
+    );
+}
+
+/// The BIP-340 verification test vectors, 0 to 14: index, public key, message, signature and
+/// whether the signature is valid. Among the invalid ones, 5 has a public key not on the curve,
+/// 6 to 10 a wrong or infinite R, 11 to 13 an r or s out of range and 14 a public key past the
+/// field size.
+const BIP340_VECTORS: &[(u8, &str, &str, &str, bool)] = &[
+    (
+        0,
+        "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215\
+         25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+        true,
+    ),
+    (
+        1,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+         8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+        true,
+    ),
+    (
+        2,
+        "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+        "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+        "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1B\
+         AB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+        true,
+    ),
+    (
+        3,
+        "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC\
+         97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+        true,
+    ),
+    (
+        4,
+        "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+        "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+        "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C63\
+         76AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+        true,
+    ),
+    (
+        5,
+        "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+         69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+        false,
+    ),
+    (
+        6,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A1460297556\
+         3CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+        false,
+    ),
+    (
+        7,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F\
+         28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+        false,
+    ),
+    (
+        8,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+         961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+        false,
+    ),
+    (
+        9,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "0000000000000000000000000000000000000000000000000000000000000000\
+         123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+        false,
+    ),
+    (
+        10,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "0000000000000000000000000000000000000000000000000000000000000001\
+         7615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+        false,
+    ),
+    (
+        11,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D\
+         69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+        false,
+    ),
+    (
+        12,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F\
+         69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+        false,
+    ),
+    (
+        13,
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+         FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        false,
+    ),
+    (
+        14,
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+         69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+        false,
+    ),
+];
+
+fn schnorr_verify(pubkey: &[u8], signature: &[u8], msg: &[u8]) -> NativeResult {
+    signature::native_schnorr_signature_verification(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(pubkey.to_vec()),
+            Value::vector_u8(signature.to_vec()),
+            Value::vector_u8(msg.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn schnorr_verify_bip340_vectors() {
+    for (index, pubkey, msg, signature, valid) in BIP340_VECTORS {
+        let result = schnorr_verify(
+            &hex::decode(pubkey).unwrap(),
+            &hex::decode(signature).unwrap(),
+            &hex::decode(msg).unwrap(),
+        );
+        assert_eq!(result.cost.get(), 32);
+        assert_eq!(result_bool(result), *valid, "test vector {}", index);
+    }
+}
+
+#[test]
+fn schnorr_verify_other_messages() {
+    let (_, pubkey, msg, signature, _) = BIP340_VECTORS[1];
+    let (pubkey, signature) = (
+        hex::decode(pubkey).unwrap(),
+        hex::decode(signature).unwrap(),
+    );
+    let mut msg = hex::decode(msg).unwrap();
+    msg[31] ^= 1;
+    assert!(!result_bool(schnorr_verify(&pubkey, &signature, &msg)));
+    // Messages are not required to be hashes, but are signed as they are
+    let result = schnorr_verify(&pubkey, &signature, &msg[..31]);
+    assert_eq!(result.cost.get(), 31);
+    assert!(!result_bool(result));
+}
+
+#[test]
+fn schnorr_verify_malformed_lengths() {
+    let (_, pubkey, msg, signature, _) = BIP340_VECTORS[0];
+    let pubkey = hex::decode(pubkey).unwrap();
+    let msg = hex::decode(msg).unwrap();
+    let signature = hex::decode(signature).unwrap();
+    let mut long_pubkey = pubkey.clone();
+    long_pubkey.push(0);
+    let mut long_signature = signature.clone();
+    long_signature.push(0);
+
+    for bad_pubkey in &[&pubkey[..31], &long_pubkey[..], &pubkey[..0]] {
+        assert_aborted(
+            schnorr_verify(bad_pubkey, &signature, &msg),
+            errors::signature::INVALID_SCHNORR_PUBLIC_KEY_LENGTH_FAILURE,
+        );
+    }
+    for bad_signature in &[&signature[..63], &long_signature[..], &signature[..0]] {
+        assert_aborted(
+            schnorr_verify(&pubkey, bad_signature, &msg),
+            errors::signature::INVALID_SCHNORR_SIGNATURE_LENGTH_FAILURE,
+        );
+    }
+    // Both are malformed: the public key is reported
+    assert_aborted(
+        schnorr_verify(&long_pubkey, &long_signature, &msg),
+        errors::signature::INVALID_SCHNORR_PUBLIC_KEY_LENGTH_FAILURE,
+    );
+}
+
+fn bls12381_aggregate_verify(pubkeys: Vec<Vec<u8>>, signature: &[u8], msg: &[u8]) -> NativeResult {
+    signature::native_bls12381_aggregate_signature_verification(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_vector_u8(pubkeys),
+            Value::vector_u8(signature.to_vec()),
+            Value::vector_u8(msg.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn result_bool(result: NativeResult) -> bool {
+    let mut values = result
+        .result
+        .expect("bls12381_aggregate_verify should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<bool>().unwrap()
+}
+
+fn assert_aborted(result: NativeResult, sub_status: u64) {
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(sub_status));
+}
+
+/// Returns `n` public keys, their aggregate signature on a message, and the message.
+fn bls12381_quorum(n: usize) -> (Vec<Vec<u8>>, Vec<u8>, Vec<u8>) {
+    let mut rng = StdRng::from_seed([7u8; 32]);
+    let hash = HashValue::from_sha3_256(b"quorum certificate");
+    let private_keys: Vec<_> = (0..n)
+        .map(|_| BLS12381PrivateKey::generate_for_testing(&mut rng))
+        .collect();
+    let signatures: Vec<_> = private_keys
+        .iter()
+        .map(|private_key| private_key.sign_message(&hash))
+        .collect();
+    let pubkeys = private_keys
+        .iter()
+        .map(|private_key| BLS12381PublicKey::from(private_key).to_bytes().to_vec())
+        .collect();
+    let signature = BLS12381Signature::aggregate(&signatures).unwrap();
+    (pubkeys, signature.to_bytes().to_vec(), hash.to_vec())
+}
+
+#[test]
+fn bls12381_aggregate_verify_quorum() {
+    let (pubkeys, signature, msg) = bls12381_quorum(4);
+    let result = bls12381_aggregate_verify(pubkeys.clone(), &signature, &msg);
+    assert_eq!(result.cost.get(), 4);
+    assert!(result_bool(result));
+
+    // Missing signer.
+    assert!(!result_bool(bls12381_aggregate_verify(
+        pubkeys[1..].to_vec(),
+        &signature,
+        &msg
+    )));
+    // Different message.
+    assert!(!result_bool(bls12381_aggregate_verify(
+        pubkeys,
+        &signature,
+        b"another message"
+    )));
+}
+
+#[test]
+fn bls12381_aggregate_verify_gas_scales_with_keys() {
+    let (pubkeys, signature, msg) = bls12381_quorum(8);
+    let small = bls12381_aggregate_verify(pubkeys[..2].to_vec(), &signature, &msg);
+    let large = bls12381_aggregate_verify(pubkeys, &signature, &msg);
+    assert_eq!(small.cost.get(), 2);
+    assert_eq!(large.cost.get(), 8);
+}
+
+#[test]
+fn bls12381_aggregate_verify_empty_keys() {
+    let (_, signature, msg) = bls12381_quorum(1);
+    let result = bls12381_aggregate_verify(vec![], &signature, &msg);
+    assert!(result.cost.get() > 0);
+    assert_aborted(result, errors::signature::EMPTY_PUBLIC_KEYS_FAILURE);
+}
+
+#[test]
+fn bls12381_aggregate_verify_too_many_keys() {
+    let (pubkeys, signature, msg) = bls12381_quorum(1);
+    let too_many = vec![pubkeys[0].clone(); signature::MAX_AGGREGATE_PUBLIC_KEYS + 1];
+    let result = bls12381_aggregate_verify(too_many, &signature, &msg);
+    assert_eq!(
+        result.cost.get(),
+        signature::MAX_AGGREGATE_PUBLIC_KEYS as u64 + 1
+    );
+    assert_aborted(result, errors::signature::TOO_MANY_PUBLIC_KEYS_FAILURE);
+}
+
+fn multi_ed25519_verify(
+    bitmap: &[u8],
+    signatures: &[u8],
+    pubkeys: &[u8],
+    message: &[u8],
+) -> NativeResult {
+    signature::native_multi_ed25519_signature_verification(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(bitmap.to_vec()),
+            Value::vector_u8(signatures.to_vec()),
+            Value::vector_u8(pubkeys.to_vec()),
+            Value::vector_u8(message.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+/// Returns the concatenated public keys of `n` signers, the concatenated signatures of the
+/// `signers` subset on a message (in key order), and the message.
+fn multi_ed25519_setup(n: usize, signers: &[usize]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut rng = StdRng::from_seed([3u8; 32]);
+    let hash = HashValue::from_sha3_256(b"wallet transaction");
+    let private_keys: Vec<_> = (0..n)
+        .map(|_| Ed25519PrivateKey::generate_for_testing(&mut rng))
+        .collect();
+    let pubkeys = private_keys
+        .iter()
+        .flat_map(|private_key| Ed25519PublicKey::from(private_key).to_bytes().to_vec())
+        .collect();
+    let signatures = signers
+        .iter()
+        .flat_map(|i| private_keys[*i].sign_message(&hash).to_bytes().to_vec())
+        .collect();
+    (pubkeys, signatures, hash.to_vec())
+}
+
+#[test]
+fn multi_ed25519_verify_subset() {
+    let (pubkeys, signatures, message) = multi_ed25519_setup(4, &[0, 2]);
+    let bitmap = [0b1010_0000, 0, 0, 0];
+    let result = multi_ed25519_verify(&bitmap, &signatures, &pubkeys, &message);
+    assert_eq!(result.cost.get(), 2);
+    assert!(result_bool(result));
+
+    // The same signatures attributed to other keys.
+    let shifted_bitmap = [0b0101_0000, 0, 0, 0];
+    assert!(!result_bool(multi_ed25519_verify(
+        &shifted_bitmap,
+        &signatures,
+        &pubkeys,
+        &message
+    )));
+    // A different message.
+    assert!(!result_bool(multi_ed25519_verify(
+        &bitmap,
+        &signatures,
+        &pubkeys,
+        b"another message"
+    )));
+}
+
+#[test]
+fn multi_ed25519_verify_gas_scales_with_signers() {
+    let (pubkeys, signatures, message) = multi_ed25519_setup(8, &[0, 1, 2, 3, 4, 5, 6, 7]);
+    let result = multi_ed25519_verify(&[0xff, 0, 0, 0], &signatures, &pubkeys, &message);
+    assert_eq!(result.cost.get(), 8);
+    assert!(result_bool(result));
+}
+
+#[test]
+fn multi_ed25519_verify_malformed_bitmap() {
+    let (pubkeys, signatures, message) = multi_ed25519_setup(4, &[0, 2]);
+    // Not BITMAP_SIZE bits long.
+    assert_aborted(
+        multi_ed25519_verify(&[0b1010_0000], &signatures, &pubkeys, &message),
+        errors::signature::INVALID_BITMAP_LENGTH_FAILURE,
+    );
+    // No signer.
+    assert_aborted(
+        multi_ed25519_verify(&[0, 0, 0, 0], &signatures, &pubkeys, &message),
+        errors::signature::ZERO_BITMAP_FAILURE,
+    );
+    // More set bits than signatures.
+    assert_aborted(
+        multi_ed25519_verify(&[0b1110_0000, 0, 0, 0], &signatures, &pubkeys, &message),
+        errors::signature::SIGNATURE_SIZE_FAILURE,
+    );
+    // A set bit past the last public key.
+    assert_aborted(
+        multi_ed25519_verify(&[0b1000_1000, 0, 0, 0], &signatures, &pubkeys, &message),
+        errors::signature::BITMAP_PUBLIC_KEY_SIZE_FAILURE,
+    );
+    // Truncated public key.
+    assert_aborted(
+        multi_ed25519_verify(
+            &[0b1010_0000, 0, 0, 0],
+            &signatures,
+            &pubkeys[..pubkeys.len() - 1],
+            &message,
+        ),
+        errors::signature::INVALID_PUBLIC_KEY_SIZE_FAILURE,
+    );
+}
+
+fn ed25519_threshold_verify(
+    bitmap: &[u8],
+    signatures: &[u8],
+    pubkeys: &[u8],
+    message: &[u8],
+) -> NativeResult {
+    signature::native_ed25519_threshold_signature_verification(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(bitmap.to_vec()),
+            Value::vector_u8(signatures.to_vec()),
+            Value::vector_u8(pubkeys.to_vec()),
+            Value::vector_u8(message.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+/// Checks that the threshold verification of `signatures` by `pubkeys` for `bitmap` aborts with
+/// `sub_status`, refunding the cost of the signatures.
+fn assert_refunds_signatures(bitmap: &[u8], signatures: &[u8], pubkeys: &[u8], sub_status: u64) {
+    // Charged per bit of the bitmap and per byte of the signatures and keys.
+    let cost = (8 * bitmap.len() + signatures.len() + pubkeys.len()) as u64;
+    let result = ed25519_threshold_verify(bitmap, signatures, pubkeys, b"message");
+    assert_eq!(result.cost.get(), cost);
+    assert_eq!(result.refund.get(), signatures.len() as u64);
+    assert_eq!(result.charged().get(), cost - signatures.len() as u64);
+    assert_aborted(result, sub_status);
+}
+
+#[test]
+fn ed25519_threshold_verify_refunds_signatures_on_malformed_bitmap() {
+    let (pubkeys, signatures, _) = multi_ed25519_setup(4, &[0, 2]);
+    assert_refunds_signatures(
+        &[0b1010_0000],
+        &signatures,
+        &pubkeys,
+        errors::signature::INVALID_BITMAP_LENGTH_FAILURE,
+    );
+    assert_refunds_signatures(
+        &[0, 0, 0, 0],
+        &signatures,
+        &pubkeys,
+        errors::signature::ZERO_BITMAP_FAILURE,
+    );
+}
+
+#[test]
+fn ed25519_threshold_verify_refunds_signatures_on_signature_size_failure() {
+    let (pubkeys, signatures, _) = multi_ed25519_setup(4, &[0, 2]);
+    // More set bits than signatures.
+    assert_refunds_signatures(
+        &[0b1110_0000, 0, 0, 0],
+        &signatures,
+        &pubkeys,
+        errors::signature::SIGNATURE_SIZE_FAILURE,
+    );
+}
+
+#[test]
+fn ed25519_threshold_verify_refunds_signatures_on_bitmap_public_key_size_failure() {
+    let (pubkeys, signatures, _) = multi_ed25519_setup(4, &[0, 2]);
+    // A set bit past the last public key.
+    assert_refunds_signatures(
+        &[0b1000_1000, 0, 0, 0],
+        &signatures,
+        &pubkeys,
+        errors::signature::BITMAP_PUBLIC_KEY_SIZE_FAILURE,
+    );
+}
+
+#[test]
+fn ed25519_threshold_verify_refunds_signatures_on_oversized_public_key_size_failure() {
+    // One key more than the bitmap can point to.
+    let (pubkeys, signatures, _) = multi_ed25519_setup(33, &[0, 2]);
+    assert_refunds_signatures(
+        &[0b1010_0000, 0, 0, 0],
+        &signatures,
+        &pubkeys,
+        errors::signature::OVERSIZED_PUBLIC_KEY_SIZE_FAILURE,
+    );
+}
+
+#[test]
+fn ed25519_threshold_verify_refunds_signatures_on_invalid_public_key_size_failure() {
+    let (pubkeys, signatures, _) = multi_ed25519_setup(4, &[0, 2]);
+    // Truncated public key.
+    assert_refunds_signatures(
+        &[0b1010_0000, 0, 0, 0],
+        &signatures,
+        &pubkeys[..pubkeys.len() - 1],
+        errors::signature::INVALID_PUBLIC_KEY_SIZE_FAILURE,
+    );
+}
+
+#[test]
+fn ed25519_threshold_verify_stops_when_out_of_gas() {
+    let (pubkeys, signatures, message) = multi_ed25519_setup(4, &[0, 2]);
+    let bitmap = [0b1010_0000, 0, 0, 0];
+    let verify = |budget| {
+        let mut context = MockNativeContext {
+            remaining_gas: GasUnits::new(budget),
+            ..MockNativeContext::for_testing()
+        };
+        NativeFunction::SigED25519ThresholdVerify
+            .dispatch(
+                &mut context,
+                &[],
+                vec![],
+                vec![],
+                args(vec![
+                    Value::vector_u8(bitmap.to_vec()),
+                    Value::vector_u8(signatures.clone()),
+                    Value::vector_u8(pubkeys.clone()),
+                    Value::vector_u8(message.clone()),
+                ]),
+            )
+            .unwrap()
+    };
+
+    // Each signature is charged per byte of the message: the budget covers only the first one.
+    let result = verify(40);
+    assert_eq!(result.cost.get(), 40);
+    assert_eq!(
+        result.result.unwrap_err().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+
+    let result = verify(64);
+    assert_eq!(result.cost.get(), 64);
+    assert_eq!(result.result.unwrap().len(), 1);
+}
+
+fn ed25519_verify(signature: &[u8], pubkey: &[u8], message: &[u8]) -> NativeResult {
+    signature::native_ed25519_signature_verification(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(signature.to_vec()),
+            Value::vector_u8(pubkey.to_vec()),
+            Value::vector_u8(message.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn ed25519_verify_malformed_inputs() {
+    let (pubkey, signature, message) = multi_ed25519_setup(1, &[0]);
+    assert!(result_bool(ed25519_verify(&signature, &pubkey, &message)));
+    assert_aborted(
+        ed25519_verify(&signature[..63], &pubkey, &message),
+        errors::signature::SIGNATURE_DESERIALIZATION_FAILURE,
+    );
+    assert_aborted(
+        ed25519_verify(&signature, &pubkey[..31], &message),
+        errors::signature::PUBLIC_KEY_DESERIALIZATION_FAILURE,
+    );
+}
+
+fn ecvrf_verify(pubkey: &[u8], proof: &[u8], input: &[u8]) -> NativeResult {
+    signature::native_ecvrf_verify(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![
+            Value::vector_u8(pubkey.to_vec()),
+            Value::vector_u8(proof.to_vec()),
+            Value::vector_u8(input.to_vec()),
+        ]),
+    )
+    .unwrap()
+}
+
+fn ecvrf_result(result: NativeResult) -> (bool, Vec<u8>) {
+    let mut values = result.result.expect("ecvrf_verify should succeed");
+    assert_eq!(values.len(), 2);
+    let output = values.pop().unwrap().value_as::<Vec<u8>>().unwrap();
+    let valid = values.pop().unwrap().value_as::<bool>().unwrap();
+    (valid, output)
+}
+
+fn ecvrf_keypair() -> (VRFPrivateKey, Vec<u8>) {
+    let mut rng = StdRng::from_seed([5u8; 32]);
+    let private_key = VRFPrivateKey::generate_for_testing(&mut rng);
+    let public_key = VRFPublicKey::from(&private_key);
+    (private_key, public_key.as_bytes().to_vec())
+}
+
+#[test]
+fn ecvrf_verify_round_trip() {
+    let (private_key, pubkey) = ecvrf_keypair();
+    let proof = private_key.prove(b"epoch 42");
+    let result = ecvrf_verify(&pubkey, &proof.to_bytes(), b"epoch 42");
+    assert_eq!(result.cost.get(), 8);
+    assert_eq!(
+        ecvrf_result(result),
+        (true, Output::from(&proof).to_bytes().to_vec())
+    );
+}
+
+#[test]
+fn ecvrf_verify_wrong_input() {
+    let (private_key, pubkey) = ecvrf_keypair();
+    let proof = private_key.prove(b"epoch 42").to_bytes();
+    assert_eq!(
+        ecvrf_result(ecvrf_verify(&pubkey, &proof, b"epoch 43")),
+        (false, vec![])
+    );
+}
+
+#[test]
+fn ecvrf_verify_malformed_inputs() {
+    let (private_key, pubkey) = ecvrf_keypair();
+    let proof = private_key.prove(b"epoch 42").to_bytes();
+    assert_aborted(
+        ecvrf_verify(&pubkey[..31], &proof, b"epoch 42"),
+        errors::signature::PUBLIC_KEY_DESERIALIZATION_FAILURE,
+    );
+    assert_aborted(
+        ecvrf_verify(&pubkey, &proof[..PROOF_LENGTH - 1], b"epoch 42"),
+        errors::signature::PROOF_DESERIALIZATION_FAILURE,
+    );
+    // A gamma whose y-coordinate has no point on the curve.
+    let mut bad_gamma = proof;
+    bad_gamma[..32].copy_from_slice(&[0u8; 32]);
+    bad_gamma[0] = 2;
+    assert_aborted(
+        ecvrf_verify(&pubkey, &bad_gamma, b"epoch 42"),
+        errors::signature::PROOF_DESERIALIZATION_FAILURE,
+    );
+}
+
+#[test]
+fn ecvrf_verify_signature_is_consistent() {
+    assert!(NativeFunction::SigEcvrfVerify
+        .signature::<CompiledModule>(None)
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn signatures_record_their_cost_breakdown() {
+    let mut context = context_with_native_cost(3, 2);
+    let (pubkeys, signatures, message) = multi_ed25519_setup(4, &[0, 2]);
+    let mut verify = |native: NativeFunction, bitmap: &[u8]| {
+        native
+            .dispatch(
+                &mut context,
+                &[],
+                vec![],
+                vec![],
+                args(vec![
+                    Value::vector_u8(bitmap.to_vec()),
+                    Value::vector_u8(signatures.clone()),
+                    Value::vector_u8(pubkeys.clone()),
+                    Value::vector_u8(message.clone()),
+                ]),
+            )
+            .unwrap()
+    };
+
+    let result = verify(
+        NativeFunction::SigED25519ThresholdVerify,
+        &[0b1010_0000, 0, 0, 0],
+    );
+    assert_cost_breakdown(&result);
+    assert_eq!(
+        result.cost_breakdown.unwrap().units,
+        2 * message.len() as u64
+    );
+
+    let result = verify(
+        NativeFunction::SigMultiEd25519Verify,
+        &[0b1010_0000, 0, 0, 0],
+    );
+    assert_cost_breakdown(&result);
+    assert_eq!(result.cost_breakdown.unwrap().units, 2);
+
+    // A bitmap rejected by the sanity check is charged for it
+    let result = verify(
+        NativeFunction::SigMultiEd25519Verify,
+        &[0b1110_0000, 0, 0, 0],
+    );
+    assert_cost_breakdown(&result);
+    assert_eq!(
+        result.cost.get(),
+        5 * (32 + signatures.len() + pubkeys.len()) as u64
+    );
+    // and is refunded the signatures
+    assert_eq!(result.refund.get(), 5 * signatures.len() as u64);
+
+    let result = signature::native_ed25519_signature_verification(
+        &context_with_native_cost(3, 2),
+        &[],
+        args(vec![
+            Value::vector_u8(vec![]),
+            Value::vector_u8(vec![]),
+            Value::vector_u8(message.clone()),
+        ]),
+    )
+    .unwrap();
+    assert_cost_breakdown(&result);
+    assert_eq!(result.cost_breakdown.unwrap().per_unit.get(), 5);
+}
@@ -0,0 +1,455 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{native_gas, NativeFunction, NativeResult, DEFAULT_NATIVE_VERSION},
+        table::{NativeFunctionEntry, NativeFunctionTable},
+    },
+    values::Value,
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    vm_error::{StatusCode, VMStatus},
+};
+use vm::{
+    errors::VMResult,
+    file_format::{CompiledModule, FunctionSignature, Kind, SignatureToken},
+    gas_schedule::{GasAlgebra, GasUnits, NativeCostIndex},
+};
+
+fn module(address: AccountAddress, name: &str) -> ModuleId {
+    ModuleId::new(address, Identifier::new(name).unwrap())
+}
+
+fn ext_module() -> ModuleId {
+    module(AccountAddress::new([0xab; 32]), "Ext")
+}
+
+fn ident(name: &str) -> &IdentStr {
+    IdentStr::new(name).unwrap()
+}
+
+fn u64_to_u64() -> FunctionSignature {
+    FunctionSignature {
+        return_types: vec![SignatureToken::U64],
+        arg_types: vec![SignatureToken::U64],
+        type_formals: vec![],
+    }
+}
+
+/// Registers `Ext::double`, which doubles its argument and returns the sender as well.
+fn register_double(table: &mut NativeFunctionTable, module: ModuleId) -> VMResult<()> {
+    table.register(
+        module,
+        Identifier::new("double").unwrap(),
+        1,
+        FunctionSignature {
+            return_types: vec![SignatureToken::U64, SignatureToken::Address],
+            ..u64_to_u64()
+        },
+        Box::new(|context, _ty_args, _kinds, _layouts, mut arguments| {
+            let n = pop_arg!(arguments, u64);
+            let cost = native_gas(context.cost_table(), NativeCostIndex::SHA3_256, 1);
+            let return_values = vec![Value::u64(2 * n), Value::address(context.sender())];
+            Ok(NativeResult::ok(cost, return_values))
+        }),
+    )
+}
+
+#[test]
+fn registered_native_dispatches() {
+    let mut table = NativeFunctionTable::new();
+    register_double(&mut table, ext_module()).unwrap();
+
+    let entry = table.resolve(&ext_module(), ident("double")).unwrap();
+    assert_eq!(entry.num_args(), 1);
+    let signature = entry.signature::<CompiledModule>(None).unwrap().unwrap();
+    assert_eq!(signature.arg_types, vec![SignatureToken::U64]);
+
+    let sender = AccountAddress::new([7; 32]);
+    let mut context = MockNativeContext::new(sender);
+    let result = entry
+        .dispatch(
+            &mut context,
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::u64(21)]),
+        )
+        .unwrap();
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.unwrap();
+    assert_eq!(values.len(), 2);
+    assert_eq!(
+        values.pop().unwrap().value_as::<AccountAddress>().unwrap(),
+        sender
+    );
+    assert_eq!(values.pop().unwrap().value_as::<u64>().unwrap(), 42);
+
+    assert!(table.resolve(&ext_module(), ident("triple")).is_none());
+    assert!(table
+        .resolve(
+            &module(AccountAddress::new([0xcd; 32]), "Ext"),
+            ident("double")
+        )
+        .is_none());
+}
+
+#[test]
+fn registered_native_events_are_passed_to_the_caller() {
+    let mut table = NativeFunctionTable::new();
+    table
+        .register(
+            ext_module(),
+            Identifier::new("emit_twice").unwrap(),
+            1,
+            FunctionSignature {
+                return_types: vec![],
+                ..u64_to_u64()
+            },
+            Box::new(|_context, _ty_args, _kinds, _layouts, mut arguments| {
+                let n = pop_arg!(arguments, u64);
+                let events = vec![
+                    (b"ext".to_vec(), 0, TypeTag::U64, Value::u64(n)),
+                    (b"ext".to_vec(), 1, TypeTag::U64, Value::u64(n + 1)),
+                ];
+                Ok(NativeResult::ok_with_events(
+                    GasUnits::new(0),
+                    vec![],
+                    events,
+                ))
+            }),
+        )
+        .unwrap();
+
+    let result = table
+        .resolve(&ext_module(), ident("emit_twice"))
+        .unwrap()
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::u64(5)]),
+        )
+        .unwrap();
+    let events = result.events();
+    assert_eq!(events.len(), 2);
+    for (i, (key, seq_num, tag, value)) in events.iter().enumerate() {
+        assert_eq!(key, b"ext");
+        assert_eq!(*seq_num, i as u64);
+        assert_eq!(tag, &TypeTag::U64);
+        assert!(value.equals(&Value::u64(5 + i as u64)).unwrap());
+    }
+}
+
+#[test]
+fn registered_native_arity_is_checked() {
+    let mut table = NativeFunctionTable::new();
+    register_double(&mut table, ext_module()).unwrap();
+    let entry = table.resolve(&ext_module(), ident("double")).unwrap();
+    for arguments in vec![vec![], vec![Value::u64(1), Value::u64(2)]] {
+        let status = entry
+            .dispatch(
+                &mut MockNativeContext::for_testing(),
+                &[],
+                vec![],
+                vec![],
+                args(arguments),
+            )
+            .err()
+            .unwrap();
+        assert_eq!(
+            status.major_status,
+            StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+        );
+    }
+}
+
+#[test]
+fn registered_native_errors_are_charged() {
+    let mut table = NativeFunctionTable::new();
+    table
+        .register(
+            ext_module(),
+            Identifier::new("fail").unwrap(),
+            1,
+            u64_to_u64(),
+            Box::new(|_context, _ty_args, _kinds, _layouts, mut arguments| {
+                let status = match pop_arg!(arguments, u64) {
+                    0 => StatusCode::OUT_OF_GAS,
+                    _ => StatusCode::UNREACHABLE,
+                };
+                Err(VMStatus::new(status))
+            }),
+        )
+        .unwrap();
+    let entry = table.resolve(&ext_module(), ident("fail")).unwrap();
+
+    // an error is charged the base cost of the native, which is none for registered ones
+    let result = entry
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::u64(1)]),
+        )
+        .unwrap();
+    assert_eq!(result.cost.get(), 0);
+    assert_eq!(
+        result.result.err().unwrap().major_status,
+        StatusCode::UNREACHABLE
+    );
+
+    // running out of gas is charged the remaining gas
+    let result = entry
+        .dispatch(
+            &mut MockNativeContext::for_testing().with_remaining_gas(7),
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::u64(0)]),
+        )
+        .unwrap();
+    assert_eq!(result.cost.get(), 7);
+    assert_eq!(
+        result.result.err().unwrap().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+}
+
+#[test]
+fn builtins_are_preregistered() {
+    let table = NativeFunctionTable::new();
+    let hash = module(CORE_CODE_ADDRESS, "Hash");
+    match table.resolve(&hash, ident("sha3_256")) {
+        Some(NativeFunctionEntry::Builtin(NativeFunction::HashSha3_256)) => (),
+        _ => panic!("Hash::sha3_256 should resolve to the built-in native"),
+    }
+    for (module, name, _) in NativeFunction::declarations() {
+        let entry = table.resolve(&module, &name).unwrap();
+        let native = NativeFunction::resolve(&module, &name).unwrap();
+        assert_eq!(entry.num_args(), native.num_args());
+    }
+}
+
+#[test]
+fn gated_builtins_resolve_from_their_version() {
+    let mut table = NativeFunctionTable::new();
+    register_double(&mut table, ext_module()).unwrap();
+    let hash = module(CORE_CODE_ADDRESS, "Hash");
+    assert!(table
+        .resolve_with_version(&hash, ident("keccak_256"), 0)
+        .is_none());
+    let keccak = table
+        .resolve_with_version(&hash, ident("keccak_256"), 1)
+        .unwrap();
+    let status = keccak
+        .dispatch_with_version(
+            0,
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::vector_u8(vec![])]),
+        )
+        .err()
+        .unwrap();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_UNAVAILABLE);
+    assert_eq!(status.sub_status, Some(1));
+
+    // Registered natives exist at every version
+    let double = table
+        .resolve_with_version(&ext_module(), ident("double"), DEFAULT_NATIVE_VERSION)
+        .unwrap();
+    double
+        .dispatch_with_version(
+            DEFAULT_NATIVE_VERSION,
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(vec![Value::u64(1)]),
+        )
+        .unwrap();
+}
+
+#[test]
+fn duplicate_registration_is_rejected() {
+    let mut table = NativeFunctionTable::new();
+    register_double(&mut table, ext_module()).unwrap();
+    let status = register_double(&mut table, ext_module()).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::DUPLICATE_ELEMENT);
+
+    // The same name in another module is a different native
+    register_double(&mut table, module(AccountAddress::new([0xab; 32]), "Other")).unwrap();
+}
+
+#[test]
+fn core_natives_cannot_be_shadowed() {
+    let mut table = NativeFunctionTable::new();
+    for module in &[
+        module(CORE_CODE_ADDRESS, "Hash"),
+        module(CORE_CODE_ADDRESS, "Ext"),
+    ] {
+        let status = table
+            .register(
+                module.clone(),
+                Identifier::new("sha3_256").unwrap(),
+                1,
+                u64_to_u64(),
+                Box::new(|_, _, _, _, _| panic!("shadowing native should not be called")),
+            )
+            .unwrap_err();
+        assert_eq!(status.major_status, StatusCode::DUPLICATE_ELEMENT);
+    }
+    match table.resolve(&module(CORE_CODE_ADDRESS, "Hash"), ident("sha3_256")) {
+        Some(NativeFunctionEntry::Builtin(_)) => (),
+        _ => panic!("Hash::sha3_256 should still resolve to the built-in native"),
+    }
+}
+
+#[test]
+fn registration_arity_must_match_signature() {
+    let mut table = NativeFunctionTable::new();
+    let status = table
+        .register(
+            ext_module(),
+            Identifier::new("double").unwrap(),
+            2,
+            u64_to_u64(),
+            Box::new(|_, _, _, _, _| panic!("inconsistent native should not be called")),
+        )
+        .unwrap_err();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+    assert!(table.resolve(&ext_module(), ident("double")).is_none());
+}
+
+/// Embedder state that a registered native counts its calls in.
+#[derive(Debug, Default)]
+struct CallCounter(u64);
+
+#[test]
+fn registered_native_reads_extensions() {
+    let mut table = NativeFunctionTable::new();
+    table
+        .register(
+            ext_module(),
+            Identifier::new("count").unwrap(),
+            0,
+            FunctionSignature {
+                return_types: vec![SignatureToken::U64],
+                arg_types: vec![],
+                type_formals: vec![],
+            },
+            Box::new(|context, _, _, _, _| {
+                let cost = native_gas(context.cost_table(), NativeCostIndex::SHA3_256, 1);
+                let counter = match context.extensions() {
+                    Some(extensions) => extensions.get_mut::<CallCounter>(),
+                    None => None,
+                };
+                let count = counter.map_or(0, |counter| {
+                    counter.0 += 1;
+                    counter.0
+                });
+                Ok(NativeResult::ok(cost, vec![Value::u64(count)]))
+            }),
+        )
+        .unwrap();
+    let entry = table.resolve(&ext_module(), ident("count")).unwrap();
+    let count = |context: &mut MockNativeContext| {
+        let result = entry
+            .dispatch(context, &[], vec![], vec![], args(vec![]))
+            .unwrap();
+        let mut values = result.result.unwrap();
+        values.pop().unwrap().value_as::<u64>().unwrap()
+    };
+
+    let mut context = MockNativeContext::for_testing().with_extension(CallCounter::default());
+    assert_eq!(count(&mut context), 1);
+    assert_eq!(count(&mut context), 2);
+    assert_eq!(context.extensions.get::<CallCounter>().unwrap().0, 2);
+
+    // Without the state, the native sees no extension of its type
+    assert_eq!(count(&mut MockNativeContext::for_testing()), 0);
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "strict_natives"))]
+fn return_values_are_checked_against_signature() {
+    let mut table = NativeFunctionTable::new();
+    // Declares a `bool` but returns a `u64`
+    table
+        .register(
+            ext_module(),
+            Identifier::new("broken").unwrap(),
+            1,
+            FunctionSignature {
+                return_types: vec![SignatureToken::Bool],
+                ..u64_to_u64()
+            },
+            Box::new(|context, _, _, _, mut arguments| {
+                let n = pop_arg!(arguments, u64);
+                let cost = native_gas(context.cost_table(), NativeCostIndex::SHA3_256, 1);
+                Ok(NativeResult::ok(cost, vec![Value::u64(n)]))
+            }),
+        )
+        .unwrap();
+    // Returns its argument as a vector of its type argument
+    table
+        .register(
+            ext_module(),
+            Identifier::new("wrap").unwrap(),
+            1,
+            FunctionSignature {
+                return_types: vec![SignatureToken::Vector(Box::new(
+                    SignatureToken::TypeParameter(0),
+                ))],
+                arg_types: vec![SignatureToken::U64],
+                type_formals: vec![Kind::Unrestricted],
+            },
+            Box::new(|context, _, _, _, mut arguments| {
+                let n = pop_arg!(arguments, u64);
+                let cost = native_gas(context.cost_table(), NativeCostIndex::SHA3_256, 1);
+                Ok(NativeResult::ok(cost, vec![Value::vector_u64(vec![n])]))
+            }),
+        )
+        .unwrap();
+
+    let call = |name: &str, t: Vec<TypeTag>| {
+        let kinds = t.iter().map(|_| Kind::Unrestricted).collect();
+        table.resolve(&ext_module(), ident(name)).unwrap().dispatch(
+            &mut MockNativeContext::for_testing(),
+            &t,
+            kinds,
+            vec![],
+            args(vec![Value::u64(1)]),
+        )
+    };
+
+    let status = call("broken", vec![]).err().unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+    let msg = status.message.unwrap();
+    assert!(msg.contains("::Ext::broken"), "{}", msg);
+    assert!(msg.contains("position 0"), "{}", msg);
+
+    assert!(call("wrap", vec![TypeTag::U64]).unwrap().result.is_ok());
+    let status = call("wrap", vec![TypeTag::Bool]).err().unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+}
@@ -0,0 +1,121 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        context::{MockNativeContext, NativeContext},
+        dispatch::{NativeFunction, NativeResult},
+        transaction,
+    },
+    values::Value,
+};
+use libra_types::{account_address::AccountAddress, vm_error::StatusCode};
+use vm::gas_schedule::{GasAlgebra, GasCarrier, GasUnits};
+
+fn single_address(result: NativeResult) -> AccountAddress {
+    assert_eq!(result.cost.get(), 1);
+    let mut values = result.result.expect("native function should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<AccountAddress>().unwrap()
+}
+
+#[test]
+fn sender_reads_the_context() {
+    for &sender in &[AccountAddress::default(), AccountAddress::new([7; 32])] {
+        let context = MockNativeContext::new(sender);
+        let result = transaction::native_sender(&context, &[], args(vec![])).unwrap();
+        assert_eq!(single_address(result), sender);
+    }
+}
+
+#[test]
+fn sender_dispatches_with_context() {
+    let sender = AccountAddress::new([9; 32]);
+    let result = NativeFunction::TxnSender
+        .dispatch(
+            &mut MockNativeContext::new(sender),
+            &[],
+            vec![],
+            vec![],
+            args(vec![]),
+        )
+        .unwrap();
+    assert_eq!(single_address(result), sender);
+}
+
+#[test]
+fn sender_takes_no_arguments() {
+    let status = transaction::native_sender(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![Value::u64(1)]),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+}
+
+fn single_u64(result: NativeResult) -> u64 {
+    let mut values = result.result.expect("native function should succeed");
+    assert_eq!(values.len(), 1);
+    values.pop().unwrap().value_as::<u64>().unwrap()
+}
+
+#[test]
+fn max_gas_amount_reads_the_context() {
+    let context = MockNativeContext {
+        max_gas_amount: GasUnits::new(1_000_000),
+        ..MockNativeContext::for_testing()
+    };
+    let result = transaction::native_max_gas_amount(&context, &[], args(vec![])).unwrap();
+    assert_eq!(result.cost.get(), 1);
+    assert_eq!(single_u64(result), 1_000_000);
+}
+
+#[test]
+fn gas_remaining_is_net_of_its_own_cost() {
+    let context = MockNativeContext {
+        remaining_gas: GasUnits::new(100),
+        ..MockNativeContext::for_testing()
+    };
+    let result = transaction::native_gas_remaining(&context, &[], args(vec![])).unwrap();
+    assert_eq!(result.cost.get(), 1);
+    assert_eq!(single_u64(result), 99);
+
+    let context = MockNativeContext::for_testing();
+    let result = transaction::native_gas_remaining(&context, &[], args(vec![])).unwrap();
+    assert_eq!(single_u64(result), 0);
+}
+
+#[test]
+fn gas_remaining_decreases_across_calls() {
+    // Charge each call to the context, as the interpreter does between consecutive calls
+    let mut context = MockNativeContext {
+        remaining_gas: GasUnits::new(10),
+        ..MockNativeContext::for_testing()
+    };
+    let mut readings = vec![];
+    while context.remaining_gas.get() > 0 {
+        let result = NativeFunction::TxnGasRemaining
+            .dispatch(&mut context, &[], vec![], vec![], args(vec![]))
+            .unwrap();
+        context.remaining_gas = context.remaining_gas.sub(result.cost);
+        let remaining = single_u64(result);
+        assert_eq!(remaining, context.remaining_gas.get());
+        readings.push(remaining);
+    }
+    assert_eq!(readings, (0..10).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn context_for_testing_has_no_transaction_metadata() {
+    let context = <dyn NativeContext>::for_testing();
+    assert_eq!(context.sender(), AccountAddress::default());
+    assert_eq!(context.max_gas_amount().get(), 0);
+    assert_eq!(context.block_height(), None);
+    assert_eq!(context.remaining_gas().get(), GasCarrier::max_value());
+}
@@ -0,0 +1,68 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::args;
+use crate::{
+    native_functions::{
+        context::MockNativeContext,
+        dispatch::{NativeFunction, NativeResult},
+        unit_test,
+    },
+    values::Value,
+};
+use libra_types::account_config::CORE_CODE_ADDRESS;
+use std::collections::HashSet;
+use vm::gas_schedule::GasAlgebra;
+
+fn dispatch(native: NativeFunction, arguments: Vec<Value>) -> NativeResult {
+    native
+        .dispatch(
+            &mut MockNativeContext::for_testing(),
+            &[],
+            vec![],
+            vec![],
+            args(arguments),
+        )
+        .unwrap()
+}
+
+#[test]
+fn unit_test_natives_are_declared() {
+    let natives = [
+        (NativeFunction::UnitTestCreateSigners, 106),
+        (NativeFunction::UnitTestPoison, 107),
+    ];
+    for (native, id) in &natives {
+        assert!(NativeFunction::all().contains(native));
+        assert_eq!(native.stable_id(), *id);
+        assert_eq!(
+            native.to_string().parse::<NativeFunction>().unwrap(),
+            *native
+        );
+    }
+}
+
+#[test]
+fn create_signers_is_deterministic() {
+    let result = dispatch(NativeFunction::UnitTestCreateSigners, vec![Value::u64(3)]);
+    assert_eq!(result.cost.get(), 0);
+    let mut values = result.result.unwrap();
+    assert_eq!(values.len(), 1);
+    let expected = Value::vector_address((0..3).map(unit_test::test_signer));
+    assert!(values.pop().unwrap().equals(&expected).unwrap());
+
+    let signers: HashSet<_> = (0..3).map(unit_test::test_signer).collect();
+    assert_eq!(signers.len(), 3);
+    assert!(!signers.contains(&CORE_CODE_ADDRESS));
+
+    let result = dispatch(NativeFunction::UnitTestCreateSigners, vec![Value::u64(0)]);
+    let empty = Value::vector_address(vec![]);
+    assert!(result.result.unwrap()[0].equals(&empty).unwrap());
+}
+
+#[test]
+fn poison_does_nothing() {
+    let result = dispatch(NativeFunction::UnitTestPoison, vec![]);
+    assert_eq!(result.cost.get(), 0);
+    assert!(result.result.unwrap().is_empty());
+}
@@ -0,0 +1,396 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{args, unit_cost_table};
+use crate::{
+    native_functions::{
+        arguments::Arguments,
+        context::{MockNativeContext, NativeContext},
+        dispatch::{native_gas, NativeResult},
+        errors,
+    },
+    values::{vector, Locals, Struct, Value},
+};
+use libra_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    vm_error::StatusCode,
+};
+use vm::{
+    errors::VMResult,
+    gas_schedule::{GasAlgebra, NativeCostIndex},
+};
+
+type Native = fn(&dyn NativeContext, &[TypeTag], Arguments) -> VMResult<NativeResult>;
+
+/// A struct holding a 1 KiB byte vector, and its type tag.
+fn kib_struct() -> (TypeTag, Value) {
+    let tag = TypeTag::Struct(StructTag {
+        address: AccountAddress::default(),
+        module: Identifier::new("M").unwrap(),
+        name: Identifier::new("S").unwrap(),
+        type_params: vec![],
+    });
+    let value = Value::struct_(Struct::pack(vec![Value::vector_u8(vec![0; 1024])]));
+    (tag, value)
+}
+
+/// Calls `native` on a reference to `v` followed by `extra_args`, returning the charged cost.
+fn call_on_vector(native: Native, tag: TypeTag, v: Value, extra_args: Vec<Value>) -> u64 {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, v).unwrap();
+    let mut arguments = vec![locals.borrow_loc(0).unwrap()];
+    arguments.extend(extra_args);
+    let result = native(&MockNativeContext::for_testing(), &[tag], args(arguments)).unwrap();
+    assert!(result.result.is_ok());
+    result.cost.get()
+}
+
+#[test]
+fn push_back_gas_scales_with_element_size() {
+    let (tag, s) = kib_struct();
+    let struct_cost = call_on_vector(
+        vector::native_push_back,
+        tag,
+        Value::vector_vector_u8(vec![]),
+        vec![s],
+    );
+    let u64_cost = call_on_vector(
+        vector::native_push_back,
+        TypeTag::U64,
+        Value::vector_u64(vec![]),
+        vec![Value::u64(1)],
+    );
+    assert!(struct_cost >= 1024);
+    assert!(struct_cost > u64_cost);
+}
+
+#[test]
+fn insert_gas_scales_with_element_size() {
+    let (tag, s) = kib_struct();
+    let struct_cost = call_on_vector(
+        vector::native_insert,
+        tag,
+        Value::vector_vector_u8(vec![]),
+        vec![Value::u64(0), s],
+    );
+    let u64_cost = call_on_vector(
+        vector::native_insert,
+        TypeTag::U64,
+        Value::vector_u64(vec![]),
+        vec![Value::u64(0), Value::u64(1)],
+    );
+    assert!(struct_cost >= 1024);
+    assert!(struct_cost > u64_cost);
+}
+
+#[test]
+fn pop_back_gas_scales_with_element_size() {
+    let (tag, s) = kib_struct();
+    let mut locals = Locals::new(1);
+    locals
+        .store_loc(0, Value::vector_vector_u8(vec![]))
+        .unwrap();
+    vector::native_push_back(
+        &MockNativeContext::for_testing(),
+        &[tag.clone()],
+        args(vec![locals.borrow_loc(0).unwrap(), s]),
+    )
+    .unwrap();
+    let struct_cost = call_on_vector(vector::native_pop, tag, locals.move_loc(0).unwrap(), vec![]);
+    let u64_cost = call_on_vector(
+        vector::native_pop,
+        TypeTag::U64,
+        Value::vector_u64(vec![1]),
+        vec![],
+    );
+    assert!(struct_cost >= 1024);
+    assert!(struct_cost > u64_cost);
+}
+
+/// Sorts `v` through a reference to a local, returning the charged cost and the sorted vector.
+fn sort_u64(v: Vec<u64>) -> (u64, Value) {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::vector_u64(v)).unwrap();
+    let result = vector::native_sort_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+    .unwrap();
+    assert!(result.result.is_ok());
+    (result.cost.get(), locals.move_loc(0).unwrap())
+}
+
+#[test]
+fn sort_u64_small() {
+    let (_, sorted) = sort_u64(vec![]);
+    assert!(sorted.equals(&Value::vector_u64(vec![])).unwrap());
+
+    let (_, sorted) = sort_u64(vec![3, 1, 2, 1]);
+    assert!(sorted.equals(&Value::vector_u64(vec![1, 1, 2, 3])).unwrap());
+}
+
+#[test]
+fn sort_u64_gas() {
+    let table = unit_cost_table();
+    let gas = |units| native_gas(&table, NativeCostIndex::SORT_U64, units).get();
+
+    assert_eq!(sort_u64(vec![]).0, gas(1));
+    assert_eq!(sort_u64(vec![7]).0, gas(1));
+    assert_eq!(sort_u64(vec![2, 1]).0, gas(1 + 2));
+    assert_eq!(sort_u64(vec![3, 2, 1]).0, gas(1 + 3 * 2));
+
+    // 10k elements are charged 10000 * ceil(log2(10000)) units.
+    let (cost, sorted) = sort_u64((0..10_000).rev().collect());
+    assert_eq!(cost, gas(1 + 10_000 * 14));
+    assert!(sorted
+        .equals(&Value::vector_u64((0..10_000).collect()))
+        .unwrap());
+}
+
+/// Searches `v` for `x`, returning the charged cost, the found flag and the index.
+fn binary_search_u64(v: Vec<u64>, x: u64) -> (u64, bool, u64) {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::vector_u64(v)).unwrap();
+    let result = vector::native_binary_search_u64(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![locals.borrow_loc(0).unwrap(), Value::u64(x)]),
+    )
+    .unwrap();
+    let mut values = result.result.unwrap();
+    assert_eq!(values.len(), 2);
+    let idx = values.pop().unwrap().value_as::<u64>().unwrap();
+    let found = values.pop().unwrap().value_as::<bool>().unwrap();
+    (result.cost.get(), found, idx)
+}
+
+#[test]
+fn binary_search_u64_gas() {
+    let table = unit_cost_table();
+    let gas = |units| native_gas(&table, NativeCostIndex::BINARY_SEARCH_U64, units).get();
+
+    assert_eq!(binary_search_u64(vec![], 1), (gas(1), false, 0));
+    assert_eq!(binary_search_u64(vec![1], 1), (gas(1), true, 0));
+    assert_eq!(
+        binary_search_u64((0..10_000).map(|x| 2 * x).collect(), 2 * 9_999 + 1),
+        (gas(1 + 14), false, 10_000)
+    );
+}
+
+#[test]
+fn binary_search_u64_unsorted() {
+    // The result is unspecified, but the search must terminate with an index in range.
+    let (_, _, idx) = binary_search_u64(vec![5, 1, 4, 2, 3, 0], 3);
+    assert!(idx <= 6);
+}
+
+/// Calls `native` on a reference to `v`, returning the charged cost and the return values.
+fn aggregate_u64(native: Native, v: Value) -> (u64, Vec<Value>) {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, v).unwrap();
+    let result = native(
+        &MockNativeContext::for_testing(),
+        &[],
+        args(vec![locals.borrow_loc(0).unwrap()]),
+    )
+    .unwrap();
+    (result.cost.get(), result.result.unwrap())
+}
+
+#[test]
+fn aggregate_u64_gas() {
+    let table = unit_cost_table();
+    for &(native, index) in &[
+        (
+            vector::native_max_u64 as Native,
+            NativeCostIndex::VECTOR_MAX_U64,
+        ),
+        (vector::native_min_u64, NativeCostIndex::VECTOR_MIN_U64),
+        (vector::native_sum_u64, NativeCostIndex::VECTOR_SUM_U64),
+    ] {
+        let gas = |units| native_gas(&table, index, units).get();
+        assert_eq!(aggregate_u64(native, Value::vector_u64(vec![])).0, gas(1));
+        assert_eq!(
+            aggregate_u64(native, Value::vector_u64((0..10_000).collect())).0,
+            gas(1 + 10_000)
+        );
+    }
+}
+
+#[test]
+fn sum_u64_large() {
+    let (_, mut values) = aggregate_u64(
+        vector::native_sum_u64,
+        Value::vector_u64(vec![std::u64::MAX; 10_000]),
+    );
+    assert_eq!(
+        values.pop().unwrap().value_as::<u128>().unwrap(),
+        u128::from(std::u64::MAX) * 10_000
+    );
+}
+
+#[test]
+fn aggregate_u64_rejects_other_vectors() {
+    for &native in &[
+        vector::native_max_u64 as Native,
+        vector::native_min_u64,
+        vector::native_sum_u64,
+    ] {
+        let mut locals = Locals::new(1);
+        locals.store_loc(0, Value::vector_u8(vec![1, 2])).unwrap();
+        let result = native(
+            &MockNativeContext::for_testing(),
+            &[],
+            args(vec![locals.borrow_loc(0).unwrap()]),
+        )
+        .unwrap();
+        let status = result.result.unwrap_err();
+        assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+        assert_eq!(
+            status.sub_status,
+            Some(errors::vector::AGGREGATE_NON_U64_VEC)
+        );
+    }
+}
+
+#[test]
+fn borrow_mut_out_of_bounds_matches_borrow() {
+    let status = |native: Native| {
+        let mut locals = Locals::new(1);
+        locals.store_loc(0, Value::vector_u64(vec![1])).unwrap();
+        let result = native(
+            &MockNativeContext::for_testing(),
+            &[TypeTag::U64],
+            args(vec![locals.borrow_loc(0).unwrap(), Value::u64(1)]),
+        )
+        .unwrap();
+        let status = result.result.unwrap_err();
+        (status.major_status, status.sub_status)
+    };
+    assert_eq!(
+        status(vector::native_borrow_mut),
+        (
+            StatusCode::ABORTED,
+            Some(errors::vector::INDEX_OUT_OF_BOUNDS)
+        )
+    );
+    assert_eq!(
+        status(vector::native_borrow),
+        status(vector::native_borrow_mut)
+    );
+}
+
+/// A context allowing vectors of at most two elements.
+fn two_element_context() -> MockNativeContext {
+    MockNativeContext::for_testing().with_max_vector_length(2)
+}
+
+/// Calls a growth native in a context allowing vectors of at most two elements on a reference to
+/// `[1, 2]`, and checks that it fails without changing the vector.
+fn assert_too_long(native: Native, extra_args: Vec<Value>) {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::vector_u64(vec![1, 2])).unwrap();
+    let mut arguments = vec![locals.borrow_loc(0).unwrap()];
+    arguments.extend(extra_args);
+    let result = native(&two_element_context(), &[TypeTag::U64], args(arguments)).unwrap();
+
+    assert!(result.cost.get() > 0);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(errors::vector::VECTOR_TOO_LONG));
+    assert!(locals
+        .move_loc(0)
+        .unwrap()
+        .equals(&Value::vector_u64(vec![1, 2]))
+        .unwrap());
+}
+
+#[test]
+fn push_back_too_long() {
+    assert_too_long(vector::native_push_back, vec![Value::u64(3)]);
+}
+
+#[test]
+fn insert_too_long() {
+    assert_too_long(vector::native_insert, vec![Value::u64(0), Value::u64(3)]);
+}
+
+#[test]
+fn push_back_below_max_length() {
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, Value::vector_u64(vec![1])).unwrap();
+    let result = vector::native_push_back(
+        &two_element_context(),
+        &[TypeTag::U64],
+        args(vec![locals.borrow_loc(0).unwrap(), Value::u64(2)]),
+    )
+    .unwrap();
+    assert!(result.result.is_ok());
+    assert!(locals
+        .move_loc(0)
+        .unwrap()
+        .equals(&Value::vector_u64(vec![1, 2]))
+        .unwrap());
+}
+
+#[test]
+fn fill_is_capped_by_max_length() {
+    let fill = |n| {
+        vector::native_fill(
+            &two_element_context(),
+            &[TypeTag::U64],
+            args(vec![Value::u64(7), Value::u64(n)]),
+        )
+        .unwrap()
+    };
+    let mut values = fill(2).result.unwrap();
+    assert!(values
+        .pop()
+        .unwrap()
+        .equals(&Value::vector_u64(vec![7, 7]))
+        .unwrap());
+
+    let result = fill(3);
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(errors::vector::FILL_TOO_LONG));
+}
+
+#[test]
+fn destroy_non_empty() {
+    let context = MockNativeContext::for_testing();
+    let result = vector::native_destroy_empty(
+        &context,
+        &[TypeTag::U64],
+        args(vec![Value::vector_u64(vec![1, 2, 3])]),
+    )
+    .unwrap();
+
+    // Failing to destroy is charged like destroying.
+    assert_eq!(
+        result.cost.get(),
+        native_gas(&context.cost_table, NativeCostIndex::DESTROY_EMPTY, 1).get()
+    );
+    let status = result.result.unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(
+        status.sub_status,
+        Some(errors::vector::DESTROY_NON_EMPTY_VEC)
+    );
+    assert!(status.message.unwrap().contains("length 3"));
+}
+
+#[test]
+fn natives_reject_arguments_left_over() {
+    let context = MockNativeContext::for_testing();
+    let status = vector::native_empty(&context, &[TypeTag::U64], args(vec![Value::u64(1)]))
+        .err()
+        .unwrap();
+    assert_eq!(
+        status.major_status,
+        StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY
+    );
+}
@@ -3,18 +3,22 @@
 
 use crate::{
     loaded_data::{struct_def::StructDef, types::Type},
-    native_functions::dispatch::{native_gas, NativeResult},
+    native_functions::{
+        arguments::Arguments,
+        context::NativeContext,
+        dispatch::{native_gas, NativeResult},
+    },
 };
 use libra_types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     byte_array::ByteArray,
     language_storage::TypeTag,
-    vm_error::{sub_status::NFE_VECTOR_ERROR_BASE, StatusCode, VMStatus},
+    vm_error::{StatusCode, VMStatus},
 };
 use std::{
     cell::{Ref, RefCell, RefMut},
-    collections::VecDeque,
-    fmt::{self, Debug, Display},
+    cmp,
+    fmt::{self, Debug, Display, Write},
     iter,
     mem::size_of,
     ops::Add,
@@ -22,7 +26,7 @@ use std::{
 };
 use vm::{
     errors::*,
-    file_format::SignatureToken,
+    file_format::{Kind, SignatureToken},
     gas_schedule::{
         words_in, AbstractMemorySize, CostTable, GasAlgebra, GasCarrier, NativeCostIndex,
         CONST_SIZE, REFERENCE_SIZE, STRUCT_SIZE,
@@ -218,6 +222,81 @@ impl Value {
             _ => false,
         }
     }
+
+    /// Checks that the value has the shape of a value of type `sig`, whose type parameters are
+    /// instantiated with `ty_args`. Only the outer layout is compared: the fields of structs and
+    /// the elements of vectors are not inspected, and a type parameter `ty_args` does not cover
+    /// matches any value.
+    pub fn has_shape_of(&self, sig: &SignatureToken, ty_args: &[TypeTag]) -> bool {
+        use SignatureToken as S;
+
+        match (sig, &self.0) {
+            (S::TypeParameter(idx), _) => ty_args
+                .get(*idx as usize)
+                .map_or(true, |tag| self.has_shape_of_tag(tag)),
+            (S::U8, ValueImpl::U8(_))
+            | (S::U64, ValueImpl::U64(_))
+            | (S::U128, ValueImpl::U128(_))
+            | (S::Bool, ValueImpl::Bool(_))
+            | (S::Address, ValueImpl::Address(_))
+            | (S::ByteArray, ValueImpl::ByteArray(_)) => true,
+            (S::Reference(_), ValueImpl::ContainerRef(_))
+            | (S::Reference(_), ValueImpl::IndexedRef(_))
+            | (S::MutableReference(_), ValueImpl::ContainerRef(_))
+            | (S::MutableReference(_), ValueImpl::IndexedRef(_)) => true,
+            (S::Struct(_, _), ValueImpl::Container(r)) => r.borrow().is_general(),
+            (S::Vector(elem), ValueImpl::Container(r)) => {
+                let container = r.borrow();
+                match &**elem {
+                    S::TypeParameter(idx) => ty_args
+                        .get(*idx as usize)
+                        .map_or(true, |tag| container.is_vector_of(tag)),
+                    S::U8 => container.is_vector_of(&TypeTag::U8),
+                    S::U64 => container.is_vector_of(&TypeTag::U64),
+                    S::U128 => container.is_vector_of(&TypeTag::U128),
+                    S::Bool => container.is_vector_of(&TypeTag::Bool),
+                    _ => container.is_general(),
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn has_shape_of_tag(&self, tag: &TypeTag) -> bool {
+        match (tag, &self.0) {
+            (TypeTag::U8, ValueImpl::U8(_))
+            | (TypeTag::U64, ValueImpl::U64(_))
+            | (TypeTag::U128, ValueImpl::U128(_))
+            | (TypeTag::Bool, ValueImpl::Bool(_))
+            | (TypeTag::Address, ValueImpl::Address(_))
+            | (TypeTag::ByteArray, ValueImpl::ByteArray(_)) => true,
+            (TypeTag::Struct(_), ValueImpl::Container(r)) => r.borrow().is_general(),
+            (TypeTag::Vector(elem), ValueImpl::Container(r)) => r.borrow().is_vector_of(elem),
+            _ => false,
+        }
+    }
+}
+
+impl Container {
+    fn is_general(&self) -> bool {
+        match self {
+            Container::General(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the container has the layout of a vector of `elem`: the specialized one for the
+    /// primitive types that have one, the general one otherwise.
+    fn is_vector_of(&self, elem: &TypeTag) -> bool {
+        match (elem, self) {
+            (TypeTag::U8, Container::U8(_))
+            | (TypeTag::U64, Container::U64(_))
+            | (TypeTag::U128, Container::U128(_))
+            | (TypeTag::Bool, Container::Bool(_)) => true,
+            (TypeTag::U8, _) | (TypeTag::U64, _) | (TypeTag::U128, _) | (TypeTag::Bool, _) => false,
+            _ => self.is_general(),
+        }
+    }
 }
 
 /***************************************************************************************
@@ -253,6 +332,17 @@ impl ContainerRef {
             }
         }
     }
+
+    fn try_borrow_mut(&self) -> Option<RefMut<Container>> {
+        match self {
+            Self::Local(container) => container.try_borrow_mut().ok(),
+            Self::Global { container, status } => {
+                let r = container.try_borrow_mut().ok()?;
+                *status.borrow_mut() = GlobalDataStatus::Dirty;
+                Some(r)
+            }
+        }
+    }
 }
 
 /***************************************************************************************
@@ -856,6 +946,24 @@ impl Value {
     pub fn vector_u8(v: Vec<u8>) -> Self {
         Self(ValueImpl::new_container(Container::U8(v)))
     }
+
+    pub fn vector_u64(v: Vec<u64>) -> Self {
+        Self(ValueImpl::new_container(Container::U64(v)))
+    }
+
+    pub fn vector_vector_u8<I: IntoIterator<Item = Vec<u8>>>(vs: I) -> Self {
+        Self(ValueImpl::new_container(Container::General(
+            vs.into_iter()
+                .map(|v| ValueImpl::new_container(Container::U8(v)))
+                .collect(),
+        )))
+    }
+
+    pub fn vector_address<I: IntoIterator<Item = AccountAddress>>(addresses: I) -> Self {
+        Self(ValueImpl::new_container(Container::General(
+            addresses.into_iter().map(ValueImpl::Address).collect(),
+        )))
+    }
 }
 
 /***************************************************************************************
@@ -966,6 +1074,20 @@ impl VMValueCast<Vec<u8>> for Value {
     }
 }
 
+impl VMValueCast<Vec<Vec<u8>>> for Value {
+    fn cast(self) -> VMResult<Vec<Vec<u8>>> {
+        match self.0 {
+            ValueImpl::Container(r) => match take_unique_ownership(r)? {
+                Container::General(v) => v.into_iter().map(|v| Value(v).value_as()).collect(),
+                v => Err(VMStatus::new(StatusCode::INTERNAL_TYPE_ERROR)
+                    .with_message(format!("cannot cast {:?} to vector<vector<u8>>", v,))),
+            },
+            v => Err(VMStatus::new(StatusCode::INTERNAL_TYPE_ERROR)
+                .with_message(format!("cannot cast {:?} to vector<vector<u8>>", v,))),
+        }
+    }
+}
+
 impl Value {
     pub fn value_as<T>(self) -> VMResult<T>
     where
@@ -1329,32 +1451,18 @@ impl IntegerValue {
 
 pub mod vector {
     use super::*;
+    use crate::native_functions::errors::vector::*;
 
-    pub const INDEX_OUT_OF_BOUNDS: u64 = NFE_VECTOR_ERROR_BASE + 1;
-    pub const POP_EMPTY_VEC: u64 = NFE_VECTOR_ERROR_BASE + 2;
-    pub const DESTROY_NON_EMPTY_VEC: u64 = NFE_VECTOR_ERROR_BASE + 3;
+    /// Maximum number of elements a vector can grow to through `push_back` and `insert`, unless
+    /// the VM is configured with another `NativeContext::max_vector_length`.
+    pub const DEFAULT_MAX_VECTOR_LENGTH: usize = 1 << 20;
 
-    macro_rules! ensure_len {
-        ($v: expr, $expected_len: expr, $type: expr, $fn: expr) => {{
-            let actual_len = $v.len();
-            let expected_len = $expected_len;
-            if actual_len != expected_len {
-                let msg = format!(
-                    "wrong number of {} for {} expected {} found {}",
-                    ($type),
-                    ($fn),
-                    expected_len,
-                    actual_len,
-                );
-                return Err(VMStatus::new(StatusCode::UNREACHABLE).with_message(msg));
-            }
-        }};
-    }
+    /// Maximum number of elements `fill` creates in one call, if the VM allows vectors that long.
+    pub const MAX_FILL_LENGTH: u64 = 1 << 16;
 
-    macro_rules! pop_arg_front {
-        ($arguments:ident, $t:ty) => {
-            $arguments.pop_front().unwrap().value_as::<$t>()?
-        };
+    /// Rounds log2(n) up, treating the logarithm of 0 and 1 as 0.
+    fn ceil_log2(n: usize) -> usize {
+        n.next_power_of_two().trailing_zeros() as usize
     }
 
     macro_rules! err_vector_elem_ty_mismatch {
@@ -1369,14 +1477,13 @@ pub mod vector {
     }
 
     pub fn native_empty(
-        ty_args: Vec<TypeTag>,
-        args: VecDeque<Value>,
-        cost_table: &CostTable,
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "empty");
-        ensure_len!(args, 0, "arguments", "empty");
+        args.finish()?;
 
-        let cost = native_gas(cost_table, NativeCostIndex::EMPTY, 1);
+        let cost = native_gas(context.cost_table(), NativeCostIndex::EMPTY, 1);
         let container = match &ty_args[0] {
             TypeTag::U8 => Container::U8(vec![]),
             TypeTag::U64 => Container::U64(vec![]),
@@ -1392,16 +1499,77 @@ pub mod vector {
         ))
     }
 
-    pub fn native_length(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+    pub fn native_singleton(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "length");
-        ensure_len!(args, 1, "arguments", "length");
+        let e = args.pop()?;
+        args.finish()?;
+        let cost = context
+            .cost_table()
+            .native_cost(NativeCostIndex::SINGLETON)
+            .total()
+            .mul(e.size());
+        let container = match &ty_args[0] {
+            TypeTag::U8 => Container::U8(vec![e.value_as()?]),
+            TypeTag::U64 => Container::U64(vec![e.value_as()?]),
+            TypeTag::U128 => Container::U128(vec![e.value_as()?]),
+            TypeTag::Bool => Container::Bool(vec![e.value_as()?]),
+
+            _ => Container::General(vec![e.0]),
+        };
+
+        Ok(NativeResult::ok(
+            cost,
+            vec![Value(ValueImpl::new_container(container))],
+        ))
+    }
+
+    pub fn native_fill(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let n = args.pop_as::<u64>()?;
+        let e = args.pop()?;
+        args.finish()?;
+
+        // Lengths above the cap are charged as the cap, which also keeps the cost from overflowing.
+        let max_length = cmp::min(MAX_FILL_LENGTH, context.max_vector_length() as u64);
+        let cost = context
+            .cost_table()
+            .native_cost(NativeCostIndex::FILL)
+            .total()
+            .mul(e.size())
+            .mul(AbstractMemorySize::new(cmp::min(n, max_length)));
+        if n > max_length {
+            return Ok(NativeResult::abort(cost, FILL_TOO_LONG));
+        }
+        let n = n as usize;
+        let container = match &ty_args[0] {
+            TypeTag::U8 => Container::U8(vec![e.value_as()?; n]),
+            TypeTag::U64 => Container::U64(vec![e.value_as()?; n]),
+            TypeTag::U128 => Container::U128(vec![e.value_as()?; n]),
+            TypeTag::Bool => Container::Bool(vec![e.value_as()?; n]),
+
+            _ => Container::General(iter::repeat_with(|| e.0.copy_value()).take(n).collect()),
+        };
 
-        let cost = native_gas(cost_table, NativeCostIndex::LENGTH, 1);
-        let r = pop_arg_front!(args, ContainerRef);
+        Ok(NativeResult::ok(
+            cost,
+            vec![Value(ValueImpl::new_container(container))],
+        ))
+    }
+
+    pub fn native_length(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let cost = native_gas(context.cost_table(), NativeCostIndex::LENGTH, 1);
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
         let v = r.borrow();
 
         let len = match (&ty_args[0], &*v) {
@@ -1411,6 +1579,7 @@ pub mod vector {
             (TypeTag::Bool, Container::Bool(v)) => v.len(),
 
             (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
             | (TypeTag::ByteArray, Container::General(v))
             | (TypeTag::Address, Container::General(v)) => v.len(),
 
@@ -1421,21 +1590,25 @@ pub mod vector {
     }
 
     pub fn native_push_back(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "push back");
-        ensure_len!(args, 2, "arguments", "push back");
-
-        let r = pop_arg_front!(args, ContainerRef);
+        let e = args.pop()?;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
         let mut v = r.borrow_mut();
-        let e = args.pop_front().unwrap();
 
-        let cost = cost_table
+        // Computing the size walks the whole element, which is paid for since the charge grows
+        // with that size.
+        let cost = context
+            .cost_table()
             .native_cost(NativeCostIndex::PUSH_BACK)
             .total()
             .mul(e.size());
+        if v.len() >= context.max_vector_length() {
+            return Ok(NativeResult::abort(cost, VECTOR_TOO_LONG));
+        }
 
         match (&ty_args[0], &mut *v) {
             (TypeTag::U8, Container::U8(v)) => v.push(e.value_as()?),
@@ -1444,6 +1617,7 @@ pub mod vector {
             (TypeTag::Bool, Container::Bool(v)) => v.push(e.value_as()?),
 
             (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
             | (TypeTag::ByteArray, Container::General(v))
             | (TypeTag::Address, Container::General(v)) => v.push(e.0),
 
@@ -1454,48 +1628,65 @@ pub mod vector {
     }
 
     pub fn native_borrow(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "borrow");
-        ensure_len!(args, 2, "arguments", "borrow");
-
-        let cost = native_gas(cost_table, NativeCostIndex::BORROW, 1);
-        let r = pop_arg_front!(args, ContainerRef);
+        let cost = native_gas(context.cost_table(), NativeCostIndex::BORROW, 1);
+        let idx = args.pop_as::<u64>()? as usize;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
         let v = r.borrow();
-        let idx = pop_arg_front!(args, u64) as usize;
 
         // TODO: check if the type tag matches the real type?
         if idx >= v.len() {
-            return Ok(NativeResult::err(
-                cost,
-                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                    .with_sub_status(INDEX_OUT_OF_BOUNDS),
-            ));
+            return Ok(NativeResult::abort(cost, INDEX_OUT_OF_BOUNDS));
         }
         let v = Value(r.borrow_elem(idx)?);
 
         Ok(NativeResult::ok(cost, vec![v]))
     }
 
-    pub fn native_pop(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+    pub fn native_borrow_mut(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "pop");
-        ensure_len!(args, 1, "arguments", "pop");
+        let cost = native_gas(context.cost_table(), NativeCostIndex::BORROW_MUT, 1);
+        let idx = args.pop_as::<u64>()? as usize;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+
+        {
+            // The vector must not be borrowed by anything else while a mutable reference into it
+            // is handed out. This also marks global data as possibly modified.
+            let v = r.try_borrow_mut().ok_or_else(|| {
+                VMStatus::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("mutably borrowing an element of a shared vector".to_string())
+            })?;
+            if idx >= v.len() {
+                return Ok(NativeResult::abort(cost, INDEX_OUT_OF_BOUNDS));
+            }
+        }
+        let v = Value(r.borrow_elem(idx)?);
 
-        let cost = native_gas(cost_table, NativeCostIndex::POP_BACK, 1);
-        let r = pop_arg_front!(args, ContainerRef);
+        Ok(NativeResult::ok(cost, vec![v]))
+    }
+
+    pub fn native_pop(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
         let mut v = r.borrow_mut();
 
         macro_rules! err_pop_empty_vec {
             () => {
-                return Ok(NativeResult::err(
-                    cost,
-                    VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR).with_sub_status(POP_EMPTY_VEC),
+                return Ok(NativeResult::abort(
+                    native_gas(context.cost_table(), NativeCostIndex::POP_BACK, 1),
+                    POP_EMPTY_VEC,
                 ));
             };
         }
@@ -1519,6 +1710,7 @@ pub mod vector {
             },
 
             (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
             | (TypeTag::ByteArray, Container::General(v))
             | (TypeTag::Address, Container::General(v)) => match v.pop() {
                 Some(x) => Value(x),
@@ -1528,66 +1720,66 @@ pub mod vector {
             (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
         };
 
+        // Like `push_back`, popping is charged by the size of the element that is moved out.
+        let cost = context
+            .cost_table()
+            .native_cost(NativeCostIndex::POP_BACK)
+            .total()
+            .mul(res.size());
+
         Ok(NativeResult::ok(cost, vec![res]))
     }
 
     pub fn native_destroy_empty(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "destroy empty");
-        ensure_len!(args, 1, "arguments", "destroy empty");
-
-        let cost = native_gas(cost_table, NativeCostIndex::DESTROY_EMPTY, 1);
-        let v = args.pop_front().unwrap().value_as::<Container>()?;
+        let cost = native_gas(context.cost_table(), NativeCostIndex::DESTROY_EMPTY, 1);
+        let v = args.pop_as::<Container>()?;
+        args.finish()?;
 
-        let is_empty = match (&ty_args[0], &v) {
-            (TypeTag::U8, Container::U8(v)) => v.is_empty(),
-            (TypeTag::U64, Container::U64(v)) => v.is_empty(),
-            (TypeTag::U128, Container::U128(v)) => v.is_empty(),
-            (TypeTag::Bool, Container::Bool(v)) => v.is_empty(),
+        let len = match (&ty_args[0], &v) {
+            (TypeTag::U8, Container::U8(v)) => v.len(),
+            (TypeTag::U64, Container::U64(v)) => v.len(),
+            (TypeTag::U128, Container::U128(v)) => v.len(),
+            (TypeTag::Bool, Container::Bool(v)) => v.len(),
 
             (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
             | (TypeTag::ByteArray, Container::General(v))
-            | (TypeTag::Address, Container::General(v)) => v.is_empty(),
+            | (TypeTag::Address, Container::General(v)) => v.len(),
 
             (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
         };
 
-        if is_empty {
+        if len == 0 {
             Ok(NativeResult::ok(cost, vec![]))
         } else {
-            Ok(NativeResult::err(
+            Ok(NativeResult::abort_with_message(
                 cost,
-                VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                    .with_sub_status(DESTROY_NON_EMPTY_VEC),
+                DESTROY_NON_EMPTY_VEC,
+                format!("destroying a non-empty vector of length {}", len),
             ))
         }
     }
 
     pub fn native_swap(
-        ty_args: Vec<TypeTag>,
-        mut args: VecDeque<Value>,
-        cost_table: &CostTable,
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
     ) -> VMResult<NativeResult> {
-        ensure_len!(ty_args, 1, "type arguments", "swap");
-        ensure_len!(args, 3, "arguments", "swap");
-
-        let cost = native_gas(cost_table, NativeCostIndex::SWAP, 1);
-        let r = pop_arg_front!(args, ContainerRef);
+        let cost = native_gas(context.cost_table(), NativeCostIndex::SWAP, 1);
+        let idx2 = args.pop_as::<u64>()? as usize;
+        let idx1 = args.pop_as::<u64>()? as usize;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
         let mut v = r.borrow_mut();
-        let idx1 = pop_arg_front!(args, u64) as usize;
-        let idx2 = pop_arg_front!(args, u64) as usize;
 
         macro_rules! swap {
             ($v: ident) => {{
                 if idx1 >= $v.len() || idx2 >= $v.len() {
-                    return Ok(NativeResult::err(
-                        cost,
-                        VMStatus::new(StatusCode::NATIVE_FUNCTION_ERROR)
-                            .with_sub_status(INDEX_OUT_OF_BOUNDS),
-                    ));
+                    return Ok(NativeResult::abort(cost, INDEX_OUT_OF_BOUNDS));
                 }
                 $v.swap(idx1, idx2);
             }};
@@ -1599,6 +1791,7 @@ pub mod vector {
             (TypeTag::U128, Container::U128(v)) => swap!(v),
             (TypeTag::Bool, Container::Bool(v)) => swap!(v),
             (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
             | (TypeTag::Address, Container::General(v))
             | (TypeTag::ByteArray, Container::General(v)) => swap!(v),
             (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
@@ -1606,6 +1799,286 @@ pub mod vector {
 
         Ok(NativeResult::ok(cost, vec![]))
     }
+
+    pub fn native_truncate(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        ty_kinds: Vec<Kind>,
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let len = args.pop_as::<u64>()? as usize;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let mut v = r.borrow_mut();
+
+        let dropped = v.len().saturating_sub(len);
+        let cost = native_gas(context.cost_table(), NativeCostIndex::TRUNCATE, 1 + dropped);
+        if dropped == 0 {
+            return Ok(NativeResult::ok(cost, vec![]));
+        }
+        // Dropping resources would break linearity, so only vectors of unrestricted values can
+        // actually be shortened.
+        if ty_kinds[0] != Kind::Unrestricted {
+            return Ok(NativeResult::err_with_sub_status(
+                cost,
+                StatusCode::NATIVE_FUNCTION_ERROR,
+                TRUNCATE_RESOURCE_VEC,
+            ));
+        }
+
+        match (&ty_args[0], &mut *v) {
+            (TypeTag::U8, Container::U8(v)) => v.truncate(len),
+            (TypeTag::U64, Container::U64(v)) => v.truncate(len),
+            (TypeTag::U128, Container::U128(v)) => v.truncate(len),
+            (TypeTag::Bool, Container::Bool(v)) => v.truncate(len),
+
+            (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
+            | (TypeTag::ByteArray, Container::General(v))
+            | (TypeTag::Address, Container::General(v)) => v.truncate(len),
+
+            (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
+        }
+
+        Ok(NativeResult::ok(cost, vec![]))
+    }
+
+    pub fn native_insert(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let e = args.pop()?;
+        let idx = args.pop_as::<u64>()? as usize;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let mut v = r.borrow_mut();
+
+        // Charged by the size of the inserted element, plus one unit for every element at or after
+        // the index that is shifted up by one.
+        let shifted = AbstractMemorySize::new(v.len().saturating_sub(idx) as GasCarrier);
+        let cost = context
+            .cost_table()
+            .native_cost(NativeCostIndex::INSERT)
+            .total()
+            .mul(e.size().add(shifted));
+        if idx > v.len() {
+            return Ok(NativeResult::abort(cost, INDEX_OUT_OF_BOUNDS));
+        }
+        if v.len() >= context.max_vector_length() {
+            return Ok(NativeResult::abort(cost, VECTOR_TOO_LONG));
+        }
+
+        match (&ty_args[0], &mut *v) {
+            (TypeTag::U8, Container::U8(v)) => v.insert(idx, e.value_as()?),
+            (TypeTag::U64, Container::U64(v)) => v.insert(idx, e.value_as()?),
+            (TypeTag::U128, Container::U128(v)) => v.insert(idx, e.value_as()?),
+            (TypeTag::Bool, Container::Bool(v)) => v.insert(idx, e.value_as()?),
+
+            (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
+            | (TypeTag::ByteArray, Container::General(v))
+            | (TypeTag::Address, Container::General(v)) => v.insert(idx, e.0),
+
+            (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
+        }
+
+        Ok(NativeResult::ok(cost, vec![]))
+    }
+
+    pub fn native_rotate(
+        context: &dyn NativeContext,
+        ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let k = args.pop_as::<u64>()?;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let mut v = r.borrow_mut();
+
+        let len = v.len();
+        let cost = native_gas(context.cost_table(), NativeCostIndex::ROTATE, 1 + len);
+        if len == 0 {
+            return Ok(NativeResult::ok(cost, vec![]));
+        }
+        let mid = (k % len as u64) as usize;
+
+        match (&ty_args[0], &mut *v) {
+            (TypeTag::U8, Container::U8(v)) => v.rotate_left(mid),
+            (TypeTag::U64, Container::U64(v)) => v.rotate_left(mid),
+            (TypeTag::U128, Container::U128(v)) => v.rotate_left(mid),
+            (TypeTag::Bool, Container::Bool(v)) => v.rotate_left(mid),
+
+            (TypeTag::Struct(_), Container::General(v))
+            | (TypeTag::Vector(_), Container::General(v))
+            | (TypeTag::ByteArray, Container::General(v))
+            | (TypeTag::Address, Container::General(v)) => v.rotate_left(mid),
+
+            (tag, v) => err_vector_elem_ty_mismatch!(tag, v),
+        }
+
+        Ok(NativeResult::ok(cost, vec![]))
+    }
+
+    pub fn native_sort_u64(
+        context: &dyn NativeContext,
+        _ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let mut v = r.borrow_mut();
+
+        // Sorting is charged n * ceil(log2(n)) units, plus one so sorting a short vector is not
+        // free.
+        let len = v.len();
+        let units = len
+            .checked_mul(ceil_log2(len))
+            .and_then(|units| units.checked_add(1))
+            .ok_or_else(|| {
+                VMStatus::new(StatusCode::ARITHMETIC_ERROR)
+                    .with_message("sort_u64 gas computation overflowed".to_string())
+            })?;
+        let cost = native_gas(context.cost_table(), NativeCostIndex::SORT_U64, units);
+
+        match &mut *v {
+            Container::U64(v) => v.sort_unstable(),
+            _ => {
+                return Ok(NativeResult::err_with_sub_status(
+                    cost,
+                    StatusCode::NATIVE_FUNCTION_ERROR,
+                    SORT_NON_U64_VEC,
+                ))
+            }
+        }
+
+        Ok(NativeResult::ok(cost, vec![]))
+    }
+
+    pub fn native_binary_search_u64(
+        context: &dyn NativeContext,
+        _ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let x = args.pop_as::<u64>()?;
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let v = r.borrow();
+
+        let cost = native_gas(
+            context.cost_table(),
+            NativeCostIndex::BINARY_SEARCH_U64,
+            1 + ceil_log2(v.len()),
+        );
+
+        // The search always terminates, but on unsorted input the result is meaningless.
+        let (found, idx) = match &*v {
+            Container::U64(v) => match v.binary_search(&x) {
+                Ok(idx) => (true, idx),
+                Err(idx) => (false, idx),
+            },
+            _ => {
+                return Ok(NativeResult::err_with_sub_status(
+                    cost,
+                    StatusCode::NATIVE_FUNCTION_ERROR,
+                    BINARY_SEARCH_NON_U64_VEC,
+                ))
+            }
+        };
+
+        Ok(NativeResult::ok(
+            cost,
+            vec![Value::bool(found), Value::u64(idx as u64)],
+        ))
+    }
+
+    pub fn native_max_u64(
+        context: &dyn NativeContext,
+        _ty_args: &[TypeTag],
+        args: Arguments,
+    ) -> VMResult<NativeResult> {
+        extremum_u64(
+            NativeCostIndex::VECTOR_MAX_U64,
+            args,
+            context.cost_table(),
+            |v| v.iter().max(),
+        )
+    }
+
+    pub fn native_min_u64(
+        context: &dyn NativeContext,
+        _ty_args: &[TypeTag],
+        args: Arguments,
+    ) -> VMResult<NativeResult> {
+        extremum_u64(
+            NativeCostIndex::VECTOR_MIN_U64,
+            args,
+            context.cost_table(),
+            |v| v.iter().min(),
+        )
+    }
+
+    /// Returns whether the vector is non-empty, together with the element picked by `pick`, or
+    /// with zero if it is empty.
+    fn extremum_u64(
+        cost_index: NativeCostIndex,
+        mut args: Arguments,
+        cost_table: &CostTable,
+        pick: fn(&[u64]) -> Option<&u64>,
+    ) -> VMResult<NativeResult> {
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let v = r.borrow();
+
+        let cost = native_gas(cost_table, cost_index, 1 + v.len());
+        let (found, x) = match &*v {
+            Container::U64(v) => match pick(v) {
+                Some(x) => (true, *x),
+                None => (false, 0),
+            },
+            _ => {
+                return Ok(NativeResult::err_with_sub_status(
+                    cost,
+                    StatusCode::NATIVE_FUNCTION_ERROR,
+                    AGGREGATE_NON_U64_VEC,
+                ))
+            }
+        };
+
+        Ok(NativeResult::ok(
+            cost,
+            vec![Value::bool(found), Value::u64(x)],
+        ))
+    }
+
+    pub fn native_sum_u64(
+        context: &dyn NativeContext,
+        _ty_args: &[TypeTag],
+        mut args: Arguments,
+    ) -> VMResult<NativeResult> {
+        let r = args.pop_as::<ContainerRef>()?;
+        args.finish()?;
+        let v = r.borrow();
+
+        let cost = native_gas(
+            context.cost_table(),
+            NativeCostIndex::VECTOR_SUM_U64,
+            1 + v.len(),
+        );
+        // A u128 cannot overflow while summing fewer than 2^64 u64s.
+        let sum = match &*v {
+            Container::U64(v) => v.iter().map(|x| u128::from(*x)).sum(),
+            _ => {
+                return Ok(NativeResult::err_with_sub_status(
+                    cost,
+                    StatusCode::NATIVE_FUNCTION_ERROR,
+                    AGGREGATE_NON_U64_VEC,
+                ))
+            }
+        };
+
+        Ok(NativeResult::ok(cost, vec![Value::u128(sum)]))
+    }
 }
 
 /***************************************************************************************
@@ -1873,6 +2346,100 @@ impl Display for Locals {
     }
 }
 
+impl Value {
+    /// Render the value as `layout` for people to read, as in debug output. Unlike `Display`,
+    /// this relies on the layout rather than the internal representation:
+    /// - `u64`s and `bool`s are written as is, `u8`s and `u128`s with a `u8` or `u128` suffix
+    /// - addresses are `0x` followed by 64 hex digits, byte arrays are `b"<hex>"`
+    /// - vectors are `[a, b]` and structs are their fields in braces, `{a, b}`
+    ///
+    /// Returns `None` if the value does not match `layout`.
+    pub fn pretty_string(&self, layout: &Type) -> Option<String> {
+        let mut out = String::new();
+        self.0.write_pretty(layout, &mut out)?;
+        Some(out)
+    }
+}
+
+impl ValueImpl {
+    fn write_pretty(&self, layout: &Type, out: &mut String) -> Option<()> {
+        match (layout, self) {
+            (Type::U8, ValueImpl::U8(x)) => write!(out, "{}u8", x).ok(),
+            (Type::U64, ValueImpl::U64(x)) => write!(out, "{}", x).ok(),
+            (Type::U128, ValueImpl::U128(x)) => write!(out, "{}u128", x).ok(),
+            (Type::Bool, ValueImpl::Bool(x)) => write!(out, "{}", x).ok(),
+            (Type::Address, ValueImpl::Address(x)) => write!(out, "0x{}", x).ok(),
+            (Type::ByteArray, ValueImpl::ByteArray(x)) => write!(out, "{}", x).ok(),
+
+            (Type::Struct(layout), ValueImpl::Container(r)) => match (layout, &*r.borrow()) {
+                (StructDef::Struct(inner), Container::General(v))
+                    if inner.field_definitions().len() == v.len() =>
+                {
+                    write_pretty_list(
+                        out,
+                        ('{', '}'),
+                        inner.field_definitions().iter().zip(v.iter()),
+                        |out, (layout, val)| val.write_pretty(layout, out),
+                    )
+                }
+                _ => None,
+            },
+
+            (Type::Vector(layout), ValueImpl::Container(r)) => {
+                let layout = &**layout;
+                let brackets = ('[', ']');
+                match (layout, &*r.borrow()) {
+                    (Type::Vector(_), Container::General(v))
+                    | (Type::Struct(_), Container::General(v))
+                    | (Type::Address, Container::General(v))
+                    | (Type::ByteArray, Container::General(v)) => {
+                        write_pretty_list(out, brackets, v, |out, val| {
+                            val.write_pretty(layout, out)
+                        })
+                    }
+
+                    (Type::U8, Container::U8(v)) => {
+                        write_pretty_list(out, brackets, v, |out, x| write!(out, "{}u8", x).ok())
+                    }
+                    (Type::U64, Container::U64(v)) => {
+                        write_pretty_list(out, brackets, v, |out, x| write!(out, "{}", x).ok())
+                    }
+                    (Type::U128, Container::U128(v)) => {
+                        write_pretty_list(out, brackets, v, |out, x| write!(out, "{}u128", x).ok())
+                    }
+                    (Type::Bool, Container::Bool(v)) => {
+                        write_pretty_list(out, brackets, v, |out, x| write!(out, "{}", x).ok())
+                    }
+
+                    _ => None,
+                }
+            }
+
+            _ => None,
+        }
+    }
+}
+
+fn write_pretty_list<T, I>(
+    out: &mut String,
+    (open, close): (char, char),
+    items: I,
+    mut write_item: impl FnMut(&mut String, T) -> Option<()>,
+) -> Option<()>
+where
+    I: IntoIterator<Item = T>,
+{
+    out.push(open);
+    for (idx, item) in items.into_iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+        write_item(out, item)?;
+    }
+    out.push(close);
+    Some(())
+}
+
 /***************************************************************************************
  *
  * Serialization & Deserialization
@@ -1923,6 +2490,77 @@ impl Struct {
     }
 }
 
+impl Value {
+    /// Compute the length of `simple_serialize(layout)` without producing the bytes. Returns
+    /// `None` exactly when `simple_serialize` would fail.
+    pub fn serialized_size(&self, layout: &Type) -> Option<usize> {
+        self.0.serialized_size(layout)
+    }
+}
+
+/// Size of the length prefix LCS writes before a sequence of `len` elements.
+fn serialized_seq_len_size(len: usize) -> Option<usize> {
+    if len > lcs::MAX_SEQUENCE_LENGTH {
+        None
+    } else {
+        Some(size_of::<u32>())
+    }
+}
+
+impl ValueImpl {
+    fn serialized_size(&self, layout: &Type) -> Option<usize> {
+        match (layout, self) {
+            (Type::U8, ValueImpl::U8(_)) => Some(size_of::<u8>()),
+            (Type::U64, ValueImpl::U64(_)) => Some(size_of::<u64>()),
+            (Type::U128, ValueImpl::U128(_)) => Some(size_of::<u128>()),
+            (Type::Bool, ValueImpl::Bool(_)) => Some(size_of::<u8>()),
+            (Type::Address, ValueImpl::Address(_)) => Some(ADDRESS_LENGTH),
+            (Type::ByteArray, ValueImpl::ByteArray(x)) => {
+                Some(serialized_seq_len_size(x.len())? + x.len())
+            }
+
+            (Type::Struct(layout), ValueImpl::Container(r)) => match (layout, &*r.borrow()) {
+                (StructDef::Struct(inner), Container::General(v))
+                    if inner.field_definitions().len() == v.len() =>
+                {
+                    inner
+                        .field_definitions()
+                        .iter()
+                        .zip(v.iter())
+                        .try_fold(0, |size, (layout, val)| {
+                            Some(size + val.serialized_size(layout)?)
+                        })
+                }
+                _ => None,
+            },
+
+            (Type::Vector(layout), ValueImpl::Container(r)) => {
+                let layout = &**layout;
+                let (len, elements_size) = match (layout, &*r.borrow()) {
+                    (Type::Vector(_), Container::General(v))
+                    | (Type::Struct(_), Container::General(v))
+                    | (Type::Address, Container::General(v))
+                    | (Type::ByteArray, Container::General(v)) => (
+                        v.len(),
+                        v.iter()
+                            .try_fold(0, |size, val| Some(size + val.serialized_size(layout)?))?,
+                    ),
+
+                    (Type::U8, Container::U8(v)) => (v.len(), v.len() * size_of::<u8>()),
+                    (Type::U64, Container::U64(v)) => (v.len(), v.len() * size_of::<u64>()),
+                    (Type::U128, Container::U128(v)) => (v.len(), v.len() * size_of::<u128>()),
+                    (Type::Bool, Container::Bool(v)) => (v.len(), v.len() * size_of::<u8>()),
+
+                    _ => return None,
+                };
+                Some(serialized_seq_len_size(len)? + elements_size)
+            }
+
+            _ => None,
+        }
+    }
+}
+
 struct AnnotatedValue<'a, 'b, T1, T2> {
     layout: &'a T1,
     val: &'b T2,
@@ -1,9 +1,20 @@
 address 0x0:
 
 // A variable-sized container that can hold both unrestricted types and resources.
+// The natives abort with codes from 0x0EC70000 up: 0x0EC70001 for an index out of bounds,
+// 0x0EC70002 for popping an empty vector, 0x0EC70003 for destroying a non-empty vector,
+// 0x0EC70005 for filling a vector that would be too long, and 0x0EC70008 for growing a vector
+// past the maximum length. Truncating a vector of resources fails with a NATIVE_FUNCTION_ERROR
+// of sub-status 0x0EC70004.
 module Vector {
     native public fun empty<Element>(): vector<Element>;
 
+    // Return a vector containing only e.
+    native public fun singleton<Element>(e: Element): vector<Element>;
+
+    // Return a vector containing n copies of e. Aborts if n is greater than 65536.
+    native public fun fill<Element: copyable>(e: Element, n: u64): vector<Element>;
+
     // Return the length of the vector.
     native public fun length<Element>(v: &vector<Element>): u64;
 
@@ -25,6 +36,36 @@ module Vector {
     // Swaps the elements at the i'th and j'th indices in the vector.
     native public fun swap<Element>(v: &mut vector<Element>, i: u64, j: u64);
 
+    // Inserts e before the i'th element, or at the end if i is the length of the vector.
+    // Aborts if i is greater than the length.
+    native public fun insert<Element>(v: &mut vector<Element>, i: u64, e: Element);
+
+    // Drops the elements after the first len, or does nothing if len is at least the length of the
+    // vector. Fails if v holds resources and elements would be dropped.
+    native public fun truncate<Element>(v: &mut vector<Element>, len: u64);
+
+    // Rotates the elements of the vector left by k positions, so the element at index k % length
+    // moves to the front. Rotating an empty vector does nothing.
+    native public fun rotate<Element>(v: &mut vector<Element>, k: u64);
+
+    // Sorts the vector of u64s in ascending order in place.
+    native public fun sort_u64(v: &mut vector<u64>);
+
+    // Searches the sorted vector of u64s for x. Returns whether x was found, and either its index
+    // or the index at which x would be inserted to keep the vector sorted.
+    native public fun binary_search_u64(v: &vector<u64>, x: u64): (bool, u64);
+
+    // Returns whether the vector of u64s is non-empty, together with its largest element, or with
+    // 0 if it is empty.
+    native public fun max_u64(v: &vector<u64>): (bool, u64);
+
+    // Returns whether the vector of u64s is non-empty, together with its smallest element, or with
+    // 0 if it is empty.
+    native public fun min_u64(v: &vector<u64>): (bool, u64);
+
+    // Returns the sum of the vector of u64s, which cannot overflow a u128.
+    native public fun sum_u64(v: &vector<u64>): u128;
+
     // Reverses the order of the elements in the vector in place.
     public fun reverse<Element>(v: &mut vector<Element>) {
         let len = length(v);
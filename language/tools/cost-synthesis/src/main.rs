@@ -16,14 +16,11 @@ use cost_synthesis::{
 };
 use language_e2e_tests::data_store::FakeDataStore;
 use libra_types::vm_error::StatusCode;
-use move_vm_types::{native_functions::hash, values::Value};
-use std::{
-    collections::{HashMap, VecDeque},
-    convert::TryFrom,
-    path::Path,
-    time::Instant,
-    u64,
+use move_vm_types::{
+    native_functions::{context::MockNativeContext, dispatch::NativeArguments, hash},
+    values::Value,
 };
+use std::{collections::HashMap, convert::TryFrom, path::Path, time::Instant, u64};
 use stdlib::env_stdlib_modules;
 use structopt::StructOpt;
 use vm::{
@@ -196,7 +193,7 @@ fn stack_instructions(options: &Opt) {
     // load the entry point
     let entry_idx = FunctionDefinitionIndex::new(0);
     let entry_func = FunctionRef::new(&loaded_module, entry_idx);
-    vm.push_frame(entry_func, vec![], vec![]);
+    vm.push_frame(entry_func, vec![], vec![], vec![]);
 
     let costs: HashMap<String, Vec<u64>> = stack_opcodes
         .into_iter()
@@ -255,15 +252,15 @@ fn stack_instructions(options: &Opt) {
 macro_rules! bench_native {
     ($name:expr, $function:path, $table:ident, $iters:expr) => {
         let mut stack_access = StackAccessorMocker::new();
-        let cost_table = CostTable::zero();
+        let context = MockNativeContext::for_testing().with_cost_table(CostTable::zero());
         let per_byte_costs: Vec<u64> = (1..512)
             .map(|i| {
                 stack_access.set_hash_length(i);
                 let time = (0..$iters).fold(0, |acc, _| {
                     let before = Instant::now();
-                    let mut args = VecDeque::new();
-                    args.push_front(Value::byte_array(stack_access.next_bytearray()));
-                    let _ = $function(vec![], args, &cost_table);
+                    let mut args = NativeArguments::new();
+                    args.push(Value::byte_array(stack_access.next_bytearray()));
+                    let _ = $function(&context, &[], args.into());
                     acc + before.elapsed().as_nanos()
                 });
                 // Time per byte averaged over the number of iterations that we performed.
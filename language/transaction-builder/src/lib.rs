@@ -3,7 +3,9 @@
 
 #![forbid(unsafe_code)]
 
-use libra_config::config::{VMConfig, VMPublishingOption};
+use libra_config::config::{
+    VMConfig, VMPublishingOption, DEFAULT_MAX_VECTOR_LENGTH, DEFAULT_NATIVE_VERSION,
+};
 use libra_crypto::HashValue;
 use libra_types::{
     account_address::AccountAddress,
@@ -220,5 +222,7 @@ pub fn default_config() -> VMConfig {
         publishing_options: VMPublishingOption::Locked(HashSet::from_iter(
             allowing_script_hashes().into_iter(),
         )),
+        max_vector_length: DEFAULT_MAX_VECTOR_LENGTH,
+        native_version: DEFAULT_NATIVE_VERSION,
     }
 }
@@ -1236,7 +1236,7 @@ pub enum Bytecode {
     Shr,
 }
 
-pub const NUMBER_OF_NATIVE_FUNCTIONS: usize = 17;
+pub const NUMBER_OF_NATIVE_FUNCTIONS: usize = 104;
 
 impl ::std::fmt::Debug for Bytecode {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
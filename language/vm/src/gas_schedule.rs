@@ -503,4 +503,91 @@ pub enum NativeCostIndex {
     SWAP = 14,
     WRITE_TO_EVENT_STORE = 15,
     SAVE_ACCOUNT = 16,
+    KECCAK_256 = 17,
+    SHA2_512 = 18,
+    RIPEMD_160 = 19,
+    BLAKE2B_256 = 20,
+    BLAKE3 = 21,
+    SHA3_512 = 22,
+    HMAC_SHA256 = 23,
+    HKDF_SHA256 = 24,
+    SECP256K1_RECOVER = 25,
+    BLS12381_AGGREGATE_VERIFY = 26,
+    MULTI_ED25519_VERIFY = 27,
+    SCHNORR_VERIFY = 28,
+    RISTRETTO_POINT_ADD = 29,
+    RISTRETTO_SCALAR_MUL = 30,
+    RISTRETTO_POINT_IS_VALID = 31,
+    PEDERSEN_VERIFY = 32,
+    ECVRF_VERIFY = 33,
+    SHA3_256_WITH_DOMAIN = 34,
+    BYTEARRAY_CONSTANT_TIME_EQ = 35,
+    U128_TO_BYTES = 36,
+    U64_TO_BYTES_BE = 37,
+    BYTES_TO_U64 = 38,
+    U128_FROM_BYTES = 39,
+    ADDRESS_FROM_BYTES = 40,
+    BASE58_ENCODE = 41,
+    BASE58_DECODE = 42,
+    BECH32_ENCODE = 43,
+    BECH32_DECODE = 44,
+    BASE64_ENCODE = 45,
+    BASE64_DECODE = 46,
+    LCS_TO_BYTES = 47,
+    LCS_FROM_BYTES_U64 = 48,
+    LCS_FROM_BYTES_BOOL = 49,
+    LCS_FROM_BYTES_ADDRESS = 50,
+    BYTEARRAY_SLICE = 51,
+    BYTEARRAY_INDEX_OF = 52,
+    BYTEARRAY_XOR = 53,
+    BYTEARRAY_AND = 54,
+    BYTEARRAY_OR = 55,
+    STRING_IS_VALID_UTF8 = 56,
+    STRING_IS_ASCII = 57,
+    STRING_TO_UPPERCASE_ASCII = 58,
+    U64_FROM_DECIMAL_STRING = 59,
+    U64_TO_DECIMAL_STRING = 60,
+    LCS_SERIALIZED_SIZE = 61,
+    INSERT = 62,
+    SINGLETON = 63,
+    TRUNCATE = 64,
+    FILL = 65,
+    ROTATE = 66,
+    SORT_U64 = 67,
+    BINARY_SEARCH_U64 = 68,
+    U64_CHECKED_ADD = 69,
+    U64_CHECKED_MUL = 70,
+    U128_CHECKED_ADD = 71,
+    U128_CHECKED_MUL = 72,
+    POW_U64 = 73,
+    SQRT_U128 = 74,
+    LOG2_FLOOR_U64 = 75,
+    LEADING_ZEROS_U64 = 76,
+    POPCOUNT_U64 = 77,
+    FIXED_POINT32_CREATE_FROM_RATIONAL = 78,
+    FIXED_POINT32_MULTIPLY = 79,
+    FIXED_POINT32_DIVIDE = 80,
+    ADD_MOD_U128 = 81,
+    MUL_MOD_U128 = 82,
+    BIGUINT_ADD = 83,
+    BIGUINT_MUL = 84,
+    BIGUINT_CMP = 85,
+    BIGUINT_MOD = 86,
+    DIV_MOD_U128 = 87,
+    U128_TO_U64 = 88,
+    MUL_DIV_U64 = 89,
+    VECTOR_MAX_U64 = 90,
+    VECTOR_MIN_U64 = 91,
+    VECTOR_SUM_U64 = 92,
+    REFLECT_TYPE_OF = 93,
+    REFLECT_TYPE_NAME = 94,
+    REFLECT_TYPES_ARE_EQUAL = 95,
+    TXN_SENDER = 96,
+    TXN_MAX_GAS_AMOUNT = 97,
+    TXN_GAS_REMAINING = 98,
+    BLOCK_GET_HEIGHT = 99,
+    BLOCK_GET_TIMESTAMP_MICROS = 100,
+    EVENT_GUID = 101,
+    ACCOUNT_ADDRESS_FROM_AUTH_KEY = 102,
+    COMPARE_GENERIC = 103,
 }
@@ -12,7 +12,10 @@ use crate::{
         loaded_module::LoadedModule,
     },
     runtime::VMRuntime,
-    system_module_names::{EMIT_EVENT_NAME, SAVE_ACCOUNT_NAME},
+    system_module_names::{
+        BLOCK_METADATA_NAME, CURRENT_TIME_MICROSECONDS_NAME, LIBRA_BLOCK_MODULE,
+        LIBRA_TIMESTAMP_MODULE,
+    },
 };
 use libra_logger::prelude::*;
 use libra_types::{
@@ -28,22 +31,27 @@ use libra_types::{
 };
 use move_vm_types::{
     loaded_data::{struct_def::StructDef, types::Type},
-    native_functions::dispatch::NativeFunction,
+    native_functions::{
+        context::{CallFrame, EventSink, NativeContext, NativeContextExtensions, ResourceWriter},
+        dispatch::{NativeArguments, NativeFunction},
+        layout::{resolve_layout, TypeLayoutResolver},
+        table::NativeFunctionEntry,
+    },
     type_context::TypeContext,
     values::{IntegerValue, Locals, Reference, Struct, StructRef, VMValueCast, Value},
 };
 #[cfg(any(test, feature = "instruction_synthesis"))]
 use std::collections::HashMap;
-use std::{collections::VecDeque, convert::TryFrom, marker::PhantomData};
+use std::{convert::TryFrom, iter, marker::PhantomData};
 use vm::{
     access::ModuleAccess,
     errors::*,
     file_format::{
-        Bytecode, FunctionHandleIndex, LocalIndex, LocalsSignatureIndex, SignatureToken,
+        Bytecode, FunctionHandleIndex, Kind, LocalIndex, LocalsSignatureIndex, SignatureToken,
         StructDefinitionIndex,
     },
     gas_schedule::{
-        calculate_intrinsic_gas, AbstractMemorySize, CostTable, GasAlgebra, GasCarrier,
+        calculate_intrinsic_gas, AbstractMemorySize, CostTable, GasAlgebra, GasCarrier, GasUnits,
         NativeCostIndex, Opcodes,
     },
     transaction_metadata::TransactionMetadata,
@@ -100,6 +108,110 @@ fn derive_type_tag(
     }
 }
 
+/// Resolves layouts from the modules the runtime loads.
+struct RuntimeLayoutResolver<'a, 'alloc> {
+    runtime: &'a VMRuntime<'alloc>,
+    data_view: &'a dyn InterpreterContext,
+}
+
+impl TypeLayoutResolver for RuntimeLayoutResolver<'_, '_> {
+    fn type_to_layout(&self, tag: &TypeTag) -> VMResult<Type> {
+        resolve_layout(tag, &|struct_tag| {
+            let module_id = ModuleId::new(struct_tag.address, struct_tag.module.clone());
+            let module = self.runtime.get_loaded_module(&module_id, self.data_view)?;
+            let idx = module.get_struct_def_index(&struct_tag.name)?;
+            let type_actuals = struct_tag
+                .type_params
+                .iter()
+                .map(|tag| self.type_to_layout(tag))
+                .collect::<VMResult<_>>()?;
+            self.runtime
+                .resolve_struct_def(module, *idx, type_actuals, self.data_view)
+        })
+    }
+}
+
+/// The metadata of the block a transaction executes in, as the block prologue stores it on chain.
+/// It is only known to the natives that read it, see `NativeFunction::reads_block_metadata`.
+#[derive(Clone, Copy, Debug, Default)]
+struct BlockInfo {
+    height: Option<u64>,
+    timestamp_micros: Option<u64>,
+}
+
+/// The `NativeContext` of the native functions called by an `Interpreter`.
+struct InterpreterNativeContext<'a, 'txn> {
+    cost_table: &'txn CostTable,
+    block_info: BlockInfo,
+    layout_resolver: &'a dyn TypeLayoutResolver,
+    txn_data: &'txn TransactionMetadata,
+    remaining_gas: GasUnits<GasCarrier>,
+    call_stack: &'a CallStack<'txn>,
+    caller: &'a Frame<'txn, FunctionRef<'txn>>,
+    max_vector_length: usize,
+}
+
+impl NativeContext for InterpreterNativeContext<'_, '_> {
+    fn cost_table(&self) -> &CostTable {
+        self.cost_table
+    }
+
+    fn sender(&self) -> AccountAddress {
+        self.txn_data.sender()
+    }
+
+    fn max_gas_amount(&self) -> GasUnits<GasCarrier> {
+        self.txn_data.max_gas_amount()
+    }
+
+    fn remaining_gas(&self) -> GasUnits<GasCarrier> {
+        self.remaining_gas
+    }
+
+    fn block_height(&self) -> Option<u64> {
+        self.block_info.height
+    }
+
+    fn block_timestamp_micros(&self) -> Option<u64> {
+        self.block_info.timestamp_micros
+    }
+
+    fn call_stack(&self) -> Option<Vec<CallFrame>> {
+        let frames = self.call_stack.0.iter().chain(iter::once(self.caller));
+        Some(
+            frames
+                .map(|frame| CallFrame {
+                    module: frame.module().self_id(),
+                    function: frame.function.name().to_owned(),
+                    // The pc of a frame is already past the call it is executing
+                    pc: frame.pc.saturating_sub(1),
+                })
+                .collect(),
+        )
+    }
+
+    fn max_vector_length(&self) -> usize {
+        self.max_vector_length
+    }
+
+    // `write_to_event_store` is executed by the interpreter, which emits to the data store
+    fn event_sink(&mut self) -> Option<&mut dyn EventSink> {
+        None
+    }
+
+    fn layout_resolver(&self) -> &dyn TypeLayoutResolver {
+        self.layout_resolver
+    }
+
+    fn resource_writer(&mut self) -> Option<&mut dyn ResourceWriter> {
+        None
+    }
+
+    fn extensions(&mut self) -> Option<&mut NativeContextExtensions> {
+        None
+    }
+}
+
 /// `Interpreter` instances can execute Move functions.
 ///
 /// An `Interpreter` instance is a stand alone execution context for a function.
@@ -120,6 +232,9 @@ pub(crate) struct Interpreter<'txn> {
     /// GetTxnSenderAddress, ...)
     txn_data: &'txn TransactionMetadata,
     gas_schedule: &'txn CostTable,
+    /// The block metadata handed to natives, read from chain when a native reading it is first
+    /// called.
+    block_info: Option<BlockInfo>,
 }
 
 impl<'txn> Interpreter<'txn> {
@@ -183,6 +298,7 @@ impl<'txn> Interpreter<'txn> {
             call_stack: CallStack::new(),
             gas_schedule,
             txn_data,
+            block_info: None,
         }
     }
 
@@ -220,7 +336,7 @@ impl<'txn> Interpreter<'txn> {
         for (i, value) in args.into_iter().enumerate() {
             locals.store_loc(i, value)?;
         }
-        let mut current_frame = Frame::new(function, vec![], vec![], locals);
+        let mut current_frame = Frame::new(function, vec![], vec![], vec![], locals);
         loop {
             let code = current_frame.code_definition();
             let exit_code = self
@@ -259,6 +375,20 @@ impl<'txn> Interpreter<'txn> {
                             )
                         })
                         .collect::<VMResult<Vec<_>>>()?;
+                    // The frame's own type actuals are fully instantiated, so these kinds are
+                    // never `Kind::All`.
+                    let type_actual_kinds = type_actuals_sig
+                        .iter()
+                        .map(|ty| {
+                            SignatureToken::kind(
+                                (
+                                    current_frame.module().struct_handles(),
+                                    current_frame.type_actual_kinds(),
+                                ),
+                                ty,
+                            )
+                        })
+                        .collect();
                     let type_context = TypeContext::new(current_frame.type_actuals().to_vec());
                     let type_actuals = type_actuals_sig
                         .iter()
@@ -276,9 +406,10 @@ impl<'txn> Interpreter<'txn> {
                         .make_call_frame(
                             runtime,
                             context,
-                            current_frame.module(),
+                            &current_frame,
                             idx,
                             type_actual_tags,
+                            type_actual_kinds,
                             type_actuals,
                         )
                         .or_else(|err| Err(self.maybe_core_dump(err, &current_frame)))?;
@@ -672,14 +803,23 @@ impl<'txn> Interpreter<'txn> {
         &mut self,
         runtime: &'txn VMRuntime<'_>,
         context: &mut dyn InterpreterContext,
-        module: &LoadedModule,
+        caller: &Frame<'txn, FunctionRef<'txn>>,
         idx: FunctionHandleIndex,
         type_actual_tags: Vec<TypeTag>,
+        type_actual_kinds: Vec<Kind>,
         type_actuals: Vec<Type>,
     ) -> VMResult<Option<Frame<'txn, FunctionRef<'txn>>>> {
-        let func = runtime.resolve_function_ref(module, idx, context)?;
+        let func = runtime.resolve_function_ref(caller.module(), idx, context)?;
         if func.is_native() {
-            self.call_native(runtime, context, func, type_actual_tags, type_actuals)?;
+            self.call_native(
+                runtime,
+                context,
+                caller,
+                func,
+                type_actual_tags,
+                type_actual_kinds,
+                type_actuals,
+            )?;
             Ok(None)
         } else {
             let mut locals = Locals::new(func.local_count());
@@ -690,6 +830,7 @@ impl<'txn> Interpreter<'txn> {
             Ok(Some(Frame::new(
                 func,
                 type_actual_tags,
+                type_actual_kinds,
                 type_actuals,
                 locals,
             )))
@@ -701,46 +842,149 @@ impl<'txn> Interpreter<'txn> {
         &mut self,
         runtime: &'txn VMRuntime<'_>,
         context: &mut dyn InterpreterContext,
+        caller: &Frame<'txn, FunctionRef<'txn>>,
         function: FunctionRef<'txn>,
         type_actual_tags: Vec<TypeTag>,
+        type_actual_kinds: Vec<Kind>,
         type_actuals: Vec<Type>,
     ) -> VMResult<()> {
         let module = function.module();
         let module_id = module.self_id();
         let function_name = function.name();
-        let native_function = NativeFunction::resolve(&module_id, function_name)
+        // Natives that do not exist at the native version of the VM are resolved all the same, so
+        // that calling them fails with the version they are available from rather than as a
+        // linker error.
+        let native_function = runtime
+            .natives()
+            .resolve(&module_id, function_name)
             .ok_or_else(|| VMStatus::new(StatusCode::LINKER_ERROR))?;
-        if module_id == *account_config::ACCOUNT_MODULE
-            && function_name == EMIT_EVENT_NAME.as_ident_str()
-        {
-            self.call_emit_event(context, type_actual_tags, type_actuals)
-        } else if module_id == *account_config::ACCOUNT_MODULE
-            && function_name == SAVE_ACCOUNT_NAME.as_ident_str()
-        {
-            self.call_save_account(runtime, context)
-        } else {
-            let mut arguments = VecDeque::new();
-            let expected_args = native_function.num_args();
-            // REVIEW: this is checked again in every functions, rationalize it!
-            if function.arg_count() != expected_args {
-                // Should not be possible due to bytecode verifier but this
-                // assertion is here to make sure
-                // the view the type checker had lines up with the
-                // execution of the native function
-                return Err(VMStatus::new(StatusCode::LINKER_ERROR));
+        match native_function {
+            NativeFunctionEntry::Builtin(native) if native.is_runtime_handled() => {
+                native.check_available(runtime.native_version())?;
+                self.call_runtime_handled(*native, runtime, context, type_actual_tags, type_actuals)
             }
-            for _ in 0..expected_args {
-                arguments.push_front(self.operand_stack.pop()?);
+            _ => {
+                // The arguments the type checker saw the function take: `dispatch` fails, before
+                // any native code runs, if the native takes a different number of them
+                let arguments = self.operand_stack.pop_native_args(function.arg_count())?;
+                let block_info = match native_function {
+                    NativeFunctionEntry::Builtin(native) if native.reads_block_metadata() => {
+                        self.block_info(runtime, context)?
+                    }
+                    _ => BlockInfo::default(),
+                };
+                let layout_resolver = RuntimeLayoutResolver {
+                    runtime,
+                    data_view: context,
+                };
+                let mut native_context = InterpreterNativeContext {
+                    cost_table: self.gas_schedule,
+                    block_info,
+                    layout_resolver: &layout_resolver,
+                    txn_data: self.txn_data,
+                    remaining_gas: context.remaining_gas(),
+                    call_stack: &self.call_stack,
+                    caller,
+                    max_vector_length: runtime.max_vector_length(),
+                };
+                let result = native_function.dispatch_with_version(
+                    runtime.native_version(),
+                    &mut native_context,
+                    &type_actual_tags,
+                    type_actual_kinds,
+                    type_actuals,
+                    arguments,
+                )?;
+                gas!(consume: context, result.charged())?;
+                result.result.and_then(|values| {
+                    for value in values {
+                        self.operand_stack.push(value)?;
+                    }
+                    Ok(())
+                })
             }
-            let result =
-                native_function.dispatch(type_actual_tags, arguments, self.gas_schedule)?;
-            gas!(consume: context, result.cost)?;
-            result.result.and_then(|values| {
-                for value in values {
-                    self.operand_stack.push(value)?;
-                }
-                Ok(())
-            })
+        }
+    }
+
+    /// The metadata of the block the transaction executes in, read from the `LibraBlock` and
+    /// `LibraTimestamp` resources of the association the first time it is needed. Only the block
+    /// prologue changes these resources, in a transaction of its own, so the metadata is the same
+    /// for all the natives a transaction calls.
+    fn block_info(
+        &mut self,
+        runtime: &'txn VMRuntime<'_>,
+        context: &mut dyn InterpreterContext,
+    ) -> VMResult<BlockInfo> {
+        if let Some(block_info) = self.block_info {
+            return Ok(block_info);
+        }
+        let block_info = BlockInfo {
+            height: Self::read_association_u64(
+                runtime,
+                context,
+                &LIBRA_BLOCK_MODULE,
+                &BLOCK_METADATA_NAME,
+            )?,
+            timestamp_micros: Self::read_association_u64(
+                runtime,
+                context,
+                &LIBRA_TIMESTAMP_MODULE,
+                &CURRENT_TIME_MICROSECONDS_NAME,
+            )?,
+        };
+        self.block_info = Some(block_info);
+        Ok(block_info)
+    }
+
+    /// The `u64` first field of the `name` resource of `module_id` held by the association, or
+    /// `None` if the module is not published or the association holds no such resource, as
+    /// before genesis initializes the block metadata.
+    fn read_association_u64(
+        runtime: &'txn VMRuntime<'_>,
+        context: &mut dyn InterpreterContext,
+        module_id: &ModuleId,
+        name: &IdentStr,
+    ) -> VMResult<Option<u64>> {
+        if !context.exists_module(module_id) {
+            return Ok(None);
+        }
+        let module = runtime.get_loaded_module(module_id, context)?;
+        let idx = *module.get_struct_def_index(name)?;
+        let ap = Self::make_access_path(module, idx, vec![], account_config::association_address());
+        let struct_def = runtime.resolve_struct_def(module, idx, vec![], context)?;
+        if !context.resource_exists(&ap, struct_def.clone())?.0 {
+            return Ok(None);
+        }
+        context
+            .borrow_global(&ap, struct_def)?
+            .borrow_global()?
+            .value_as::<StructRef>()?
+            .borrow_field(0)?
+            .value_as::<Reference>()?
+            .read_ref()?
+            .value_as::<u64>()
+            .map(Some)
+    }
+
+    /// Execute a native function the interpreter handles itself.
+    fn call_runtime_handled(
+        &mut self,
+        native_function: NativeFunction,
+        runtime: &'txn VMRuntime<'_>,
+        context: &mut dyn InterpreterContext,
+        type_actual_tags: Vec<TypeTag>,
+        type_actuals: Vec<Type>,
+    ) -> VMResult<()> {
+        match native_function {
+            NativeFunction::AccountWriteEvent => {
+                self.call_emit_event(context, type_actual_tags, type_actuals)
+            }
+            NativeFunction::AccountSaveAccount => self.call_save_account(runtime, context),
+            _ => Err(
+                VMStatus::new(StatusCode::NATIVE_FUNCTION_INTERNAL_INCONSISTENCY).with_message(
+                    format!("{} is not handled by the interpreter", native_function),
+                ),
+            ),
         }
     }
 
@@ -1093,6 +1337,17 @@ impl Stack {
         let args = self.0.split_off(remaining_stack_size);
         Ok(args)
     }
+
+    /// Pop the `n` arguments of a native function off the stack, first argument first, without
+    /// allocating for the few arguments natives take.
+    fn pop_native_args(&mut self, n: usize) -> VMResult<NativeArguments> {
+        let remaining_stack_size = self
+            .0
+            .len()
+            .checked_sub(n)
+            .ok_or_else(|| VMStatus::new(StatusCode::EMPTY_VALUE_STACK))?;
+        Ok(self.0.drain(remaining_stack_size..).collect())
+    }
 }
 
 /// A call stack.
@@ -1132,6 +1387,7 @@ struct Frame<'txn, F: 'txn> {
     locals: Locals,
     function: F,
     type_actual_tags: Vec<TypeTag>,
+    type_actual_kinds: Vec<Kind>,
     type_actuals: Vec<Type>,
     phantom: PhantomData<&'txn F>,
 }
@@ -1155,6 +1411,7 @@ where
     fn new(
         function: F,
         type_actual_tags: Vec<TypeTag>,
+        type_actual_kinds: Vec<Kind>,
         type_actuals: Vec<Type>,
         locals: Locals,
     ) -> Self {
@@ -1163,6 +1420,7 @@ where
             locals,
             function,
             type_actual_tags,
+            type_actual_kinds,
             type_actuals,
             phantom: PhantomData,
         }
@@ -1206,6 +1464,10 @@ where
         &self.type_actual_tags
     }
 
+    fn type_actual_kinds(&self) -> &[Kind] {
+        &self.type_actual_kinds
+    }
+
     fn type_actuals(&self) -> &[Type] {
         &self.type_actuals
     }
@@ -1249,6 +1511,7 @@ impl<'txn> InterpreterForCostSynthesis<'txn> {
         &mut self,
         func: FunctionRef<'txn>,
         type_actual_tags: Vec<TypeTag>,
+        type_actual_kinds: Vec<Kind>,
         type_actuals: Vec<Type>,
     ) {
         let count = func.local_count();
@@ -1257,6 +1520,7 @@ impl<'txn> InterpreterForCostSynthesis<'txn> {
             .push(Frame::new(
                 func,
                 type_actual_tags,
+                type_actual_kinds,
                 type_actuals,
                 Locals::new(count),
             ))
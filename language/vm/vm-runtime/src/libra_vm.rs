@@ -25,7 +25,7 @@ use libra_types::{
     vm_error::{sub_status, StatusCode, VMStatus},
     write_set::WriteSet,
 };
-use move_vm_types::values::Value;
+use move_vm_types::{native_functions::table::NativeFunctionTable, values::Value};
 use rayon::prelude::*;
 use std::sync::Arc;
 use vm::{
@@ -46,7 +46,11 @@ pub struct LibraVM {
 
 impl LibraVM {
     pub fn new(config: &VMConfig) -> Self {
-        let inner = MoveVM::new();
+        let inner = MoveVM::with_natives(
+            NativeFunctionTable::new(),
+            config.max_vector_length,
+            config.native_version,
+        );
         Self {
             move_vm: Arc::new(inner),
             gas_schedule: None,
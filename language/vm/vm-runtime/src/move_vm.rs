@@ -12,7 +12,11 @@ use libra_types::{
 };
 use move_vm_cache::Arena;
 use move_vm_definition::MoveVMImpl;
-use move_vm_types::{loaded_data::struct_def::StructDef, values::Value};
+use move_vm_types::{
+    loaded_data::struct_def::StructDef,
+    native_functions::{dispatch::DEFAULT_NATIVE_VERSION, table::NativeFunctionTable},
+    values::{vector::DEFAULT_MAX_VECTOR_LENGTH, Value},
+};
 use vm::{errors::VMResult, gas_schedule::CostTable, transaction_metadata::TransactionMetadata};
 
 rental! {
@@ -31,8 +35,28 @@ pub struct MoveVM(MoveVMImpl);
 
 impl MoveVM {
     pub fn new() -> Self {
+        Self::with_max_vector_length(DEFAULT_MAX_VECTOR_LENGTH)
+    }
+
+    /// A VM whose vector natives grow vectors to at most `max_vector_length` elements.
+    pub fn with_max_vector_length(max_vector_length: usize) -> Self {
+        Self::with_natives(
+            NativeFunctionTable::new(),
+            max_vector_length,
+            DEFAULT_NATIVE_VERSION,
+        )
+    }
+
+    /// A VM calling the native functions of `natives`, which holds the built-in ones and the ones
+    /// an embedder registered, as they exist at protocol version `native_version`, and whose
+    /// vector natives grow vectors to at most `max_vector_length` elements.
+    pub fn with_natives(
+        natives: NativeFunctionTable,
+        max_vector_length: usize,
+        native_version: u64,
+    ) -> Self {
         MoveVM(MoveVMImpl::new(Box::new(Arena::new()), |arena| {
-            VMRuntime::new(&*arena)
+            VMRuntime::new(&*arena, natives, max_vector_length, native_version)
         }))
     }
 
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    code_cache::{module_cache::VMModuleCache, script_cache::ScriptCache},
+    code_cache::{
+        module_cache::{load_and_verify_module_id, VMModuleCache},
+        script_cache::ScriptCache,
+    },
     execution_context::InterpreterContext,
     identifier::resource_storage_key,
     interpreter::Interpreter,
@@ -11,7 +14,7 @@ use crate::{
         loaded_module::LoadedModule,
     },
 };
-use bytecode_verifier::VerifiedModule;
+use bytecode_verifier::{verify_module_dependencies_with_natives, VerifiedModule};
 use libra_logger::prelude::*;
 use libra_types::{
     identifier::{IdentStr, Identifier},
@@ -21,6 +24,7 @@ use libra_types::{
 use move_vm_cache::Arena;
 use move_vm_types::{
     loaded_data::{struct_def::StructDef, types::Type},
+    native_functions::table::NativeFunctionTable,
     type_context::TypeContext,
     values::Value,
 };
@@ -44,18 +48,45 @@ use vm::{
 pub struct VMRuntime<'alloc> {
     code_cache: VMModuleCache<'alloc>,
     script_cache: ScriptCache<'alloc>,
+    natives: NativeFunctionTable,
+    max_vector_length: usize,
+    native_version: u64,
 }
 
 impl<'alloc> VMRuntime<'alloc> {
-    /// Create a new VM instance with an Arena allocator to store the modules and a `config` that
-    /// contains the whitelist that this VM is allowed to execute.
-    pub fn new(allocator: &'alloc Arena<LoadedModule>) -> Self {
+    /// Create a new VM instance with an Arena allocator to store the modules, calling the native
+    /// functions of `natives` that exist at protocol version `native_version`, whose vector
+    /// natives grow vectors to at most `max_vector_length` elements.
+    pub fn new(
+        allocator: &'alloc Arena<LoadedModule>,
+        natives: NativeFunctionTable,
+        max_vector_length: usize,
+        native_version: u64,
+    ) -> Self {
         VMRuntime {
             code_cache: VMModuleCache::new(allocator),
             script_cache: ScriptCache::new(allocator),
+            natives,
+            max_vector_length,
+            native_version,
         }
     }
 
+    /// The native functions the VM calls.
+    pub(crate) fn natives(&self) -> &NativeFunctionTable {
+        &self.natives
+    }
+
+    /// The maximum number of elements the vector natives grow a vector to.
+    pub(crate) fn max_vector_length(&self) -> usize {
+        self.max_vector_length
+    }
+
+    /// The protocol version natives are resolved and run at.
+    pub(crate) fn native_version(&self) -> u64 {
+        self.native_version
+    }
+
     pub(crate) fn publish_module(
         &self,
         module: Vec<u8>,
@@ -91,7 +122,7 @@ impl<'alloc> VMRuntime<'alloc> {
             ));
         };
 
-        match VerifiedModule::new(compiled_module) {
+        let ver_module = match VerifiedModule::new(compiled_module) {
             Ok(ver_module) => ver_module,
             Err((_, mut errors)) => {
                 let err = if errors.is_empty() {
@@ -104,6 +135,26 @@ impl<'alloc> VMRuntime<'alloc> {
             }
         };
 
+        // Make sure the module links against its dependencies, and only declares the natives the
+        // VM calls at its native version.
+        let mut deps = vec![];
+        for module_handle in ver_module.module_handles() {
+            if module_handle == ver_module.self_handle() {
+                continue;
+            }
+            let dep_id = ver_module.module_id_for_handle(module_handle);
+            deps.push(load_and_verify_module_id(&dep_id, context)?);
+        }
+        let mut errors = verify_module_dependencies_with_natives(
+            &ver_module,
+            &deps,
+            &self.natives,
+            self.native_version,
+        );
+        if !errors.is_empty() {
+            return Err(errors.remove(0));
+        }
+
         context.publish_module(module_id, module)
     }
 
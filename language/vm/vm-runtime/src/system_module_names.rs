@@ -41,6 +41,13 @@ pub static LIBRA_BLOCK_MODULE: Lazy<ModuleId> = Lazy::new(|| {
         Identifier::new("LibraBlock").unwrap(),
     )
 });
+/// The ModuleId for the libra timestamp module
+pub static LIBRA_TIMESTAMP_MODULE: Lazy<ModuleId> = Lazy::new(|| {
+    ModuleId::new(
+        account_config::CORE_CODE_ADDRESS,
+        Identifier::new("LibraTimestamp").unwrap(),
+    )
+});
 /// The ModuleId for the gas schedule module
 pub static GAS_SCHEDULE_MODULE: Lazy<ModuleId> = Lazy::new(|| {
     ModuleId::new(
@@ -67,3 +74,7 @@ pub static PROLOGUE_NAME: Lazy<Identifier> = Lazy::new(|| Identifier::new("prolo
 pub static EPILOGUE_NAME: Lazy<Identifier> = Lazy::new(|| Identifier::new("epilogue").unwrap());
 pub static BLOCK_PROLOGUE: Lazy<Identifier> =
     Lazy::new(|| Identifier::new("block_prologue").unwrap());
+pub static BLOCK_METADATA_NAME: Lazy<Identifier> =
+    Lazy::new(|| Identifier::new("BlockMetadata").unwrap());
+pub static CURRENT_TIME_MICROSECONDS_NAME: Lazy<Identifier> =
+    Lazy::new(|| Identifier::new("CurrentTimeMicroseconds").unwrap());
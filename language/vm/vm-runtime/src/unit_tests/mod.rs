@@ -1,11 +1,17 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use libra_types::identifier::Identifier;
+use crate::data_cache::RemoteCache;
+use compiler::Compiler;
+use libra_types::{access_path::AccessPath, identifier::Identifier};
+use std::collections::HashMap;
+use vm::{errors::VMResult, file_format::CompiledModule};
 
 mod block_chunking_test;
 mod identifier_prop_tests;
 mod module_cache_tests;
+mod native_context_tests;
+mod vm_config_tests;
 
 // Helper methods for identifiers in tests.
 
@@ -16,3 +22,44 @@ fn ident(name: impl Into<Box<str>>) -> Identifier {
 fn idents(names: impl IntoIterator<Item = &'static str>) -> Vec<Identifier> {
     names.into_iter().map(ident).collect()
 }
+
+/// A data store holding the modules and resources tests put in it.
+#[derive(Debug, Default)]
+struct FakeDataCache {
+    data: HashMap<AccessPath, Vec<u8>>,
+}
+
+impl FakeDataCache {
+    fn get(&self, access_path: &AccessPath) -> VMResult<Option<Vec<u8>>> {
+        Ok(self.data.get(access_path).cloned())
+    }
+
+    fn set(&mut self, module: CompiledModule) {
+        let ap: AccessPath = (&module.self_id()).into();
+        let mut blob: Vec<u8> = vec![];
+        module.serialize(&mut blob).expect("Module must serialize");
+        self.data.insert(ap, blob);
+    }
+
+    /// Store `blob`, the LCS serialization of a resource, under `ap`.
+    fn set_resource(&mut self, ap: AccessPath, blob: Vec<u8>) {
+        self.data.insert(ap, blob);
+    }
+}
+
+impl RemoteCache for FakeDataCache {
+    fn get(&self, access_path: &AccessPath) -> VMResult<Option<Vec<u8>>> {
+        FakeDataCache::get(self, access_path)
+    }
+}
+
+fn parse_and_compile_modules(s: impl AsRef<str>) -> Vec<CompiledModule> {
+    let compiler = Compiler {
+        skip_stdlib_deps: true,
+        ..Compiler::default()
+    };
+    compiler
+        .into_compiled_program("file_name", s.as_ref())
+        .expect("Failed to compile program")
+        .modules
+}
@@ -5,7 +5,7 @@ use super::*;
 use crate::{
     chain_state::{SystemExecutionContext, TransactionExecutionContext},
     code_cache::module_cache::VMModuleCache,
-    data_cache::{BlockDataCache, RemoteCache},
+    data_cache::BlockDataCache,
     loaded_data::{
         function::{FunctionRef, FunctionReference},
         loaded_module::LoadedModule,
@@ -13,7 +13,6 @@ use crate::{
 };
 use anyhow::{format_err, Result};
 use bytecode_verifier::{VerifiedModule, VerifiedScript};
-use compiler::Compiler;
 use libra_state_view::StateView;
 use libra_types::{
     access_path::AccessPath,
@@ -23,10 +22,8 @@ use libra_types::{
 };
 use move_vm_cache::Arena;
 use move_vm_types::loaded_data::{struct_def::StructDef, types::Type};
-use std::collections::HashMap;
 use vm::{
     access::ModuleAccess,
-    errors::VMResult,
     file_format::*,
     gas_schedule::{GasAlgebra, GasUnits},
 };
@@ -47,30 +44,6 @@ impl StateView for NullStateView {
     }
 }
 
-#[derive(Debug, Default)]
-struct FakeDataCache {
-    data: HashMap<AccessPath, Vec<u8>>,
-}
-
-impl FakeDataCache {
-    fn get(&self, access_path: &AccessPath) -> VMResult<Option<Vec<u8>>> {
-        Ok(self.data.get(access_path).cloned())
-    }
-
-    fn set(&mut self, module: CompiledModule) {
-        let ap: AccessPath = (&module.self_id()).into();
-        let mut blob: Vec<u8> = vec![];
-        module.serialize(&mut blob).expect("Module must serialize");
-        self.data.insert(ap, blob);
-    }
-}
-
-impl RemoteCache for FakeDataCache {
-    fn get(&self, access_path: &AccessPath) -> VMResult<Option<Vec<u8>>> {
-        FakeDataCache::get(self, access_path)
-    }
-}
-
 fn test_module(name: &'static str) -> VerifiedModule {
     let compiled_module = CompiledModuleMut {
         module_handles: vec![ModuleHandle {
@@ -468,17 +441,6 @@ fn test_multi_level_cache_write_back() {
     assert_eq!(func2_ref.code_definition(), vec![Bytecode::Ret].as_slice());
 }
 
-fn parse_and_compile_modules(s: impl AsRef<str>) -> Vec<CompiledModule> {
-    let compiler = Compiler {
-        skip_stdlib_deps: true,
-        ..Compiler::default()
-    };
-    compiler
-        .into_compiled_program("file_name", s.as_ref())
-        .expect("Failed to compile program")
-        .modules
-}
-
 #[test]
 fn test_same_module_struct_resolution() {
     let allocator = Arena::new();
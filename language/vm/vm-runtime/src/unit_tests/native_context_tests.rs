@@ -0,0 +1,365 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    chain_state::TransactionExecutionContext, identifier::create_access_path, move_vm::MoveVM,
+};
+use bytecode_verifier::{
+    verify_module_dependencies, verify_module_dependencies_with_natives, VerifiedModule,
+};
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config,
+    language_storage::{ModuleId, StructTag},
+    vm_error::StatusCode,
+};
+use move_vm_types::{
+    native_functions::{
+        block,
+        dispatch::{NativeResult, DEFAULT_NATIVE_VERSION, LATEST_NATIVE_VERSION},
+        table::NativeFunctionTable,
+    },
+    values::{vector::DEFAULT_MAX_VECTOR_LENGTH, Value},
+};
+use vm::{
+    errors::VMResult,
+    file_format::{FunctionSignature, SignatureToken},
+    gas_schedule::{CostTable, GasAlgebra, GasUnits},
+    transaction_metadata::TransactionMetadata,
+};
+
+/// The resources the block prologue stores the block metadata in, reduced to the fields the
+/// interpreter reads, and a module checking what the block natives return and calling another
+/// native.
+const BLOCK_MODULES: &str = "
+    modules:
+    module LibraBlock {
+        resource BlockMetadata { height: u64 }
+    }
+    module LibraTimestamp {
+        resource CurrentTimeMicroseconds { microseconds: u64 }
+    }
+    module Block {
+        native public get_height(): u64;
+        native public get_timestamp_micros(): u64;
+    }
+    module Hash {
+        native public sha3_256(data: vector<u8>): vector<u8>;
+    }
+    module M {
+        import 0x0.Block;
+        import 0x0.Hash;
+        public check_block(height: u64, timestamp_micros: u64) {
+            assert(Block.get_height() == move(height), 1);
+            assert(Block.get_timestamp_micros() == move(timestamp_micros), 2);
+            return;
+        }
+        public hash(data: vector<u8>) {
+            let digest: vector<u8>;
+            digest = Hash.sha3_256(move(data));
+            return;
+        }
+    }
+    script:
+    main() {
+        return;
+    }
+";
+
+/// The access path of the `name` resource of `module` held by the association.
+fn association_resource(module: &str, name: &str) -> AccessPath {
+    let struct_tag = StructTag {
+        address: account_config::CORE_CODE_ADDRESS,
+        module: ident(module),
+        name: ident(name),
+        type_params: vec![],
+    };
+    create_access_path(&account_config::association_address(), struct_tag)
+}
+
+/// Stores `value` as the only field of the `name` resource of `module` held by the association.
+fn set_association_u64(data_cache: &mut FakeDataCache, module: &str, name: &str, value: u64) {
+    let ap = association_resource(module, name);
+    data_cache.set_resource(ap, lcs::to_bytes(&value).unwrap());
+}
+
+/// Calls `function(args)` of `module` through the interpreter of `move_vm` on `data_cache`.
+fn execute_function(
+    move_vm: &MoveVM,
+    data_cache: &FakeDataCache,
+    module: ModuleId,
+    function: &str,
+    args: Vec<Value>,
+) -> VMResult<()> {
+    let mut context = TransactionExecutionContext::new(GasUnits::new(100_000_000), data_cache);
+    move_vm.execute_function(
+        &module,
+        &ident(function),
+        &CostTable::zero(),
+        &mut context,
+        &TransactionMetadata::default(),
+        args,
+    )
+}
+
+/// Calls `M.check_block(height, timestamp_micros)` through the interpreter on `data_cache`, at
+/// the native version the block natives are rolled out from.
+fn check_block(data_cache: &FakeDataCache, height: u64, timestamp_micros: u64) -> VMResult<()> {
+    execute_function(
+        &MoveVM::with_natives(
+            NativeFunctionTable::new(),
+            DEFAULT_MAX_VECTOR_LENGTH,
+            LATEST_NATIVE_VERSION,
+        ),
+        data_cache,
+        ModuleId::new(AccountAddress::default(), ident("M")),
+        "check_block",
+        vec![Value::u64(height), Value::u64(timestamp_micros)],
+    )
+}
+
+fn block_modules() -> FakeDataCache {
+    let mut data_cache = FakeDataCache::default();
+    for module in parse_and_compile_modules(BLOCK_MODULES) {
+        data_cache.set(module);
+    }
+    data_cache
+}
+
+#[test]
+fn block_natives_read_the_block_metadata_resources() {
+    let mut data_cache = block_modules();
+    set_association_u64(&mut data_cache, "LibraBlock", "BlockMetadata", 42);
+    set_association_u64(
+        &mut data_cache,
+        "LibraTimestamp",
+        "CurrentTimeMicroseconds",
+        1_580_000_000_000_000,
+    );
+
+    check_block(&data_cache, 42, 1_580_000_000_000_000).unwrap();
+    let status = check_block(&data_cache, 41, 1_580_000_000_000_000).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(1));
+}
+
+#[test]
+fn block_natives_fail_without_the_block_metadata_resources() {
+    let data_cache = block_modules();
+    let status = check_block(&data_cache, 0, 0).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_ERROR);
+    assert_eq!(status.sub_status, Some(block::NO_BLOCK_METADATA));
+}
+
+#[test]
+fn other_natives_do_not_read_the_block_metadata_resources() {
+    let mut data_cache = block_modules();
+    let ap = association_resource("LibraBlock", "BlockMetadata");
+    data_cache.set_resource(ap, vec![]);
+
+    // The block natives fail to read a malformed resource, which other natives never load
+    assert!(check_block(&data_cache, 0, 0).is_err());
+    execute_function(
+        &MoveVM::new(),
+        &data_cache,
+        ModuleId::new(AccountAddress::default(), ident("M")),
+        "hash",
+        vec![Value::vector_u8(b"data".to_vec())],
+    )
+    .unwrap();
+}
+
+/// The address of the modules declaring the natives an embedder registers.
+fn embedder_address() -> AccountAddress {
+    AccountAddress::new([1; 32])
+}
+
+/// A module declaring the native an embedder registers, and a module checking what it returns.
+const EMBEDDER_MODULES: &str = "
+    modules:
+    module Natives {
+        native public double(x: u64): u64;
+    }
+    module N {
+        import Transaction.Natives;
+        public check_double(x: u64, expected: u64) {
+            assert(Natives.double(move(x)) == move(expected), 1);
+            return;
+        }
+    }
+    script:
+    main() {
+        return;
+    }
+";
+
+fn compile_embedder_modules() -> Vec<CompiledModule> {
+    let compiler = Compiler {
+        address: embedder_address(),
+        skip_stdlib_deps: true,
+        ..Compiler::default()
+    };
+    compiler
+        .into_compiled_program("file_name", EMBEDDER_MODULES)
+        .expect("Failed to compile program")
+        .modules
+}
+
+/// The built-in natives, and `Natives.double` of the embedder.
+fn embedder_natives() -> NativeFunctionTable {
+    let mut natives = NativeFunctionTable::new();
+    natives
+        .register(
+            ModuleId::new(embedder_address(), ident("Natives")),
+            ident("double"),
+            1,
+            FunctionSignature {
+                return_types: vec![SignatureToken::U64],
+                arg_types: vec![SignatureToken::U64],
+                type_formals: vec![],
+            },
+            Box::new(|_, _, _, _, mut arguments| {
+                let x = arguments.pop().unwrap().value_as::<u64>()?;
+                Ok(NativeResult::ok(GasUnits::new(1), vec![Value::u64(2 * x)]))
+            }),
+        )
+        .unwrap();
+    natives
+}
+
+fn check_double(
+    move_vm: &MoveVM,
+    data_cache: &FakeDataCache,
+    x: u64,
+    expected: u64,
+) -> VMResult<()> {
+    execute_function(
+        move_vm,
+        data_cache,
+        ModuleId::new(embedder_address(), ident("N")),
+        "check_double",
+        vec![Value::u64(x), Value::u64(expected)],
+    )
+}
+
+#[test]
+fn registered_native_is_called_from_a_module() {
+    let mut data_cache = FakeDataCache::default();
+    for module in compile_embedder_modules() {
+        data_cache.set(module);
+    }
+
+    let move_vm = MoveVM::with_natives(
+        embedder_natives(),
+        DEFAULT_MAX_VECTOR_LENGTH,
+        DEFAULT_NATIVE_VERSION,
+    );
+    check_double(&move_vm, &data_cache, 21, 42).unwrap();
+    let status = check_double(&move_vm, &data_cache, 21, 41).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(1));
+
+    // A VM without the native cannot link the call
+    let status = check_double(&MoveVM::new(), &data_cache, 21, 42).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::LINKER_ERROR);
+}
+
+#[test]
+fn verifier_resolves_natives_in_the_table() {
+    let natives_module = compile_embedder_modules().into_iter().next().unwrap();
+    let natives_module = VerifiedModule::new(natives_module).unwrap();
+    assert_eq!(
+        verify_module_dependencies_with_natives(
+            &natives_module,
+            vec![],
+            &embedder_natives(),
+            DEFAULT_NATIVE_VERSION
+        ),
+        vec![]
+    );
+
+    let errors = verify_module_dependencies(&natives_module, vec![]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].major_status, StatusCode::MISSING_DEPENDENCY);
+}
+
+/// A module declaring the natives rolled out from protocol version 1, and a module calling one.
+const GATED_MODULES: &str = "
+    modules:
+    module Hash {
+        native public keccak_256(data: vector<u8>): vector<u8>;
+    }
+    module K {
+        import 0x0.Hash;
+        public hash(data: vector<u8>) {
+            let digest: vector<u8>;
+            digest = Hash.keccak_256(move(data));
+            return;
+        }
+    }
+    script:
+    main() {
+        return;
+    }
+";
+
+#[test]
+fn gated_natives_are_not_called_before_their_version() {
+    let modules = parse_and_compile_modules(GATED_MODULES);
+    let mut data_cache = FakeDataCache::default();
+    for module in modules.iter().cloned() {
+        data_cache.set(module);
+    }
+    let call_hash = |native_version: u64| {
+        let move_vm = MoveVM::with_natives(
+            NativeFunctionTable::new(),
+            DEFAULT_MAX_VECTOR_LENGTH,
+            native_version,
+        );
+        execute_function(
+            &move_vm,
+            &data_cache,
+            ModuleId::new(AccountAddress::default(), ident("K")),
+            "hash",
+            vec![Value::vector_u8(vec![])],
+        )
+    };
+
+    let status = call_hash(0).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::NATIVE_FUNCTION_UNAVAILABLE);
+    assert_eq!(status.sub_status, Some(1));
+    call_hash(1).unwrap();
+
+    let hash_module = VerifiedModule::new(modules[0].clone()).unwrap();
+    let natives = NativeFunctionTable::new();
+    let errors = verify_module_dependencies_with_natives(&hash_module, vec![], &natives, 0);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].major_status, StatusCode::MISSING_DEPENDENCY);
+    assert_eq!(
+        verify_module_dependencies_with_natives(&hash_module, vec![], &natives, 1),
+        vec![]
+    );
+}
+
+#[test]
+fn published_natives_are_verified_at_the_native_version() {
+    let hash_module = parse_and_compile_modules(GATED_MODULES).remove(0);
+    let mut blob = vec![];
+    hash_module.serialize(&mut blob).unwrap();
+    let data_cache = FakeDataCache::default();
+    let publish = |native_version: u64| {
+        let move_vm = MoveVM::with_natives(
+            NativeFunctionTable::new(),
+            DEFAULT_MAX_VECTOR_LENGTH,
+            native_version,
+        );
+        let mut context = TransactionExecutionContext::new(GasUnits::new(100_000_000), &data_cache);
+        move_vm.publish_module(blob.clone(), &mut context, &TransactionMetadata::default())
+    };
+
+    let status = publish(0).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::MISSING_DEPENDENCY);
+    publish(1).unwrap();
+}
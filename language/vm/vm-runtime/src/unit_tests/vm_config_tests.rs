@@ -0,0 +1,38 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::move_vm::MoveVM;
+use libra_config::config::VMConfig;
+use move_vm_types::{
+    native_functions::{dispatch::DEFAULT_NATIVE_VERSION, table::NativeFunctionTable},
+    values::vector::DEFAULT_MAX_VECTOR_LENGTH,
+};
+
+#[test]
+fn default_max_vector_length_matches_the_natives() {
+    assert_eq!(
+        VMConfig::default().max_vector_length,
+        DEFAULT_MAX_VECTOR_LENGTH
+    );
+    MoveVM::new()
+        .with_runtime(|runtime| assert_eq!(runtime.max_vector_length(), DEFAULT_MAX_VECTOR_LENGTH));
+}
+
+#[test]
+fn runtime_uses_the_configured_max_vector_length() {
+    MoveVM::with_max_vector_length(2)
+        .with_runtime(|runtime| assert_eq!(runtime.max_vector_length(), 2));
+}
+
+#[test]
+fn default_native_version_matches_the_natives() {
+    assert_eq!(VMConfig::default().native_version, DEFAULT_NATIVE_VERSION);
+    MoveVM::new()
+        .with_runtime(|runtime| assert_eq!(runtime.native_version(), DEFAULT_NATIVE_VERSION));
+}
+
+#[test]
+fn runtime_uses_the_configured_native_version() {
+    MoveVM::with_natives(NativeFunctionTable::new(), DEFAULT_MAX_VECTOR_LENGTH, 1)
+        .with_runtime(|runtime| assert_eq!(runtime.native_version(), 1));
+}
@@ -453,6 +453,8 @@ pub enum StatusCode {
     NATIVE_FUNCTION_ERROR = 4022,
     GAS_SCHEDULE_ERROR = 4023,
     CREATE_NULL_ACCOUNT = 4024,
+    // A native function was called at a protocol version older than the one it is available from.
+    NATIVE_FUNCTION_UNAVAILABLE = 4025,
 
     // A reserved status to represent an unknown vm status.
     UNKNOWN_STATUS = std::u64::MAX,
@@ -648,6 +650,7 @@ impl TryFrom<u64> for StatusCode {
             4022 => Ok(StatusCode::NATIVE_FUNCTION_ERROR),
             4023 => Ok(StatusCode::GAS_SCHEDULE_ERROR),
             4024 => Ok(StatusCode::CREATE_NULL_ACCOUNT),
+            4025 => Ok(StatusCode::NATIVE_FUNCTION_UNAVAILABLE),
             std::u64::MAX => Ok(StatusCode::UNKNOWN_STATUS),
             _ => Err("invalid StatusCode"),
         }
@@ -677,7 +680,18 @@ pub mod sub_status {
     pub const DRE_GLOBAL_ALREADY_BORROWED: u64 = 4;
 
     // Native Function Error sub-codes
-    pub const NFE_VECTOR_ERROR_BASE: u64 = 0;
+    pub const NFE_VECTOR_ERROR_BASE: u64 = 0x0EC7_0000;
+    // Vector native codes, reported as abort codes, or as sub-statuses of a NATIVE_FUNCTION_ERROR
+    // for the element types the natives do not support
+    pub const NFE_VECTOR_INDEX_OUT_OF_BOUNDS: u64 = NFE_VECTOR_ERROR_BASE + 1;
+    pub const NFE_VECTOR_POP_EMPTY: u64 = NFE_VECTOR_ERROR_BASE + 2;
+    pub const NFE_VECTOR_DESTROY_NON_EMPTY: u64 = NFE_VECTOR_ERROR_BASE + 3;
+    pub const NFE_VECTOR_TRUNCATE_RESOURCE: u64 = NFE_VECTOR_ERROR_BASE + 4;
+    pub const NFE_VECTOR_FILL_TOO_LONG: u64 = NFE_VECTOR_ERROR_BASE + 5;
+    pub const NFE_VECTOR_SORT_NON_U64: u64 = NFE_VECTOR_ERROR_BASE + 6;
+    pub const NFE_VECTOR_BINARY_SEARCH_NON_U64: u64 = NFE_VECTOR_ERROR_BASE + 7;
+    pub const NFE_VECTOR_TOO_LONG: u64 = NFE_VECTOR_ERROR_BASE + 8;
+    pub const NFE_VECTOR_AGGREGATE_NON_U64: u64 = NFE_VECTOR_ERROR_BASE + 9;
 
     pub const GSE_UNABLE_TO_LOAD_MODULE: u64 = 0;
     pub const GSE_UNABLE_TO_LOAD_RESOURCE: u64 = 1;